@@ -0,0 +1,53 @@
+//!Runs local VNDB server with JSON dataset or, with `mirror` feature, SQLite mirror.
+//!
+//!Usage: `cargo run --example server --features server -- <dataset.json|mirror.sqlite> [address]`
+
+use vndb::server::{Dataset, Server, dataset::JsonDataset};
+
+fn run<D: Dataset>(dataset: D, addr: &str) -> ! {
+    let server = match Server::builder(dataset).start(addr) {
+        Ok(server) => server,
+        Err(error) => {
+            eprintln!("{}: Unable to start server: {}", addr, error);
+            std::process::exit(1);
+        }
+    };
+
+    println!("Listening on {}", server.addr());
+    loop {
+        std::thread::park();
+    }
+}
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let path = match args.next() {
+        Some(path) => path,
+        None => {
+            eprintln!("Usage: server <dataset.json|mirror.sqlite> [address]");
+            std::process::exit(1);
+        }
+    };
+    let addr = args.next().unwrap_or_else(|| "127.0.0.1:19534".to_owned());
+
+    #[cfg(feature = "mirror")]
+    {
+        if path.ends_with(".sqlite") || path.ends_with(".db") {
+            match vndb::server::dataset::MirrorDataset::open(&path) {
+                Ok(dataset) => run(dataset, &addr),
+                Err(error) => {
+                    eprintln!("{}: Unable to open mirror: {}", path, error);
+                    std::process::exit(1);
+                }
+            }
+        }
+    }
+
+    match JsonDataset::open(&path) {
+        Ok(dataset) => run(dataset, &addr),
+        Err(error) => {
+            eprintln!("{}: Unable to load dataset: {}", path, error);
+            std::process::exit(1);
+        }
+    }
+}
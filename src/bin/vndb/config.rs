@@ -0,0 +1,80 @@
+//!Configuration directory of CLI.
+
+use std::path::{Path, PathBuf};
+use std::{fs, io};
+
+use serde::{Deserialize, Serialize};
+
+const SESSION_FILE: &str = "session.json";
+
+#[derive(Serialize, Deserialize, Debug)]
+///Stored login session.
+pub struct Session {
+    ///Name of user.
+    pub username: String,
+    ///Session token, created on login.
+    pub token: String,
+}
+
+///Returns default configuration directory, if environment allows to determine it.
+pub fn default_dir() -> Option<PathBuf> {
+    if let Some(dir) = std::env::var_os("XDG_CONFIG_HOME").filter(|dir| !dir.is_empty()) {
+        return Some(PathBuf::from(dir).join("vndb"));
+    }
+
+    if let Some(dir) = std::env::var_os("APPDATA").filter(|dir| !dir.is_empty()) {
+        return Some(PathBuf::from(dir).join("vndb"));
+    }
+
+    std::env::var_os("HOME").filter(|dir| !dir.is_empty()).map(|dir| PathBuf::from(dir).join(".config").join("vndb"))
+}
+
+///Loads stored session, if any.
+pub fn load_session(dir: &Path) -> io::Result<Option<Session>> {
+    let session = match fs::read(dir.join(SESSION_FILE)) {
+        Ok(session) => session,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(error) => return Err(error),
+    };
+
+    serde_json::from_slice(&session).map(Some).map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+}
+
+///Stores session, readable only by current user.
+///
+///Session is written into new temporary file, which then replaces existing one,
+///so that permissions of existing file are not kept.
+pub fn store_session(dir: &Path, session: &Session) -> io::Result<()> {
+    fs::create_dir_all(dir)?;
+
+    let path = dir.join(SESSION_FILE);
+    let tmp = path.with_extension("tmp");
+    match fs::remove_file(&tmp) {
+        Err(error) if error.kind() != io::ErrorKind::NotFound => return Err(error),
+        _ => (),
+    }
+
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+
+    let file = options.open(&tmp)?;
+    if let Err(error) = serde_json::to_writer(file, session) {
+        let _ = fs::remove_file(&tmp);
+        return Err(error.into());
+    }
+    fs::rename(tmp, path)
+}
+
+///Removes stored session, returning whether there was one.
+pub fn remove_session(dir: &Path) -> io::Result<bool> {
+    match fs::remove_file(dir.join(SESSION_FILE)) {
+        Ok(()) => Ok(true),
+        Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(false),
+        Err(error) => Err(error),
+    }
+}
@@ -0,0 +1,393 @@
+//!`vndb` command-line tool.
+
+use core::convert::TryInto;
+use std::io::{self, BufRead, Read, Write};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::{Args, Parser, Subcommand};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use vndb::{export, import};
+use vndb::protocol::message::request::{get, Get, Login};
+use vndb::protocol::message::response::{results, typed, Results};
+
+mod config;
+mod output;
+mod repl;
+
+use output::Format;
+
+type Client<IO> = vndb::client::simple::Client<IO>;
+type TlsStream = rustls::StreamOwned<rustls::ClientConnection, std::net::TcpStream>;
+type Result<T> = core::result::Result<T, Box<dyn std::error::Error>>;
+
+const CLIENT_NAME: &str = "vndb-rs-cli";
+
+#[derive(Parser, Debug)]
+#[command(name = "vndb", version, about = "Command-line client of VNDB API")]
+struct Cli {
+    ///Address of VNDB server. Defaults to VNDB's TLS port, or its plain TCP port with `--insecure`.
+    #[arg(long, env = "VNDB_ADDR", global = true)]
+    addr: Option<String>,
+    ///Connects over plain TCP instead of TLS, sending password and session token unencrypted.
+    #[arg(long, global = true)]
+    insecure: bool,
+    ///Output format.
+    #[arg(short = 'o', long, value_enum, default_value_t = Format::Json, global = true)]
+    format: Format,
+    ///Directory to store session in. Defaults to `vndb` within user's configuration directory.
+    #[arg(long, env = "VNDB_CONFIG_DIR", global = true)]
+    config_dir: Option<PathBuf>,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    ///Prints database statistics.
+    Dbstats,
+    ///Retrieves entities of specified type.
+    Get(GetArgs),
+    ///Logs in and stores session token in configuration directory.
+    Login {
+        ///Name of user.
+        #[arg(short, long)]
+        username: String,
+        ///Password of user. Read from stdin, if not specified.
+        #[arg(long, env = "VNDB_PASSWORD", hide_env_values = true)]
+        password: Option<String>,
+    },
+    ///Removes stored session.
+    Logout,
+    ///Operations on user's list.
+    #[command(subcommand)]
+    List(ListCommand),
+    ///Starts interactive session.
+    Repl {
+        ///Prints exact bytes of each request.
+        #[arg(long)]
+        show_bytes: bool,
+        ///File to keep history in. Defaults to `history` within configuration directory.
+        #[arg(long)]
+        history: Option<PathBuf>,
+    },
+}
+
+#[derive(Args, Debug)]
+struct GetArgs {
+    ///Type of entity: vn, release, producer, character, staff, user, votelist, vnlist, wishlist, ulist, tag or trait.
+    kind: String,
+    ///Comma separated flags.
+    #[arg(long, default_value = "basic")]
+    flags: String,
+    ///Filter expression, parentheses are optional. Defaults to all entities.
+    #[arg(long)]
+    filter: Option<String>,
+    #[command(flatten)]
+    paging: Paging,
+}
+
+#[derive(Args, Debug)]
+struct Paging {
+    ///Page to start from.
+    #[arg(long, default_value_t = 1)]
+    page: u32,
+    ///Number of results per page.
+    #[arg(long, default_value_t = 10)]
+    results: u32,
+    ///Field to sort by.
+    #[arg(long)]
+    sort: Option<String>,
+    ///Reverses order of results.
+    #[arg(long)]
+    reverse: bool,
+    ///Retrieves all pages, starting from `--page`.
+    #[arg(long)]
+    all: bool,
+}
+
+#[derive(Subcommand, Debug)]
+enum ListCommand {
+    ///Exports whole list of user.
+    Export {
+        ///User's ID.
+        #[arg(long)]
+        uid: u64,
+        ///Exports list joined with VN titles in specified format, instead of printing raw items.
+        #[arg(long, value_enum)]
+        to: Option<ExportFormat>,
+        ///Uses legacy vnlist, votelist and wishlist instead of ulist. Requires `--to`.
+        #[arg(long, requires = "to")]
+        legacy: bool,
+    },
+    ///Imports list from file, printing changes without applying them, unless `--apply` is specified.
+    Import {
+        ///User's ID.
+        #[arg(long)]
+        uid: u64,
+        ///File to import.
+        file: PathBuf,
+        ///Format of file. Determined by file's extension, if not specified.
+        #[arg(long, value_enum)]
+        from: Option<ExportFormat>,
+        ///Applies changes. Requires login.
+        #[arg(long)]
+        apply: bool,
+    },
+}
+
+#[derive(clap::ValueEnum, Copy, Clone, Debug)]
+enum ExportFormat {
+    ///JSON, that can be read back.
+    Json,
+    ///CSV with header.
+    Csv,
+    ///XML in format of MyAnimeList export.
+    Xml,
+}
+
+impl From<ExportFormat> for export::Format {
+    fn from(format: ExportFormat) -> Self {
+        match format {
+            ExportFormat::Json => export::Format::Json,
+            ExportFormat::Csv => export::Format::Csv,
+            ExportFormat::Xml => export::Format::Xml,
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    let result = match (cli.insecure, cli.addr.clone()) {
+        (true, addr) => {
+            let addr = addr.unwrap_or_else(|| format!("{}:{}", vndb::client::API_HOST, vndb::client::API_PORT));
+            run(cli, || Ok(Client::connect_to(addr.as_str())?))
+        },
+        (false, None) => run(cli, || Ok(Client::<TlsStream>::connect_tls()?)),
+        (false, Some(addr)) => match tls_config(&addr) {
+            Ok((dns_name, config)) => run(cli, || Ok(Client::<TlsStream>::connect_tls_to(addr.as_str(), dns_name.clone(), config.clone())?)),
+            Err(error) => Err(error),
+        },
+    };
+
+    match result {
+        Ok(out) => {
+            print!("{}", out);
+            ExitCode::SUCCESS
+        },
+        Err(error) => {
+            eprintln!("error: {}", error);
+            ExitCode::FAILURE
+        },
+    }
+}
+
+//Returns name and configuration to verify certificate of server at address.
+fn tls_config(addr: &str) -> Result<(rustls::pki_types::ServerName<'static>, std::sync::Arc<rustls::ClientConfig>)> {
+    let host = match addr.rsplit_once(':') {
+        Some((host, _)) => host,
+        None => addr,
+    };
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    let dns_name = host.to_owned().try_into().map_err(|_| format!("Invalid host '{}'", host))?;
+
+    let mut certs = rustls::RootCertStore::empty();
+    certs.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+    let config = rustls::ClientConfig::builder().with_root_certificates(certs)
+                                                .with_no_client_auth();
+
+    Ok((dns_name, std::sync::Arc::new(config)))
+}
+
+fn run<IO: Read + Write, F: Fn() -> Result<Client<IO>>>(cli: Cli, open: F) -> Result<String> {
+    let config_dir = match cli.config_dir {
+        Some(config_dir) => config_dir,
+        None => config::default_dir().ok_or("Unable to determine configuration directory, specify --config-dir")?,
+    };
+
+    let value = match cli.command {
+        Command::Login { username, password } => {
+            let password = match password {
+                Some(password) => password,
+                None => read_password()?,
+            };
+            let token = login(&open, &username, &password)?;
+            config::store_session(&config_dir, &config::Session { username: username.clone(), token })?;
+            json!({"username": username, "logged_in": true})
+        },
+        Command::Logout => {
+            let removed = config::remove_session(&config_dir)?;
+            json!({"logged_out": removed})
+        },
+        Command::Dbstats => {
+            let mut client = connect(&open, &config_dir)?;
+            serde_json::to_value(client.dbstats()?)?
+        },
+        Command::Get(args) => {
+            let kind = get::Type::from_str(&args.kind).ok_or_else(|| format!("Unknown type '{}'", args.kind))?;
+            let flags = get::Flags::from_str(&args.flags).map_err(|flag| format!("Unknown flag '{}'", flag))?;
+            let filters = match args.filter.as_deref().map(str::trim) {
+                Some(filter) if filter.starts_with('(') => get::Filters::parse(filter)?,
+                Some(filter) => get::Filters::parse(&format!("({})", filter))?,
+                None => get::Filters::new().filter(vndb::filter!(id >= 1)),
+            };
+
+            let mut client = connect(&open, &config_dir)?;
+            let results = fetch(&mut client, kind.clone(), flags, filters, &args.paging)?;
+            typed(&kind, &results)?
+        },
+        Command::List(ListCommand::Export { uid, to: Some(format), legacy }) => {
+            let mut client = connect(&open, &config_dir)?;
+            let list = match legacy {
+                true => export::UserList::fetch_legacy(&mut client, uid)?,
+                false => export::UserList::fetch(&mut client, uid)?,
+            };
+
+            let mut out = Vec::new();
+            list.write(format.into(), &mut out)?;
+            return Ok(String::from_utf8(out)?);
+        },
+        Command::List(ListCommand::Import { uid, file, from, apply }) => {
+            let format = match from {
+                Some(format) => format.into(),
+                None => file.extension().and_then(|ext| ext.to_str()).and_then(export::Format::from_str).ok_or("Unable to determine format of file, specify --from")?,
+            };
+            let items = import::read(format, std::fs::File::open(&file)?)?;
+
+            let mut client = connect(&open, &config_dir)?;
+            let importer = import::Importer::new();
+            let plan = importer.plan(&mut client, uid, items)?;
+            let mut out = plan.to_string();
+            if apply {
+                let stats = importer.apply(&mut client, &plan)?;
+                out.push_str(&format!("applied {} changes\n", stats.applied));
+            }
+            return Ok(out);
+        },
+        Command::List(ListCommand::Export { uid, to: None, .. }) => {
+            let paging = Paging {
+                page: 1,
+                results: 25,
+                sort: None,
+                reverse: false,
+                all: true,
+            };
+            let filters = get::Filters::new().filter(vndb::filter!(uid = uid));
+
+            let mut client = connect(&open, &config_dir)?;
+            let results = fetch(&mut client, get::Type::ulist(), get::Flags::new().basic(), filters, &paging)?;
+            typed(&get::Type::ulist(), &results)?
+        },
+        Command::Repl { show_bytes, history } => {
+            let client = connect(&open, &config_dir)?;
+            repl::run(client, Some(history.unwrap_or_else(|| config_dir.join("history"))), show_bytes)?;
+            return Ok(String::new());
+        },
+    };
+
+    Ok(output::render(cli.format, &value))
+}
+
+fn read_password() -> Result<String> {
+    let mut password = String::new();
+    io::stdin().lock().read_line(&mut password)?;
+    let password = password.trim_end_matches(['\r', '\n']);
+
+    match password.is_empty() {
+        true => Err("Password is not specified".into()),
+        false => Ok(password.to_owned()),
+    }
+}
+
+fn login<IO: Read + Write, F: Fn() -> Result<Client<IO>>>(open: &F, username: &str, password: &str) -> Result<String> {
+    let mut client = open()?;
+    let mut login = Login::new(Some((username, password)));
+    login.client = CLIENT_NAME.into();
+    login.create_session = true;
+
+    client.login(&login)?.ok_or_else(|| "VNDB did not create session".into())
+}
+
+//Connects and logs in, using stored session if any.
+fn connect<IO: Read + Write, F: Fn() -> Result<Client<IO>>>(open: &F, config_dir: &std::path::Path) -> Result<Client<IO>> {
+    let session = config::load_session(config_dir)?;
+    let mut client = open()?;
+
+    let mut login = Login::new(None);
+    login.client = CLIENT_NAME.into();
+    login.session = session.as_ref().map(|session| (session.username.as_str().into(), session.token.as_str().into()));
+
+    match client.login(&login) {
+        Ok(_) => Ok(client),
+        Err(vndb::Error::Server(error)) if session.is_some() => Err(format!("{}. Session may be expired, run `vndb login` again", error).into()),
+        Err(error) => Err(error.into()),
+    }
+}
+
+fn fetch<IO: Read + Write>(client: &mut Client<IO>, kind: get::Type, flags: get::Flags, filters: get::Filters, paging: &Paging) -> Result<Results> {
+    let mut get = Get {
+        kind,
+        flags,
+        filters,
+        options: Some(get::Options {
+            page: Some(paging.page),
+            results: Some(paging.results),
+            sort: paging.sort.as_deref(),
+            reverse: Some(paging.reverse),
+        }),
+    };
+
+    let results = client.get(&get)?;
+    if !paging.all {
+        return Ok(results);
+    }
+
+    let mut results = results;
+    let mut items = Vec::new();
+    loop {
+        if let Some(page) = results.get("items").and_then(Value::as_array) {
+            items.extend(page.iter().cloned());
+        }
+
+        match results.get("more").and_then(Value::as_bool).unwrap_or(false) {
+            true => {
+                if let Some(options) = get.options.as_mut() {
+                    options.page = options.page.map(|page| page + 1);
+                }
+                results = client.get(&get)?;
+            },
+            false => break,
+        }
+    }
+
+    Ok(Results::new(json!({
+        "num": items.len(),
+        "more": false,
+        "items": items,
+    })))
+}
+
+//Validates results against typed representation of entity, when it is available.
+fn typed(kind: &get::Type, results: &Results) -> Result<Value> {
+    fn to_value<'de, T: Deserialize<'de> + Serialize>(results: &'de Value) -> Result<Value> {
+        let results = typed::Results::<T>::deserialize(results)?;
+        Ok(serde_json::to_value(results)?)
+    }
+
+    match kind.as_str() {
+        "vn" => to_value::<results::Vn>(results),
+        "release" => to_value::<results::Release>(results),
+        "producer" => to_value::<results::Producer>(results),
+        "character" => to_value::<results::Character>(results),
+        "tag" => to_value::<results::Tag>(results),
+        "trait" => to_value::<results::Trait>(results),
+        "user" => to_value::<results::User>(results),
+        "votelist" => to_value::<results::VoteList>(results),
+        "vnlist" => to_value::<results::VnList>(results),
+        "ulist" => to_value::<results::UList>(results),
+        _ => Ok(Value::clone(results)),
+    }
+}
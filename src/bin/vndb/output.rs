@@ -0,0 +1,167 @@
+//!Output formats of CLI.
+
+use core::fmt::Write;
+
+use serde_json::Value;
+
+const MAX_CELL: usize = 40;
+
+#[derive(clap::ValueEnum, Copy, Clone, Debug, PartialEq, Eq)]
+///Output format.
+pub enum Format {
+    ///Pretty printed JSON.
+    Json,
+    ///Table with column per field.
+    Table,
+    ///YAML document.
+    Yaml,
+}
+
+///Renders value in specified format.
+///
+///Table format lists `items` of results, other objects are rendered as single row.
+///Columns are ordered by name, except for `id`, which always goes first.
+pub fn render(format: Format, value: &Value) -> String {
+    match format {
+        Format::Json => {
+            let mut out = serde_json::to_string_pretty(value).expect("To serialize JSON");
+            out.push('\n');
+            out
+        },
+        Format::Yaml => yaml(value),
+        Format::Table => match value.get("items").and_then(Value::as_array) {
+            Some(items) => table(items),
+            None => table(core::slice::from_ref(value)),
+        },
+    }
+}
+
+fn table(rows: &[Value]) -> String {
+    let mut columns = Vec::<&str>::new();
+    for row in rows {
+        if let Some(row) = row.as_object() {
+            for key in row.keys() {
+                if !columns.contains(&key.as_str()) {
+                    columns.push(key);
+                }
+            }
+        }
+    }
+    if let Some(idx) = columns.iter().position(|column| *column == "id") {
+        let id = columns.remove(idx);
+        columns.insert(0, id);
+    }
+
+    let cells = rows.iter().map(|row| columns.iter().map(|column| cell(&row[*column])).collect::<Vec<_>>()).collect::<Vec<_>>();
+    let widths = columns.iter().enumerate().map(|(idx, column)| {
+        cells.iter().map(|row| row[idx].chars().count()).fold(column.chars().count(), usize::max)
+    }).collect::<Vec<_>>();
+
+    let mut out = String::new();
+    let mut write_row = |row: &mut dyn Iterator<Item=&str>| {
+        let mut line = String::new();
+        for (value, width) in row.zip(widths.iter()) {
+            let _ = write!(line, "{:<width$}  ", value, width = width);
+        }
+        out.push_str(line.trim_end());
+        out.push('\n');
+    };
+
+    write_row(&mut columns.iter().copied());
+    let separator = widths.iter().map(|width| "-".repeat(*width)).collect::<Vec<_>>();
+    write_row(&mut separator.iter().map(String::as_str));
+    for row in cells.iter() {
+        write_row(&mut row.iter().map(String::as_str));
+    }
+
+    out
+}
+
+fn cell(value: &Value) -> String {
+    let value = match value {
+        Value::Null => return String::new(),
+        Value::String(value) => value.clone(),
+        value => value.to_string(),
+    };
+    let value = value.replace(['\n', '\r', '\t'], " ");
+
+    match value.chars().count() > MAX_CELL {
+        true => {
+            let mut value = value.chars().take(MAX_CELL - 3).collect::<String>();
+            value.push_str("...");
+            value
+        },
+        false => value,
+    }
+}
+
+fn yaml(value: &Value) -> String {
+    let mut out = String::new();
+    match value {
+        Value::Object(map) if !map.is_empty() => yaml_map(&mut out, map, 0),
+        Value::Array(items) if !items.is_empty() => yaml_seq(&mut out, items, 0),
+        value => {
+            out.push_str(&yaml_scalar(value));
+            out.push('\n');
+        },
+    }
+    out
+}
+
+fn yaml_map(out: &mut String, map: &serde_json::Map<String, Value>, indent: usize) {
+    for (key, value) in map {
+        let _ = write!(out, "{:indent$}{}:", "", yaml_key(key), indent = indent);
+        yaml_nested(out, value, indent + 2);
+    }
+}
+
+fn yaml_seq(out: &mut String, items: &[Value], indent: usize) {
+    for value in items {
+        let _ = write!(out, "{:indent$}-", "", indent = indent);
+        match value {
+            //Mapping starts on the same line as its dash.
+            Value::Object(map) if !map.is_empty() => {
+                let mut nested = String::new();
+                yaml_map(&mut nested, map, indent + 2);
+                out.push(' ');
+                out.push_str(&nested[indent + 2..]);
+            },
+            value => yaml_nested(out, value, indent + 2),
+        }
+    }
+}
+
+fn yaml_nested(out: &mut String, value: &Value, indent: usize) {
+    match value {
+        Value::Object(map) if !map.is_empty() => {
+            out.push('\n');
+            yaml_map(out, map, indent);
+        },
+        Value::Array(items) if !items.is_empty() => {
+            out.push('\n');
+            yaml_seq(out, items, indent);
+        },
+        value => {
+            out.push(' ');
+            out.push_str(&yaml_scalar(value));
+            out.push('\n');
+        },
+    }
+}
+
+fn yaml_key(key: &str) -> String {
+    match !key.is_empty() && key.chars().all(|ch| ch.is_ascii_alphanumeric() || ch == '_' || ch == '-') {
+        true => key.to_owned(),
+        false => Value::from(key).to_string(),
+    }
+}
+
+fn yaml_scalar(value: &Value) -> String {
+    match value {
+        Value::Null => "null".to_owned(),
+        Value::Object(_) => "{}".to_owned(),
+        Value::Array(_) => "[]".to_owned(),
+        //JSON strings are valid YAML double-quoted scalars.
+        value => value.to_string(),
+    }
+}
@@ -0,0 +1,245 @@
+//!Interactive mode.
+//!
+//!Accepts raw protocol lines (`get vn basic (id = 17)`) or shorter syntax `<type> [flags] [filter]`,
+//!where filter can omit parentheses and single number is treated as ID:
+//!
+//!- `vn 17` - `get vn basic (id = 17)`;
+//!- `release basic,details vn = 17` - `get release basic,details (vn = 17)`.
+
+use std::io::{Read, Write};
+use std::path::PathBuf;
+
+use rustyline::completion::Completer;
+use rustyline::error::ReadlineError;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::validate::Validator;
+
+use vndb::protocol::message::request::{get, Set};
+use vndb::protocol::{Request, Response};
+
+use crate::{Client, Result};
+
+const PROMPT: &str = "vndb> ";
+const COMMANDS: [&str; 4] = ["get", "set", "dbstats", "login"];
+const META: [&str; 3] = [":bytes", ":help", ":quit"];
+const TYPES: [&str; 12] = ["vn", "release", "producer", "character", "staff", "user", "votelist", "vnlist", "wishlist", "ulist", "tag", "trait"];
+const FLAGS: [&str; 15] = ["basic", "details", "anime", "relations", "tags", "stats", "screens", "staff", "vn", "producers", "meas", "traits", "vns", "voiced", "labels"];
+const HELP: &str = "\
+Input is either raw protocol command or `<type> [flags] [filter]`:
+  get vn basic,details (id = 17)
+  set ulist 17 {\"vote\":80}
+  vn basic,details id = 17
+  vn 17
+Commands:
+  :bytes - toggles printing of exact bytes sent
+  :help  - prints this message
+  :quit  - exits";
+
+//Returns filter fields, available for type.
+fn filter_fields(kind: &str) -> &'static [&'static str] {
+    match kind {
+        "vn" => &["id", "title", "original", "firstchar", "released", "platforms", "languages", "orig_lang", "search", "tags"],
+        "release" => &["id", "vn", "producer", "title", "original", "released", "patch", "freeware", "doujin", "type", "gtin", "catalog", "languages", "platforms"],
+        "producer" => &["id", "name", "original", "type", "language", "search"],
+        "character" => &["id", "name", "original", "search", "vn", "traits"],
+        "staff" => &["id", "aid", "search"],
+        "user" => &["id", "username"],
+        "votelist" | "vnlist" | "wishlist" => &["uid", "vn"],
+        "ulist" => &["uid", "vn", "label"],
+        "tag" | "trait" => &["id", "name", "search"],
+        _ => &[],
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Input {
+    Empty,
+    Bytes,
+    Help,
+    Quit,
+    Request(String),
+}
+
+//Converts line into raw protocol message, without terminator.
+fn parse(line: &str) -> core::result::Result<Input, String> {
+    let line = line.trim();
+    let (first, rest) = line.split_once(char::is_whitespace).map(|(first, rest)| (first, rest.trim())).unwrap_or((line, ""));
+
+    match first {
+        "" => Ok(Input::Empty),
+        ":bytes" => Ok(Input::Bytes),
+        ":help" => Ok(Input::Help),
+        ":quit" | ":exit" => Ok(Input::Quit),
+        meta if meta.starts_with(':') => Err(format!("Unknown command '{}'", meta)),
+        command if COMMANDS.contains(&command) => Ok(Input::Request(line.to_owned())),
+        kind => {
+            if get::Type::from_str(kind).is_none() {
+                return Err(format!("Unknown type '{}'", kind));
+            }
+
+            let (flags, filter) = match rest.split_once(char::is_whitespace).map(|(flags, filter)| (flags, filter.trim())).unwrap_or((rest, "")) {
+                //Flag can be also a field name, e.g. `vn`, followed by operator.
+                (flags, filter) if !flags.is_empty() && !filter.starts_with(['=', '!', '<', '>', '~']) && get::Flags::from_str(flags).is_ok() => (flags, filter),
+                _ => ("basic", rest),
+            };
+
+            let filter = match filter {
+                "" => "(id >= 1)".to_owned(),
+                id if id.bytes().all(|byte| byte.is_ascii_digit()) => format!("(id = {})", id),
+                filter if filter.starts_with('(') => filter.to_owned(),
+                filter => format!("({})", filter),
+            };
+
+            Ok(Input::Request(format!("get {} {} {}", kind, flags, filter)))
+        },
+    }
+}
+
+//Escapes control characters, leaving the rest of message as it is.
+fn escape(message: &str) -> String {
+    let mut escaped = String::with_capacity(message.len());
+    for ch in message.chars() {
+        match ch.is_control() {
+            true => escaped.push_str(&format!("\\x{:02x}", ch as u32)),
+            false => escaped.push(ch),
+        }
+    }
+    escaped
+}
+
+//Returns start of completed word and its candidates.
+fn complete(line: &str, pos: usize) -> (usize, Vec<String>) {
+    let line = &line[..pos];
+    let start = line.rfind(|ch: char| ch.is_whitespace() || ch == ',' || ch == '(').map(|idx| idx + 1).unwrap_or(0);
+    let word = &line[start..];
+
+    let mut words = line[..start].split_whitespace();
+    let candidates: Vec<&str> = match words.next() {
+        None => COMMANDS.iter().chain(TYPES.iter()).chain(META.iter()).copied().collect(),
+        Some("set") => match words.count() {
+            0 => Set::TYPES.iter().map(|kind| kind.as_str()).collect(),
+            _ => Vec::new(),
+        },
+        Some(command) => {
+            let (kind, raw) = match command {
+                "get" => (words.next(), true),
+                kind => (Some(kind), false),
+            };
+
+            //Short syntax allows to omit flags.
+            match (kind, words.count()) {
+                (None, _) => TYPES.to_vec(),
+                (Some(_), 0) if raw => FLAGS.to_vec(),
+                (Some(kind), 0) => FLAGS.iter().chain(filter_fields(kind)).copied().collect(),
+                (Some(kind), _) => filter_fields(kind).to_vec(),
+            }
+        },
+    };
+
+    (start, candidates.into_iter().filter(|candidate| candidate.starts_with(word)).map(str::to_owned).collect())
+}
+
+struct Helper;
+
+impl Completer for Helper {
+    type Candidate = String;
+
+    fn complete(&self, line: &str, pos: usize, _: &rustyline::Context<'_>) -> rustyline::Result<(usize, Vec<String>)> {
+        Ok(complete(line, pos))
+    }
+}
+
+impl Hinter for Helper {
+    type Hint = String;
+}
+
+impl Highlighter for Helper {}
+impl Validator for Helper {}
+impl rustyline::Helper for Helper {}
+
+fn print_response(response: Response) {
+    match response {
+        Response::Ok => println!("ok"),
+        Response::Session(token) => println!("session {}", token),
+        Response::Results(results) => println!("{}", serde_json::to_string_pretty(&*results).expect("To serialize JSON")),
+        Response::DBstats(stats) => println!("{}", serde_json::to_string_pretty(&stats).expect("To serialize JSON")),
+        Response::Error(error) => println!("error: {}", error),
+    }
+}
+
+///Runs interactive session until it is terminated by user.
+pub fn run<IO: Read + Write>(mut client: Client<IO>, history: Option<PathBuf>, mut show_bytes: bool) -> Result<()> {
+    let mut editor = rustyline::Editor::<Helper, rustyline::history::DefaultHistory>::new()?;
+    editor.set_helper(Some(Helper));
+
+    if let Some(history) = history.as_ref() {
+        match editor.load_history(history) {
+            Ok(()) => (),
+            Err(ReadlineError::Io(error)) if error.kind() == std::io::ErrorKind::NotFound => (),
+            Err(error) => return Err(error.into()),
+        }
+    }
+
+    loop {
+        let line = match editor.readline(PROMPT) {
+            Ok(line) => line,
+            Err(ReadlineError::Eof | ReadlineError::Interrupted) => break,
+            Err(error) => return Err(error.into()),
+        };
+        let input = parse(&line);
+        //Login carries credentials, so it is never written into history file.
+        let is_login = matches!(&input, Ok(Input::Request(message)) if message.split_whitespace().next() == Some("login"));
+        if !line.trim().is_empty() && !is_login {
+            editor.add_history_entry(line.as_str())?;
+        }
+
+        let message = match input {
+            Ok(Input::Empty) => continue,
+            Ok(Input::Bytes) => {
+                show_bytes = !show_bytes;
+                println!("bytes: {}", if show_bytes { "on" } else { "off" });
+                continue;
+            },
+            Ok(Input::Help) => {
+                println!("{}", HELP);
+                continue;
+            },
+            Ok(Input::Quit) => break,
+            Ok(Input::Request(message)) => message,
+            Err(error) => {
+                println!("error: {}", error);
+                continue;
+            },
+        };
+
+        let request = match Request::from_str(&message) {
+            Ok(request) => request,
+            Err(error) => {
+                println!("error: {}", error);
+                continue;
+            },
+        };
+
+        if show_bytes {
+            let bytes = request.to_string();
+            println!(">> {} bytes: {}", bytes.len(), escape(&bytes));
+        }
+
+        match client.send(&request).and_then(|_| client.receive()) {
+            Ok(Some(response)) => print_response(response),
+            Ok(None) => return Err("Connection is closed by server".into()),
+            Err(error) if error.is_disconnect() => return Err(error.into()),
+            Err(error) => println!("error: {}", error),
+        }
+    }
+
+    if let Some(history) = history.as_ref() {
+        if let Some(dir) = history.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        editor.save_history(history)?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,252 @@
+//!Catalogue of producer's works.
+//!
+//!All releases, credited to producer, are fetched page by page and grouped by VN,
+//!separating releases in which producer took part as developer from those it published.
+//!Optionally catalogue includes related producers, following chosen kinds of relations transitively,
+//!e.g. all subsidiaries of subsidiaries.
+//!
+//!Example of usage:
+//!
+//!```no_run
+//!use vndb::catalogue::Catalogue;
+//!use vndb::protocol::message::response::results::ProducerRelationKind;
+//!
+//!let mut client = vndb::client::simple::Client::connect().expect("To connect");
+//!let catalogue = Catalogue::fetch_related(&mut client, 24, &[ProducerRelationKind::Subsidiary]).expect("To fetch catalogue");
+//!
+//!for title in catalogue.developed() {
+//!    println!("v{}: {} ({} releases)", title.id, title.title, title.developed.len());
+//!}
+//!```
+
+use std::collections::{BTreeMap, HashSet};
+use std::io::{Read, Write};
+
+use serde::Serialize;
+
+use crate::client::many::PAGE_SIZE;
+use crate::client::simple::Client;
+use crate::protocol::message::request::{get, Get};
+use crate::protocol::message::response::{results, typed};
+use crate::protocol::message::response::results::ProducerRelationKind;
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+///Producer, included in catalogue.
+pub struct Producer {
+    ///Producer's ID.
+    pub id: u64,
+    ///Name in romaji.
+    pub name: String,
+    ///Name in original language.
+    pub original: Option<String>,
+    ///Relation, through which producer is included.
+    ///
+    ///`None` for producer, catalogue is requested for.
+    pub relation: Option<ProducerRelationKind>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+///Release of VN.
+pub struct Release {
+    ///Release's ID.
+    pub id: u64,
+    ///Main title.
+    pub title: String,
+    ///Title in original language.
+    pub original: Option<String>,
+    ///Date of release.
+    pub released: Option<String>,
+    ///Type of release: "complete", "partial" or "trial".
+    pub kind: Option<String>,
+    ///IDs of catalogue's producers, credited in the role.
+    pub producers: Vec<u64>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+///VN with its releases by catalogue's producers.
+pub struct Title {
+    ///VN's ID.
+    pub id: u64,
+    ///VN's title.
+    pub title: String,
+    ///VN's title in original language.
+    pub original: Option<String>,
+    ///Releases, developed by catalogue's producers, ordered by date.
+    pub developed: Vec<Release>,
+    ///Releases, published by catalogue's producers, ordered by date.
+    pub published: Vec<Release>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+///Catalogue of producer's works.
+pub struct Catalogue {
+    ///ID of producer, catalogue is requested for.
+    pub root: u64,
+    ///Producers, whose releases are included, starting with root.
+    ///
+    ///Producers, that are not found, are omitted.
+    pub producers: Vec<Producer>,
+    ///VNs, ordered by ID.
+    pub titles: Vec<Title>,
+}
+
+impl Catalogue {
+    #[inline]
+    ///Fetches catalogue of single producer.
+    pub fn fetch<IO: Read + Write>(client: &mut Client<IO>, producer: u64) -> crate::Result<Self> {
+        Self::fetch_related(client, producer, &[])
+    }
+
+    ///Fetches catalogue of producer together with related producers.
+    ///
+    ///Each kind of relation is followed separately, so that following `Parent` and `Subsidiary`
+    ///includes parents of parents and subsidiaries of subsidiaries, but not siblings.
+    pub fn fetch_related<IO: Read + Write>(client: &mut Client<IO>, producer: u64, relations: &[ProducerRelationKind]) -> crate::Result<Self> {
+        let producers = fetch_producers(client, producer, relations)?;
+
+        let mut releases = BTreeMap::new();
+        let mut ids = vec![producer];
+        ids.extend(producers.iter().map(|producer| producer.id).filter(|id| *id != producer));
+        for id in ids.iter() {
+            for release in fetch_releases(client, *id)? {
+                releases.insert(release.id, release);
+            }
+        }
+
+        let ids = ids.into_iter().collect::<HashSet<_>>();
+        let mut titles = BTreeMap::new();
+        for release in releases.into_values() {
+            let credited = |role: fn(&results::ReleaseProducer) -> bool| release.producers.iter()
+                                                                                    .filter(|producer| ids.contains(&producer.id) && role(producer))
+                                                                                    .map(|producer| producer.id)
+                                                                                    .collect::<Vec<_>>();
+            let developers = credited(|producer| producer.developer);
+            let publishers = credited(|producer| producer.publisher);
+
+            for vn in release.vn.iter() {
+                let title = titles.entry(vn.id).or_insert_with(|| Title {
+                    id: vn.id,
+                    title: vn.title.clone(),
+                    original: vn.original.clone(),
+                    developed: Vec::new(),
+                    published: Vec::new(),
+                });
+
+                let entry = |producers: &Vec<u64>| Release {
+                    id: release.id,
+                    title: release.title.clone().unwrap_or_default(),
+                    original: release.original.clone(),
+                    released: release.released.clone(),
+                    kind: release.kind.clone(),
+                    producers: producers.clone(),
+                };
+                if !developers.is_empty() {
+                    title.developed.push(entry(&developers));
+                }
+                if !publishers.is_empty() {
+                    title.published.push(entry(&publishers));
+                }
+            }
+        }
+
+        let mut titles = titles.into_values().collect::<Vec<_>>();
+        for title in titles.iter_mut() {
+            title.developed.sort_by(|left, right| release_order(left).cmp(&release_order(right)));
+            title.published.sort_by(|left, right| release_order(left).cmp(&release_order(right)));
+        }
+
+        Ok(Self {
+            root: producer,
+            producers,
+            titles,
+        })
+    }
+
+    ///Returns VNs, developed by catalogue's producers.
+    pub fn developed(&self) -> impl Iterator<Item = &Title> + '_ {
+        self.titles.iter().filter(|title| !title.developed.is_empty())
+    }
+
+    ///Returns VNs, published by catalogue's producers.
+    pub fn published(&self) -> impl Iterator<Item = &Title> + '_ {
+        self.titles.iter().filter(|title| !title.published.is_empty())
+    }
+}
+
+//Unknown dates sort last, while partial dates like `2003` sort before full ones of the same year.
+fn release_order(release: &Release) -> (bool, Option<&str>, u64) {
+    (release.released.is_none(), release.released.as_deref(), release.id)
+}
+
+//Fetches root and related producers, level by level.
+fn fetch_producers<IO: Read + Write>(client: &mut Client<IO>, root: u64, relations: &[ProducerRelationKind]) -> crate::Result<Vec<Producer>> {
+    let mut flags = get::Flags::new().basic();
+    if !relations.is_empty() {
+        flags = flags.relations();
+    }
+
+    let mut producers = Vec::new();
+    let mut seen = HashSet::new();
+    seen.insert(root);
+    let mut level = vec![(root, None::<ProducerRelationKind>)];
+
+    while !level.is_empty() {
+        let ids = level.iter().map(|(id, _)| *id).collect::<Vec<_>>();
+        let many = client.get_many_producer(&ids, flags.clone())?;
+
+        let mut next = Vec::new();
+        for producer in many.items {
+            let via = level.iter().find(|(id, _)| *id == producer.id).and_then(|(_, via)| via.clone());
+            for relation in producer.relations.iter() {
+                //Root follows every requested relation, while related producers follow only the one they are reached by.
+                let follow = match via {
+                    Some(ref via) => *via == relation.relation,
+                    None => relations.contains(&relation.relation),
+                };
+
+                if follow && seen.insert(relation.id) {
+                    next.push((relation.id, Some(relation.relation.clone())));
+                }
+            }
+
+            producers.push(Producer {
+                id: producer.id,
+                name: producer.name.unwrap_or_default(),
+                original: producer.original,
+                relation: via,
+            });
+        }
+
+        level = next;
+    }
+
+    Ok(producers)
+}
+
+//Fetches all pages of releases, credited to producer.
+fn fetch_releases<IO: Read + Write>(client: &mut Client<IO>, producer: u64) -> crate::Result<Vec<results::Release>> {
+    let mut releases = Vec::new();
+    let mut page = 1;
+
+    loop {
+        let get = Get {
+            kind: get::Type::release(),
+            flags: get::Flags::new().basic().producers().vn(),
+            filters: get::Filters::new().filter(crate::filter!(producer = producer)),
+            options: Some(get::Options {
+                page: Some(page),
+                results: Some(PAGE_SIZE),
+                ..Default::default()
+            }),
+        };
+
+        let results = client.get(&get)?;
+        let results = results.to_typed::<typed::Release>()?;
+        releases.extend(results.items);
+
+        match results.more {
+            true => page += 1,
+            false => break Ok(releases),
+        }
+    }
+}
@@ -0,0 +1,593 @@
+//!Caching of `get` results.
+//!
+//!Results are cached by [Key](struct.Key.html), which is built from normalized `Get` request,
+//!so that requests differing only in order of flags or filters share the same entry.
+//!Each entry expires after configured TTL.
+//!
+//!User lists (`ulist`, `votelist`, `vnlist` and `wishlist`) are never cached,
+//!as they depend on logged in user and may contain private entries.
+//!
+//!Concurrent identical requests, made through the same [Cache](struct.Cache.html), are coalesced:
+//!only the first one is sent to VNDB, while others wait for its results.
+//!If it fails, waiting requests are sent on their own.
+//!
+//!Storage of entries is provided by [Backend](trait.Backend.html):
+//!
+//!- [Memory](struct.Memory.html) - In-memory storage with LRU eviction;
+//!- [Directory](struct.Directory.html) - On-disk storage with file per entry.
+//!
+//!Example of usage:
+//!
+//!```no_run
+//!use std::sync::Arc;
+//!use std::time::Duration;
+//!
+//!use vndb::client::cache::{Cache, Cached};
+//!use vndb::protocol::message::request::{get, Get};
+//!
+//!let cache = Arc::new(Cache::new(1024, Duration::from_secs(60)));
+//!let client = vndb::client::simple::Client::connect().expect("To connect");
+//!let mut client = Cached::new(client, cache.clone());
+//!
+//!let get = Get {
+//!    kind: get::Type::vn(),
+//!    flags: get::Flags::new().basic().details(),
+//!    filters: get::Filters::new().filter(vndb::filter!(id = 17)),
+//!    options: None,
+//!};
+//!let results = client.get(&get).expect("To get results");
+//!//Served from cache
+//!let results = client.get(&get).expect("To get results");
+//!```
+
+use core::fmt;
+use std::collections::{BTreeMap, HashMap};
+use std::io::{Read, Write};
+use std::path::PathBuf;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::{fs, io};
+
+use crate::protocol::message::request::Get;
+use crate::protocol::message::response::Results;
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+//Returns whether results of request are the same for every user.
+fn is_shared(get: &Get<'_>) -> bool {
+    !matches!(get.kind.as_str(), "ulist" | "votelist" | "vnlist" | "wishlist")
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+///Cache key, that is normalized `get` request.
+///
+///Flags are ordered, filters are [normalized](../../protocol/message/request/filter/enum.Expr.html#method.normalized)
+///and options are filled with default values.
+pub struct Key {
+    inner: String,
+}
+
+impl Key {
+    ///Creates key from request.
+    pub fn new(get: &Get<'_>) -> Self {
+        use fmt::Write;
+
+        let filters = match get.filters.expr() {
+            Ok(expr) => expr.normalized().to_string(),
+            Err(_) => get.filters.to_string(),
+        };
+        let options = get.options.clone().unwrap_or_default();
+
+        let mut inner = format!("get {} {} ({}) {{\"page\":{},\"results\":{}",
+                                get.kind, get.flags, filters, options.page.unwrap_or(1), options.results.unwrap_or(10));
+        if let Some(sort) = options.sort {
+            let _ = write!(inner, ",\"sort\":{:?}", sort);
+        }
+        let _ = write!(inner, ",\"reverse\":{}}}", options.reverse.unwrap_or(false));
+
+        Self {
+            inner
+        }
+    }
+
+    #[inline]
+    ///Returns textual representation of key.
+    pub fn as_str(&self) -> &str {
+        &self.inner
+    }
+}
+
+impl fmt::Display for Key {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.inner)
+    }
+}
+
+#[derive(Clone, Debug)]
+///Cached results.
+pub struct Entry {
+    ///Results of request.
+    pub results: Results,
+    ///Time after which entry is no longer valid.
+    pub expires_at: SystemTime,
+}
+
+impl Entry {
+    #[inline]
+    ///Returns whether entry is expired at specified time.
+    pub fn is_expired(&self, now: SystemTime) -> bool {
+        self.expires_at <= now
+    }
+}
+
+///Storage of cache entries.
+///
+///Backend is not required to check expiration of entries.
+pub trait Backend: Send + Sync {
+    ///Loads entry, if it is present.
+    fn load(&self, key: &Key) -> io::Result<Option<Entry>>;
+    ///Stores entry, replacing previous one.
+    fn store(&self, key: &Key, entry: &Entry) -> io::Result<()>;
+    ///Removes entry, if it is present.
+    fn remove(&self, key: &Key) -> io::Result<()>;
+}
+
+struct Lru {
+    entries: HashMap<Key, (Entry, u64)>,
+    order: BTreeMap<u64, Key>,
+    tick: u64,
+}
+
+impl Lru {
+    fn touch(&mut self, key: &Key) -> Option<&Entry> {
+        let tick = self.tick + 1;
+        let (entry, used) = self.entries.get_mut(key)?;
+        self.order.remove(used);
+        self.order.insert(tick, key.clone());
+        *used = tick;
+        self.tick = tick;
+        Some(entry)
+    }
+}
+
+///In-memory storage, evicting least recently used entries.
+pub struct Memory {
+    capacity: usize,
+    inner: Mutex<Lru>,
+}
+
+impl Memory {
+    ///Creates new instance with maximum number of entries.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            inner: Mutex::new(Lru {
+                entries: HashMap::new(),
+                order: BTreeMap::new(),
+                tick: 0,
+            }),
+        }
+    }
+
+    #[inline]
+    ///Returns number of stored entries.
+    pub fn len(&self) -> usize {
+        lock(&self.inner).entries.len()
+    }
+
+    #[inline]
+    ///Returns whether there are no entries.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl Backend for Memory {
+    fn load(&self, key: &Key) -> io::Result<Option<Entry>> {
+        Ok(lock(&self.inner).touch(key).cloned())
+    }
+
+    fn store(&self, key: &Key, entry: &Entry) -> io::Result<()> {
+        let mut lru = lock(&self.inner);
+
+        if lru.touch(key).is_none() && lru.entries.len() >= self.capacity {
+            if let Some((_, oldest)) = lru.order.pop_first() {
+                lru.entries.remove(&oldest);
+            }
+        }
+
+        lru.tick += 1;
+        let tick = lru.tick;
+        if let Some((_, used)) = lru.entries.insert(key.clone(), (entry.clone(), tick)) {
+            lru.order.remove(&used);
+        }
+        lru.order.insert(tick, key.clone());
+        Ok(())
+    }
+
+    fn remove(&self, key: &Key) -> io::Result<()> {
+        let mut lru = lock(&self.inner);
+        if let Some((_, used)) = lru.entries.remove(key) {
+            lru.order.remove(&used);
+        }
+        Ok(())
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct StoredEntry<'a> {
+    #[serde(borrow)]
+    key: std::borrow::Cow<'a, str>,
+    expires_at: u64,
+    results: serde_json::Value,
+}
+
+///On-disk storage, keeping each entry in separate JSON file within directory.
+///
+///Expired entries are removed only when they are accessed.
+pub struct Directory {
+    path: PathBuf,
+}
+
+impl Directory {
+    ///Creates new instance, creating directory if necessary.
+    pub fn new<P: Into<PathBuf>>(path: P) -> io::Result<Self> {
+        let path = path.into();
+        fs::create_dir_all(&path)?;
+        Ok(Self {
+            path
+        })
+    }
+
+    //FNV-1a, which is stable across versions unlike std's hasher.
+    fn file(&self, key: &Key) -> PathBuf {
+        let mut hash = 0xcbf2_9ce4_8422_2325u64;
+        for byte in key.as_str().bytes() {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+
+        self.path.join(format!("{:016x}.json", hash))
+    }
+}
+
+impl Backend for Directory {
+    fn load(&self, key: &Key) -> io::Result<Option<Entry>> {
+        let data = match fs::read(self.file(key)) {
+            Ok(data) => data,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(error) => return Err(error),
+        };
+
+        let stored: StoredEntry = serde_json::from_slice(&data)?;
+        //Hash collision
+        if stored.key != key.as_str() {
+            return Ok(None);
+        }
+
+        Ok(Some(Entry {
+            results: Results::new(stored.results),
+            expires_at: UNIX_EPOCH + Duration::from_secs(stored.expires_at),
+        }))
+    }
+
+    fn store(&self, key: &Key, entry: &Entry) -> io::Result<()> {
+        let stored = StoredEntry {
+            key: key.as_str().into(),
+            expires_at: entry.expires_at.duration_since(UNIX_EPOCH).map(|time| time.as_secs()).unwrap_or(0),
+            results: (*entry.results).clone(),
+        };
+
+        let file = self.file(key);
+        let tmp = file.with_extension("tmp");
+        fs::write(&tmp, serde_json::to_vec(&stored)?)?;
+        fs::rename(tmp, file)
+    }
+
+    fn remove(&self, key: &Key) -> io::Result<()> {
+        match fs::remove_file(self.file(key)) {
+            Err(error) if error.kind() != io::ErrorKind::NotFound => Err(error),
+            _ => Ok(()),
+        }
+    }
+}
+
+//Request in progress, that others can wait for.
+struct Flight {
+    //`Some` once request is finished, containing results on success.
+    outcome: Mutex<Option<Option<Results>>>,
+    done: Condvar,
+    #[cfg(feature = "tokio-on")]
+    notify: tokio::sync::Notify,
+}
+
+impl Flight {
+    fn new() -> Self {
+        Self {
+            outcome: Mutex::new(None),
+            done: Condvar::new(),
+            #[cfg(feature = "tokio-on")]
+            notify: tokio::sync::Notify::new(),
+        }
+    }
+
+    #[cfg(feature = "tokio-on")]
+    fn outcome(&self) -> Option<Option<Results>> {
+        lock(&self.outcome).clone()
+    }
+
+    fn wait(&self) -> Option<Results> {
+        let mut outcome = lock(&self.outcome);
+        loop {
+            match outcome.as_ref() {
+                Some(results) => break results.clone(),
+                None => outcome = self.done.wait(outcome).unwrap_or_else(PoisonError::into_inner),
+            }
+        }
+    }
+
+    #[cfg(feature = "tokio-on")]
+    async fn wait_async(&self) -> Option<Results> {
+        loop {
+            let notified = self.notify.notified();
+            let mut notified = core::pin::pin!(notified);
+            notified.as_mut().enable();
+
+            if let Some(results) = self.outcome() {
+                break results;
+            }
+            notified.await;
+        }
+    }
+}
+
+//Finishes flight on drop, even if request is cancelled or panics.
+struct Leader<'a, B: Backend> {
+    cache: &'a Cache<B>,
+    key: &'a Key,
+    flight: Arc<Flight>,
+}
+
+impl<'a, B: Backend> Leader<'a, B> {
+    fn finish(&self, results: Option<&Results>) {
+        if let Some(results) = results {
+            self.cache.insert(self.key, results.clone());
+        }
+
+        let mut outcome = lock(&self.flight.outcome);
+        if outcome.is_none() {
+            *outcome = Some(results.cloned());
+            drop(outcome);
+
+            lock(&self.cache.in_flight).remove(self.key);
+            self.flight.done.notify_all();
+            #[cfg(feature = "tokio-on")]
+            self.flight.notify.notify_waiters();
+        }
+    }
+}
+
+impl<'a, B: Backend> Drop for Leader<'a, B> {
+    #[inline]
+    fn drop(&mut self) {
+        self.finish(None);
+    }
+}
+
+enum Role<'a, B: Backend> {
+    Cached(Results),
+    Leader(Leader<'a, B>),
+    Follower(Arc<Flight>),
+}
+
+///Cache of `get` results.
+pub struct Cache<B = Memory> {
+    backend: B,
+    ttl: Duration,
+    in_flight: Mutex<HashMap<Key, Arc<Flight>>>,
+}
+
+impl Cache<Memory> {
+    #[inline]
+    ///Creates in-memory cache with maximum number of entries and their TTL.
+    pub fn new(capacity: usize, ttl: Duration) -> Self {
+        Self::with_backend(Memory::new(capacity), ttl)
+    }
+}
+
+impl<B: Backend> Cache<B> {
+    ///Creates cache with specified backend and TTL of entries.
+    pub fn with_backend(backend: B, ttl: Duration) -> Self {
+        Self {
+            backend,
+            ttl,
+            in_flight: Mutex::new(HashMap::new()),
+        }
+    }
+
+    #[inline]
+    ///Returns backend.
+    pub fn backend(&self) -> &B {
+        &self.backend
+    }
+
+    ///Returns cached results, if they are not expired.
+    ///
+    ///Backend errors are treated as absence of results.
+    pub fn get(&self, key: &Key) -> Option<Results> {
+        let entry = self.backend.load(key).ok()??;
+        match entry.is_expired(SystemTime::now()) {
+            true => {
+                let _ = self.backend.remove(key);
+                None
+            },
+            false => Some(entry.results),
+        }
+    }
+
+    ///Stores results, ignoring backend errors.
+    pub fn insert(&self, key: &Key, results: Results) {
+        let entry = Entry {
+            results,
+            expires_at: SystemTime::now() + self.ttl,
+        };
+        let _ = self.backend.store(key, &entry);
+    }
+
+    ///Removes cached results.
+    pub fn invalidate(&self, key: &Key) -> io::Result<()> {
+        self.backend.remove(key)
+    }
+
+    fn join<'a>(&'a self, key: &'a Key) -> Role<'a, B> {
+        if let Some(results) = self.get(key) {
+            return Role::Cached(results);
+        }
+
+        let flight = {
+            let mut in_flight = lock(&self.in_flight);
+            match in_flight.get(key) {
+                Some(flight) => return Role::Follower(flight.clone()),
+                None => {
+                    let flight = Arc::new(Flight::new());
+                    in_flight.insert(key.clone(), flight.clone());
+                    flight
+                }
+            }
+        };
+
+        let leader = Leader {
+            cache: self,
+            key,
+            flight,
+        };
+
+        //Previous request might have been finished before we joined.
+        match self.get(key) {
+            Some(results) => {
+                leader.finish(Some(&results));
+                Role::Cached(results)
+            },
+            None => Role::Leader(leader),
+        }
+    }
+
+    ///Returns cached results of request or fetches them using provided function.
+    ///
+    ///Results of user lists are always fetched.
+    pub fn fetch<F: FnOnce(&Get<'_>) -> crate::Result<Results>>(&self, get: &Get<'_>, fetch: F) -> crate::Result<Results> {
+        if !is_shared(get) {
+            return fetch(get);
+        }
+
+        let key = Key::new(get);
+        let role = self.join(&key);
+
+        match role {
+            Role::Cached(results) => Ok(results),
+            Role::Leader(leader) => {
+                let results = fetch(get);
+                leader.finish(results.as_ref().ok());
+                results
+            },
+            Role::Follower(flight) => match flight.wait() {
+                Some(results) => Ok(results),
+                None => fetch(get),
+            },
+        }
+    }
+
+    #[cfg(feature = "tokio-on")]
+    ///Returns cached results of request or fetches them using provided future.
+    ///
+    ///Results of user lists are always fetched.
+    pub async fn fetch_async<F, R>(&self, get: &Get<'_>, fetch: F) -> crate::Result<Results> where F: FnOnce() -> R, R: core::future::Future<Output=crate::Result<Results>> {
+        if !is_shared(get) {
+            return fetch().await;
+        }
+
+        let key = Key::new(get);
+        let role = self.join(&key);
+
+        match role {
+            Role::Cached(results) => Ok(results),
+            Role::Leader(leader) => {
+                let results = fetch().await;
+                leader.finish(results.as_ref().ok());
+                results
+            },
+            Role::Follower(flight) => match flight.wait_async().await {
+                Some(results) => Ok(results),
+                None => fetch().await,
+            },
+        }
+    }
+}
+
+///Client, which `get` requests are cached.
+///
+///Cache can be shared between multiple clients to coalesce their requests.
+pub struct Cached<C, B = Memory> {
+    client: C,
+    cache: Arc<Cache<B>>,
+}
+
+impl<C, B: Backend> Cached<C, B> {
+    ///Creates new instance.
+    pub fn new(client: C, cache: Arc<Cache<B>>) -> Self {
+        Self {
+            client,
+            cache,
+        }
+    }
+
+    #[inline]
+    ///Returns cache.
+    pub fn cache(&self) -> &Arc<Cache<B>> {
+        &self.cache
+    }
+
+    #[inline]
+    ///Returns reference to underlying client.
+    pub fn get_ref(&self) -> &C {
+        &self.client
+    }
+
+    #[inline]
+    ///Returns mutable reference to underlying client.
+    ///
+    ///Requests, sent directly, are not cached.
+    pub fn get_mut(&mut self) -> &mut C {
+        &mut self.client
+    }
+
+    #[inline]
+    ///Returns underlying client.
+    pub fn into_inner(self) -> C {
+        self.client
+    }
+}
+
+impl<IO: Read + Write, B: Backend> Cached<super::simple::Client<IO>, B> {
+    ///Returns cached results of request or sends it.
+    ///
+    ///Must not be used while there are pending responses to previously sent requests.
+    pub fn get(&mut self, get: &Get<'_>) -> crate::Result<Results> {
+        let client = &mut self.client;
+        self.cache.fetch(get, |get| client.get(get))
+    }
+}
+
+#[cfg(feature = "tokio-on")]
+impl<IO: tokio::io::AsyncRead + tokio::io::AsyncWrite, B: Backend> Cached<super::tokio::Client<IO>, B> {
+    ///Returns cached results of request or sends it.
+    ///
+    ///Must not be used while there are pending responses to previously sent requests.
+    pub async fn get(&mut self, get: &Get<'_>) -> crate::Result<Results> {
+        let client = &mut self.client;
+        self.cache.fetch_async(get, || client.get(get)).await
+    }
+}
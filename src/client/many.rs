@@ -0,0 +1,135 @@
+//!Retrieval of many entities by their IDs.
+//!
+//!IDs are de-duplicated and split into chunks of at most [MAX_IDS](constant.MAX_IDS.html),
+//!each requested with `id = [..]` filter and paginated until all of its results are received.
+//!
+//!Example of usage:
+//!
+//!```no_run
+//!use vndb::protocol::message::request::get::Flags;
+//!
+//!let mut client = vndb::client::simple::Client::connect().expect("To connect");
+//!let ids = (1..=5000).collect::<Vec<_>>();
+//!let vns = client.get_many_vn(&ids, Flags::new().basic()).expect("To get VNs");
+//!
+//!println!("Missing VNs: {:?}", vns.missing);
+//!for vn in vns.iter() {
+//!    println!("v{}: {:?}", vn.id, vn.title);
+//!}
+//!```
+
+use core::ops::Deref;
+use std::collections::{HashMap, HashSet};
+
+use serde::de::DeserializeOwned;
+
+use crate::protocol::message::request::{get, Get};
+use crate::protocol::message::response::{self, Results};
+
+///Maximum number of IDs in single array filter.
+pub const MAX_IDS: usize = 100;
+///Maximum number of results per page, allowed by VNDB.
+pub const PAGE_SIZE: u32 = 25;
+
+#[derive(Debug)]
+///Entities, retrieved by IDs.
+pub struct Many<T> {
+    ///Found entities, in the order of requested IDs.
+    pub items: Vec<T>,
+    ///IDs, for which no entity is found, in the order they were requested.
+    pub missing: Vec<u64>,
+}
+
+impl<T> Deref for Many<T> {
+    type Target = [T];
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.items
+    }
+}
+
+//Plans requests and collects their results.
+pub(crate) struct Collector<T> {
+    kind: get::Type,
+    flags: get::Flags,
+    ids: Vec<u64>,
+    chunk: usize,
+    page: u32,
+    found: HashMap<u64, T>,
+}
+
+impl<T: DeserializeOwned> Collector<T> {
+    pub(crate) fn new(kind: get::Type, ids: &[u64], flags: get::Flags) -> Self {
+        let mut unique = HashSet::with_capacity(ids.len());
+        let ids = ids.iter().copied().filter(|id| unique.insert(*id)).collect::<Vec<_>>();
+
+        Self {
+            kind,
+            flags,
+            found: HashMap::with_capacity(ids.len()),
+            ids,
+            chunk: 0,
+            page: 1,
+        }
+    }
+
+    //Returns next request to send, if any.
+    pub(crate) fn request(&self) -> Option<Get<'static>> {
+        let ids = self.ids.chunks(MAX_IDS).nth(self.chunk)?;
+        let ids = serde_json::Value::from(ids);
+
+        Some(Get {
+            kind: self.kind.clone(),
+            flags: self.flags.clone(),
+            filters: get::Filters::new().filter(crate::filter!(id = ids)),
+            options: Some(get::Options {
+                page: Some(self.page),
+                results: Some(PAGE_SIZE),
+                ..Default::default()
+            }),
+        })
+    }
+
+    //Adds results of last request.
+    pub(crate) fn push(&mut self, results: Results) -> crate::Result<()> {
+        if let Some(items) = results.get("items").and_then(|items| items.as_array()) {
+            for (idx, item) in items.iter().enumerate() {
+                let id = match item.get("id").and_then(|id| id.as_u64()) {
+                    Some(id) => id,
+                    None => continue,
+                };
+
+                let item = response::deserialize_at(item, &format!("items[{}]", idx))?;
+                self.found.insert(id, item);
+            }
+        }
+
+        match results.get("more").and_then(|more| more.as_bool()).unwrap_or(false) {
+            true => self.page += 1,
+            false => {
+                self.chunk += 1;
+                self.page = 1;
+            },
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn finish(mut self) -> Many<T> {
+        let mut items = Vec::with_capacity(self.found.len());
+        let mut missing = Vec::new();
+
+        for id in self.ids {
+            match self.found.remove(&id) {
+                Some(item) => items.push(item),
+                None => missing.push(id),
+            }
+        }
+
+        Many {
+            items,
+            missing,
+        }
+    }
+}
@@ -1,6 +1,6 @@
 //! Client implementations
 
-use std::io;
+use crate::error::{Error, FramingError};
 
 ///VNDB Host
 pub const API_HOST: &'static str = "api.vndb.org";
@@ -9,57 +9,133 @@ pub const API_PORT: u16 = 19534;
 ///VNDB SSL port
 pub const API_SSL_PORT: u16 = 19535;
 
-fn parse_response(buf: &[u8]) -> io::Result<Option<crate::protocol::Response>> {
+fn parse_response(buf: &[u8]) -> crate::Result<crate::protocol::Response> {
     let buf = match buf.split_last() {
         Some((0x04, buf)) => buf,
-        _ => return Ok(None), //incomplete read, connection is reset most likely
+        _ => return Err(FramingError::Incomplete.into()), //incomplete read, connection is reset most likely
     };
 
     let msg = match core::str::from_utf8(&buf) {
         Ok(msg) => msg,
-        Err(err) => return Err(io::Error::new(io::ErrorKind::InvalidData, err)),
+        Err(err) => return Err(FramingError::InvalidUtf8(err).into()),
     };
 
     match crate::protocol::Response::from_str(msg) {
-        Ok(msg) => Ok(Some(msg)),
-        Err(err) => Err(io::Error::new(io::ErrorKind::InvalidData, err)),
+        Ok(msg) => Ok(msg),
+        Err(err) => Err(err.into()),
+    }
+}
+
+fn expect_dbstats(response: Option<crate::protocol::Response>) -> crate::Result<crate::protocol::message::response::DBstats> {
+    use crate::protocol::Response;
+
+    match response {
+        Some(Response::DBstats(stats)) => Ok(stats),
+        Some(Response::Error(error)) => Err(error.into()),
+        Some(response) => Err(Error::Unexpected(response)),
+        None => Err(FramingError::Incomplete.into()),
+    }
+}
+
+fn expect_login(response: Option<crate::protocol::Response>) -> crate::Result<Option<String>> {
+    use crate::protocol::Response;
+
+    match response {
+        Some(Response::Ok) => Ok(None),
+        Some(Response::Session(token)) => Ok(Some(token)),
+        Some(Response::Error(error)) => Err(error.into()),
+        Some(response) => Err(Error::Unexpected(response)),
+        None => Err(FramingError::Incomplete.into()),
+    }
+}
+
+fn expect_ok(response: Option<crate::protocol::Response>) -> crate::Result<()> {
+    use crate::protocol::Response;
+
+    match response {
+        Some(Response::Ok) => Ok(()),
+        Some(Response::Error(error)) => Err(error.into()),
+        Some(response) => Err(Error::Unexpected(response)),
+        None => Err(FramingError::Incomplete.into()),
+    }
+}
+
+fn expect_results(response: Option<crate::protocol::Response>) -> crate::Result<crate::protocol::message::response::Results> {
+    use crate::protocol::Response;
+
+    match response {
+        Some(Response::Results(results)) => Ok(results),
+        Some(Response::Error(error)) => Err(error.into()),
+        Some(response) => Err(Error::Unexpected(response)),
+        None => Err(FramingError::Incomplete.into()),
+    }
+}
+
+//Returns time to wait, suggested by VNDB, if error is `throttled`.
+pub(crate) fn throttle_wait(error: &Error) -> Option<std::time::Duration> {
+    match error {
+        Error::Server(error) if error.id == "throttled" => {
+            let wait = error.extra.get("minwait").and_then(serde_json::Value::as_f64).unwrap_or(1.0).max(0.0);
+            Some(std::time::Duration::try_from_secs_f64(wait).unwrap_or(std::time::Duration::MAX))
+        },
+        _ => None,
+    }
+}
+
+///Default number of retries of throttled request.
+pub const DEFAULT_MAX_RETRIES: u32 = 5;
+///Default limit on time to wait before retrying throttled request.
+pub const DEFAULT_MAX_WAIT: std::time::Duration = std::time::Duration::from_secs(60);
+
+//Performs request, retrying it while it is throttled.
+//
+//Waits for `minwait`, suggested by VNDB, but no longer than `max_wait`, counting each retry in `retries`.
+pub(crate) fn retry_throttled<T, F: FnMut() -> crate::Result<T>>(max_retries: u32, max_wait: std::time::Duration, retries: &mut usize, mut request: F) -> crate::Result<T> {
+    let mut attempt = 0;
+
+    loop {
+        match request() {
+            Err(error) => match throttle_wait(&error) {
+                Some(wait) if attempt < max_retries => {
+                    std::thread::sleep(wait.min(max_wait));
+                    attempt += 1;
+                    *retries += 1;
+                },
+                _ => break Err(error),
+            },
+            result => break result,
+        }
     }
 }
 
 #[cfg(feature = "rustls-on")]
 fn get_rustls_config() -> (rustls::pki_types::ServerName<'static>, std::sync::Arc<rustls::ClientConfig>) {
-    use core::mem::MaybeUninit;
     use core::convert::TryInto;
-    use std::sync::Arc;
+    use std::sync::{Arc, OnceLock};
 
-    static mut SERVER_NAME: MaybeUninit<rustls::pki_types::ServerName<'static>> = MaybeUninit::uninit();
-    static mut CFG: MaybeUninit<Arc<rustls::ClientConfig>> = MaybeUninit::uninit();
-    static CFG_ONCE: std::sync::Once = std::sync::Once::new();
+    static CFG: OnceLock<(rustls::pki_types::ServerName<'static>, Arc<rustls::ClientConfig>)> = OnceLock::new();
 
-    CFG_ONCE.call_once(|| {
+    let (server, config) = CFG.get_or_init(|| {
         let mut certs = rustls::RootCertStore::empty();
         certs.extend(webpki_roots::TLS_SERVER_ROOTS.into_iter().cloned());
         let config = rustls::ClientConfig::builder().with_root_certificates(certs)
                                                     .with_no_client_auth();
-        unsafe {
-            CFG.as_mut_ptr().write(Arc::new(config));
-        }
 
         let server = match API_HOST.try_into() {
             Ok(server) => server,
             Err(_) => unreachable!()
         };
 
-        unsafe {
-            SERVER_NAME.as_mut_ptr().write(server)
-        }
+        (server, Arc::new(config))
     });
 
-    unsafe {
-        ((*SERVER_NAME.as_ptr()).clone(), (*(CFG.as_ptr())).clone())
-    }
+    (server.clone(), config.clone())
 }
 
+pub mod transcript;
+pub mod cache;
+pub mod many;
+
 pub mod simple;
 ///Alias to simple std based client
 pub type Simple<IO> = simple::Client<IO>;
@@ -4,6 +4,9 @@ use std::{io, net};
 use std::io::{Read, Write, BufRead, BufReader};
 
 use super::{API_HOST, API_PORT};
+use super::many::{self, Many};
+use crate::protocol::message::request::get;
+use crate::protocol::message::response::results;
 
 ///Simple synchronous Client implementation
 pub struct Client<IO> where IO: Read {
@@ -18,12 +21,18 @@ impl Client<net::TcpStream> {
 
     #[inline(always)]
     ///Connects over plain TCP
-    pub fn connect() -> io::Result<Self> {
+    pub fn connect() -> crate::Result<Self> {
         Ok(Self::new(Self::socket_connect()?))
     }
 
+    #[inline]
+    ///Connects over plain TCP to specified address, instead of VNDB.
+    pub fn connect_to<A: net::ToSocketAddrs>(addr: A) -> crate::Result<Self> {
+        Ok(Self::new(net::TcpStream::connect(addr)?))
+    }
+
     ///Re-connects over plain TCP, aborting previous connection if any
-    pub fn reconnect(&mut self) -> io::Result<()> {
+    pub fn reconnect(&mut self) -> crate::Result<()> {
         let _ = self.io.get_mut().shutdown(std::net::Shutdown::Both);
 
         self.io = BufReader::new(Self::socket_connect()?);
@@ -35,9 +44,13 @@ impl Client<net::TcpStream> {
 #[cfg(feature = "rustls-on")]
 impl Client<rustls::StreamOwned<rustls::ClientConnection, net::TcpStream>> {
     fn socket_connect_tls() -> io::Result<rustls::StreamOwned<rustls::ClientConnection, net::TcpStream>> {
-        let socket = std::net::TcpStream::connect((API_HOST, super::API_SSL_PORT))?;
-
         let (dns_name, config) = super::get_rustls_config();
+        Self::socket_connect_tls_to((API_HOST, super::API_SSL_PORT), dns_name, config)
+    }
+
+    fn socket_connect_tls_to<A: net::ToSocketAddrs>(addr: A, dns_name: rustls::pki_types::ServerName<'static>, config: std::sync::Arc<rustls::ClientConfig>) -> io::Result<rustls::StreamOwned<rustls::ClientConnection, net::TcpStream>> {
+        let socket = std::net::TcpStream::connect(addr)?;
+
         let sess = match rustls::ClientConnection::new(config, dns_name) {
             Ok(sess) => sess,
             Err(error) => return Err(std::io::Error::new(std::io::ErrorKind::Other, error)),
@@ -48,12 +61,20 @@ impl Client<rustls::StreamOwned<rustls::ClientConnection, net::TcpStream>> {
 
     #[inline(always)]
     ///Connects with TLS
-    pub fn connect_tls() -> io::Result<Self> {
+    pub fn connect_tls() -> crate::Result<Self> {
         Ok(Self::new(Self::socket_connect_tls()?))
     }
 
+    #[inline]
+    ///Connects with TLS to specified address, instead of VNDB.
+    ///
+    ///Server's certificate is verified using provided name and configuration.
+    pub fn connect_tls_to<A: net::ToSocketAddrs>(addr: A, dns_name: rustls::pki_types::ServerName<'static>, config: std::sync::Arc<rustls::ClientConfig>) -> crate::Result<Self> {
+        Ok(Self::new(Self::socket_connect_tls_to(addr, dns_name, config)?))
+    }
+
     ///Re-connects over TLS, aborting previous connection if any
-    pub fn reconnect_tls(&mut self) -> io::Result<()> {
+    pub fn reconnect_tls(&mut self) -> crate::Result<()> {
         let _ = self.io.get_mut().sock.shutdown(std::net::Shutdown::Both);
 
         self.io = BufReader::new(Self::socket_connect_tls()?);
@@ -65,14 +86,121 @@ impl Client<rustls::StreamOwned<rustls::ClientConnection, net::TcpStream>> {
 impl<IO: Read + Write> Client<IO> {
     #[inline]
     ///Sends request to the server
-    pub fn send(&mut self, req: &crate::protocol::Request) -> io::Result<()> {
-        self.io.get_mut().write_fmt(format_args!("{}", req))
+    pub fn send(&mut self, req: &crate::protocol::Request) -> crate::Result<()> {
+        self.io.get_mut().write_fmt(format_args!("{}", req))?;
+        Ok(())
     }
 
     #[inline]
     ///Flushes sent requests
-    pub fn flush(&mut self) -> io::Result<()> {
-        self.io.get_mut().flush()
+    pub fn flush(&mut self) -> crate::Result<()> {
+        self.io.get_mut().flush()?;
+        Ok(())
+    }
+
+    ///Logs in and waits for response.
+    ///
+    ///Returns session token, if it is requested.
+    ///Must not be used while there are pending responses to previously sent requests.
+    pub fn login(&mut self, login: &crate::protocol::message::request::Login<'_>) -> crate::Result<Option<String>> {
+        self.send(&crate::protocol::Request::Login(login.clone()))?;
+        self.flush()?;
+        let response = self.receive()?;
+        super::expect_login(response)
+    }
+
+    ///Requests database statistics and waits for response.
+    ///
+    ///Must not be used while there are pending responses to previously sent requests.
+    pub fn dbstats(&mut self) -> crate::Result<crate::protocol::message::response::DBstats> {
+        self.send(&crate::protocol::Request::DBstats)?;
+        self.flush()?;
+        let response = self.receive()?;
+        super::expect_dbstats(response)
+    }
+
+    ///Sends get request and waits for its results.
+    ///
+    ///Must not be used while there are pending responses to previously sent requests.
+    pub fn get(&mut self, get: &crate::protocol::message::request::Get<'_>) -> crate::Result<crate::protocol::message::response::Results> {
+        self.send(&crate::protocol::Request::Get(get.clone()))?;
+        self.flush()?;
+        let response = self.receive()?;
+        super::expect_results(response)
+    }
+
+    ///Sends set request and waits for confirmation.
+    ///
+    ///Must not be used while there are pending responses to previously sent requests.
+    pub fn set(&mut self, set: &crate::protocol::message::request::Set) -> crate::Result<()> {
+        self.send(&crate::protocol::Request::Set(set.clone()))?;
+        self.flush()?;
+        let response = self.receive()?;
+        super::expect_ok(response)
+    }
+
+    ///Requests entities by IDs, splitting them into as few requests as possible.
+    ///
+    ///See [many](../many/index.html) for details.
+    ///Must not be used while there are pending responses to previously sent requests.
+    pub fn get_many<T: serde::de::DeserializeOwned>(&mut self, kind: get::Type, ids: &[u64], flags: get::Flags) -> crate::Result<Many<T>> {
+        let mut collector = many::Collector::new(kind, ids, flags);
+        while let Some(get) = collector.request() {
+            let results = self.get(&get)?;
+            collector.push(results)?;
+        }
+
+        Ok(collector.finish())
+    }
+
+    #[inline]
+    ///Requests VNs by IDs.
+    pub fn get_many_vn(&mut self, ids: &[u64], flags: get::Flags) -> crate::Result<Many<results::Vn>> {
+        self.get_many(get::Type::vn(), ids, flags)
+    }
+
+    #[inline]
+    ///Requests releases by IDs.
+    pub fn get_many_release(&mut self, ids: &[u64], flags: get::Flags) -> crate::Result<Many<results::Release>> {
+        self.get_many(get::Type::release(), ids, flags)
+    }
+
+    #[inline]
+    ///Requests producers by IDs.
+    pub fn get_many_producer(&mut self, ids: &[u64], flags: get::Flags) -> crate::Result<Many<results::Producer>> {
+        self.get_many(get::Type::producer(), ids, flags)
+    }
+
+    #[inline]
+    ///Requests characters by IDs.
+    pub fn get_many_character(&mut self, ids: &[u64], flags: get::Flags) -> crate::Result<Many<results::Character>> {
+        self.get_many(get::Type::character(), ids, flags)
+    }
+
+    #[inline]
+    ///Requests staff by IDs.
+    ///
+    ///Staff is returned as loosely typed JSON.
+    pub fn get_many_staff(&mut self, ids: &[u64], flags: get::Flags) -> crate::Result<Many<serde_json::Value>> {
+        self.get_many(get::Type::staff(), ids, flags)
+    }
+
+    #[inline]
+    ///Requests tags by IDs.
+    pub fn get_many_tag(&mut self, ids: &[u64], flags: get::Flags) -> crate::Result<Many<results::Tag>> {
+        self.get_many(get::Type::tag(), ids, flags)
+    }
+
+    #[inline]
+    ///Requests traits by IDs.
+    pub fn get_many_trait(&mut self, ids: &[u64], flags: get::Flags) -> crate::Result<Many<results::Trait>> {
+        self.get_many(get::Type::r#trait(), ids, flags)
+    }
+
+    #[inline]
+    ///Requests users by IDs.
+    pub fn get_many_user(&mut self, ids: &[u64], flags: get::Flags) -> crate::Result<Many<results::User>> {
+        self.get_many(get::Type::user(), ids, flags)
     }
 }
 
@@ -88,7 +216,9 @@ impl<IO: Read> Client<IO> {
     ///Reads single incoming response.
     ///
     ///If `None` is returned, then it means connection is closed.
-    pub fn receive(&mut self) -> io::Result<Option<crate::protocol::Response>> {
+    ///
+    ///Note that VNDB errors are returned as `Response::Error`.
+    pub fn receive(&mut self) -> crate::Result<Option<crate::protocol::Response>> {
         let size = self.io.read_until(0x04, &mut self.read_buf)?;
 
         if size == 0 {
@@ -97,6 +227,6 @@ impl<IO: Read> Client<IO> {
 
         let result = super::parse_response(&self.read_buf);
         self.read_buf.clear();
-        result
+        result.map(Some)
     }
 }
@@ -6,6 +6,9 @@ use tokio::net;
 use tokio::io::{self, AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
 
 use super::{API_HOST, API_PORT};
+use super::many::{self, Many};
+use crate::protocol::message::request::get;
+use crate::protocol::message::response::results;
 use crate::utils::AsPin;
 
 ///Tokio based VNDB Client
@@ -21,12 +24,18 @@ impl Client<net::TcpStream> {
 
     #[inline(always)]
     ///Connects over plain TCP
-    pub async fn connect() -> io::Result<Self> {
+    pub async fn connect() -> crate::Result<Self> {
         Ok(Self::new(Self::socket_connect().await?))
     }
 
+    #[inline]
+    ///Connects over plain TCP to specified address, instead of VNDB.
+    pub async fn connect_to<A: net::ToSocketAddrs>(addr: A) -> crate::Result<Self> {
+        Ok(Self::new(net::TcpStream::connect(addr).await?))
+    }
+
     ///Re-connects over plain TCP, aborting previous connection if any
-    pub async fn reconnect(&mut self) -> io::Result<()> {
+    pub async fn reconnect(&mut self) -> crate::Result<()> {
         let _ = self.io.get_mut().shutdown().await;
 
         self.io = BufReader::new(Self::socket_connect().await?);
@@ -39,9 +48,13 @@ impl Client<net::TcpStream> {
 #[cfg(feature = "rustls-on")]
 impl Client<tokio_rustls::client::TlsStream<net::TcpStream>> {
     async fn socket_connect_tls() -> io::Result<tokio_rustls::client::TlsStream<net::TcpStream>> {
-        let socket = net::TcpStream::connect((API_HOST, super::API_SSL_PORT)).await?;
-
         let (dns_name, config) = super::get_rustls_config();
+        Self::socket_connect_tls_to((API_HOST, super::API_SSL_PORT), dns_name, config).await
+    }
+
+    async fn socket_connect_tls_to<A: net::ToSocketAddrs>(addr: A, dns_name: rustls::pki_types::ServerName<'static>, config: std::sync::Arc<rustls::ClientConfig>) -> io::Result<tokio_rustls::client::TlsStream<net::TcpStream>> {
+        let socket = net::TcpStream::connect(addr).await?;
+
         let config = tokio_rustls::TlsConnector::from(config);
 
         config.connect(dns_name, socket).await
@@ -49,12 +62,20 @@ impl Client<tokio_rustls::client::TlsStream<net::TcpStream>> {
 
     #[inline(always)]
     ///Connects with TLS
-    pub async fn connect_tls() -> io::Result<Self> {
+    pub async fn connect_tls() -> crate::Result<Self> {
         Ok(Self::new(Self::socket_connect_tls().await?))
     }
 
+    #[inline]
+    ///Connects with TLS to specified address, instead of VNDB.
+    ///
+    ///Server's certificate is verified using provided name and configuration.
+    pub async fn connect_tls_to<A: net::ToSocketAddrs>(addr: A, dns_name: rustls::pki_types::ServerName<'static>, config: std::sync::Arc<rustls::ClientConfig>) -> crate::Result<Self> {
+        Ok(Self::new(Self::socket_connect_tls_to(addr, dns_name, config).await?))
+    }
+
     ///Re-connects over TLS, aborting previous connection if any
-    pub async fn reconnect_tls(&mut self) -> io::Result<()> {
+    pub async fn reconnect_tls(&mut self) -> crate::Result<()> {
         let _ = self.io.get_mut().get_mut().0.shutdown().await;
 
         self.io = BufReader::new(Self::socket_connect_tls().await?);
@@ -66,16 +87,123 @@ impl Client<tokio_rustls::client::TlsStream<net::TcpStream>> {
 impl<IO: AsyncRead + AsyncWrite> Client<IO> {
     #[inline]
     ///Sends request to the server
-    pub async fn send(&mut self, req: &crate::protocol::Request<'_>) -> io::Result<()> {
+    pub async fn send(&mut self, req: &crate::protocol::Request<'_>) -> crate::Result<()> {
         let io = self.io.as_pin();
-        BufReader::get_pin_mut(io).write_all(req.to_string().as_bytes()).await
+        BufReader::get_pin_mut(io).write_all(req.to_string().as_bytes()).await?;
+        Ok(())
     }
 
     #[inline]
     ///Flushes sent requests
-    pub async fn flush(&mut self) -> io::Result<()> {
+    pub async fn flush(&mut self) -> crate::Result<()> {
         let io = self.io.as_pin();
-        BufReader::get_pin_mut(io).flush().await
+        BufReader::get_pin_mut(io).flush().await?;
+        Ok(())
+    }
+
+    ///Logs in and waits for response.
+    ///
+    ///Returns session token, if it is requested.
+    ///Must not be used while there are pending responses to previously sent requests.
+    pub async fn login(&mut self, login: &crate::protocol::message::request::Login<'_>) -> crate::Result<Option<String>> {
+        self.send(&crate::protocol::Request::Login(login.clone())).await?;
+        self.flush().await?;
+        let response = self.receive().await?;
+        super::expect_login(response)
+    }
+
+    ///Requests database statistics and waits for response.
+    ///
+    ///Must not be used while there are pending responses to previously sent requests.
+    pub async fn dbstats(&mut self) -> crate::Result<crate::protocol::message::response::DBstats> {
+        self.send(&crate::protocol::Request::DBstats).await?;
+        self.flush().await?;
+        let response = self.receive().await?;
+        super::expect_dbstats(response)
+    }
+
+    ///Sends get request and waits for its results.
+    ///
+    ///Must not be used while there are pending responses to previously sent requests.
+    pub async fn get(&mut self, get: &crate::protocol::message::request::Get<'_>) -> crate::Result<crate::protocol::message::response::Results> {
+        self.send(&crate::protocol::Request::Get(get.clone())).await?;
+        self.flush().await?;
+        let response = self.receive().await?;
+        super::expect_results(response)
+    }
+
+    ///Sends set request and waits for confirmation.
+    ///
+    ///Must not be used while there are pending responses to previously sent requests.
+    pub async fn set(&mut self, set: &crate::protocol::message::request::Set) -> crate::Result<()> {
+        self.send(&crate::protocol::Request::Set(set.clone())).await?;
+        self.flush().await?;
+        let response = self.receive().await?;
+        super::expect_ok(response)
+    }
+
+    ///Requests entities by IDs, splitting them into as few requests as possible.
+    ///
+    ///See [many](../many/index.html) for details.
+    ///Must not be used while there are pending responses to previously sent requests.
+    pub async fn get_many<T: serde::de::DeserializeOwned>(&mut self, kind: get::Type, ids: &[u64], flags: get::Flags) -> crate::Result<Many<T>> {
+        let mut collector = many::Collector::new(kind, ids, flags);
+        while let Some(get) = collector.request() {
+            let results = self.get(&get).await?;
+            collector.push(results)?;
+        }
+
+        Ok(collector.finish())
+    }
+
+    #[inline]
+    ///Requests VNs by IDs.
+    pub async fn get_many_vn(&mut self, ids: &[u64], flags: get::Flags) -> crate::Result<Many<results::Vn>> {
+        self.get_many(get::Type::vn(), ids, flags).await
+    }
+
+    #[inline]
+    ///Requests releases by IDs.
+    pub async fn get_many_release(&mut self, ids: &[u64], flags: get::Flags) -> crate::Result<Many<results::Release>> {
+        self.get_many(get::Type::release(), ids, flags).await
+    }
+
+    #[inline]
+    ///Requests producers by IDs.
+    pub async fn get_many_producer(&mut self, ids: &[u64], flags: get::Flags) -> crate::Result<Many<results::Producer>> {
+        self.get_many(get::Type::producer(), ids, flags).await
+    }
+
+    #[inline]
+    ///Requests characters by IDs.
+    pub async fn get_many_character(&mut self, ids: &[u64], flags: get::Flags) -> crate::Result<Many<results::Character>> {
+        self.get_many(get::Type::character(), ids, flags).await
+    }
+
+    #[inline]
+    ///Requests staff by IDs.
+    ///
+    ///Staff is returned as loosely typed JSON.
+    pub async fn get_many_staff(&mut self, ids: &[u64], flags: get::Flags) -> crate::Result<Many<serde_json::Value>> {
+        self.get_many(get::Type::staff(), ids, flags).await
+    }
+
+    #[inline]
+    ///Requests tags by IDs.
+    pub async fn get_many_tag(&mut self, ids: &[u64], flags: get::Flags) -> crate::Result<Many<results::Tag>> {
+        self.get_many(get::Type::tag(), ids, flags).await
+    }
+
+    #[inline]
+    ///Requests traits by IDs.
+    pub async fn get_many_trait(&mut self, ids: &[u64], flags: get::Flags) -> crate::Result<Many<results::Trait>> {
+        self.get_many(get::Type::r#trait(), ids, flags).await
+    }
+
+    #[inline]
+    ///Requests users by IDs.
+    pub async fn get_many_user(&mut self, ids: &[u64], flags: get::Flags) -> crate::Result<Many<results::User>> {
+        self.get_many(get::Type::user(), ids, flags).await
     }
 }
 
@@ -91,7 +219,9 @@ impl<IO: AsyncRead> Client<IO> {
     ///Reads single incoming response.
     ///
     ///If `None` is returned, then it means connection is closed.
-    pub async fn receive(&mut self) -> io::Result<Option<crate::protocol::Response>> {
+    ///
+    ///Note that VNDB errors are returned as `Response::Error`.
+    pub async fn receive(&mut self) -> crate::Result<Option<crate::protocol::Response>> {
         let mut io = self.io.as_pin();
 
         let size = io.read_until(0x04, &mut self.read_buf).await?;
@@ -102,6 +232,6 @@ impl<IO: AsyncRead> Client<IO> {
 
         let result = super::parse_response(&self.read_buf);
         self.read_buf.clear();
-        result
+        result.map(Some)
     }
 }
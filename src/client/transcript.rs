@@ -0,0 +1,419 @@
+//!Record and replay of client sessions.
+//!
+//![Recorder](struct.Recorder.html) wraps IO object and writes each sent request and received response
+//!into transcript in [JSON Lines](https://jsonlines.org/) format.
+//![Replayer](struct.Replayer.html) serves transcript back, verifying that requests match recorded ones.
+//!
+//!Both can be used as IO object of [simple](../simple/struct.Client.html) and [tokio](../tokio/struct.Client.html) clients.
+//!
+//!Example of usage:
+//!
+//!```
+//!use vndb::client::simple::Client;
+//!use vndb::client::transcript::{Replayer, ReplayOptions};
+//!
+//!let transcript = r#"{"elapsed_ms":0,"direction":"send","frame":"dbstats"}
+//!{"elapsed_ms":35,"direction":"recv","frame":"dbstats {\"tags\":1,\"releases\":2,\"producers\":3,\"chars\":4,\"vn\":5,\"traits\":6}"}
+//!"#;
+//!
+//!let replayer = Replayer::from_reader(transcript.as_bytes(), ReplayOptions::default()).expect("To read transcript");
+//!let mut client = Client::new(replayer);
+//!assert_eq!(client.dbstats().expect("To get dbstats").vn, 5);
+//!```
+
+use std::{fs, io};
+use std::io::{BufRead, Read, Write};
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use serde::{Serialize, Deserialize};
+
+use crate::protocol::message::Request;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+///Direction of transcript's frame.
+pub enum Direction {
+    #[serde(rename = "send")]
+    ///Request sent by client.
+    Send,
+    #[serde(rename = "recv")]
+    ///Response received by client.
+    Receive,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+///Single frame of transcript.
+pub struct Entry {
+    ///Milliseconds since start of recording.
+    pub elapsed_ms: u64,
+    ///Direction of frame.
+    pub direction: Direction,
+    ///Frame's text without `0x04` byte.
+    pub frame: String,
+}
+
+const REDACTED: &str = "<redacted>";
+
+//Removes credentials from login request.
+fn without_credentials(frame: &str, replacement: Option<&str>) -> Option<String> {
+    match Request::from_str(frame) {
+        Ok(Request::Login(mut login)) => {
+            login.creds = match (login.creds, replacement) {
+                (Some(_), Some(replacement)) => Some((replacement.into(), replacement.into())),
+                _ => None,
+            };
+            login.session = match (login.session, replacement) {
+                (Some(_), Some(replacement)) => Some((replacement.into(), replacement.into())),
+                _ => None,
+            };
+            Some(Request::Login(login).to_string().trim_end_matches('\x04').to_owned())
+        },
+        _ => None,
+    }
+}
+
+fn invalid_data<E: Into<Box<dyn std::error::Error + Send + Sync>>>(error: E) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, error)
+}
+
+//Extracts complete frames from buffer.
+fn drain_frames(buf: &mut Vec<u8>) -> Vec<String> {
+    let mut frames = Vec::new();
+    while let Some(end) = buf.iter().position(|byte| *byte == 0x04) {
+        let frame = buf.drain(..=end).collect::<Vec<_>>();
+        frames.push(String::from_utf8_lossy(&frame[..end]).into_owned());
+    }
+    frames
+}
+
+///IO wrapper that records session into transcript.
+pub struct Recorder<IO, W: Write> {
+    io: IO,
+    transcript: W,
+    start: Instant,
+    redact_credentials: bool,
+    send_buf: Vec<u8>,
+    recv_buf: Vec<u8>,
+}
+
+impl<IO> Recorder<IO, io::BufWriter<fs::File>> {
+    ///Creates new instance, writing transcript into specified file.
+    ///
+    ///File is truncated, if it exists.
+    pub fn create<P: AsRef<std::path::Path>>(io: IO, path: P) -> io::Result<Self> {
+        Ok(Self::new(io, io::BufWriter::new(fs::File::create(path)?)))
+    }
+}
+
+impl<IO, W: Write> Recorder<IO, W> {
+    ///Creates new instance, writing transcript into provided writer.
+    pub fn new(io: IO, transcript: W) -> Self {
+        Self {
+            io,
+            transcript,
+            start: Instant::now(),
+            redact_credentials: false,
+            send_buf: Vec::new(),
+            recv_buf: Vec::new(),
+        }
+    }
+
+    ///Sets whether to replace credentials of login requests and session tokens, issued by server, in transcript.
+    ///
+    ///Such transcript must be replayed with `ignore_credentials` option.
+    pub fn redact_credentials(mut self, redact_credentials: bool) -> Self {
+        self.redact_credentials = redact_credentials;
+        self
+    }
+
+    #[inline]
+    ///Returns reference to underlying IO object.
+    pub fn get_ref(&self) -> &IO {
+        &self.io
+    }
+
+    #[inline]
+    ///Returns mutable reference to underlying IO object.
+    pub fn get_mut(&mut self) -> &mut IO {
+        &mut self.io
+    }
+
+    ///Returns underlying IO object and transcript's writer.
+    pub fn into_inner(self) -> (IO, W) {
+        (self.io, self.transcript)
+    }
+
+    fn record(&mut self, direction: Direction, data: &[u8]) -> io::Result<()> {
+        let buf = match direction {
+            Direction::Send => &mut self.send_buf,
+            Direction::Receive => &mut self.recv_buf,
+        };
+        buf.extend_from_slice(data);
+
+        let frames = drain_frames(buf);
+        if frames.is_empty() {
+            return Ok(());
+        }
+
+        let elapsed_ms = self.start.elapsed().as_millis() as u64;
+        for frame in frames {
+            let frame = match (direction, self.redact_credentials) {
+                (Direction::Send, true) => without_credentials(&frame, Some(REDACTED)).unwrap_or(frame),
+                (Direction::Receive, true) if frame.starts_with("session ") => format!("session {}", REDACTED),
+                _ => frame,
+            };
+
+            let entry = Entry {
+                elapsed_ms,
+                direction,
+                frame,
+            };
+            serde_json::to_writer(&mut self.transcript, &entry).map_err(invalid_data)?;
+            self.transcript.write_all(b"\n")?;
+        }
+
+        self.transcript.flush()
+    }
+}
+
+impl<IO: Read, W: Write> Read for Recorder<IO, W> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let size = self.io.read(buf)?;
+        self.record(Direction::Receive, &buf[..size])?;
+        Ok(size)
+    }
+}
+
+impl<IO: Write, W: Write> Write for Recorder<IO, W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let size = self.io.write(buf)?;
+        self.record(Direction::Send, &buf[..size])?;
+        Ok(size)
+    }
+
+    #[inline]
+    fn flush(&mut self) -> io::Result<()> {
+        self.io.flush()
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default)]
+///Options of [Replayer](struct.Replayer.html)
+pub struct ReplayOptions {
+    ///Ignores username and password, when comparing login requests.
+    pub ignore_credentials: bool,
+    ///Delays responses according to recorded timing.
+    ///
+    ///Only applicable to synchronous IO.
+    pub realtime: bool,
+}
+
+///IO object that serves recorded transcript.
+///
+///Writes fail with `InvalidData` error, when request doesn't match recorded one.
+///Reads fail with `InvalidInput` error, when transcript expects request to be sent first.
+pub struct Replayer {
+    entries: VecDeque<Entry>,
+    options: ReplayOptions,
+    start: Instant,
+    send_buf: Vec<u8>,
+    recv_buf: VecDeque<u8>,
+}
+
+impl Replayer {
+    ///Creates new instance from transcript's entries.
+    pub fn new(entries: Vec<Entry>, options: ReplayOptions) -> Self {
+        Self {
+            entries: entries.into(),
+            options,
+            start: Instant::now(),
+            send_buf: Vec::new(),
+            recv_buf: VecDeque::new(),
+        }
+    }
+
+    ///Creates new instance by reading transcript in JSON Lines format.
+    pub fn from_reader<R: BufRead>(reader: R, options: ReplayOptions) -> io::Result<Self> {
+        let mut entries = Vec::new();
+        for line in reader.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            entries.push(serde_json::from_str(&line).map_err(invalid_data)?);
+        }
+
+        Ok(Self::new(entries, options))
+    }
+
+    #[inline]
+    ///Creates new instance by reading transcript file.
+    pub fn open<P: AsRef<std::path::Path>>(path: P, options: ReplayOptions) -> io::Result<Self> {
+        Self::from_reader(io::BufReader::new(fs::File::open(path)?), options)
+    }
+
+    ///Returns number of transcript's entries, that are not replayed yet.
+    pub fn remaining(&self) -> usize {
+        self.entries.len()
+    }
+
+    fn is_same_request(&self, expected: &str, actual: &str) -> bool {
+        if expected == actual {
+            return true;
+        }
+
+        match self.options.ignore_credentials {
+            true => match (without_credentials(expected, None), without_credentials(actual, None)) {
+                (Some(expected), Some(actual)) => expected == actual,
+                _ => false,
+            },
+            false => false,
+        }
+    }
+
+    fn on_send(&mut self, buf: &[u8]) -> io::Result<()> {
+        self.send_buf.extend_from_slice(buf);
+
+        for frame in drain_frames(&mut self.send_buf) {
+            //Responses to previous requests may be not read yet.
+            let idx = match self.entries.iter().position(|entry| entry.direction == Direction::Send) {
+                Some(idx) => idx,
+                None => return Err(invalid_data(format!("Unexpected request '{}': transcript has no more requests", frame))),
+            };
+
+            if !self.is_same_request(&self.entries[idx].frame, &frame) {
+                return Err(invalid_data(format!("Request mismatch: expected '{}', got '{}'", self.entries[idx].frame, frame)));
+            }
+
+            self.entries.remove(idx);
+        }
+
+        Ok(())
+    }
+
+    //Returns delay before response can be read.
+    fn on_receive(&mut self) -> io::Result<Option<Duration>> {
+        if !self.recv_buf.is_empty() {
+            return Ok(None);
+        }
+
+        match self.entries.front() {
+            Some(entry) if entry.direction == Direction::Receive => (),
+            Some(entry) => return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("Transcript expects request '{}' to be sent", entry.frame))),
+            None => return Ok(None),
+        }
+
+        let entry = match self.entries.pop_front() {
+            Some(entry) => entry,
+            None => unreachable!(),
+        };
+
+        self.recv_buf.extend(entry.frame.as_bytes());
+        self.recv_buf.push_back(0x04);
+
+        let delay = Duration::from_millis(entry.elapsed_ms).checked_sub(self.start.elapsed());
+        Ok(delay)
+    }
+
+    fn read_buf(&mut self, buf: &mut [u8]) -> usize {
+        let size = buf.len().min(self.recv_buf.len());
+        for (dest, byte) in buf.iter_mut().zip(self.recv_buf.drain(..size)) {
+            *dest = byte;
+        }
+        size
+    }
+}
+
+impl Read for Replayer {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if let Some(delay) = self.on_receive()? {
+            if self.options.realtime {
+                std::thread::sleep(delay);
+            }
+        }
+
+        Ok(self.read_buf(buf))
+    }
+}
+
+impl Write for Replayer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.on_send(buf)?;
+        Ok(buf.len())
+    }
+
+    #[inline]
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(feature = "tokio-on")]
+mod tokio_impl {
+    use core::pin::Pin;
+    use core::task::{Context, Poll};
+    use std::io::{self, Write};
+
+    use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+    use super::{Direction, Recorder, Replayer};
+
+    impl<IO: AsyncRead + Unpin, W: Write + Unpin> AsyncRead for Recorder<IO, W> {
+        fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+            let this = self.get_mut();
+            let filled = buf.filled().len();
+
+            match Pin::new(&mut this.io).poll_read(cx, buf) {
+                Poll::Ready(Ok(())) => Poll::Ready(this.record(Direction::Receive, &buf.filled()[filled..])),
+                result => result,
+            }
+        }
+    }
+
+    impl<IO: AsyncWrite + Unpin, W: Write + Unpin> AsyncWrite for Recorder<IO, W> {
+        fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+            let this = self.get_mut();
+
+            match Pin::new(&mut this.io).poll_write(cx, buf) {
+                Poll::Ready(Ok(size)) => Poll::Ready(this.record(Direction::Send, &buf[..size]).map(|_| size)),
+                result => result,
+            }
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Pin::new(&mut self.get_mut().io).poll_flush(cx)
+        }
+
+        fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Pin::new(&mut self.get_mut().io).poll_shutdown(cx)
+        }
+    }
+
+    impl AsyncRead for Replayer {
+        fn poll_read(self: Pin<&mut Self>, _: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+            let this = self.get_mut();
+            if let Err(error) = this.on_receive() {
+                return Poll::Ready(Err(error));
+            }
+
+            let size = this.read_buf(buf.initialize_unfilled());
+            buf.advance(size);
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    impl AsyncWrite for Replayer {
+        fn poll_write(self: Pin<&mut Self>, _: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+            Poll::Ready(self.get_mut().on_send(buf).map(|_| buf.len()))
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn poll_shutdown(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+    }
+}
@@ -0,0 +1,269 @@
+//!Reader of VNDB [database dumps](https://vndb.org/d14).
+//!
+//!All dumps are read in streaming fashion and can be compressed with gzip or zstd,
+//!which is detected by content of file.
+//!
+//!- Database dump must be extracted into directory, see [Database](struct.Database.html);
+//!- Tags and traits dumps are JSON arrays, see [tags](fn.tags.html) and [traits](fn.traits.html);
+//!- Votes dump is text file with a vote per line, see [votes](fn.votes.html).
+//!
+//!Example of usage:
+//!
+//!```no_run
+//!let tags = vndb::dump::tags("vndb-tags-latest.json.gz").expect("To open tags dump");
+//!for tag in tags {
+//!    let tag = tag.expect("To read tag");
+//!    println!("{}: {}", tag.id, tag.name);
+//!}
+//!```
+
+use std::{fs, io};
+use std::io::{BufRead, Read};
+use std::marker::PhantomData;
+use std::path::Path;
+
+use crate::protocol::message::response::results;
+
+pub mod table;
+mod database;
+
+pub use database::{Database, Entities};
+
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+
+fn invalid_data<E: Into<Box<dyn std::error::Error + Send + Sync>>>(error: E) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, error)
+}
+
+///Wraps reader with decoder, according to its compression.
+pub fn decompress<R: Read + 'static>(reader: R) -> io::Result<Box<dyn BufRead>> {
+    let mut reader = io::BufReader::new(reader);
+    let header = reader.fill_buf()?;
+
+    if header.starts_with(&GZIP_MAGIC) {
+        Ok(Box::new(io::BufReader::new(flate2::bufread::MultiGzDecoder::new(reader))))
+    } else if header.starts_with(&ZSTD_MAGIC) {
+        Ok(Box::new(io::BufReader::new(zstd::stream::read::Decoder::with_buffer(reader)?)))
+    } else {
+        Ok(Box::new(reader))
+    }
+}
+
+#[inline]
+///Opens file, decompressing it if necessary.
+pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Box<dyn BufRead>> {
+    decompress(fs::File::open(path)?)
+}
+
+///Iterator over elements of JSON array, that doesn't load whole array into memory.
+pub struct JsonArray<R, T> {
+    reader: R,
+    buf: Vec<u8>,
+    is_started: bool,
+    is_finished: bool,
+    _type: PhantomData<fn() -> T>,
+}
+
+impl<R: BufRead, T: serde::de::DeserializeOwned> JsonArray<R, T> {
+    ///Creates new instance.
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            buf: Vec::new(),
+            is_started: false,
+            is_finished: false,
+            _type: PhantomData,
+        }
+    }
+
+    fn next_byte(&mut self) -> io::Result<Option<u8>> {
+        let byte = match self.reader.fill_buf()?.first() {
+            Some(byte) => *byte,
+            None => return Ok(None),
+        };
+        self.reader.consume(1);
+        Ok(Some(byte))
+    }
+
+    fn next_non_whitespace(&mut self) -> io::Result<Option<u8>> {
+        loop {
+            match self.next_byte()? {
+                Some(byte) if byte.is_ascii_whitespace() => continue,
+                byte => return Ok(byte),
+            }
+        }
+    }
+
+    //Reads next element into buffer, returning whether there is element.
+    fn read_element(&mut self) -> io::Result<bool> {
+        if !self.is_started {
+            self.is_started = true;
+            if self.next_non_whitespace()? != Some(b'[') {
+                return Err(invalid_data("Expected JSON array"));
+            }
+        }
+
+        self.buf.clear();
+        let mut depth = 0usize;
+        let mut is_string = false;
+        let mut is_escape = false;
+
+        loop {
+            let byte = match self.next_byte()? {
+                Some(byte) => byte,
+                None => return Err(io::ErrorKind::UnexpectedEof.into()),
+            };
+
+            if is_string {
+                self.buf.push(byte);
+                match byte {
+                    _ if is_escape => is_escape = false,
+                    b'\\' => is_escape = true,
+                    b'"' => is_string = false,
+                    _ => (),
+                }
+                continue;
+            }
+
+            match byte {
+                b',' | b']' if depth == 0 => {
+                    if byte == b']' {
+                        self.is_finished = true;
+                    }
+
+                    return match self.buf.iter().all(u8::is_ascii_whitespace) {
+                        //Empty array
+                        true if byte == b']' => Ok(false),
+                        true => Err(invalid_data("Expected JSON value")),
+                        false => Ok(true),
+                    };
+                },
+                b'"' => is_string = true,
+                b'[' | b'{' => depth += 1,
+                b']' | b'}' => depth = match depth.checked_sub(1) {
+                    Some(depth) => depth,
+                    None => return Err(invalid_data("Unbalanced JSON brackets")),
+                },
+                _ => (),
+            }
+
+            self.buf.push(byte);
+        }
+    }
+}
+
+impl<R: BufRead, T: serde::de::DeserializeOwned> Iterator for JsonArray<R, T> {
+    type Item = io::Result<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.is_finished {
+            return None;
+        }
+
+        match self.read_element() {
+            Ok(true) => Some(serde_json::from_slice(&self.buf).map_err(invalid_data)),
+            Ok(false) => None,
+            Err(error) => {
+                self.is_finished = true;
+                Some(Err(error))
+            },
+        }
+    }
+}
+
+///Reads tags dump.
+pub fn tags<P: AsRef<Path>>(path: P) -> io::Result<JsonArray<Box<dyn BufRead>, results::Tag>> {
+    open(path).map(JsonArray::new)
+}
+
+///Reads traits dump.
+pub fn traits<P: AsRef<Path>>(path: P) -> io::Result<JsonArray<Box<dyn BufRead>, results::Trait>> {
+    open(path).map(JsonArray::new)
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+///User's vote from votes dump.
+pub struct Vote {
+    ///VN's ID.
+    pub vn: u64,
+    ///User's ID.
+    pub uid: u64,
+    ///Vote value in range from 10 to 100.
+    pub vote: u8,
+    ///Date of vote in format `YYYY-MM-DD`.
+    pub date: String,
+}
+
+impl Vote {
+    ///Parses line of votes dump in format `<vn> <uid> <vote> <date>`.
+    ///
+    ///IDs can be with or without type prefix.
+    pub fn from_str(line: &str) -> Option<Self> {
+        fn id(text: &str) -> Option<u64> {
+            text.trim_start_matches(|ch: char| ch.is_ascii_alphabetic()).parse().ok()
+        }
+
+        let mut parts = line.split_ascii_whitespace();
+        let vote = Vote {
+            vn: id(parts.next()?)?,
+            uid: id(parts.next()?)?,
+            vote: parts.next()?.parse().ok()?,
+            date: parts.next()?.to_owned(),
+        };
+
+        match parts.next() {
+            Some(_) => None,
+            None => Some(vote),
+        }
+    }
+}
+
+///Iterator over votes dump.
+pub struct Votes<R> {
+    reader: R,
+    line: String,
+    line_num: usize,
+}
+
+impl<R: BufRead> Votes<R> {
+    ///Creates new instance.
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            line: String::new(),
+            line_num: 0,
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for Votes<R> {
+    type Item = io::Result<Vote>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            self.line.clear();
+            self.line_num += 1;
+            match self.reader.read_line(&mut self.line) {
+                Ok(0) => return None,
+                Ok(_) => (),
+                Err(error) => return Some(Err(error)),
+            }
+
+            let line = self.line.trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            return match Vote::from_str(line) {
+                Some(vote) => Some(Ok(vote)),
+                None => Some(Err(invalid_data(format!("Invalid vote at line {}", self.line_num)))),
+            };
+        }
+    }
+}
+
+///Reads votes dump.
+pub fn votes<P: AsRef<Path>>(path: P) -> io::Result<Votes<Box<dyn BufRead>>> {
+    open(path).map(Votes::new)
+}
@@ -0,0 +1,466 @@
+use std::collections::HashMap;
+use std::io;
+use std::io::BufRead;
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+
+use serde_json::{json, Value};
+
+use super::invalid_data;
+use super::table::{Row, Table};
+use crate::protocol::message::response::results;
+
+const IMAGE_URL: &str = "https://s2.vndb.org";
+
+type Titles = HashMap<u64, Vec<Title>>;
+//`aid -> (sid, name, original)`
+type StaffAliases = HashMap<u64, Vec<(u64, Value, Value)>>;
+
+struct Title {
+    lang: String,
+    title: String,
+    latin: Option<String>,
+}
+
+//Returns pair of `title` and `original` in API format.
+fn main_title(titles: Option<&Vec<Title>>, olang: Option<&str>) -> (Value, Value) {
+    let titles = match titles {
+        Some(titles) => titles,
+        None => return (Value::Null, Value::Null),
+    };
+
+    let title = match olang {
+        Some(olang) => titles.iter().find(|title| title.lang == olang).or_else(|| titles.first()),
+        None => titles.first(),
+    };
+
+    match title {
+        Some(Title { latin: Some(latin), title, .. }) => (latin.as_str().into(), title.as_str().into()),
+        Some(Title { title, .. }) => (title.as_str().into(), Value::Null),
+        None => (Value::Null, Value::Null),
+    }
+}
+
+//Converts date in format `YYYYMMDD` into API format.
+fn date(date: Option<u32>) -> Value {
+    match date {
+        None | Some(0) => Value::Null,
+        Some(99999999) => "tba".into(),
+        Some(date) => {
+            let (year, month, day) = (date / 10000, date / 100 % 100, date % 100);
+            match (month, day) {
+                (99, _) => format!("{:04}", year).into(),
+                (month, 99) => format!("{:04}-{:02}", year, month).into(),
+                (month, day) => format!("{:04}-{:02}-{:02}", year, month, day).into(),
+            }
+        },
+    }
+}
+
+//Converts image ID, such as `cv1234`, into URL.
+fn image(id: Option<&str>) -> Value {
+    let id = match id {
+        Some(id) => id,
+        None => return Value::Null,
+    };
+
+    let kind_len = id.find(|ch: char| ch.is_ascii_digit()).unwrap_or(id.len());
+    let (kind, num) = id.split_at(kind_len);
+    match num.parse::<u64>() {
+        Ok(num) => format!("{}/{}/{:02}/{}.jpg", IMAGE_URL, kind, num % 100, num).into(),
+        Err(_) => Value::Null,
+    }
+}
+
+//Zero means that value is unknown.
+fn non_zero(value: Option<u64>) -> Value {
+    match value {
+        None | Some(0) => Value::Null,
+        Some(value) => value.into(),
+    }
+}
+
+///Iterator over typed entities of database dump.
+pub struct Entities<T> {
+    table: Table<Box<dyn BufRead>>,
+    build: Box<dyn FnMut(&Row) -> Value>,
+    _type: PhantomData<fn() -> T>,
+}
+
+impl<T: serde::de::DeserializeOwned> Iterator for Entities<T> {
+    type Item = io::Result<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let row = match self.table.next()? {
+            Ok(row) => row,
+            Err(error) => return Some(Err(error)),
+        };
+
+        let entity = (self.build)(&row);
+        Some(T::deserialize(&entity).map_err(|error| invalid_data(format!("Entity {}: {}", entity["id"], error))))
+    }
+}
+
+///Extracted database dump.
+///
+///Entities are produced by streaming through main table of entity, such as `vn`,
+///while related tables, such as `vn_titles`, are loaded into memory beforehand.
+///Missing related tables are treated as empty.
+///
+///Entities are produced in format of API, but following information is not available:
+///
+///- VN's anime and screenshots;
+///- Image flagging.
+pub struct Database {
+    dir: PathBuf,
+}
+
+impl Database {
+    ///Opens database, using path to extracted `db` directory.
+    pub fn open<P: AsRef<Path>>(dir: P) -> io::Result<Self> {
+        let dir = dir.as_ref();
+        match dir.is_dir() {
+            true => Ok(Self {
+                dir: dir.to_owned(),
+            }),
+            false => Err(io::Error::new(io::ErrorKind::NotFound, format!("{}: no such directory", dir.display()))),
+        }
+    }
+
+    #[inline]
+    ///Opens table by name.
+    pub fn table(&self, name: &str) -> io::Result<Table<Box<dyn BufRead>>> {
+        Table::open(&self.dir, name)
+    }
+
+    //Loads related table, grouping its rows by key column.
+    fn load<T, F: FnMut(&Row) -> Option<T>>(&self, name: &str, key: &str, mut map: F) -> io::Result<HashMap<u64, Vec<T>>> {
+        let table = match self.table(name) {
+            Ok(table) => table,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(HashMap::new()),
+            Err(error) => return Err(error),
+        };
+
+        let mut result = HashMap::<u64, Vec<T>>::new();
+        for row in table {
+            let row = row?;
+            if let (Some(key), Some(value)) = (row.get_id(key), map(&row)) {
+                result.entry(key).or_default().push(value);
+            }
+        }
+
+        Ok(result)
+    }
+
+    fn load_titles(&self, name: &str) -> io::Result<Titles> {
+        self.load(name, "id", |row| Some(Title {
+            lang: row.get("lang")?.to_owned(),
+            title: row.get("title")?.to_owned(),
+            latin: row.get("latin").map(str::to_owned),
+        }))
+    }
+
+    //Loads main titles of VNs, in API format.
+    fn load_vn_titles(&self) -> io::Result<HashMap<u64, (Value, Value)>> {
+        let titles = self.load_titles("vn_titles")?;
+        let olangs = self.load("vn", "id", |row| row.get("olang").map(str::to_owned))?;
+
+        Ok(titles.iter().map(|(id, vn_titles)| {
+            let olang = olangs.get(id).and_then(|olang| olang.first()).map(String::as_str);
+            (*id, main_title(Some(vn_titles), olang))
+        }).collect())
+    }
+
+    fn load_staff_aliases(&self) -> io::Result<StaffAliases> {
+        self.load("staff_alias", "aid", |row| {
+            let name = row.get("name")?;
+            let (name, original) = match row.get("latin") {
+                Some(latin) => (Value::from(latin), Value::from(name)),
+                None => (Value::from(name), Value::Null),
+            };
+            Some((row.get_id("id")?, name, original))
+        })
+    }
+
+    fn entities<T>(&self, name: &str, build: Box<dyn FnMut(&Row) -> Value>) -> io::Result<Entities<T>> {
+        Ok(Entities {
+            table: self.table(name)?,
+            build,
+            _type: PhantomData,
+        })
+    }
+
+    ///Reads VNs.
+    pub fn vn(&self) -> io::Result<Entities<results::Vn>> {
+        let mut titles = self.load_titles("vn_titles")?;
+        let vn_titles = self.load_vn_titles()?;
+        let mut relations = self.load("vn_relations", "id", |row| {
+            let vid = row.get_id("vid")?;
+            let (title, original) = vn_titles.get(&vid)?.clone();
+            Some(json!({
+                "id": vid,
+                "relation": row.get("relation")?,
+                "title": title,
+                "original": original,
+                "official": row.get_bool("official").unwrap_or(true),
+            }))
+        })?;
+
+        //tag -> (score sum, votes, spoiler sum, spoiler votes)
+        let mut votes = HashMap::<u64, HashMap<u64, (i64, i64, i64, i64)>>::new();
+        for (vid, tag_votes) in self.load("tags_vn", "vid", |row| {
+            match row.get_bool("ignore") {
+                Some(true) => None,
+                _ => Some((row.get_id("tag")?, row.get_num::<i64>("vote")?, row.get_num::<i64>("spoiler"))),
+            }
+        })? {
+            let tags = votes.entry(vid).or_default();
+            for (tag, vote, spoiler) in tag_votes {
+                let entry = tags.entry(tag).or_default();
+                entry.0 += vote;
+                entry.1 += 1;
+                if let Some(spoiler) = spoiler {
+                    entry.2 += spoiler;
+                    entry.3 += 1;
+                }
+            }
+        }
+
+        let aliases = self.load_staff_aliases()?;
+        let mut staff = self.load("vn_staff", "id", |row| {
+            let aid = row.get_id("aid")?;
+            let (sid, name, original) = aliases.get(&aid)?.first()?.clone();
+            Some(json!({
+                "sid": sid,
+                "aid": aid,
+                "name": name,
+                "original": original,
+                "role": row.get("role")?,
+                "note": row.get("note").filter(|note| !note.is_empty()),
+            }))
+        })?;
+
+        self.entities("vn", Box::new(move |row| {
+            let id = row.get_id("id").unwrap_or(0);
+            let olang = row.get("olang");
+            let (title, original) = main_title(titles.remove(&id).as_ref(), olang);
+
+            let mut tags = votes.remove(&id).unwrap_or_default().into_iter().filter(|(_, (score, _, _, _))| *score > 0).map(|(tag, (score, count, spoiler, spoiler_count))| {
+                let spoiler = match spoiler_count {
+                    0 => 0,
+                    count => (spoiler as f64 / count as f64).round() as i64,
+                };
+                json!({
+                    "id": tag,
+                    "score": score as f64 / count as f64,
+                    "spoiler level": spoiler.clamp(0, 2),
+                })
+            }).collect::<Vec<_>>();
+            tags.sort_by_key(|tag| tag["id"].as_u64());
+
+            //Recent dumps store rating multiplied by 100, while older ones by 10.
+            let rating = match row.get_num::<f64>("c_rating") {
+                Some(rating) if rating > 100.0 => Value::from(rating / 100.0),
+                Some(rating) if rating > 10.0 => Value::from(rating / 10.0),
+                Some(rating) => Value::from(rating),
+                None => Value::Null,
+            };
+
+            json!({
+                "id": id,
+                "title": title,
+                "original": original,
+                "released": date(row.get_num("c_released")),
+                "languages": row.get_array("c_languages"),
+                "orig_lang": olang.into_iter().collect::<Vec<_>>(),
+                "platforms": row.get_array("c_platforms"),
+                "aliases": row.get("alias").filter(|alias| !alias.is_empty()),
+                "length": non_zero(row.get_num("length")),
+                "description": row.get("description").filter(|description| !description.is_empty()),
+                "links": {
+                    "wikidata": row.get("l_wikidata").map(|id| format!("Q{}", id)),
+                    "renai": row.get("l_renai").filter(|renai| !renai.is_empty()),
+                },
+                "image": image(row.get("image")),
+                "relations": relations.remove(&id).unwrap_or_default(),
+                "tags": tags,
+                "popularity": row.get_num::<f64>("c_popularity").unwrap_or(0.0),
+                "rating": rating,
+                "votecount": row.get_num::<u64>("c_votecount"),
+                "staff": staff.remove(&id).unwrap_or_default(),
+            })
+        }))
+    }
+
+    ///Reads releases.
+    pub fn releases(&self) -> io::Result<Entities<results::Release>> {
+        let mut titles = self.load_titles("releases_titles")?;
+        let vn_titles = self.load_vn_titles()?;
+        let mut vns = self.load("releases_vn", "id", |row| Some((row.get_id("vid")?, row.get("rtype").map(str::to_owned))))?;
+        let producer_names = self.load("producers", "id", |row| Some(json!({
+            "name": row.get("latin").or_else(|| row.get("name"))?,
+            "original": row.get("latin").and(row.get("name")),
+            "type": row.get("type"),
+        })))?;
+        let mut producers = self.load("releases_producers", "id", |row| {
+            let pid = row.get_id("pid")?;
+            let mut producer = producer_names.get(&pid)?.first()?.clone();
+            producer["id"] = pid.into();
+            producer["developer"] = row.get_bool("developer").unwrap_or(false).into();
+            producer["publisher"] = row.get_bool("publisher").unwrap_or(false).into();
+            Some(producer)
+        })?;
+        let mut platforms = self.load("releases_platforms", "id", |row| row.get("platform").map(str::to_owned))?;
+        let mut media = self.load("releases_media", "id", |row| Some(json!({
+            "medium": row.get("medium")?,
+            "qty": non_zero(row.get_num("qty")),
+        })))?;
+
+        self.entities("releases", Box::new(move |row| {
+            let id = row.get_id("id").unwrap_or(0);
+            let release_titles = titles.remove(&id);
+            let (title, original) = main_title(release_titles.as_ref(), row.get("olang"));
+            let languages = release_titles.iter().flatten().map(|title| title.lang.as_str()).collect::<Vec<_>>();
+
+            let vns = vns.remove(&id).unwrap_or_default();
+            let kind = row.get("type").map(str::to_owned).or_else(|| vns.iter().find_map(|(_, rtype)| rtype.clone()));
+            let vns = vns.iter().filter_map(|(vid, _)| {
+                let (title, original) = vn_titles.get(vid)?.clone();
+                Some(json!({
+                    "id": vid,
+                    "title": title,
+                    "original": original,
+                }))
+            }).collect::<Vec<_>>();
+
+            let resolution = match (row.get_num::<u32>("reso_x"), row.get_num::<u32>("reso_y")) {
+                (Some(0), Some(0)) | (None, None) => row.get("resolution").map(str::to_owned),
+                (Some(0), Some(1)) => Some("non-standard".to_owned()),
+                (Some(x), Some(y)) => Some(format!("{}x{}", x, y)),
+                _ => None,
+            };
+
+            let animation = match (non_zero(row.get_num("ani_story")), non_zero(row.get_num("ani_ero"))) {
+                (Value::Null, Value::Null) => Value::Null,
+                (story, ero) => json!([story, ero]),
+            };
+
+            json!({
+                "id": id,
+                "title": title,
+                "original": original,
+                "released": date(row.get_num("released")),
+                "type": kind,
+                "patch": row.get_bool("patch"),
+                "freeware": row.get_bool("freeware"),
+                "doujin": row.get_bool("doujin"),
+                "languages": languages,
+                "website": row.get("website").filter(|website| !website.is_empty()),
+                "notes": row.get("notes").filter(|notes| !notes.is_empty()),
+                "minage": row.get_num::<u8>("minage"),
+                "gtin": row.get("gtin").filter(|gtin| *gtin != "0"),
+                "catalog": row.get("catalog").filter(|catalog| !catalog.is_empty()),
+                "platforms": platforms.remove(&id).unwrap_or_default(),
+                "media": media.remove(&id).unwrap_or_default(),
+                "resolution": resolution,
+                "voiced": non_zero(row.get_num("voiced")),
+                "animation": animation,
+                "vn": vns,
+                "producers": producers.remove(&id).unwrap_or_default(),
+            })
+        }))
+    }
+
+    ///Reads producers.
+    pub fn producers(&self) -> io::Result<Entities<results::Producer>> {
+        let names = self.load("producers", "id", |row| Some((
+            Value::from(row.get("latin").or_else(|| row.get("name"))?),
+            Value::from(row.get("latin").and(row.get("name"))),
+        )))?;
+        let mut relations = self.load("producers_relations", "id", |row| {
+            let pid = row.get_id("pid")?;
+            let (name, original) = names.get(&pid)?.first()?.clone();
+            Some(json!({
+                "id": pid,
+                "relation": row.get("relation")?,
+                "name": name,
+                "original": original,
+            }))
+        })?;
+
+        self.entities("producers", Box::new(move |row| {
+            let id = row.get_id("id").unwrap_or(0);
+            json!({
+                "id": id,
+                "name": row.get("latin").or_else(|| row.get("name")),
+                "original": row.get("latin").and(row.get("name")),
+                "type": row.get("type"),
+                "language": row.get("lang"),
+                "links": {
+                    "homepage": row.get("website").filter(|website| !website.is_empty()),
+                    "wikidata": row.get("l_wikidata").map(|id| format!("Q{}", id)),
+                },
+                "aliases": row.get("alias").filter(|alias| !alias.is_empty()),
+                "description": row.get("description").filter(|description| !description.is_empty()),
+                "relations": relations.remove(&id).unwrap_or_default(),
+            })
+        }))
+    }
+
+    ///Reads characters.
+    pub fn characters(&self) -> io::Result<Entities<results::Character>> {
+        let mut traits = self.load("chars_traits", "id", |row| Some(json!([row.get_id("tid")?, row.get_num::<u8>("spoil").unwrap_or(0)])))?;
+        let mut vns = self.load("chars_vns", "id", |row| Some(json!([
+            row.get_id("vid")?,
+            row.get_id("rid").unwrap_or(0),
+            row.get_num::<u8>("spoil").unwrap_or(0),
+            row.get("role")?,
+        ])))?;
+        let aliases = self.load_staff_aliases()?;
+        let mut voiced = self.load("vn_seiyuu", "cid", |row| {
+            let aid = row.get_id("aid")?;
+            let (sid, _, _) = aliases.get(&aid)?.first()?.clone();
+            Some(json!({
+                "id": sid,
+                "aid": aid,
+                "vid": row.get_id("id")?,
+                "note": row.get("note").unwrap_or_default(),
+            }))
+        })?;
+
+        self.entities("chars", Box::new(move |row| {
+            let id = row.get_id("id").unwrap_or(0);
+
+            let birthday = match row.get_num::<u32>("birthday") {
+                Some(birthday) => (birthday / 100, birthday % 100),
+                None => (row.get_num("b_month").unwrap_or(0), row.get_num("b_day").unwrap_or(0)),
+            };
+            let birthday = match birthday {
+                (0, _) | (_, 0) => Value::Null,
+                (month, day) => json!([day, month]),
+            };
+
+            json!({
+                "id": id,
+                "name": row.get("latin").or_else(|| row.get("name")),
+                "original": row.get("latin").and(row.get("name")),
+                "gender": row.get("gender").filter(|gender| matches!(*gender, "m" | "f" | "b")),
+                "bloodt": row.get("bloodt").filter(|bloodt| *bloodt != "unknown"),
+                "birthday": birthday,
+                "aliases": row.get("alias").filter(|alias| !alias.is_empty()),
+                "description": row.get("description").filter(|description| !description.is_empty()),
+                "age": row.get_num::<u8>("age"),
+                "image": image(row.get("image")),
+                "bust": non_zero(row.get_num("s_bust")),
+                "waist": non_zero(row.get_num("s_waist")),
+                "hip": non_zero(row.get_num("s_hip")),
+                "height": non_zero(row.get_num("height")),
+                "weight": non_zero(row.get_num("weight")),
+                "cup_size": row.get("cup_size").filter(|cup_size| !cup_size.is_empty()),
+                "traits": traits.remove(&id).unwrap_or_default(),
+                "vns": vns.remove(&id).unwrap_or_default(),
+                "voiced": voiced.remove(&id).unwrap_or_default(),
+            })
+        }))
+    }
+}
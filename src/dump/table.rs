@@ -0,0 +1,216 @@
+//!Reader of database dump's tables.
+//!
+//!Each table is stored in PostgreSQL `COPY` text format, with column names in separate `<table>.header` file.
+
+use std::io;
+use std::io::BufRead;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use super::invalid_data;
+
+//Unescapes `COPY` field, returning `None` for `NULL`.
+fn unescape(field: &str) -> Option<String> {
+    if field == "\\N" {
+        return None;
+    }
+
+    if !field.contains('\\') {
+        return Some(field.to_owned());
+    }
+
+    //Octal escapes denote bytes, so result is decoded only once it is complete.
+    let mut result = Vec::with_capacity(field.len());
+    let mut bytes = field.bytes().peekable();
+    while let Some(byte) = bytes.next() {
+        if byte != b'\\' {
+            result.push(byte);
+            continue;
+        }
+
+        match bytes.next() {
+            Some(b'b') => result.push(b'\x08'),
+            Some(b'f') => result.push(b'\x0c'),
+            Some(b'n') => result.push(b'\n'),
+            Some(b'r') => result.push(b'\r'),
+            Some(b't') => result.push(b'\t'),
+            Some(b'v') => result.push(b'\x0b'),
+            Some(digit @ b'0'..=b'7') => {
+                let mut code = u32::from(digit - b'0');
+                for _ in 0..2 {
+                    match bytes.peek() {
+                        Some(digit @ b'0'..=b'7') => {
+                            code = code * 8 + u32::from(digit - b'0');
+                            bytes.next();
+                        },
+                        _ => break,
+                    }
+                }
+                //PostgreSQL takes only low-order 8 bits.
+                result.push(code as u8);
+            },
+            Some(byte) => result.push(byte),
+            None => result.push(b'\\'),
+        }
+    }
+
+    Some(String::from_utf8(result).unwrap_or_else(|error| String::from_utf8_lossy(error.as_bytes()).into_owned()))
+}
+
+///Parses PostgreSQL array, such as `{en,ja}`.
+pub fn parse_array(text: &str) -> Vec<String> {
+    let text = match text.strip_prefix('{').and_then(|text| text.strip_suffix('}')) {
+        Some(text) => text,
+        None => return vec![text.to_owned()],
+    };
+
+    let mut result = Vec::new();
+    let mut chars = text.chars();
+    let mut element = String::new();
+    let mut is_quoted = false;
+    let mut has_element = false;
+
+    while let Some(ch) = chars.next() {
+        match ch {
+            '"' => {
+                is_quoted = !is_quoted;
+                has_element = true;
+            },
+            '\\' if is_quoted => element.extend(chars.next()),
+            ',' if !is_quoted => {
+                result.push(core::mem::take(&mut element));
+                has_element = false;
+            },
+            ch => {
+                element.push(ch);
+                has_element = true;
+            },
+        }
+    }
+
+    if has_element {
+        result.push(element);
+    }
+
+    result
+}
+
+///Parses VNDB identifier, with or without type prefix, such as `v17` or `17`.
+pub fn parse_id(text: &str) -> Option<u64> {
+    text.trim_start_matches(|ch: char| ch.is_ascii_alphabetic()).parse().ok()
+}
+
+#[derive(Clone, Debug)]
+///Table's row.
+pub struct Row {
+    columns: Arc<[String]>,
+    values: Vec<Option<String>>,
+}
+
+impl Row {
+    ///Returns value of column, if it is present and not `NULL`.
+    pub fn get(&self, column: &str) -> Option<&str> {
+        let idx = self.columns.iter().position(|name| name == column)?;
+        self.values.get(idx)?.as_deref()
+    }
+
+    ///Returns whether table has column.
+    pub fn has(&self, column: &str) -> bool {
+        self.columns.iter().any(|name| name == column)
+    }
+
+    ///Returns value of column, parsed as number.
+    pub fn get_num<T: FromStr>(&self, column: &str) -> Option<T> {
+        self.get(column)?.parse().ok()
+    }
+
+    ///Returns value of boolean column.
+    pub fn get_bool(&self, column: &str) -> Option<bool> {
+        match self.get(column)? {
+            "t" | "true" => Some(true),
+            "f" | "false" => Some(false),
+            _ => None,
+        }
+    }
+
+    ///Returns value of identifier column.
+    pub fn get_id(&self, column: &str) -> Option<u64> {
+        parse_id(self.get(column)?)
+    }
+
+    ///Returns value of array column, empty if it is `NULL`.
+    pub fn get_array(&self, column: &str) -> Vec<String> {
+        self.get(column).map(parse_array).unwrap_or_default()
+    }
+}
+
+///Iterator over rows of table.
+pub struct Table<R> {
+    reader: R,
+    columns: Arc<[String]>,
+    line: String,
+    line_num: usize,
+}
+
+impl Table<Box<dyn BufRead>> {
+    ///Opens table `<dir>/<name>` with header `<dir>/<name>.header`.
+    ///
+    ///Table can be compressed.
+    pub fn open<P: AsRef<Path>>(dir: P, name: &str) -> io::Result<Self> {
+        let dir = dir.as_ref();
+
+        let mut header = String::new();
+        super::open(dir.join(format!("{}.header", name)))?.read_line(&mut header)?;
+        let columns = header.trim_end_matches(['\r', '\n']).split('\t').map(str::to_owned).collect();
+
+        Ok(Self::new(columns, super::open(dir.join(name))?))
+    }
+}
+
+impl<R: BufRead> Table<R> {
+    ///Creates new instance with column names.
+    pub fn new(columns: Vec<String>, reader: R) -> Self {
+        Self {
+            reader,
+            columns: columns.into(),
+            line: String::new(),
+            line_num: 0,
+        }
+    }
+
+    #[inline]
+    ///Returns column names.
+    pub fn columns(&self) -> &[String] {
+        &self.columns
+    }
+}
+
+impl<R: BufRead> Iterator for Table<R> {
+    type Item = io::Result<Row>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.line.clear();
+        self.line_num += 1;
+        match self.reader.read_line(&mut self.line) {
+            Ok(0) => return None,
+            Ok(_) => (),
+            Err(error) => return Some(Err(error)),
+        }
+
+        let line = self.line.trim_end_matches(['\r', '\n']);
+        //End of data marker
+        if line == "\\." {
+            return None;
+        }
+
+        let values = line.split('\t').map(unescape).collect::<Vec<_>>();
+        match values.len() == self.columns.len() {
+            true => Some(Ok(Row {
+                columns: self.columns.clone(),
+                values,
+            })),
+            false => Some(Err(invalid_data(format!("Line {}: expected {} columns, got {}", self.line_num, self.columns.len(), values.len())))),
+        }
+    }
+}
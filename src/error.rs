@@ -0,0 +1,113 @@
+//!Error types.
+
+use core::fmt;
+use std::io;
+
+use crate::protocol::message::ResponseParseError;
+use crate::protocol::message::response::VndbError;
+
+#[derive(Debug)]
+///Error of message framing.
+///
+///VNDB messages are terminated by `0x04` byte and must be valid UTF-8.
+pub enum FramingError {
+    ///Connection is closed in the middle of message.
+    Incomplete,
+    ///Message is not valid UTF-8.
+    InvalidUtf8(core::str::Utf8Error),
+}
+
+impl fmt::Display for FramingError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FramingError::Incomplete => fmt.write_str("VNDB message is incomplete"),
+            FramingError::InvalidUtf8(ref error) => write!(fmt, "VNDB message is not valid UTF-8: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for FramingError {}
+
+#[derive(Debug)]
+///VNDB library error.
+pub enum Error {
+    ///I/O error.
+    Io(io::Error),
+    ///Invalid message framing.
+    Framing(FramingError),
+    ///Unable to parse response.
+    Parse(ResponseParseError),
+    ///VNDB responded with error.
+    Server(VndbError),
+    ///VNDB responded with unexpected response.
+    Unexpected(crate::protocol::Response),
+}
+
+impl Error {
+    ///Returns whether error indicates that connection is no longer usable.
+    pub fn is_disconnect(&self) -> bool {
+        match self {
+            Error::Io(error) => match error.kind() {
+                io::ErrorKind::UnexpectedEof | io::ErrorKind::ConnectionReset | io::ErrorKind::ConnectionAborted | io::ErrorKind::BrokenPipe => true,
+                _ => false,
+            },
+            Error::Framing(FramingError::Incomplete) => true,
+            _ => false,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    #[inline]
+    fn from(error: io::Error) -> Self {
+        Error::Io(error)
+    }
+}
+
+impl From<FramingError> for Error {
+    #[inline]
+    fn from(error: FramingError) -> Self {
+        Error::Framing(error)
+    }
+}
+
+impl From<ResponseParseError> for Error {
+    #[inline]
+    fn from(error: ResponseParseError) -> Self {
+        Error::Parse(error)
+    }
+}
+
+impl From<VndbError> for Error {
+    #[inline]
+    fn from(error: VndbError) -> Self {
+        Error::Server(error)
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Io(ref error) => write!(fmt, "I/O error: {}", error),
+            Error::Framing(ref error) => fmt::Display::fmt(error, fmt),
+            Error::Parse(ref error) => fmt::Display::fmt(error, fmt),
+            Error::Server(ref error) => write!(fmt, "VNDB error: {}", error),
+            Error::Unexpected(ref response) => write!(fmt, "VNDB sent unexpected response: {:?}", response),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(ref error) => Some(error),
+            Error::Framing(ref error) => Some(error),
+            Error::Parse(ref error) => Some(error),
+            Error::Server(ref error) => Some(error),
+            Error::Unexpected(_) => None,
+        }
+    }
+}
+
+///Result with VNDB library [Error](enum.Error.html)
+pub type Result<T> = core::result::Result<T, Error>;
@@ -0,0 +1,502 @@
+//!Export of user's lists.
+//!
+//!User's list is fetched page by page, either from `ulist` or from legacy `vnlist`, `votelist` and `wishlist`,
+//!and joined with titles of VNs.
+//!Result can be written as JSON, CSV or XML in format of MyAnimeList export.
+//!
+//!Votes are exported in range from 1 to 10, while VNDB uses range from 10 to 100.
+//!
+//!Example of usage:
+//!
+//!```no_run
+//!use vndb::export::{Format, UserList};
+//!
+//!let mut client = vndb::client::simple::Client::connect().expect("To connect");
+//!let list = UserList::fetch(&mut client, 2).expect("To fetch list");
+//!
+//!let file = std::fs::File::create("vndb-list.xml").expect("To create file");
+//!list.write(Format::Xml, std::io::BufWriter::new(file)).expect("To write list");
+//!```
+
+use core::fmt;
+use std::collections::BTreeMap;
+use std::io::{self, Read, Write};
+
+use serde::{Deserialize, Serialize};
+use serde::de::DeserializeOwned;
+
+use crate::client::simple::Client;
+use crate::protocol::message::request::{get, Get};
+use crate::protocol::message::response::{results, typed, Results};
+use crate::utils::xml_escape;
+
+///Number of results per page, requested from VNDB.
+pub const PAGE_SIZE: u32 = 25;
+
+const CSV_HEADER: &str = "vn,title,status,vote,notes,labels,added,modified,voted,started,finished";
+const MAL_EMPTY_DATE: &str = "0000-00-00";
+
+#[derive(Debug)]
+///Export error.
+pub enum Error {
+    ///Client error.
+    Client(crate::Error),
+    ///Unable to parse results.
+    Json(serde_json::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Client(error) => write!(fmt, "Client error: {}", error),
+            Error::Json(error) => write!(fmt, "Invalid list item: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Client(error) => Some(error),
+            Error::Json(error) => Some(error),
+        }
+    }
+}
+
+impl From<crate::Error> for Error {
+    #[inline]
+    fn from(error: crate::Error) -> Self {
+        Error::Client(error)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    #[inline]
+    fn from(error: serde_json::Error) -> Self {
+        Error::Json(error)
+    }
+}
+
+///Export result.
+pub type Result<T> = core::result::Result<T, Error>;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+///Status of VN in user's list.
+pub enum Status {
+    ///Status is not set.
+    Unknown,
+    ///Currently playing.
+    Playing,
+    ///Finished.
+    Finished,
+    ///Stalled.
+    Stalled,
+    ///Dropped.
+    Dropped,
+    ///In wishlist.
+    Wishlist,
+    ///Blacklisted.
+    Blacklist,
+}
+
+impl Status {
+    //Labels in order of precedence.
+    const LABELS: [(u32, Status); 6] = [
+        (2, Status::Finished),
+        (1, Status::Playing),
+        (3, Status::Stalled),
+        (4, Status::Dropped),
+        (5, Status::Wishlist),
+        (6, Status::Blacklist),
+    ];
+
+    ///Returns name of status.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Status::Unknown => "unknown",
+            Status::Playing => "playing",
+            Status::Finished => "finished",
+            Status::Stalled => "stalled",
+            Status::Dropped => "dropped",
+            Status::Wishlist => "wishlist",
+            Status::Blacklist => "blacklist",
+        }
+    }
+
+    ///Returns corresponding status of MyAnimeList.
+    ///
+    ///Blacklisted VNs are exported as dropped, and VNs without status as planned.
+    pub fn mal(&self) -> &'static str {
+        match self {
+            Status::Playing => "Watching",
+            Status::Finished => "Completed",
+            Status::Stalled => "On-Hold",
+            Status::Dropped | Status::Blacklist => "Dropped",
+            Status::Wishlist | Status::Unknown => "Plan to Watch",
+        }
+    }
+
+    ///Returns identifier of built-in label, corresponding to status.
+    pub fn label(&self) -> Option<u32> {
+        Self::LABELS.iter().find(|(_, status)| status == self).map(|(id, _)| *id)
+    }
+
+    ///Returns status by its name.
+    pub fn from_str(name: &str) -> Option<Self> {
+        const ALL: [Status; 7] = [Status::Unknown, Status::Playing, Status::Finished, Status::Stalled, Status::Dropped, Status::Wishlist, Status::Blacklist];
+
+        ALL.iter().find(|status| status.as_str() == name).copied()
+    }
+
+    pub(crate) fn from_labels(labels: &[results::UListLabel]) -> Self {
+        Self::LABELS.iter().find(|(id, _)| labels.iter().any(|label| label.id == *id)).map(|(_, status)| *status).unwrap_or(Status::Unknown)
+    }
+}
+
+impl From<results::VnStatus> for Status {
+    fn from(status: results::VnStatus) -> Self {
+        match status {
+            results::VnStatus::Unknown => Status::Unknown,
+            results::VnStatus::Playing => Status::Playing,
+            results::VnStatus::Finished => Status::Finished,
+            results::VnStatus::Stalled => Status::Stalled,
+            results::VnStatus::Dropped => Status::Dropped,
+        }
+    }
+}
+
+impl fmt::Display for Status {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+//Votes are stored in range 10..=100, but exported in range 1..=10.
+mod vote {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(vote: &Option<u8>, serializer: S) -> Result<S::Ok, S::Error> {
+        match vote {
+            Some(vote) => serializer.serialize_f32(super::score(*vote)),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<u8>, D::Error> {
+        let vote: Option<f32> = Deserialize::deserialize(deserializer)?;
+        Ok(vote.map(|vote| (vote * 10.0).round().clamp(10.0, 100.0) as u8))
+    }
+}
+
+#[inline]
+pub(crate) fn score(vote: u8) -> f32 {
+    vote as f32 / 10.0
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+///Item of user's list.
+pub struct Entry {
+    ///Unique identifier of VN.
+    pub vn: u64,
+    ///Title of VN, if VN is still available.
+    pub title: Option<String>,
+    ///Status.
+    pub status: Status,
+    #[serde(default, with = "vote")]
+    ///Vote in range from 10 to 100.
+    ///
+    ///Exported in range from 1 to 10.
+    pub vote: Option<u8>,
+    ///User's notes.
+    pub notes: Option<String>,
+    #[serde(default)]
+    ///Labels, other than ones describing status.
+    pub labels: Vec<String>,
+    ///Unix timestamp of when item is added.
+    pub added: Option<u64>,
+    ///Unix timestamp of when item is last modified.
+    pub modified: Option<u64>,
+    ///Unix timestamp of when vote is cast.
+    pub voted: Option<u64>,
+    ///Date when user started playing, in format `YYYY-MM-DD`.
+    pub started: Option<String>,
+    ///Date when user finished playing, in format `YYYY-MM-DD`.
+    pub finished: Option<String>,
+}
+
+impl Entry {
+    fn new(vn: u64) -> Self {
+        Self {
+            vn,
+            title: None,
+            status: Status::Unknown,
+            vote: None,
+            notes: None,
+            labels: Vec::new(),
+            added: None,
+            modified: None,
+            voted: None,
+            started: None,
+            finished: None,
+        }
+    }
+
+    #[inline]
+    ///Returns vote in range from 1 to 10, as it is exported.
+    pub fn score(&self) -> Option<f32> {
+        self.vote.map(score)
+    }
+
+    fn from_ulist(item: results::UList) -> Option<Self> {
+        let mut entry = Self::new(item.vn?);
+        entry.status = Status::from_labels(&item.labels);
+        entry.vote = item.vote;
+        entry.notes = item.notes.filter(|notes| !notes.is_empty());
+        entry.labels = item.labels.into_iter().filter(|label| label.id > 7).map(|label| label.label).collect();
+        entry.added = Some(item.added).filter(|added| *added > 0);
+        entry.modified = Some(item.last_mod).filter(|modified| *modified > 0);
+        entry.voted = Some(item.voted).filter(|voted| *voted > 0);
+        entry.started = item.started;
+        entry.finished = item.finished;
+        Some(entry)
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+///Format of export.
+pub enum Format {
+    ///JSON document, that can be read back with [UserList::from_json](struct.UserList.html#method.from_json).
+    Json,
+    ///CSV with header.
+    Csv,
+    ///XML in format of MyAnimeList export.
+    Xml,
+}
+
+impl Format {
+    ///Returns format by its name: `json`, `csv` or `xml`.
+    pub fn from_str(name: &str) -> Option<Self> {
+        match name {
+            "json" => Some(Format::Json),
+            "csv" => Some(Format::Csv),
+            "xml" => Some(Format::Xml),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+///User's list.
+pub struct UserList {
+    ///User's ID.
+    pub uid: u64,
+    ///User's name, if user is found.
+    pub username: Option<String>,
+    ///Items, ordered by VN's ID.
+    pub entries: Vec<Entry>,
+}
+
+impl UserList {
+    ///Fetches list from `ulist`.
+    pub fn fetch<IO: Read + Write>(client: &mut Client<IO>, uid: u64) -> Result<Self> {
+        let items = fetch_all::<results::UList, _>(|get| client.get(get), get::Type::ulist(), get::Flags::new().basic().labels(), uid)?;
+        let entries = items.into_iter().filter_map(Entry::from_ulist).map(|entry| (entry.vn, entry)).collect();
+
+        Self::complete(client, uid, entries)
+    }
+
+    ///Fetches list from legacy `vnlist`, `votelist` and `wishlist`.
+    pub fn fetch_legacy<IO: Read + Write>(client: &mut Client<IO>, uid: u64) -> Result<Self> {
+        let mut entries = BTreeMap::new();
+
+        for item in fetch_all::<results::VnList, _>(|get| client.get(get), get::Type::vnlist(), get::Flags::new().basic(), uid)? {
+            let vn = match item.vn {
+                Some(vn) => vn,
+                None => continue,
+            };
+            let entry = entries.entry(vn).or_insert_with(|| Entry::new(vn));
+            entry.status = item.status.map(Status::from).unwrap_or(Status::Unknown);
+            entry.notes = item.notes.filter(|notes| !notes.is_empty());
+            entry.added = Some(item.added).filter(|added| *added > 0);
+        }
+
+        for item in fetch_all::<results::VoteList, _>(|get| client.get(get), get::Type::votelist(), get::Flags::new().basic(), uid)? {
+            let vn = match item.vn {
+                Some(vn) => vn,
+                None => continue,
+            };
+            let entry = entries.entry(vn).or_insert_with(|| Entry::new(vn));
+            entry.vote = item.vote;
+            entry.voted = item.added;
+            entry.added = entry.added.or(item.added);
+        }
+
+        for item in fetch_all::<results::WishList, _>(|get| client.get(get), get::Type::wishlist(), get::Flags::new().basic(), uid)? {
+            let vn = match item.vn {
+                Some(vn) => vn,
+                None => continue,
+            };
+            let entry = entries.entry(vn).or_insert_with(|| Entry::new(vn));
+            if entry.status == Status::Unknown {
+                entry.status = match item.priority {
+                    Some(results::WishPriority::Blacklist) => Status::Blacklist,
+                    _ => Status::Wishlist,
+                };
+            }
+            entry.added = entry.added.or(Some(item.added).filter(|added| *added > 0));
+        }
+
+        Self::complete(client, uid, entries)
+    }
+
+    //Joins entries with VN titles and user's name.
+    fn complete<IO: Read + Write>(client: &mut Client<IO>, uid: u64, mut entries: BTreeMap<u64, Entry>) -> Result<Self> {
+        let ids = entries.keys().copied().collect::<Vec<_>>();
+        for vn in client.get_many_vn(&ids, get::Flags::new().basic())?.items {
+            if let Some(entry) = entries.get_mut(&vn.id) {
+                entry.title = vn.title;
+            }
+        }
+
+        let user = client.get(&Get {
+            kind: get::Type::user(),
+            flags: get::Flags::new().basic(),
+            filters: get::Filters::new().filter(crate::filter!(id = uid)),
+            options: None,
+        })?;
+        let username = typed::User::deserialize(&*user)?.items.into_iter().next().and_then(|user| user.name);
+
+        Ok(Self {
+            uid,
+            username,
+            entries: entries.into_values().collect(),
+        })
+    }
+
+    ///Reads list, written in JSON format.
+    pub fn from_json<R: Read>(input: R) -> serde_json::Result<Self> {
+        serde_json::from_reader(input)
+    }
+
+    ///Writes list in specified format.
+    pub fn write<W: Write>(&self, format: Format, out: W) -> io::Result<()> {
+        match format {
+            Format::Json => self.write_json(out),
+            Format::Csv => self.write_csv(out),
+            Format::Xml => self.write_mal_xml(out),
+        }
+    }
+
+    ///Writes list as pretty printed JSON.
+    pub fn write_json<W: Write>(&self, mut out: W) -> io::Result<()> {
+        serde_json::to_writer_pretty(&mut out, self)?;
+        out.write_all(b"\n")?;
+        out.flush()
+    }
+
+    ///Writes list as CSV with header.
+    ///
+    ///Labels are separated by `;`, timestamps are written as is.
+    pub fn write_csv<W: Write>(&self, mut out: W) -> io::Result<()> {
+        writeln!(out, "{}", CSV_HEADER)?;
+
+        for entry in self.entries.iter() {
+            let labels = entry.labels.join(";");
+            let fields = [
+                entry.vn.to_string(),
+                csv_field(entry.title.as_deref().unwrap_or("")),
+                entry.status.to_string(),
+                entry.score().map(|score| score.to_string()).unwrap_or_default(),
+                csv_field(entry.notes.as_deref().unwrap_or("")),
+                csv_field(&labels),
+                entry.added.map(|added| added.to_string()).unwrap_or_default(),
+                entry.modified.map(|modified| modified.to_string()).unwrap_or_default(),
+                entry.voted.map(|voted| voted.to_string()).unwrap_or_default(),
+                entry.started.clone().unwrap_or_default(),
+                entry.finished.clone().unwrap_or_default(),
+            ];
+            writeln!(out, "{}", fields.join(","))?;
+        }
+
+        out.flush()
+    }
+
+    ///Writes list as XML in format of MyAnimeList export.
+    ///
+    ///Items are written as `anime` elements with VNDB's identifiers of VNs,
+    ///and votes are rounded to the nearest integer.
+    pub fn write_mal_xml<W: Write>(&self, mut out: W) -> io::Result<()> {
+        writeln!(out, "<?xml version=\"1.0\" encoding=\"UTF-8\" ?>")?;
+        writeln!(out, "<myanimelist>")?;
+        writeln!(out, "\t<myinfo>")?;
+        writeln!(out, "\t\t<user_id>{}</user_id>", self.uid)?;
+        writeln!(out, "\t\t<user_name>{}</user_name>", xml_escape(self.username.as_deref().unwrap_or("")))?;
+        writeln!(out, "\t\t<user_export_type>1</user_export_type>")?;
+        writeln!(out, "\t\t<user_total_anime>{}</user_total_anime>", self.entries.len())?;
+        for status in ["Watching", "Completed", "On-Hold", "Dropped", "Plan to Watch"] {
+            let total = self.entries.iter().filter(|entry| entry.status.mal() == status).count();
+            let tag = format!("user_total_{}", status.to_lowercase().replace([' ', '-'], ""));
+            writeln!(out, "\t\t<{tag}>{}</{tag}>", total, tag = tag)?;
+        }
+        writeln!(out, "\t</myinfo>")?;
+
+        for entry in self.entries.iter() {
+            writeln!(out, "\t<anime>")?;
+            writeln!(out, "\t\t<series_animedb_id>{}</series_animedb_id>", entry.vn)?;
+            writeln!(out, "\t\t<series_title>{}</series_title>", xml_cdata(entry.title.as_deref().unwrap_or("")))?;
+            writeln!(out, "\t\t<series_type>Visual Novel</series_type>")?;
+            writeln!(out, "\t\t<my_start_date>{}</my_start_date>", entry.started.as_deref().unwrap_or(MAL_EMPTY_DATE))?;
+            writeln!(out, "\t\t<my_finish_date>{}</my_finish_date>", entry.finished.as_deref().unwrap_or(MAL_EMPTY_DATE))?;
+            writeln!(out, "\t\t<my_score>{}</my_score>", entry.score().map(|score| score.round() as u8).unwrap_or(0))?;
+            writeln!(out, "\t\t<my_status>{}</my_status>", entry.status.mal())?;
+            writeln!(out, "\t\t<my_comments>{}</my_comments>", xml_cdata(entry.notes.as_deref().unwrap_or("")))?;
+            writeln!(out, "\t\t<my_tags>{}</my_tags>", xml_cdata(&entry.labels.join(", ")))?;
+            writeln!(out, "\t\t<update_on_import>1</update_on_import>")?;
+            writeln!(out, "\t</anime>")?;
+        }
+
+        writeln!(out, "</myanimelist>")?;
+        out.flush()
+    }
+}
+
+//Fetches all pages of user's list, using provided function to send get requests.
+pub(crate) fn fetch_all<T: DeserializeOwned, F: FnMut(&Get<'_>) -> crate::Result<Results>>(mut get_fn: F, kind: get::Type, flags: get::Flags, uid: u64) -> Result<Vec<T>> {
+    let mut items = Vec::new();
+    let mut page = 1;
+
+    loop {
+        let get = Get {
+            kind: kind.clone(),
+            flags: flags.clone(),
+            filters: get::Filters::new().filter(crate::filter!(uid = uid)),
+            options: Some(get::Options {
+                page: Some(page),
+                results: Some(PAGE_SIZE),
+                ..Default::default()
+            }),
+        };
+
+        let results = get_fn(&get)?;
+        let results = typed::Results::<T>::deserialize(&*results)?;
+        items.extend(results.items);
+
+        match results.more {
+            true => page += 1,
+            false => break Ok(items),
+        }
+    }
+}
+
+fn csv_field(value: &str) -> String {
+    match value.contains([',', '"', '\n', '\r']) {
+        true => format!("\"{}\"", value.replace('"', "\"\"")),
+        false => value.to_owned(),
+    }
+}
+
+fn xml_cdata(value: &str) -> String {
+    format!("<![CDATA[{}]]>", value.replace("]]>", "]]]]><![CDATA[>"))
+}
@@ -0,0 +1,528 @@
+//!Feeds of new and upcoming releases.
+//!
+//!Feed is built from releases, matching saved [query](struct.Query.html), and can be rendered
+//!as RSS 2.0, Atom or iCalendar.
+//!Query's filters may refer to current date as `"today"`, which is resolved each time feed is fetched.
+//!
+//!Releases with partial dates are rendered as all-day events, spanning whole month or year, marked as tentative,
+//!while releases without date are not included in calendar.
+//!
+//!Example of usage:
+//!
+//!```no_run
+//!use vndb::feed::{Feed, Format, Query};
+//!
+//!let mut client = vndb::client::simple::Client::connect().expect("To connect");
+//!let query = Query::upcoming("Upcoming English releases").language("en");
+//!let feed = Feed::fetch(&mut client, &query).expect("To fetch feed");
+//!
+//!feed.write(Format::Ical, std::io::stdout()).expect("To write calendar");
+//!```
+
+use core::fmt;
+use std::io::{self, Read, Write};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::client::many::PAGE_SIZE;
+use crate::client::simple::Client;
+use crate::markup::VNDB_URL;
+use crate::protocol::message::request::{get, Get};
+use crate::protocol::message::request::filter::{Condition, Expr, FilterParseError, Operator};
+use crate::protocol::message::response::{results, typed};
+use crate::protocol::message::response::results::{civil_date, ReleaseDate};
+use crate::utils::xml_escape;
+
+///Default maximum number of releases in feed.
+pub const DEFAULT_LIMIT: usize = 100;
+///Value of `released` filter, that is replaced with current date.
+pub const TODAY: &str = "today";
+
+const MONTHS: [&str; 12] = ["Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec"];
+const WEEKDAYS: [&str; 7] = ["Thu", "Fri", "Sat", "Sun", "Mon", "Tue", "Wed"];
+
+#[derive(Debug)]
+///Feed error.
+pub enum Error {
+    ///Client error.
+    Client(crate::Error),
+    ///Invalid filters of query.
+    Filter(FilterParseError),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Client(error) => write!(fmt, "Client error: {}", error),
+            Error::Filter(error) => write!(fmt, "Invalid filters: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Client(error) => Some(error),
+            Error::Filter(error) => Some(error),
+        }
+    }
+}
+
+impl From<crate::Error> for Error {
+    #[inline]
+    fn from(error: crate::Error) -> Self {
+        Error::Client(error)
+    }
+}
+
+impl From<FilterParseError> for Error {
+    #[inline]
+    fn from(error: FilterParseError) -> Self {
+        Error::Filter(error)
+    }
+}
+
+///Feed result.
+pub type Result<T> = core::result::Result<T, Error>;
+
+fn default_limit() -> usize {
+    DEFAULT_LIMIT
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+///Saved query of releases.
+pub struct Query {
+    ///Title of feed.
+    pub title: String,
+    #[serde(default)]
+    ///Filters of `get release` command, with or without outer parenthesis.
+    ///
+    ///Empty to match all releases.
+    pub filters: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    ///Languages, in any of which release must be available.
+    pub languages: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    ///Platforms, on any of which release must be available.
+    pub platforms: Vec<String>,
+    #[serde(default)]
+    ///Whether releases are ordered from latest to earliest.
+    pub reverse: bool,
+    #[serde(default = "default_limit")]
+    ///Maximum number of releases.
+    pub limit: usize,
+}
+
+impl Query {
+    ///Creates new query with specified filters.
+    pub fn new<T: Into<String>, F: Into<String>>(title: T, filters: F) -> Self {
+        Self {
+            title: title.into(),
+            filters: filters.into(),
+            languages: Vec::new(),
+            platforms: Vec::new(),
+            reverse: false,
+            limit: DEFAULT_LIMIT,
+        }
+    }
+
+    ///Creates query of releases after today, from the nearest one.
+    pub fn upcoming<T: Into<String>>(title: T) -> Self {
+        Self::new(title, format!("released > \"{}\"", TODAY))
+    }
+
+    ///Creates query of releases up to today, from the latest one.
+    pub fn recent<T: Into<String>>(title: T) -> Self {
+        let mut query = Self::new(title, format!("released <= \"{}\"", TODAY));
+        query.reverse = true;
+        query
+    }
+
+    ///Adds language, in which release can be available.
+    pub fn language<T: Into<String>>(mut self, language: T) -> Self {
+        self.languages.push(language.into());
+        self
+    }
+
+    ///Adds platform, on which release can be available.
+    pub fn platform<T: Into<String>>(mut self, platform: T) -> Self {
+        self.platforms.push(platform.into());
+        self
+    }
+
+    ///Sets maximum number of releases.
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    ///Returns filters of `get release` command, replacing `"today"` in `released` conditions with specified date.
+    pub fn resolve(&self, today: ReleaseDate) -> Result<get::Filters> {
+        fn resolve_today(expr: &mut Expr, today: &str) {
+            match expr {
+                Expr::Condition(condition) => if condition.field == "released" && condition.value == TODAY {
+                    condition.value = serde_json::Value::String(today.to_owned());
+                },
+                Expr::And(exprs) | Expr::Or(exprs) => for expr in exprs.iter_mut() {
+                    resolve_today(expr, today);
+                },
+            }
+        }
+
+        fn any_of(field: &str, values: &[String]) -> Expr {
+            let value = match values {
+                [value] => serde_json::Value::from(value.as_str()),
+                values => serde_json::Value::from(values),
+            };
+
+            Expr::Condition(Condition {
+                field: field.to_owned(),
+                op: Operator::Eq,
+                value,
+            })
+        }
+
+        let mut exprs = Vec::new();
+        if !self.filters.trim().is_empty() {
+            let mut expr = Expr::parse(&self.filters)?;
+            resolve_today(&mut expr, &today.to_string());
+            exprs.push(expr);
+        }
+        if !self.languages.is_empty() {
+            exprs.push(any_of("languages", &self.languages));
+        }
+        if !self.platforms.is_empty() {
+            exprs.push(any_of("platforms", &self.platforms));
+        }
+
+        let expr = match exprs.len() {
+            //Command requires filters, so match every release.
+            0 => crate::filter!(id >= 1).to_string(),
+            1 => exprs.remove(0).to_string(),
+            _ => Expr::And(exprs).to_string(),
+        };
+        Ok(get::Filters::new().filter(expr))
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+///Release, included in feed.
+pub struct Entry {
+    ///Release's ID.
+    pub id: u64,
+    ///Main title.
+    pub title: String,
+    ///Title in original language.
+    pub original: Option<String>,
+    ///Date of release.
+    ///
+    ///Release without valid date is treated as `tba`.
+    pub released: ReleaseDate,
+    ///Type of release: "complete", "partial" or "trial".
+    pub kind: Option<String>,
+    ///Languages in which release is available.
+    pub languages: Vec<String>,
+    ///Platforms on which release is available.
+    pub platforms: Vec<String>,
+    ///Titles of related VNs.
+    pub vns: Vec<String>,
+    ///Names of producers.
+    pub producers: Vec<String>,
+}
+
+impl From<results::Release> for Entry {
+    fn from(release: results::Release) -> Self {
+        Self {
+            id: release.id,
+            released: release.release_date().unwrap_or(ReleaseDate::Tba),
+            title: release.title.unwrap_or_default(),
+            original: release.original,
+            kind: release.kind,
+            languages: release.languages,
+            platforms: release.platforms,
+            vns: release.vn.into_iter().map(|vn| vn.title).collect(),
+            producers: release.producers.into_iter().map(|producer| producer.name).collect(),
+        }
+    }
+}
+
+impl Entry {
+    ///Returns URL of release's page on VNDB.
+    pub fn link(&self) -> String {
+        format!("{}/r{}", VNDB_URL, self.id)
+    }
+
+    ///Returns title with date of release.
+    pub fn headline(&self) -> String {
+        match self.released {
+            ReleaseDate::Tba => format!("{} (TBA)", self.title),
+            released => format!("{} ({})", self.title, released),
+        }
+    }
+
+    ///Returns description of release, one property per line.
+    pub fn summary(&self) -> String {
+        let mut lines = Vec::new();
+        if let Some(original) = self.original.as_deref() {
+            lines.push(format!("Original title: {}", original));
+        }
+        if !self.vns.is_empty() {
+            lines.push(format!("VN: {}", self.vns.join(", ")));
+        }
+        if !self.producers.is_empty() {
+            lines.push(format!("Producers: {}", self.producers.join(", ")));
+        }
+        if let Some(kind) = self.kind.as_deref() {
+            lines.push(format!("Type: {}", kind));
+        }
+        if !self.languages.is_empty() {
+            lines.push(format!("Languages: {}", self.languages.join(", ")));
+        }
+        if !self.platforms.is_empty() {
+            lines.push(format!("Platforms: {}", self.platforms.join(", ")));
+        }
+        lines.join("\n")
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+///Format of feed.
+pub enum Format {
+    ///RSS 2.0.
+    Rss,
+    ///Atom.
+    Atom,
+    ///iCalendar.
+    Ical,
+}
+
+impl Format {
+    ///Returns format by its name: `rss`, `atom` or `ical`.
+    pub fn from_str(name: &str) -> Option<Self> {
+        match name {
+            "rss" => Some(Format::Rss),
+            "atom" => Some(Format::Atom),
+            "ical" => Some(Format::Ical),
+            _ => None,
+        }
+    }
+
+    ///Returns MIME type of format.
+    pub fn content_type(self) -> &'static str {
+        match self {
+            Format::Rss => "application/rss+xml",
+            Format::Atom => "application/atom+xml",
+            Format::Ical => "text/calendar",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+///Feed of releases.
+pub struct Feed {
+    ///Title.
+    pub title: String,
+    ///URL of feed, used as its ID in Atom.
+    ///
+    ///By default it is page of releases on VNDB.
+    pub link: String,
+    ///Unix timestamp of when feed is fetched.
+    pub updated: u64,
+    ///Releases in order of query.
+    pub entries: Vec<Entry>,
+}
+
+impl Feed {
+    ///Fetches releases, matching query, as of now.
+    pub fn fetch<IO: Read + Write>(client: &mut Client<IO>, query: &Query) -> Result<Self> {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).map(|time| time.as_secs()).unwrap_or(0);
+        Self::fetch_at(client, query, now)
+    }
+
+    ///Fetches releases, matching query, as of specified unix timestamp.
+    pub fn fetch_at<IO: Read + Write>(client: &mut Client<IO>, query: &Query, now: u64) -> Result<Self> {
+        let filters = query.resolve(ReleaseDate::from_timestamp(now))?;
+        let mut entries = Vec::new();
+        let mut page = 1;
+
+        while entries.len() < query.limit {
+            let get = Get {
+                kind: get::Type::release(),
+                flags: get::Flags::new().basic().details().vn().producers(),
+                filters: filters.clone(),
+                options: Some(get::Options {
+                    page: Some(page),
+                    results: Some(PAGE_SIZE),
+                    sort: Some("released"),
+                    reverse: Some(query.reverse),
+                }),
+            };
+
+            let results = client.get(&get)?;
+            let results = results.to_typed::<typed::Release>()?;
+            entries.extend(results.items.into_iter().map(Entry::from));
+
+            match results.more {
+                true => page += 1,
+                false => break,
+            }
+        }
+        entries.truncate(query.limit);
+
+        Ok(Self {
+            title: query.title.clone(),
+            link: format!("{}/r", VNDB_URL),
+            updated: now,
+            entries,
+        })
+    }
+
+    ///Writes feed in specified format.
+    pub fn write<W: Write>(&self, format: Format, out: W) -> io::Result<()> {
+        match format {
+            Format::Rss => self.write_rss(out),
+            Format::Atom => self.write_atom(out),
+            Format::Ical => self.write_ical(out),
+        }
+    }
+
+    ///Writes feed as RSS 2.0.
+    ///
+    ///Publication date of item is the first day of release, if date is known.
+    pub fn write_rss<W: Write>(&self, mut out: W) -> io::Result<()> {
+        writeln!(out, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>")?;
+        writeln!(out, "<rss version=\"2.0\">")?;
+        writeln!(out, "\t<channel>")?;
+        writeln!(out, "\t\t<title>{}</title>", xml_escape(&self.title))?;
+        writeln!(out, "\t\t<link>{}</link>", xml_escape(&self.link))?;
+        writeln!(out, "\t\t<description>{}</description>", xml_escape(&self.title))?;
+        writeln!(out, "\t\t<lastBuildDate>{}</lastBuildDate>", rfc822(self.updated))?;
+
+        for entry in self.entries.iter() {
+            let link = entry.link();
+            writeln!(out, "\t\t<item>")?;
+            writeln!(out, "\t\t\t<title>{}</title>", xml_escape(&entry.headline()))?;
+            writeln!(out, "\t\t\t<link>{}</link>", link)?;
+            writeln!(out, "\t\t\t<guid isPermaLink=\"true\">{}</guid>", link)?;
+            writeln!(out, "\t\t\t<description>{}</description>", xml_escape(&entry.summary()))?;
+            if let Some(timestamp) = entry.released.timestamp() {
+                writeln!(out, "\t\t\t<pubDate>{}</pubDate>", rfc822(timestamp))?;
+            }
+            writeln!(out, "\t\t</item>")?;
+        }
+
+        writeln!(out, "\t</channel>")?;
+        writeln!(out, "</rss>")?;
+        out.flush()
+    }
+
+    ///Writes feed as Atom.
+    ///
+    ///Entry is considered updated on the first day of release, if date is known, or when feed is fetched otherwise.
+    pub fn write_atom<W: Write>(&self, mut out: W) -> io::Result<()> {
+        writeln!(out, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>")?;
+        writeln!(out, "<feed xmlns=\"http://www.w3.org/2005/Atom\">")?;
+        writeln!(out, "\t<title>{}</title>", xml_escape(&self.title))?;
+        writeln!(out, "\t<id>{}</id>", xml_escape(&self.link))?;
+        writeln!(out, "\t<link href=\"{}\"/>", xml_escape(&self.link))?;
+        writeln!(out, "\t<updated>{}</updated>", rfc3339(self.updated))?;
+        writeln!(out, "\t<author><name>VNDB</name></author>")?;
+
+        for entry in self.entries.iter() {
+            let link = entry.link();
+            writeln!(out, "\t<entry>")?;
+            writeln!(out, "\t\t<title>{}</title>", xml_escape(&entry.headline()))?;
+            writeln!(out, "\t\t<id>{}</id>", link)?;
+            writeln!(out, "\t\t<link href=\"{}\"/>", link)?;
+            writeln!(out, "\t\t<updated>{}</updated>", rfc3339(entry.released.timestamp().unwrap_or(self.updated)))?;
+            writeln!(out, "\t\t<summary>{}</summary>", xml_escape(&entry.summary()))?;
+            writeln!(out, "\t</entry>")?;
+        }
+
+        writeln!(out, "</feed>")?;
+        out.flush()
+    }
+
+    ///Writes feed as iCalendar, with all-day event per release.
+    ///
+    ///Event of partial date spans whole month or year and is tentative,
+    ///while releases without date are skipped.
+    pub fn write_ical<W: Write>(&self, mut out: W) -> io::Result<()> {
+        let stamp = ical_timestamp(self.updated);
+
+        ical_line(&mut out, "BEGIN:VCALENDAR")?;
+        ical_line(&mut out, "VERSION:2.0")?;
+        ical_line(&mut out, "PRODID:-//vndb.rs//Release feed//EN")?;
+        ical_line(&mut out, "CALSCALE:GREGORIAN")?;
+        ical_line(&mut out, &format!("X-WR-CALNAME:{}", ical_escape(&self.title)))?;
+
+        for entry in self.entries.iter() {
+            let (start, end) = match (entry.released.first_day(), entry.released.end()) {
+                (Some(start), Some(end)) => (start, end),
+                _ => continue,
+            };
+            let status = match entry.released.is_exact() {
+                true => "CONFIRMED",
+                false => "TENTATIVE",
+            };
+
+            ical_line(&mut out, "BEGIN:VEVENT")?;
+            ical_line(&mut out, &format!("UID:r{}@vndb.org", entry.id))?;
+            ical_line(&mut out, &format!("DTSTAMP:{}", stamp))?;
+            ical_line(&mut out, &format!("DTSTART;VALUE=DATE:{:04}{:02}{:02}", start.0, start.1, start.2))?;
+            ical_line(&mut out, &format!("DTEND;VALUE=DATE:{:04}{:02}{:02}", end.0, end.1, end.2))?;
+            ical_line(&mut out, &format!("SUMMARY:{}", ical_escape(&entry.headline())))?;
+            ical_line(&mut out, &format!("DESCRIPTION:{}", ical_escape(&entry.summary())))?;
+            ical_line(&mut out, &format!("URL:{}", entry.link()))?;
+            ical_line(&mut out, &format!("STATUS:{}", status))?;
+            ical_line(&mut out, "TRANSP:TRANSPARENT")?;
+            ical_line(&mut out, "END:VEVENT")?;
+        }
+
+        ical_line(&mut out, "END:VCALENDAR")?;
+        out.flush()
+    }
+}
+
+fn ical_escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace(';', "\\;").replace(',', "\\,").replace('\n', "\\n")
+}
+
+//Writes content line, folding it into lines of at most 75 octets.
+fn ical_line<W: Write>(out: &mut W, line: &str) -> io::Result<()> {
+    let mut rest = line;
+    let mut limit = 75;
+    while rest.len() > limit {
+        let mut split = limit;
+        while !rest.is_char_boundary(split) {
+            split -= 1;
+        }
+        out.write_all(&rest.as_bytes()[..split])?;
+        out.write_all(b"\r\n ")?;
+        rest = &rest[split..];
+        //Continuation starts with space.
+        limit = 74;
+    }
+    out.write_all(rest.as_bytes())?;
+    out.write_all(b"\r\n")
+}
+
+fn rfc822(timestamp: u64) -> String {
+    let (year, month, day) = civil_date(timestamp);
+    let weekday = WEEKDAYS[(timestamp / 86400 % 7) as usize];
+    let time = timestamp % 86400;
+    format!("{}, {:02} {} {:04} {:02}:{:02}:{:02} GMT", weekday, day, MONTHS[month as usize - 1], year, time / 3600, time / 60 % 60, time % 60)
+}
+
+fn rfc3339(timestamp: u64) -> String {
+    let (year, month, day) = civil_date(timestamp);
+    let time = timestamp % 86400;
+    format!("{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z", year, month, day, time / 3600, time / 60 % 60, time % 60)
+}
+
+fn ical_timestamp(timestamp: u64) -> String {
+    let (year, month, day) = civil_date(timestamp);
+    let time = timestamp % 86400;
+    format!("{:04}{:02}{:02}T{:02}{:02}{:02}Z", year, month, day, time / 3600, time / 60 % 60, time % 60)
+}
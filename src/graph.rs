@@ -0,0 +1,386 @@
+//!Relation graphs of VNs and producers.
+//!
+//!Graph is crawled breadth-first from root entity, fetching each level of graph with as few requests as possible,
+//!until either maximum depth or maximum number of nodes is reached.
+//!Edges are directed and typed by kind of relation, describing what target is to the source,
+//!as reported by VNDB. As VNDB stores relations in both directions, most edges have a reverse counterpart.
+//!
+//!Only edges between crawled nodes are part of graph.
+//!Graph can be written as JSON, Graphviz's DOT or GraphML.
+//!
+//!Example of usage:
+//!
+//!```no_run
+//!use vndb::graph::{Crawler, Format};
+//!
+//!let mut client = vndb::client::simple::Client::connect().expect("To connect");
+//!let graph = Crawler::new().max_depth(2).crawl_vn(&mut client, 17).expect("To crawl graph");
+//!
+//!println!("Crawled {} VNs", graph.nodes.len());
+//!graph.write(Format::Dot, std::io::stdout()).expect("To write graph");
+//!```
+
+use core::fmt;
+use std::collections::HashSet;
+use std::io::{self, Read, Write};
+
+use serde::Serialize;
+
+use crate::client::simple::Client;
+use crate::client::many::Many;
+use crate::protocol::message::request::get;
+use crate::protocol::message::response::results::{self, ProducerRelationKind, VnRelationKind};
+use crate::utils::xml_escape;
+
+///Default maximum depth of crawl.
+pub const DEFAULT_MAX_DEPTH: u32 = 3;
+///Default maximum number of nodes.
+pub const DEFAULT_MAX_NODES: usize = 500;
+
+///Kind of relation, used as type of graph's edge.
+pub trait Relation: Clone + fmt::Debug + Serialize {
+    ///Prefix of entity's identifier, used by VNDB: `v` or `p`.
+    const PREFIX: &'static str;
+
+    ///Returns VNDB's code.
+    fn code(&self) -> &str;
+    ///Returns human readable label.
+    fn label(&self) -> &str;
+}
+
+impl Relation for VnRelationKind {
+    const PREFIX: &'static str = "v";
+
+    #[inline]
+    fn code(&self) -> &str {
+        VnRelationKind::code(self)
+    }
+
+    #[inline]
+    fn label(&self) -> &str {
+        VnRelationKind::label(self)
+    }
+}
+
+impl Relation for ProducerRelationKind {
+    const PREFIX: &'static str = "p";
+
+    #[inline]
+    fn code(&self) -> &str {
+        ProducerRelationKind::code(self)
+    }
+
+    #[inline]
+    fn label(&self) -> &str {
+        ProducerRelationKind::label(self)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+///Node of graph.
+pub struct Node {
+    ///Entity's ID.
+    pub id: u64,
+    ///Title of VN or name of producer.
+    pub title: String,
+    ///Title or name in original language.
+    pub original: Option<String>,
+    ///Distance from root.
+    pub depth: u32,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+///Directed edge of graph.
+pub struct Edge<K> {
+    ///ID of source.
+    pub from: u64,
+    ///ID of target.
+    pub to: u64,
+    ///What target is to the source.
+    pub relation: K,
+    ///Whether relation is official.
+    ///
+    ///Always `true` for producers.
+    pub official: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+///Relation graph.
+pub struct Graph<K> {
+    ///ID of root.
+    pub root: u64,
+    ///Nodes, in order of crawl.
+    pub nodes: Vec<Node>,
+    ///Edges, in order of crawl.
+    pub edges: Vec<Edge<K>>,
+    ///IDs of related entities, that are not found.
+    pub missing: Vec<u64>,
+    ///Whether some related entities are left out due to limits.
+    pub truncated: bool,
+}
+
+///Graph of VN relations.
+pub type VnGraph = Graph<VnRelationKind>;
+///Graph of producer relations.
+pub type ProducerGraph = Graph<ProducerRelationKind>;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+///Format of graph.
+pub enum Format {
+    ///JSON document.
+    Json,
+    ///Graphviz's DOT.
+    Dot,
+    ///GraphML.
+    GraphMl,
+}
+
+impl Format {
+    ///Returns format by its name: `json`, `dot` or `graphml`.
+    pub fn from_str(name: &str) -> Option<Self> {
+        match name {
+            "json" => Some(Format::Json),
+            "dot" => Some(Format::Dot),
+            "graphml" => Some(Format::GraphMl),
+            _ => None,
+        }
+    }
+}
+
+impl<K: Relation> Graph<K> {
+    ///Returns node by ID.
+    pub fn node(&self, id: u64) -> Option<&Node> {
+        self.nodes.iter().find(|node| node.id == id)
+    }
+
+    ///Returns edges, going out of node.
+    pub fn edges_from(&self, id: u64) -> impl Iterator<Item = &Edge<K>> + '_ {
+        self.edges.iter().filter(move |edge| edge.from == id)
+    }
+
+    ///Writes graph in specified format.
+    pub fn write<W: Write>(&self, format: Format, out: W) -> io::Result<()> {
+        match format {
+            Format::Json => self.write_json(out),
+            Format::Dot => self.write_dot(out),
+            Format::GraphMl => self.write_graphml(out),
+        }
+    }
+
+    ///Writes graph as pretty printed JSON.
+    ///
+    ///Relations are written as VNDB's codes.
+    pub fn write_json<W: Write>(&self, mut out: W) -> io::Result<()> {
+        serde_json::to_writer_pretty(&mut out, self)?;
+        out.write_all(b"\n")?;
+        out.flush()
+    }
+
+    ///Writes graph as Graphviz's DOT.
+    ///
+    ///Nodes are labeled with titles and edges with relations, while unofficial relations are dashed.
+    pub fn write_dot<W: Write>(&self, mut out: W) -> io::Result<()> {
+        writeln!(out, "digraph {}{} {{", K::PREFIX, self.root)?;
+
+        for node in self.nodes.iter() {
+            let shape = match node.id == self.root {
+                true => ", shape=box",
+                false => "",
+            };
+            writeln!(out, "\t{}{} [label=\"{}\"{}];", K::PREFIX, node.id, dot_escape(&node.title), shape)?;
+        }
+
+        for edge in self.edges.iter() {
+            let style = match edge.official {
+                true => "",
+                false => ", style=dashed",
+            };
+            writeln!(out, "\t{prefix}{} -> {prefix}{} [label=\"{}\"{}];", edge.from, edge.to, dot_escape(edge.relation.label()), style, prefix = K::PREFIX)?;
+        }
+
+        writeln!(out, "}}")?;
+        out.flush()
+    }
+
+    ///Writes graph as GraphML.
+    ///
+    ///Nodes have `title`, `original` and `depth` attributes, while edges have `relation` code and `official` attributes.
+    pub fn write_graphml<W: Write>(&self, mut out: W) -> io::Result<()> {
+        writeln!(out, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>")?;
+        writeln!(out, "<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">")?;
+        writeln!(out, "\t<key id=\"title\" for=\"node\" attr.name=\"title\" attr.type=\"string\"/>")?;
+        writeln!(out, "\t<key id=\"original\" for=\"node\" attr.name=\"original\" attr.type=\"string\"/>")?;
+        writeln!(out, "\t<key id=\"depth\" for=\"node\" attr.name=\"depth\" attr.type=\"int\"/>")?;
+        writeln!(out, "\t<key id=\"relation\" for=\"edge\" attr.name=\"relation\" attr.type=\"string\"/>")?;
+        writeln!(out, "\t<key id=\"official\" for=\"edge\" attr.name=\"official\" attr.type=\"boolean\"/>")?;
+        writeln!(out, "\t<graph id=\"{}{}\" edgedefault=\"directed\">", K::PREFIX, self.root)?;
+
+        for node in self.nodes.iter() {
+            writeln!(out, "\t\t<node id=\"{}{}\">", K::PREFIX, node.id)?;
+            writeln!(out, "\t\t\t<data key=\"title\">{}</data>", xml_escape(&node.title))?;
+            if let Some(original) = node.original.as_deref() {
+                writeln!(out, "\t\t\t<data key=\"original\">{}</data>", xml_escape(original))?;
+            }
+            writeln!(out, "\t\t\t<data key=\"depth\">{}</data>", node.depth)?;
+            writeln!(out, "\t\t</node>")?;
+        }
+
+        for edge in self.edges.iter() {
+            writeln!(out, "\t\t<edge source=\"{prefix}{}\" target=\"{prefix}{}\">", edge.from, edge.to, prefix = K::PREFIX)?;
+            writeln!(out, "\t\t\t<data key=\"relation\">{}</data>", xml_escape(edge.relation.code()))?;
+            writeln!(out, "\t\t\t<data key=\"official\">{}</data>", edge.official)?;
+            writeln!(out, "\t\t</edge>")?;
+        }
+
+        writeln!(out, "\t</graph>")?;
+        writeln!(out, "</graphml>")?;
+        out.flush()
+    }
+}
+
+//Entity with relations, that can be crawled.
+struct Crawled<K> {
+    id: u64,
+    title: String,
+    original: Option<String>,
+    relations: Vec<(u64, K, bool)>,
+}
+
+impl From<results::Vn> for Crawled<VnRelationKind> {
+    fn from(vn: results::Vn) -> Self {
+        Self {
+            id: vn.id,
+            title: vn.title.unwrap_or_default(),
+            original: vn.original,
+            relations: vn.relations.into_iter().map(|relation| (relation.id, relation.relation, relation.official)).collect(),
+        }
+    }
+}
+
+impl From<results::Producer> for Crawled<ProducerRelationKind> {
+    fn from(producer: results::Producer) -> Self {
+        Self {
+            id: producer.id,
+            title: producer.name.unwrap_or_default(),
+            original: producer.original,
+            relations: producer.relations.into_iter().map(|relation| (relation.id, relation.relation, true)).collect(),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+///Breadth-first crawler of relation graphs.
+pub struct Crawler {
+    max_depth: u32,
+    max_nodes: usize,
+}
+
+impl Default for Crawler {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Crawler {
+    ///Creates new instance with default limits.
+    pub const fn new() -> Self {
+        Self {
+            max_depth: DEFAULT_MAX_DEPTH,
+            max_nodes: DEFAULT_MAX_NODES,
+        }
+    }
+
+    #[inline]
+    ///Sets maximum distance from root, with `0` meaning only root itself.
+    pub const fn max_depth(mut self, max_depth: u32) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    #[inline]
+    ///Sets maximum number of nodes, including root.
+    pub const fn max_nodes(mut self, max_nodes: usize) -> Self {
+        self.max_nodes = max_nodes;
+        self
+    }
+
+    ///Crawls graph of VN relations, starting from specified VN.
+    pub fn crawl_vn<IO: Read + Write>(&self, client: &mut Client<IO>, root: u64) -> crate::Result<VnGraph> {
+        let flags = get::Flags::new().basic().relations();
+        self.crawl(root, |ids| client.get_many_vn(ids, flags.clone()))
+    }
+
+    ///Crawls graph of producer relations, starting from specified producer.
+    pub fn crawl_producer<IO: Read + Write>(&self, client: &mut Client<IO>, root: u64) -> crate::Result<ProducerGraph> {
+        let flags = get::Flags::new().basic().relations();
+        self.crawl(root, |ids| client.get_many_producer(ids, flags.clone()))
+    }
+
+    fn crawl<K: Relation, T: Into<Crawled<K>>, F: FnMut(&[u64]) -> crate::Result<Many<T>>>(&self, root: u64, mut fetch: F) -> crate::Result<Graph<K>> {
+        let mut graph = Graph {
+            root,
+            nodes: Vec::new(),
+            edges: Vec::new(),
+            missing: Vec::new(),
+            truncated: false,
+        };
+        let mut seen = HashSet::new();
+        seen.insert(root);
+
+        let mut relations = Vec::new();
+        let mut level = vec![root];
+        let mut depth = 0;
+
+        while !level.is_empty() {
+            let many = fetch(&level)?;
+            graph.missing.extend(many.missing);
+
+            let mut next = Vec::new();
+            for item in many.items {
+                let item = item.into();
+                for (id, _, _) in item.relations.iter() {
+                    if seen.contains(id) {
+                        continue;
+                    } else if depth >= self.max_depth || seen.len() >= self.max_nodes {
+                        graph.truncated = true;
+                    } else {
+                        seen.insert(*id);
+                        next.push(*id);
+                    }
+                }
+
+                relations.push((item.id, item.relations));
+                graph.nodes.push(Node {
+                    id: item.id,
+                    title: item.title,
+                    original: item.original,
+                    depth,
+                });
+            }
+
+            level = next;
+            depth += 1;
+        }
+
+        let crawled = graph.nodes.iter().map(|node| node.id).collect::<HashSet<_>>();
+        for (from, relations) in relations {
+            for (to, relation, official) in relations {
+                if crawled.contains(&to) {
+                    graph.edges.push(Edge {
+                        from,
+                        to,
+                        relation,
+                        official,
+                    });
+                }
+            }
+        }
+
+        Ok(graph)
+    }
+}
+
+fn dot_escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
@@ -0,0 +1,561 @@
+//!Import of user's lists into VNDB.
+//!
+//!Counterpart to [export](../export/index.html), that reads lists in JSON, CSV or XML in format of MyAnimeList export.
+//!
+//!Items without VNDB's identifier are matched by title, using `search ~` filter.
+//!Matched items are compared with user's current `ulist` to produce [Plan](struct.Plan.html),
+//!that can be reviewed as dry-run diff, before it is applied through `set ulist` commands.
+//!
+//!Import only adds or overwrites values, it never removes items or values, that are missing in imported list.
+//!Requests, that are throttled by VNDB, are retried after waiting for time, suggested by VNDB.
+//!
+//!Example of usage:
+//!
+//!```no_run
+//!use vndb::export::Format;
+//!use vndb::import::{self, Importer};
+//!
+//!let mut client = vndb::client::simple::Client::connect().expect("To connect");
+//!//Set requires login with credentials or session token.
+//!
+//!let items = import::read(Format::Xml, std::fs::File::open("animelist.xml").expect("To open file")).expect("To read list");
+//!let importer = Importer::new();
+//!let plan = importer.plan(&mut client, 2, items).expect("To plan import");
+//!print!("{}", plan);
+//!
+//!importer.apply(&mut client, &plan).expect("To apply import");
+//!```
+
+use core::fmt;
+use std::collections::HashMap;
+use std::io::{self, Read, Write};
+use std::time::Duration;
+
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+use crate::client::simple::Client;
+use crate::client::{DEFAULT_MAX_RETRIES, DEFAULT_MAX_WAIT};
+use crate::export::{self, Format, Result, Status, UserList};
+use crate::protocol::message::request::{get, Get, Set};
+use crate::protocol::message::response::{results, typed};
+
+const MAL_EMPTY_DATE: &str = "0000-00-00";
+const MAL_VN_TYPE: &str = "Visual Novel";
+
+#[derive(Clone, Debug, PartialEq)]
+///Item of imported list.
+pub struct Item {
+    ///Identifier of VN, if it is known.
+    pub vn: Option<u64>,
+    ///Title of VN, used to find VN without identifier.
+    pub title: Option<String>,
+    ///Status. Ignored when `Unknown`.
+    pub status: Status,
+    ///Vote in range from 10 to 100.
+    pub vote: Option<u8>,
+    ///User's notes.
+    pub notes: Option<String>,
+    ///Date when user started playing, in format `YYYY-MM-DD`.
+    pub started: Option<String>,
+    ///Date when user finished playing, in format `YYYY-MM-DD`.
+    pub finished: Option<String>,
+}
+
+impl From<export::Entry> for Item {
+    fn from(entry: export::Entry) -> Self {
+        Self {
+            vn: Some(entry.vn),
+            title: entry.title,
+            status: entry.status,
+            vote: entry.vote,
+            notes: entry.notes,
+            started: entry.started,
+            finished: entry.finished,
+        }
+    }
+}
+
+impl Item {
+    fn describe(&self) -> String {
+        match (self.vn, self.title.as_deref()) {
+            (Some(vn), Some(title)) => format!("v{} {}", vn, title),
+            (Some(vn), None) => format!("v{}", vn),
+            (None, Some(title)) => format!("{:?}", title),
+            (None, None) => "<unnamed>".to_owned(),
+        }
+    }
+}
+
+fn invalid_data<E: Into<Box<dyn std::error::Error + Send + Sync>>>(error: E) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, error)
+}
+
+///Reads list in specified format.
+pub fn read<R: Read>(format: Format, input: R) -> io::Result<Vec<Item>> {
+    match format {
+        Format::Json => read_json(input),
+        Format::Csv => read_csv(input),
+        Format::Xml => read_mal_xml(input),
+    }
+}
+
+///Reads list, written by [UserList::write_json](../export/struct.UserList.html#method.write_json).
+pub fn read_json<R: Read>(input: R) -> io::Result<Vec<Item>> {
+    let list = UserList::from_json(input).map_err(invalid_data)?;
+    Ok(list.entries.into_iter().map(Item::from).collect())
+}
+
+///Reads CSV with header.
+///
+///Columns are identified by name, as written by [UserList::write_csv](../export/struct.UserList.html#method.write_csv).
+///Either `vn` or `title` column is required, others are optional.
+pub fn read_csv<R: Read>(mut input: R) -> io::Result<Vec<Item>> {
+    let mut text = String::new();
+    input.read_to_string(&mut text)?;
+
+    let mut records = csv_records(&text).into_iter();
+    let header = records.next().unwrap_or_default();
+    let column = |name: &str| header.iter().position(|column| column.trim() == name);
+    let (vn, title, status, vote, notes, started, finished) = (column("vn"), column("title"), column("status"), column("vote"), column("notes"), column("started"), column("finished"));
+    if vn.is_none() && title.is_none() {
+        return Err(invalid_data("CSV has neither 'vn' nor 'title' column"));
+    }
+
+    let mut items = Vec::new();
+    for (line, record) in records.enumerate() {
+        let field = |idx: Option<usize>| idx.and_then(|idx| record.get(idx)).map(|value| value.trim()).filter(|value| !value.is_empty());
+        let context = |error: String| invalid_data(format!("Record {}: {}", line + 1, error));
+
+        items.push(Item {
+            vn: field(vn).map(|vn| vn.trim_start_matches('v').parse()).transpose().map_err(|error| context(format!("invalid vn: {}", error)))?,
+            title: field(title).map(str::to_owned),
+            status: match field(status) {
+                Some(status) => Status::from_str(status).ok_or_else(|| context(format!("unknown status '{}'", status)))?,
+                None => Status::Unknown,
+            },
+            vote: field(vote).map(parse_score).transpose().map_err(context)?,
+            notes: field(notes).map(str::to_owned),
+            started: field(started).map(str::to_owned),
+            finished: field(finished).map(str::to_owned),
+        });
+    }
+
+    Ok(items)
+}
+
+///Reads XML in format of MyAnimeList export.
+///
+///Items exported by [UserList::write_mal_xml](../export/struct.UserList.html#method.write_mal_xml) keep VNDB's identifiers,
+///while other items are matched by title.
+pub fn read_mal_xml<R: Read>(mut input: R) -> io::Result<Vec<Item>> {
+    let mut text = String::new();
+    input.read_to_string(&mut text)?;
+
+    let mut items = Vec::new();
+    for element in ["anime", "manga"] {
+        for block in xml_elements(&text, element) {
+            let child = |name: &str| xml_child(block, name).filter(|value| !value.is_empty());
+            let is_vndb = child("series_type").as_deref() == Some(MAL_VN_TYPE);
+            let date = |name: &str| child(name).filter(|date| date != MAL_EMPTY_DATE);
+
+            let vn = match is_vndb {
+                true => Some(child("series_animedb_id").ok_or_else(|| invalid_data("VN without series_animedb_id"))?
+                                                      .parse()
+                                                      .map_err(|error| invalid_data(format!("Invalid series_animedb_id: {}", error)))?),
+                false => None,
+            };
+            let status = match child("my_status") {
+                Some(status) => mal_status(&status).ok_or_else(|| invalid_data(format!("Unknown my_status '{}'", status)))?,
+                None => Status::Unknown,
+            };
+            let vote = match child("my_score") {
+                Some(score) => score.parse::<u8>().map_err(|error| invalid_data(format!("Invalid my_score: {}", error)))?,
+                None => 0,
+            };
+
+            items.push(Item {
+                vn,
+                title: child("series_title").or_else(|| child("manga_title")),
+                status,
+                vote: match vote {
+                    0 => None,
+                    vote => Some(vote.min(10) * 10),
+                },
+                notes: child("my_comments"),
+                started: date("my_start_date"),
+                finished: date("my_finish_date"),
+            });
+        }
+    }
+
+    Ok(items)
+}
+
+fn parse_score(score: &str) -> core::result::Result<u8, String> {
+    match score.parse::<f32>() {
+        Ok(score) if (1.0..=10.0).contains(&score) => Ok((score * 10.0).round() as u8),
+        _ => Err(format!("invalid vote '{}', expected number from 1 to 10", score)),
+    }
+}
+
+fn mal_status(status: &str) -> Option<Status> {
+    match status {
+        "Watching" | "Reading" | "1" => Some(Status::Playing),
+        "Completed" | "2" => Some(Status::Finished),
+        "On-Hold" | "3" => Some(Status::Stalled),
+        "Dropped" | "4" => Some(Status::Dropped),
+        "Plan to Watch" | "Plan to Read" | "6" => Some(Status::Wishlist),
+        _ => None,
+    }
+}
+
+fn csv_records(text: &str) -> Vec<Vec<String>> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut chars = text.chars().peekable();
+    let mut in_quotes = false;
+
+    while let Some(ch) = chars.next() {
+        match (ch, in_quotes) {
+            ('"', true) if chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            },
+            ('"', true) => in_quotes = false,
+            ('"', false) if field.is_empty() => in_quotes = true,
+            (',', false) => record.push(core::mem::take(&mut field)),
+            ('\r', false) => (),
+            ('\n', false) => {
+                record.push(core::mem::take(&mut field));
+                records.push(core::mem::take(&mut record));
+            },
+            (ch, _) => field.push(ch),
+        }
+    }
+
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+
+    records.retain(|record| !(record.len() == 1 && record[0].is_empty()));
+    records
+}
+
+//Returns content of every element with specified name.
+fn xml_elements<'a>(text: &'a str, name: &str) -> Vec<&'a str> {
+    let (open, close) = (format!("<{}>", name), format!("</{}>", name));
+    let mut elements = Vec::new();
+    let mut rest = text;
+
+    while let Some(start) = rest.find(&open) {
+        rest = &rest[start + open.len()..];
+        match rest.find(&close) {
+            Some(end) => {
+                elements.push(&rest[..end]);
+                rest = &rest[end + close.len()..];
+            },
+            None => break,
+        }
+    }
+
+    elements
+}
+
+//Returns decoded text of child element.
+fn xml_child(block: &str, name: &str) -> Option<String> {
+    let text = xml_elements(block, name).into_iter().next()?.trim();
+
+    let mut value = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("<![CDATA[") {
+        value.push_str(&xml_unescape(&rest[..start]));
+        rest = &rest[start + 9..];
+        let end = rest.find("]]>").unwrap_or(rest.len());
+        value.push_str(&rest[..end]);
+        rest = rest.get(end + 3..).unwrap_or("");
+    }
+    value.push_str(&xml_unescape(rest));
+
+    Some(value.trim().to_owned())
+}
+
+fn xml_unescape(text: &str) -> String {
+    text.replace("&lt;", "<").replace("&gt;", ">").replace("&quot;", "\"").replace("&apos;", "'").replace("&amp;", "&")
+}
+
+#[derive(Clone, Debug, PartialEq)]
+///Difference of single field.
+///
+///Values are the same as in `set ulist` command, except for status, which is set through labels.
+pub struct FieldChange {
+    ///Name of field in `set ulist` command.
+    pub field: &'static str,
+    ///Current value.
+    pub before: Value,
+    ///New value.
+    pub after: Value,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+///Change of single item in user's list.
+pub struct Change {
+    ///Identifier of VN.
+    pub vn: u64,
+    ///Title of VN, if known.
+    pub title: Option<String>,
+    ///Whether VN is not yet in user's list.
+    pub is_new: bool,
+    ///Changed fields.
+    pub fields: Vec<FieldChange>,
+    ///Labels after change.
+    labels: Option<Vec<u32>>,
+}
+
+impl Change {
+    ///Returns `set ulist` command, that applies change.
+    pub fn request(&self) -> Set {
+        let mut fields = Map::new();
+        for change in self.fields.iter().filter(|change| change.field != "status") {
+            fields.insert(change.field.to_owned(), change.after.clone());
+        }
+        if let Some(labels) = self.labels.as_ref() {
+            fields.insert("labels".to_owned(), Value::from(labels.clone()));
+        }
+
+        Set {
+            kind: get::Type::ulist(),
+            id: self.vn,
+            fields: Some(fields),
+        }
+    }
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} v{}", if self.is_new { "+" } else { "~" }, self.vn)?;
+        if let Some(title) = self.title.as_ref() {
+            write!(f, " {}", title)?;
+        }
+
+        for (idx, change) in self.fields.iter().enumerate() {
+            let separator = if idx == 0 { ": " } else { ", " };
+            match self.is_new {
+                true => write!(f, "{}{}={}", separator, change.field, change.after)?,
+                false => write!(f, "{}{}: {} -> {}", separator, change.field, change.before, change.after)?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+///Changes, required to import list.
+pub struct Plan {
+    ///Changes to apply, ordered by VN's identifier.
+    pub changes: Vec<Change>,
+    ///Identifiers of VNs, that are already up to date.
+    pub unchanged: Vec<u64>,
+    ///Items, that cannot be matched to VN.
+    pub unmatched: Vec<Item>,
+}
+
+impl fmt::Display for Plan {
+    ///Writes dry-run diff, one line per change.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for change in self.changes.iter() {
+            writeln!(f, "{}", change)?;
+        }
+        for item in self.unmatched.iter() {
+            writeln!(f, "? {}: no match", item.describe())?;
+        }
+
+        let added = self.changes.iter().filter(|change| change.is_new).count();
+        writeln!(f, "{} to add, {} to update, {} unchanged, {} unmatched", added, self.changes.len() - added, self.unchanged.len(), self.unmatched.len())
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+///Statistics of applied import.
+pub struct ApplyStats {
+    ///Number of applied changes.
+    pub applied: usize,
+    ///Number of throttled requests, that were retried.
+    pub retries: usize,
+}
+
+#[derive(Clone, Debug)]
+///Importer of user's lists.
+pub struct Importer {
+    max_retries: u32,
+    max_wait: Duration,
+}
+
+impl Default for Importer {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Importer {
+    ///Creates new instance with default settings.
+    pub fn new() -> Self {
+        Self {
+            max_retries: DEFAULT_MAX_RETRIES,
+            max_wait: DEFAULT_MAX_WAIT,
+        }
+    }
+
+    ///Sets number of retries of throttled request.
+    ///
+    ///Defaults to [DEFAULT_MAX_RETRIES](../client/constant.DEFAULT_MAX_RETRIES.html).
+    pub fn max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    ///Sets limit on time to wait before retrying throttled request.
+    ///
+    ///Defaults to [DEFAULT_MAX_WAIT](../client/constant.DEFAULT_MAX_WAIT.html).
+    pub fn max_wait(mut self, max_wait: Duration) -> Self {
+        self.max_wait = max_wait;
+        self
+    }
+
+    #[inline]
+    //Performs request, retrying it while it is throttled.
+    fn retry<T, F: FnMut() -> crate::Result<T>>(&self, retries: &mut usize, request: F) -> crate::Result<T> {
+        crate::client::retry_throttled(self.max_retries, self.max_wait, retries, request)
+    }
+
+    //Finds VN by title, preferring exact match.
+    fn find<IO: Read + Write>(&self, client: &mut Client<IO>, title: &str) -> Result<Option<results::Vn>> {
+        let search = Value::from(title);
+        let get = Get {
+            kind: get::Type::vn(),
+            flags: get::Flags::new().basic(),
+            filters: get::Filters::new().filter(crate::filter!(search ~ search)),
+            options: Some(get::Options {
+                results: Some(export::PAGE_SIZE),
+                ..Default::default()
+            }),
+        };
+
+        let results = self.retry(&mut 0, || client.get(&get))?;
+        let mut vns = typed::VN::deserialize(&*results)?.items;
+        let title = title.to_lowercase();
+
+        match vns.iter().position(|vn| [vn.title.as_deref(), vn.original.as_deref()].iter().flatten().any(|name| name.to_lowercase() == title)) {
+            Some(idx) => Ok(Some(vns.swap_remove(idx))),
+            None if vns.len() == 1 => Ok(vns.pop()),
+            None => Ok(None),
+        }
+    }
+
+    ///Matches items with VNs and compares them with user's current `ulist`.
+    pub fn plan<IO: Read + Write>(&self, client: &mut Client<IO>, uid: u64, items: Vec<Item>) -> Result<Plan> {
+        let mut plan = Plan::default();
+        let mut matched = Vec::with_capacity(items.len());
+
+        for mut item in items {
+            if item.vn.is_none() {
+                if let Some(title) = item.title.clone() {
+                    if let Some(vn) = self.find(client, &title)? {
+                        item.vn = Some(vn.id);
+                        item.title = vn.title.or(item.title);
+                    }
+                }
+            }
+
+            match item.vn {
+                Some(_) => matched.push(item),
+                None => plan.unmatched.push(item),
+            }
+        }
+
+        let current = export::fetch_all::<results::UList, _>(|get| self.retry(&mut 0, || client.get(get)), get::Type::ulist(), get::Flags::new().basic().labels(), uid)?;
+        let current = current.into_iter().filter_map(|item| item.vn.map(|vn| (vn, item))).collect::<HashMap<_, _>>();
+
+        matched.sort_by_key(|item| item.vn);
+        for item in matched {
+            let vn = item.vn.unwrap_or_default();
+            match diff(&item, current.get(&vn)) {
+                Some(change) => plan.changes.push(change),
+                None => plan.unchanged.push(vn),
+            }
+        }
+
+        Ok(plan)
+    }
+
+    ///Applies changes through `set ulist` commands.
+    ///
+    ///Client must be logged in with credentials or session token.
+    pub fn apply<IO: Read + Write>(&self, client: &mut Client<IO>, plan: &Plan) -> Result<ApplyStats> {
+        let mut stats = ApplyStats::default();
+
+        for change in plan.changes.iter() {
+            let set = change.request();
+            self.retry(&mut stats.retries, || client.set(&set))?;
+            stats.applied += 1;
+        }
+
+        Ok(stats)
+    }
+}
+
+fn diff(item: &Item, current: Option<&results::UList>) -> Option<Change> {
+    let mut fields = Vec::new();
+    let mut push = |field: &'static str, before: Value, after: Value| if before != after {
+        fields.push(FieldChange {
+            field,
+            before,
+            after,
+        });
+    };
+
+    let before_status = current.map(|current| Status::from_labels(&current.labels)).unwrap_or(Status::Unknown);
+    if item.status != Status::Unknown {
+        push("status", Value::from(before_status.as_str()), Value::from(item.status.as_str()));
+    }
+    if let Some(vote) = item.vote {
+        push("vote", current.and_then(|current| current.vote).map(Value::from).unwrap_or(Value::Null), Value::from(vote));
+    }
+    if let Some(notes) = item.notes.as_deref() {
+        push("notes", Value::from(current.and_then(|current| current.notes.as_deref()).unwrap_or("")), Value::from(notes));
+    }
+    if let Some(started) = item.started.as_deref() {
+        push("started", current.and_then(|current| current.started.as_deref()).map(Value::from).unwrap_or(Value::Null), Value::from(started));
+    }
+    if let Some(finished) = item.finished.as_deref() {
+        push("finished", current.and_then(|current| current.finished.as_deref()).map(Value::from).unwrap_or(Value::Null), Value::from(finished));
+    }
+
+    if current.is_some() && fields.is_empty() {
+        return None;
+    }
+
+    //Labels are replaced as whole, hence custom labels must be preserved.
+    let labels = match fields.iter().any(|change| change.field == "status") {
+        true => {
+            let mut labels = current.map(|current| current.labels.iter().map(|label| label.id).filter(|id| *id > 6).collect()).unwrap_or_else(Vec::new);
+            labels.extend(item.status.label());
+            labels.sort_unstable();
+            Some(labels)
+        },
+        false if current.is_none() => Some(Vec::new()),
+        false => None,
+    };
+
+    Some(Change {
+        vn: item.vn.unwrap_or_default(),
+        title: item.title.clone(),
+        is_new: current.is_none(),
+        fields,
+        labels,
+    })
+}
@@ -4,6 +4,11 @@
 //!
 //!- `tokio-on` - Enables [tokio](https://tokio.rs/) implementation.
 //!- `rustls-on` - Enables TLS implementation, using rustls
+//!- `testing` - Enables in-process mock VNDB server.
+//!- `server` - Enables local VNDB-compatible server, backed by offline dataset.
+//!- `dump` - Enables reader of VNDB database dumps.
+//!- `mirror` - Enables local SQLite mirror of VNDB data.
+//!- `cli` - Enables `vndb` command-line tool.
 //!
 //!## TLS client
 //!
@@ -15,3 +20,25 @@
 mod utils;
 pub mod protocol;
 pub mod client;
+mod error;
+pub use error::{Error, FramingError, Result};
+pub mod markup;
+pub mod spoiler;
+pub mod export;
+pub mod import;
+pub mod graph;
+pub mod catalogue;
+pub mod recommend;
+pub mod stats;
+pub mod watch;
+pub mod feed;
+#[cfg(any(feature = "testing", feature = "server"))]
+mod listener;
+#[cfg(feature = "testing")]
+pub mod testing;
+#[cfg(feature = "server")]
+pub mod server;
+#[cfg(feature = "dump")]
+pub mod dump;
+#[cfg(feature = "mirror")]
+pub mod mirror;
@@ -0,0 +1,108 @@
+//!TCP listener, shared by mock and local servers.
+
+use std::{io, net, thread};
+use std::io::{BufRead, BufReader};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+///Connection's handler of request.
+///
+///Returns error, when connection must be closed.
+pub type Handler = Box<dyn FnMut(&str, &mut dyn io::Write) -> io::Result<()> + Send>;
+
+#[cfg(feature = "rustls-on")]
+pub type TlsConfig = Option<Arc<rustls::ServerConfig>>;
+#[cfg(not(feature = "rustls-on"))]
+pub type TlsConfig = Option<core::convert::Infallible>;
+
+pub struct Listener {
+    addr: net::SocketAddr,
+    is_shutdown: Arc<AtomicBool>,
+    worker: Option<thread::JoinHandle<()>>,
+}
+
+impl Listener {
+    ///Starts listening, creating handler for each new connection.
+    pub fn start<A: net::ToSocketAddrs, F: Fn() -> Handler + Send + 'static>(addr: A, tls: TlsConfig, new_handler: F) -> io::Result<Self> {
+        let listener = net::TcpListener::bind(addr)?;
+        let addr = listener.local_addr()?;
+        let is_shutdown = Arc::new(AtomicBool::new(false));
+
+        let worker = {
+            let is_shutdown = is_shutdown.clone();
+            thread::spawn(move || {
+                for stream in listener.incoming() {
+                    if is_shutdown.load(Ordering::Acquire) {
+                        break;
+                    }
+
+                    let stream = match stream {
+                        Ok(stream) => stream,
+                        Err(_) => continue,
+                    };
+
+                    let handler = new_handler();
+                    #[cfg(feature = "rustls-on")]
+                    let tls = tls.clone();
+                    thread::spawn(move || {
+                        #[cfg(feature = "rustls-on")]
+                        if let Some(tls) = tls {
+                            if let Ok(connection) = rustls::ServerConnection::new(tls) {
+                                serve(rustls::StreamOwned::new(connection, stream), handler);
+                            }
+                            return;
+                        }
+                        #[cfg(not(feature = "rustls-on"))]
+                        let _ = tls;
+
+                        serve(stream, handler);
+                    });
+                }
+            })
+        };
+
+        Ok(Self {
+            addr,
+            is_shutdown,
+            worker: Some(worker),
+        })
+    }
+
+    #[inline]
+    pub fn addr(&self) -> net::SocketAddr {
+        self.addr
+    }
+}
+
+fn serve<IO: io::Read + io::Write>(io: IO, mut handler: Handler) {
+    let mut io = BufReader::new(io);
+    let mut buf = Vec::new();
+
+    loop {
+        buf.clear();
+        match io.read_until(0x04, &mut buf) {
+            Ok(0) | Err(_) => break,
+            Ok(_) => (),
+        }
+
+        let request = match buf.split_last() {
+            Some((0x04, request)) => String::from_utf8_lossy(request).into_owned(),
+            _ => break,
+        };
+
+        if handler(&request, io.get_mut()).is_err() {
+            break;
+        }
+    }
+}
+
+impl Drop for Listener {
+    fn drop(&mut self) {
+        self.is_shutdown.store(true, Ordering::Release);
+        //Wake up listener
+        let _ = net::TcpStream::connect(self.addr);
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
@@ -0,0 +1,533 @@
+//!VNDB formatting codes.
+//!
+//!Descriptions and notes, returned by VNDB, may contain BBCode-like formatting codes.
+//![Reference](https://vndb.org/d9#4).
+//!
+//!Example of usage:
+//!
+//!```
+//!use vndb::markup::{Markup, SpoilerMode};
+//!
+//!let markup = Markup::parse("[b]Sequel[/b] to v17. [spoiler]Everyone dies[/spoiler]");
+//!assert_eq!(markup.to_text(SpoilerMode::Hide), "Sequel to v17. ");
+//!assert_eq!(markup.to_html(SpoilerMode::Hide), "<b>Sequel</b> to <a href=\"https://vndb.org/v17\">v17</a>. ");
+//!```
+
+use core::fmt;
+
+///VNDB site URL, used to link references.
+pub const VNDB_URL: &str = "https://vndb.org";
+
+///Characters that can start reference to VNDB entry, such as `v17`.
+const REF_KINDS: &[char] = &['v', 'r', 'p', 'c', 's', 'g', 'i', 'u', 'd', 't'];
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+///Describes how to render spoilers.
+pub enum SpoilerMode {
+    ///Omits spoilers.
+    Hide,
+    ///Shows spoilers as regular content.
+    Show,
+    ///Shows spoilers, marking them as such.
+    Mark,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+///Element of formatted text.
+pub enum Node {
+    ///Plain text.
+    Text(String),
+    ///Reference to VNDB entry, such as `v17`.
+    Ref {
+        ///Type of entry. I.e. `v` for VN.
+        kind: char,
+        ///Entry's ID.
+        id: u64,
+    },
+    ///`[url=...]` link.
+    Url {
+        ///Link's target.
+        url: String,
+        ///Link's content.
+        children: Vec<Node>,
+    },
+    ///`[b]` bold text.
+    Bold(Vec<Node>),
+    ///`[i]` italic text.
+    Italic(Vec<Node>),
+    ///`[u]` underlined text.
+    Underline(Vec<Node>),
+    ///`[s]` strike-through text.
+    Strike(Vec<Node>),
+    ///`[spoiler]` content.
+    Spoiler(Vec<Node>),
+    ///`[quote]` content.
+    Quote(Vec<Node>),
+    ///`[raw]` text, which is not interpreted.
+    Raw(String),
+    ///`[code]` block, which is not interpreted.
+    Code(String),
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Tag {
+    Url,
+    Bold,
+    Italic,
+    Underline,
+    Strike,
+    Spoiler,
+    Quote,
+}
+
+impl Tag {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "url" => Some(Tag::Url),
+            "b" => Some(Tag::Bold),
+            "i" => Some(Tag::Italic),
+            "u" => Some(Tag::Underline),
+            "s" => Some(Tag::Strike),
+            "spoiler" => Some(Tag::Spoiler),
+            "quote" => Some(Tag::Quote),
+            _ => None,
+        }
+    }
+}
+
+struct Open {
+    tag: Tag,
+    //Text of opening tag, to restore it when tag is not closed.
+    source: String,
+    url: Option<String>,
+    children: Vec<Node>,
+}
+
+impl Open {
+    fn close(self) -> Node {
+        let Open { tag, url, children, .. } = self;
+        match tag {
+            Tag::Url => match url {
+                Some(url) => Node::Url { url, children },
+                None => Node::Url { url: plain_text(&children), children },
+            },
+            Tag::Bold => Node::Bold(children),
+            Tag::Italic => Node::Italic(children),
+            Tag::Underline => Node::Underline(children),
+            Tag::Strike => Node::Strike(children),
+            Tag::Spoiler => Node::Spoiler(children),
+            Tag::Quote => Node::Quote(children),
+        }
+    }
+
+    //Adds unclosed tag to parent as text, unless it is spoiler.
+    fn unwind(self, parent: &mut Vec<Node>) {
+        if self.tag == Tag::Spoiler {
+            return parent.push(self.close());
+        }
+
+        push_text(parent, &self.source);
+        for child in self.children {
+            match child {
+                Node::Text(text) => push_text(parent, &text),
+                child => parent.push(child),
+            }
+        }
+    }
+}
+
+fn plain_text(nodes: &[Node]) -> String {
+    let mut result = String::new();
+    for node in nodes {
+        match node {
+            Node::Text(text) | Node::Raw(text) | Node::Code(text) => result.push_str(text),
+            Node::Ref { kind, id } => {
+                result.push(*kind);
+                result.push_str(&id.to_string());
+            },
+            Node::Url { children, .. } | Node::Bold(children) | Node::Italic(children) | Node::Underline(children)
+            | Node::Strike(children) | Node::Spoiler(children) | Node::Quote(children) => result.push_str(&plain_text(children)),
+        }
+    }
+    result
+}
+
+fn push_text(nodes: &mut Vec<Node>, text: &str) {
+    if text.is_empty() {
+        return;
+    }
+
+    match nodes.last_mut() {
+        Some(Node::Text(last)) => last.push_str(text),
+        _ => nodes.push(Node::Text(text.to_owned())),
+    }
+}
+
+//Splits text into text and references, when allowed.
+fn push_linked_text(nodes: &mut Vec<Node>, text: &str, link_refs: bool) {
+    if !link_refs {
+        return push_text(nodes, text);
+    }
+
+    let bytes = text.as_bytes();
+    let mut start = 0;
+    let mut idx = 0;
+    while idx < bytes.len() {
+        let ch = bytes[idx] as char;
+        let is_boundary = idx == 0 || !(bytes[idx - 1] as char).is_ascii_alphanumeric();
+
+        if is_boundary && REF_KINDS.contains(&ch) {
+            let digits = bytes[idx + 1..].iter().take_while(|byte| byte.is_ascii_digit()).count();
+            let end = idx + 1 + digits;
+            let is_end_boundary = end == bytes.len() || !(bytes[end] as char).is_ascii_alphanumeric();
+
+            if digits > 0 && is_end_boundary {
+                if let Ok(id) = text[idx + 1..end].parse() {
+                    push_text(nodes, &text[start..idx]);
+                    nodes.push(Node::Ref { kind: ch, id });
+                    start = end;
+                    idx = end;
+                    continue;
+                }
+            }
+        }
+
+        idx += 1;
+    }
+
+    push_text(nodes, &text[start..]);
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+///Parsed formatted text.
+pub struct Markup {
+    ///Top level elements.
+    pub nodes: Vec<Node>,
+}
+
+impl Markup {
+    ///Parses formatted text.
+    ///
+    ///Parser never fails, tags that are not closed or unknown are treated as plain text.
+    ///The only exception is spoiler, which is never revealed: when not closed, it extends to the end of enclosing tag or text.
+    pub fn parse(text: &str) -> Self {
+        let mut stack: Vec<Open> = Vec::new();
+        let mut nodes = Vec::new();
+        let mut rest = text;
+
+        macro_rules! current {
+            () => {
+                match stack.last_mut() {
+                    Some(open) => &mut open.children,
+                    None => &mut nodes,
+                }
+            }
+        }
+
+        macro_rules! link_refs {
+            () => {
+                stack.iter().all(|open| open.tag != Tag::Url)
+            }
+        }
+
+        while let Some(tag_start) = rest.find('[') {
+            let link_refs = link_refs!();
+            push_linked_text(current!(), &rest[..tag_start], link_refs);
+            rest = &rest[tag_start..];
+
+            let tag_end = match rest.find(']') {
+                Some(tag_end) => tag_end,
+                None => break,
+            };
+            let source = &rest[..=tag_end];
+            let content = &rest[1..tag_end];
+
+            //Literal blocks
+            let literal = match content.to_ascii_lowercase().as_str() {
+                "raw" => Some(("[/raw]", false)),
+                "code" => Some(("[/code]", true)),
+                _ => None,
+            };
+            if let Some((closing, is_code)) = literal {
+                let body = &rest[tag_end + 1..];
+                if let Some(body_end) = find_ignore_case(body, closing) {
+                    let literal = body[..body_end].to_owned();
+                    current!().push(match is_code {
+                        true => Node::Code(literal),
+                        false => Node::Raw(literal),
+                    });
+                    rest = &body[body_end + closing.len()..];
+                    continue;
+                }
+            }
+
+            if let Some(name) = content.strip_prefix('/') {
+                let tag = Tag::from_name(&name.to_ascii_lowercase());
+                match tag {
+                    Some(tag) if stack.iter().any(|open| open.tag == tag) => {
+                        //Close all unclosed tags within closing one as plain text.
+                        while let Some(open) = stack.pop() {
+                            if open.tag == tag {
+                                let node = open.close();
+                                current!().push(node);
+                                break;
+                            }
+
+                            open.unwind(current!());
+                        }
+                    },
+                    _ => {
+                        push_text(current!(), "[");
+                        rest = &rest[1..];
+                        continue;
+                    },
+                }
+            } else {
+                let (name, url) = match content.split_once('=') {
+                    Some((name, url)) => (name, Some(url)),
+                    None => (content, None),
+                };
+
+                match Tag::from_name(&name.to_ascii_lowercase()) {
+                    Some(tag) if url.is_none() || tag == Tag::Url => stack.push(Open {
+                        tag,
+                        source: source.to_owned(),
+                        url: url.map(|url| url.to_owned()),
+                        children: Vec::new(),
+                    }),
+                    //Not a tag, so only bracket is text, while the rest may contain tags.
+                    _ => {
+                        push_text(current!(), "[");
+                        rest = &rest[1..];
+                        continue;
+                    },
+                }
+            }
+
+            rest = &rest[tag_end + 1..];
+        }
+
+        let link_refs = link_refs!();
+        push_linked_text(current!(), rest, link_refs);
+
+        while let Some(open) = stack.pop() {
+            open.unwind(current!());
+        }
+
+        Self {
+            nodes
+        }
+    }
+
+    ///Returns whether text contains spoilers.
+    pub fn has_spoilers(&self) -> bool {
+        fn has_spoilers(nodes: &[Node]) -> bool {
+            nodes.iter().any(|node| match node {
+                Node::Spoiler(_) => true,
+                Node::Url { children, .. } | Node::Bold(children) | Node::Italic(children) | Node::Underline(children)
+                | Node::Strike(children) | Node::Quote(children) => has_spoilers(children),
+                _ => false,
+            })
+        }
+
+        has_spoilers(&self.nodes)
+    }
+
+    ///Removes all spoilers from text.
+    pub fn strip_spoilers(&mut self) {
+        fn strip(nodes: &mut Vec<Node>) {
+            nodes.retain(|node| !matches!(node, Node::Spoiler(_)));
+            for node in nodes.iter_mut() {
+                match node {
+                    Node::Url { children, .. } | Node::Bold(children) | Node::Italic(children) | Node::Underline(children)
+                    | Node::Strike(children) | Node::Quote(children) => strip(children),
+                    _ => (),
+                }
+            }
+        }
+
+        strip(&mut self.nodes)
+    }
+
+    ///Renders text as HTML.
+    ///
+    ///All text is escaped and only `http`, `https` and site relative links are rendered as links.
+    ///Marked spoilers are wrapped into `<span class="spoiler">`
+    pub fn to_html(&self, spoilers: SpoilerMode) -> String {
+        let mut result = String::new();
+        render_html(&mut result, &self.nodes, spoilers);
+        result
+    }
+
+    ///Renders text as plain text, omitting formatting.
+    ///
+    ///Marked spoilers are written as `[Spoiler: <text>]`
+    pub fn to_text(&self, spoilers: SpoilerMode) -> String {
+        let mut result = String::new();
+        render_text(&mut result, &self.nodes, spoilers);
+        result
+    }
+}
+
+fn find_ignore_case(text: &str, pattern: &str) -> Option<usize> {
+    let pattern = pattern.as_bytes();
+    text.as_bytes().windows(pattern.len()).position(|window| window.eq_ignore_ascii_case(pattern))
+}
+
+fn escape_html(result: &mut String, text: &str) {
+    for ch in text.chars() {
+        match ch {
+            '&' => result.push_str("&amp;"),
+            '<' => result.push_str("&lt;"),
+            '>' => result.push_str("&gt;"),
+            '"' => result.push_str("&quot;"),
+            '\'' => result.push_str("&#39;"),
+            ch => result.push(ch),
+        }
+    }
+}
+
+fn link_target(url: &str) -> Option<String> {
+    let lower = url.trim().to_ascii_lowercase();
+    if lower.starts_with("http://") || lower.starts_with("https://") {
+        Some(url.trim().to_owned())
+    } else if lower.starts_with('/') && !lower.starts_with("//") {
+        Some(format!("{}{}", VNDB_URL, url.trim()))
+    } else {
+        None
+    }
+}
+
+fn render_html(result: &mut String, nodes: &[Node], spoilers: SpoilerMode) {
+    macro_rules! wrap {
+        ($tag:literal, $children:expr) => {{
+            result.push_str(concat!("<", $tag, ">"));
+            render_html(result, $children, spoilers);
+            result.push_str(concat!("</", $tag, ">"));
+        }}
+    }
+
+    for node in nodes {
+        match node {
+            Node::Text(text) => {
+                let mut lines = text.split('\n');
+                if let Some(line) = lines.next() {
+                    escape_html(result, line);
+                }
+                for line in lines {
+                    result.push_str("<br>");
+                    escape_html(result, line);
+                }
+            },
+            Node::Raw(text) => escape_html(result, text),
+            Node::Code(text) => {
+                result.push_str("<pre>");
+                escape_html(result, text);
+                result.push_str("</pre>");
+            },
+            Node::Ref { kind, id } => {
+                result.push_str(&format!("<a href=\"{}/{}{}\">{}{}</a>", VNDB_URL, kind, id, kind, id));
+            },
+            Node::Url { url, children } => match link_target(url) {
+                Some(url) => {
+                    result.push_str("<a href=\"");
+                    escape_html(result, &url);
+                    result.push_str("\" rel=\"nofollow\">");
+                    render_html(result, children, spoilers);
+                    result.push_str("</a>");
+                },
+                None => render_html(result, children, spoilers),
+            },
+            Node::Bold(children) => wrap!("b", children),
+            Node::Italic(children) => wrap!("i", children),
+            Node::Underline(children) => wrap!("u", children),
+            Node::Strike(children) => wrap!("s", children),
+            Node::Quote(children) => wrap!("blockquote", children),
+            Node::Spoiler(children) => match spoilers {
+                SpoilerMode::Hide => (),
+                SpoilerMode::Show => render_html(result, children, spoilers),
+                SpoilerMode::Mark => {
+                    result.push_str("<span class=\"spoiler\">");
+                    render_html(result, children, spoilers);
+                    result.push_str("</span>");
+                },
+            },
+        }
+    }
+}
+
+fn render_text(result: &mut String, nodes: &[Node], spoilers: SpoilerMode) {
+    for node in nodes {
+        match node {
+            Node::Text(text) | Node::Raw(text) | Node::Code(text) => result.push_str(text),
+            Node::Ref { kind, id } => {
+                result.push(*kind);
+                result.push_str(&id.to_string());
+            },
+            Node::Url { children, .. } | Node::Bold(children) | Node::Italic(children)
+            | Node::Underline(children) | Node::Strike(children) => render_text(result, children, spoilers),
+            Node::Quote(children) => {
+                let mut quote = String::new();
+                render_text(&mut quote, children, spoilers);
+                for (idx, line) in quote.lines().enumerate() {
+                    if idx > 0 {
+                        result.push('\n');
+                    }
+                    result.push_str("> ");
+                    result.push_str(line);
+                }
+            },
+            Node::Spoiler(children) => match spoilers {
+                SpoilerMode::Hide => (),
+                SpoilerMode::Show => render_text(result, children, spoilers),
+                SpoilerMode::Mark => {
+                    result.push_str("[Spoiler: ");
+                    render_text(result, children, spoilers);
+                    result.push(']');
+                },
+            },
+        }
+    }
+}
+
+impl fmt::Display for Node {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fn children(f: &mut fmt::Formatter, tag: &str, children: &[Node]) -> fmt::Result {
+            write!(f, "[{}]", tag)?;
+            for child in children {
+                fmt::Display::fmt(child, f)?;
+            }
+            write!(f, "[/{}]", tag)
+        }
+
+        match self {
+            Node::Text(text) => f.write_str(text),
+            Node::Ref { kind, id } => write!(f, "{}{}", kind, id),
+            Node::Url { url, children: nodes } => {
+                write!(f, "[url={}]", url)?;
+                for child in nodes {
+                    fmt::Display::fmt(child, f)?;
+                }
+                f.write_str("[/url]")
+            },
+            Node::Bold(nodes) => children(f, "b", nodes),
+            Node::Italic(nodes) => children(f, "i", nodes),
+            Node::Underline(nodes) => children(f, "u", nodes),
+            Node::Strike(nodes) => children(f, "s", nodes),
+            Node::Spoiler(nodes) => children(f, "spoiler", nodes),
+            Node::Quote(nodes) => children(f, "quote", nodes),
+            Node::Raw(text) => write!(f, "[raw]{}[/raw]", text),
+            Node::Code(text) => write!(f, "[code]{}[/code]", text),
+        }
+    }
+}
+
+///Writes text back as VNDB formatting codes.
+impl fmt::Display for Markup {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for node in self.nodes.iter() {
+            fmt::Display::fmt(node, f)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,545 @@
+//!Local SQLite mirror of VNDB data.
+//!
+//!Mirror stores typed results of `get vn` and `get release` in SQLite database,
+//!alongside with time when each entity was fetched.
+//!
+//!Entities are synchronized through client in batches of ID ranges, each fetched page by page.
+//!Batches are aligned to multiple of [batch size](struct.Mirror.html#method.batch_size),
+//!and are fetched again only once they become older than [max age](struct.Mirror.html#method.max_age).
+//!Entities, that are no longer returned for fetched batch, are removed from mirror.
+//!Requests, that are throttled by VNDB, are retried after waiting for time, suggested by VNDB.
+//!
+//!Example of usage:
+//!
+//!```no_run
+//!use vndb::mirror::Mirror;
+//!use vndb::protocol::message::Request;
+//!use vndb::protocol::message::request::Login;
+//!
+//!let mut client = vndb::client::simple::Client::connect().expect("To connect");
+//!client.send(&Request::Login(Login::default())).expect("To send");
+//!client.flush().expect("To flush");
+//!client.receive().expect("To login");
+//!
+//!let mut mirror = Mirror::open("vndb.sqlite").expect("To open mirror");
+//!mirror.sync_vn(&mut client, 1..=100).expect("To sync");
+//!
+//!for vn in mirror.vn_by_title("ever17").expect("To query") {
+//!    println!("v{}: {:?}", vn.id, vn.title);
+//!}
+//!```
+
+use core::fmt;
+use core::ops::RangeInclusive;
+use std::io::{Read, Write};
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use rusqlite::{params, Connection, OptionalExtension, Transaction};
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+
+use crate::client::simple::Client;
+use crate::client::{DEFAULT_MAX_RETRIES, DEFAULT_MAX_WAIT};
+use crate::protocol::message::request::{get, Get};
+use crate::protocol::message::response::{results, typed};
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS vns (
+    id INTEGER PRIMARY KEY,
+    title TEXT,
+    original TEXT,
+    aliases TEXT,
+    released TEXT,
+    data TEXT NOT NULL,
+    fetched_at INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS vn_tags (
+    vn INTEGER NOT NULL,
+    tag INTEGER NOT NULL,
+    score REAL NOT NULL,
+    spoiler INTEGER NOT NULL,
+    PRIMARY KEY (vn, tag)
+);
+CREATE INDEX IF NOT EXISTS vn_tags_tag ON vn_tags (tag);
+CREATE TABLE IF NOT EXISTS releases (
+    id INTEGER PRIMARY KEY,
+    title TEXT,
+    original TEXT,
+    released TEXT,
+    data TEXT NOT NULL,
+    fetched_at INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS release_vns (
+    release INTEGER NOT NULL,
+    vn INTEGER NOT NULL,
+    PRIMARY KEY (release, vn)
+);
+CREATE INDEX IF NOT EXISTS release_vns_vn ON release_vns (vn);
+CREATE TABLE IF NOT EXISTS batches (
+    kind TEXT NOT NULL,
+    first INTEGER NOT NULL,
+    last INTEGER NOT NULL,
+    fetched_at INTEGER NOT NULL,
+    PRIMARY KEY (kind, first, last)
+);
+";
+
+///Default age, after which batch is considered stale.
+pub const DEFAULT_MAX_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+///Default number of IDs in single batch.
+pub const DEFAULT_BATCH_SIZE: u64 = 100;
+///Maximum number of results per page, allowed by VNDB.
+pub const MAX_PAGE_SIZE: u32 = 25;
+
+#[derive(Debug)]
+///Mirror error.
+pub enum Error {
+    ///Database error.
+    Database(rusqlite::Error),
+    ///Client error.
+    Client(crate::Error),
+    ///Unable to serialize or deserialize entity.
+    Json(serde_json::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Database(error) => write!(fmt, "Database error: {}", error),
+            Error::Client(error) => write!(fmt, "Client error: {}", error),
+            Error::Json(error) => write!(fmt, "Invalid entity: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Database(error) => Some(error),
+            Error::Client(error) => Some(error),
+            Error::Json(error) => Some(error),
+        }
+    }
+}
+
+impl From<rusqlite::Error> for Error {
+    #[inline]
+    fn from(error: rusqlite::Error) -> Self {
+        Error::Database(error)
+    }
+}
+
+impl From<crate::Error> for Error {
+    #[inline]
+    fn from(error: crate::Error) -> Self {
+        Error::Client(error)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    #[inline]
+    fn from(error: serde_json::Error) -> Self {
+        Error::Json(error)
+    }
+}
+
+///Mirror result.
+pub type Result<T> = core::result::Result<T, Error>;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+///Statistics of synchronization.
+pub struct SyncStats {
+    ///Number of fetched batches.
+    pub fetched: usize,
+    ///Number of batches, skipped as they are not stale yet.
+    pub skipped: usize,
+    ///Number of sent requests.
+    pub requests: usize,
+    ///Number of stored entities.
+    pub updated: usize,
+    ///Number of removed entities, that are no longer present in VNDB.
+    pub removed: usize,
+    ///Number of throttled requests, that were retried.
+    pub retries: usize,
+}
+
+//Entity, that can be stored in mirror.
+trait Entity: serde::Serialize + DeserializeOwned {
+    const TABLE: &'static str;
+
+    fn kind() -> get::Type;
+    fn flags() -> get::Flags;
+    fn id(&self) -> u64;
+    fn store(&self, tx: &Transaction, data: &str, fetched_at: i64) -> rusqlite::Result<()>;
+    fn remove(tx: &Transaction, id: u64) -> rusqlite::Result<()>;
+}
+
+impl Entity for results::Vn {
+    const TABLE: &'static str = "vns";
+
+    fn kind() -> get::Type {
+        get::Type::vn()
+    }
+
+    fn flags() -> get::Flags {
+        get::Flags::new().basic().details().anime().relations().tags().stats().screens().staff()
+    }
+
+    #[inline]
+    fn id(&self) -> u64 {
+        self.id
+    }
+
+    fn store(&self, tx: &Transaction, data: &str, fetched_at: i64) -> rusqlite::Result<()> {
+        tx.execute("INSERT OR REPLACE INTO vns (id, title, original, aliases, released, data, fetched_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                   params![self.id, self.title, self.original, self.aliases, self.released, data, fetched_at])?;
+
+        tx.execute("DELETE FROM vn_tags WHERE vn = ?1", [self.id])?;
+        for tag in self.tags.iter() {
+            tx.execute("INSERT OR REPLACE INTO vn_tags (vn, tag, score, spoiler) VALUES (?1, ?2, ?3, ?4)",
+                       params![self.id, tag.id, tag.score, tag.spoiler as u8])?;
+        }
+
+        Ok(())
+    }
+
+    fn remove(tx: &Transaction, id: u64) -> rusqlite::Result<()> {
+        tx.execute("DELETE FROM vn_tags WHERE vn = ?1", [id])?;
+        tx.execute("DELETE FROM vns WHERE id = ?1", [id])?;
+        Ok(())
+    }
+}
+
+impl Entity for results::Release {
+    const TABLE: &'static str = "releases";
+
+    fn kind() -> get::Type {
+        get::Type::release()
+    }
+
+    fn flags() -> get::Flags {
+        get::Flags::new().basic().details().vn().producers()
+    }
+
+    #[inline]
+    fn id(&self) -> u64 {
+        self.id
+    }
+
+    fn store(&self, tx: &Transaction, data: &str, fetched_at: i64) -> rusqlite::Result<()> {
+        tx.execute("INSERT OR REPLACE INTO releases (id, title, original, released, data, fetched_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                   params![self.id, self.title, self.original, self.released, data, fetched_at])?;
+
+        tx.execute("DELETE FROM release_vns WHERE release = ?1", [self.id])?;
+        for vn in self.vn.iter() {
+            tx.execute("INSERT OR REPLACE INTO release_vns (release, vn) VALUES (?1, ?2)", [self.id, vn.id])?;
+        }
+
+        Ok(())
+    }
+
+    fn remove(tx: &Transaction, id: u64) -> rusqlite::Result<()> {
+        tx.execute("DELETE FROM release_vns WHERE release = ?1", [id])?;
+        tx.execute("DELETE FROM releases WHERE id = ?1", [id])?;
+        Ok(())
+    }
+}
+
+#[cfg(feature = "server")]
+//Returns table of entities by their type.
+fn table(kind: &str) -> Option<&'static str> {
+    if kind == results::Vn::kind().as_str() {
+        Some(results::Vn::TABLE)
+    } else if kind == results::Release::kind().as_str() {
+        Some(results::Release::TABLE)
+    } else {
+        None
+    }
+}
+
+fn unix_now() -> i64 {
+    match SystemTime::now().duration_since(UNIX_EPOCH) {
+        Ok(now) => now.as_secs() as i64,
+        Err(_) => 0,
+    }
+}
+
+///Local SQLite mirror of VNDB.
+pub struct Mirror {
+    conn: Connection,
+    max_age: Duration,
+    batch_size: u64,
+    page_size: u32,
+    max_retries: u32,
+    max_wait: Duration,
+}
+
+impl Mirror {
+    ///Creates new instance from existing connection, creating schema if necessary.
+    pub fn new(conn: Connection) -> Result<Self> {
+        conn.execute_batch(SCHEMA)?;
+
+        Ok(Self {
+            conn,
+            max_age: DEFAULT_MAX_AGE,
+            batch_size: DEFAULT_BATCH_SIZE,
+            page_size: MAX_PAGE_SIZE,
+            max_retries: DEFAULT_MAX_RETRIES,
+            max_wait: DEFAULT_MAX_WAIT,
+        })
+    }
+
+    #[inline]
+    ///Opens mirror from database file, creating it if necessary.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::new(Connection::open(path)?)
+    }
+
+    #[inline]
+    ///Opens mirror in memory.
+    pub fn open_in_memory() -> Result<Self> {
+        Self::new(Connection::open_in_memory()?)
+    }
+
+    ///Sets age, after which batch is fetched again.
+    ///
+    ///Default is [DEFAULT_MAX_AGE](constant.DEFAULT_MAX_AGE.html).
+    pub fn max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = max_age;
+        self
+    }
+
+    ///Sets number of IDs in single batch.
+    ///
+    ///Default is [DEFAULT_BATCH_SIZE](constant.DEFAULT_BATCH_SIZE.html).
+    pub fn batch_size(mut self, batch_size: u64) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    ///Sets number of results per page, limited by [MAX_PAGE_SIZE](constant.MAX_PAGE_SIZE.html).
+    pub fn page_size(mut self, page_size: u32) -> Self {
+        self.page_size = page_size.clamp(1, MAX_PAGE_SIZE);
+        self
+    }
+
+    ///Sets number of retries of throttled request.
+    ///
+    ///Default is [DEFAULT_MAX_RETRIES](../client/constant.DEFAULT_MAX_RETRIES.html).
+    pub fn max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    ///Sets limit on time to wait before retrying throttled request.
+    ///
+    ///Default is [DEFAULT_MAX_WAIT](../client/constant.DEFAULT_MAX_WAIT.html).
+    pub fn max_wait(mut self, max_wait: Duration) -> Self {
+        self.max_wait = max_wait;
+        self
+    }
+
+    #[inline]
+    ///Returns underlying connection, which can be used for custom queries.
+    pub fn connection(&self) -> &Connection {
+        &self.conn
+    }
+
+    ///Synchronizes VNs with IDs in range.
+    ///
+    ///Client must be logged in.
+    pub fn sync_vn<IO: Read + Write>(&mut self, client: &mut Client<IO>, ids: RangeInclusive<u64>) -> Result<SyncStats> {
+        self.sync::<results::Vn, IO>(client, ids)
+    }
+
+    ///Synchronizes releases with IDs in range.
+    ///
+    ///Client must be logged in.
+    pub fn sync_releases<IO: Read + Write>(&mut self, client: &mut Client<IO>, ids: RangeInclusive<u64>) -> Result<SyncStats> {
+        self.sync::<results::Release, IO>(client, ids)
+    }
+
+    ///Returns VN by its ID.
+    pub fn vn(&self, id: u64) -> Result<Option<results::Vn>> {
+        self.select("SELECT data FROM vns WHERE id = ?1", [id]).map(|mut vns| vns.pop())
+    }
+
+    ///Returns VNs, which title, original title or aliases contain text, ignoring ASCII case.
+    pub fn vn_by_title(&self, title: &str) -> Result<Vec<results::Vn>> {
+        self.select("SELECT data FROM vns WHERE instr(lower(title), lower(?1)) OR instr(lower(original), lower(?1)) OR instr(lower(aliases), lower(?1)) ORDER BY id", [title])
+    }
+
+    ///Returns VNs with tag, ordered by tag's score.
+    pub fn vn_by_tag(&self, tag: u64) -> Result<Vec<results::Vn>> {
+        self.select("SELECT vns.data FROM vns JOIN vn_tags ON vn_tags.vn = vns.id WHERE vn_tags.tag = ?1 ORDER BY vn_tags.score DESC, vns.id", [tag])
+    }
+
+    ///Returns time, when VN was fetched.
+    pub fn vn_fetched_at(&self, id: u64) -> Result<Option<SystemTime>> {
+        self.fetched_at::<results::Vn>(id)
+    }
+
+    ///Returns release by its ID.
+    pub fn release(&self, id: u64) -> Result<Option<results::Release>> {
+        self.select("SELECT data FROM releases WHERE id = ?1", [id]).map(|mut releases| releases.pop())
+    }
+
+    ///Returns releases, which title or original title contain text, ignoring ASCII case.
+    pub fn release_by_title(&self, title: &str) -> Result<Vec<results::Release>> {
+        self.select("SELECT data FROM releases WHERE instr(lower(title), lower(?1)) OR instr(lower(original), lower(?1)) ORDER BY id", [title])
+    }
+
+    ///Returns releases of VN.
+    pub fn release_by_vn(&self, vn: u64) -> Result<Vec<results::Release>> {
+        self.select("SELECT releases.data FROM releases JOIN release_vns ON release_vns.release = releases.id WHERE release_vns.vn = ?1 ORDER BY releases.id", [vn])
+    }
+
+    ///Returns time, when release was fetched.
+    pub fn release_fetched_at(&self, id: u64) -> Result<Option<SystemTime>> {
+        self.fetched_at::<results::Release>(id)
+    }
+
+    #[cfg(feature = "server")]
+    //Returns all stored entities of type in format of `get` results, ordered by ID.
+    pub(crate) fn entities(&self, kind: &str) -> Result<Vec<serde_json::Value>> {
+        let table = match table(kind) {
+            Some(table) => table,
+            None => return Ok(Vec::new()),
+        };
+
+        let mut stmt = self.conn.prepare_cached(&format!("SELECT data FROM {} ORDER BY id", table))?;
+        let mut rows = stmt.query([])?;
+
+        let mut result = Vec::new();
+        while let Some(row) = rows.next()? {
+            let data = row.get_ref(0)?.as_str().map_err(rusqlite::Error::from)?;
+            result.push(serde_json::from_str(data)?);
+        }
+
+        Ok(result)
+    }
+
+    #[cfg(feature = "server")]
+    //Returns number of stored entities of type.
+    pub(crate) fn count(&self, kind: &str) -> Result<u64> {
+        match table(kind) {
+            Some(table) => Ok(self.conn.query_row(&format!("SELECT COUNT(*) FROM {}", table), [], |row| row.get(0))?),
+            None => Ok(0),
+        }
+    }
+
+    fn select<T: Entity, P: rusqlite::Params>(&self, sql: &str, params: P) -> Result<Vec<T>> {
+        let mut stmt = self.conn.prepare_cached(sql)?;
+        let mut rows = stmt.query(params)?;
+
+        let mut result = Vec::new();
+        while let Some(row) = rows.next()? {
+            let data = row.get_ref(0)?.as_str().map_err(rusqlite::Error::from)?;
+            result.push(serde_json::from_str(data)?);
+        }
+
+        Ok(result)
+    }
+
+    fn fetched_at<T: Entity>(&self, id: u64) -> Result<Option<SystemTime>> {
+        let sql = format!("SELECT fetched_at FROM {} WHERE id = ?1", T::TABLE);
+        let fetched_at = self.conn.query_row(&sql, [id], |row| row.get::<_, i64>(0)).optional()?;
+        Ok(fetched_at.map(|secs| UNIX_EPOCH + Duration::from_secs(secs.max(0) as u64)))
+    }
+
+    fn is_fresh<T: Entity>(&self, first: u64, last: u64, now: i64) -> Result<bool> {
+        let fetched_at = self.conn.query_row("SELECT fetched_at FROM batches WHERE kind = ?1 AND first = ?2 AND last = ?3",
+                                             params![T::kind().as_str(), first, last],
+                                             |row| row.get::<_, i64>(0)).optional()?;
+
+        Ok(match fetched_at {
+            Some(fetched_at) => now.saturating_sub(fetched_at) < self.max_age.as_secs() as i64,
+            None => false,
+        })
+    }
+
+    fn sync<T: Entity, IO: Read + Write>(&mut self, client: &mut Client<IO>, ids: RangeInclusive<u64>) -> Result<SyncStats> {
+        let mut stats = SyncStats::default();
+        if ids.is_empty() {
+            return Ok(stats);
+        }
+
+        let mut first = ids.start() - ids.start() % self.batch_size;
+        loop {
+            let last = first.saturating_add(self.batch_size - 1);
+            let now = unix_now();
+
+            if self.is_fresh::<T>(first, last, now)? {
+                stats.skipped += 1;
+            } else {
+                let items = self.fetch::<T, IO>(client, first, last, &mut stats)?;
+                self.store(items, first, last, now, &mut stats)?;
+                stats.fetched += 1;
+            }
+
+            first = match last.checked_add(1) {
+                Some(next) if next <= *ids.end() => next,
+                _ => break Ok(stats),
+            };
+        }
+    }
+
+    fn fetch<T: Entity, IO: Read + Write>(&self, client: &mut Client<IO>, first: u64, last: u64, stats: &mut SyncStats) -> Result<Vec<T>> {
+        let mut items = Vec::new();
+        let mut page = 1;
+
+        loop {
+            let get = Get {
+                kind: T::kind(),
+                flags: T::flags(),
+                filters: get::Filters::new().filter(crate::filter!(id >= first)).and(crate::filter!(id <= last)),
+                options: Some(get::Options {
+                    page: Some(page),
+                    results: Some(self.page_size),
+                    ..Default::default()
+                }),
+            };
+
+            let mut retries = 0;
+            let results = crate::client::retry_throttled(self.max_retries, self.max_wait, &mut retries, || client.get(&get))?;
+            stats.requests += 1 + retries;
+            stats.retries += retries;
+            let results = typed::Results::<T>::deserialize(&*results)?;
+            items.extend(results.items);
+
+            match results.more {
+                true => page += 1,
+                false => break Ok(items),
+            }
+        }
+    }
+
+    fn store<T: Entity>(&mut self, items: Vec<T>, first: u64, last: u64, now: i64, stats: &mut SyncStats) -> Result<()> {
+        let tx = self.conn.transaction()?;
+
+        let mut ids = Vec::with_capacity(items.len());
+        for item in items.iter() {
+            let data = serde_json::to_string(item)?;
+            item.store(&tx, &data, now)?;
+            ids.push(item.id());
+        }
+        stats.updated += items.len();
+
+        let removed = {
+            let sql = format!("SELECT id FROM {} WHERE id BETWEEN ?1 AND ?2", T::TABLE);
+            let mut stmt = tx.prepare(&sql)?;
+            let existing = stmt.query_map([first, last], |row| row.get::<_, u64>(0))?.collect::<rusqlite::Result<Vec<_>>>()?;
+            existing.into_iter().filter(|id| !ids.contains(id)).collect::<Vec<_>>()
+        };
+        for id in removed.iter() {
+            T::remove(&tx, *id)?;
+        }
+        stats.removed += removed.len();
+
+        tx.execute("INSERT OR REPLACE INTO batches (kind, first, last, fetched_at) VALUES (?1, ?2, ?3, ?4)",
+                   params![T::kind().as_str(), first, last, now])?;
+
+        tx.commit().map_err(Error::from)
+    }
+}
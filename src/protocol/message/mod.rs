@@ -5,7 +5,7 @@ use core::{str, fmt, convert};
 pub mod request;
 pub mod response;
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq)]
 ///VNDB Request
 ///
 ///On error returns [Response::Error](response/Struct.VndbError.html).
@@ -18,6 +18,10 @@ pub enum Request<'a> {
     ///
     ///On success returns [Response::Results](response/Struct.Results.html)
     Get(request::Get<'a>),
+    ///Set request.
+    ///
+    ///On success returns `Response::Ok`
+    Set(request::Set),
     ///VNDB statistic request.
     ///
     ///On success returns [Response::DBstats](response/Struct.DBstats.html)
@@ -36,16 +40,93 @@ impl<'a> convert::From<request::Get<'a>> for Request<'a> {
     }
 }
 
+impl<'a> convert::From<request::Set> for Request<'a> {
+    fn from(set: request::Set) -> Self {
+        Request::Set(set)
+    }
+}
+
 impl<'a> fmt::Display for Request<'a> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             Request::Login(ref login) => write!(f, "{}\x04", login),
             Request::Get(ref get) => write!(f, "{}\x04", get),
+            Request::Set(ref set) => write!(f, "{}\x04", set),
             Request::DBstats => write!(f, "dbstats\x04")
         }
     }
 }
 
+impl<'a> Request<'a> {
+    ///Parses request from text message, with or without 0x04 byte.
+    ///
+    ///Parsed request is displayed in the same format as original, except for filters,
+    ///which are normalized.
+    pub fn from_str(msg: &'a str) -> Result<Self, RequestParseError<'a>> {
+        let msg = msg.strip_suffix('\x04').unwrap_or(msg);
+        let (command, args) = match msg.split_once(' ') {
+            Some((command, args)) => (command, Some(args)),
+            None => (msg, None),
+        };
+
+        match command {
+            "" => Err(RequestParseError::EmptyRequest),
+            "login" => match args {
+                Some(args) => request::Login::from_str(args).map(Request::Login).map_err(RequestParseError::InvalidLogin),
+                None => Err(RequestParseError::MissingArguments("login")),
+            },
+            "get" => match args {
+                Some(args) => request::Get::from_str(args).map(Request::Get).map_err(RequestParseError::InvalidGet),
+                None => Err(RequestParseError::MissingArguments("get")),
+            },
+            "set" => match args {
+                Some(args) => request::Set::from_str(args).map(Request::Set).map_err(RequestParseError::InvalidSet),
+                None => Err(RequestParseError::MissingArguments("set")),
+            },
+            "dbstats" => match args {
+                None => Ok(Request::DBstats),
+                Some(_) => Err(RequestParseError::UnexpectedArguments("dbstats")),
+            },
+            command => Err(RequestParseError::UnknownCommand(command)),
+        }
+    }
+}
+
+#[derive(Debug)]
+///Result of Request parser
+pub enum RequestParseError<'a> {
+    ///Request is empty.
+    EmptyRequest,
+    ///Command requires arguments, but there are none.
+    MissingArguments(&'static str),
+    ///Command doesn't accept arguments.
+    UnexpectedArguments(&'static str),
+    ///Invalid login arguments.
+    InvalidLogin(serde_json::Error),
+    ///Invalid get arguments.
+    InvalidGet(request::GetParseError<'a>),
+    ///Invalid set arguments.
+    InvalidSet(request::SetParseError<'a>),
+    ///Unknown command is specified.
+    UnknownCommand(&'a str),
+}
+
+impl<'a> fmt::Display for RequestParseError<'a> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RequestParseError::EmptyRequest => fmt.write_str("Empty request."),
+            RequestParseError::MissingArguments(command) => write!(fmt, "Command '{}' requires arguments.", command),
+            RequestParseError::UnexpectedArguments(command) => write!(fmt, "Command '{}' has no arguments.", command),
+            RequestParseError::InvalidLogin(ref error) => write!(fmt, "Invalid login arguments: {}", error),
+            RequestParseError::InvalidGet(ref error) => write!(fmt, "Invalid get arguments: {}", error),
+            RequestParseError::InvalidSet(ref error) => write!(fmt, "Invalid set arguments: {}", error),
+            RequestParseError::UnknownCommand(command) => write!(fmt, "Unknown command '{}'", command),
+        }
+    }
+}
+
+impl<'a> std::error::Error for RequestParseError<'a> {}
+
 #[derive(Debug, Clone)]
 ///VNDB Response
 pub enum Response {
@@ -56,7 +137,9 @@ pub enum Response {
     ///DB statistic response.
     DBstats(response::DBstats),
     ///VNDB Error in case of invalid request.
-    Error(response::VndbError)
+    Error(response::VndbError),
+    ///Session token, created on login.
+    Session(String),
 }
 
 impl convert::From<response::Results> for Response {
@@ -77,47 +160,85 @@ impl convert::From<response::VndbError> for Response {
     }
 }
 
+impl fmt::Display for Response {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fn json<T: serde::Serialize>(f: &mut fmt::Formatter, command: &str, payload: &T) -> fmt::Result {
+            match serde_json::to_string(payload) {
+                Ok(payload) => write!(f, "{} {}\x04", command, payload),
+                Err(_) => Err(fmt::Error),
+            }
+        }
+
+        match self {
+            Response::Ok => f.write_str("ok\x04"),
+            Response::Results(ref results) => write!(f, "results {}\x04", results),
+            Response::DBstats(ref stats) => json(f, "dbstats", stats),
+            Response::Error(ref error) => json(f, "error", error),
+            Response::Session(ref token) => write!(f, "session {}\x04", token),
+        }
+    }
+}
+
 impl Response {
     ///Parses response from text message without 0x04 byte.
     pub fn from_str(msg: &str) -> Result<Self, ResponseParseError> {
         let mut split_msg = msg.splitn(2, ' ');
 
         let command = match split_msg.next() {
-            Some(command) => command,
-            None => return Err(ResponseParseError::EmptyResponse),
+            Some(command) if !command.is_empty() => command,
+            _ => return Err(ResponseParseError::new(ResponseParseErrorKind::EmptyResponse, "", "")),
         };
+        let payload = split_msg.next();
+
+        macro_rules! parse_payload {
+            ($empty:ident, $invalid:ident) => {
+                match payload {
+                    Some(payload) => match parse_json(payload) {
+                        Ok(payload) => payload,
+                        Err((path, error)) => {
+                            let mut error = ResponseParseError::new(ResponseParseErrorKind::$invalid(error), command, payload);
+                            error.path = Some(path);
+                            return Err(error);
+                        }
+                    },
+                    None => return Err(ResponseParseError::new(ResponseParseErrorKind::$empty, command, "")),
+                }
+            }
+        }
 
         match command {
             "ok" => Ok(Response::Ok),
-            "results" => match split_msg.next() {
-                Some(results) => match response::Results::from_str(results) {
-                    Ok(results) => Ok(Response::Results(results)),
-                    Err(error) => Err(ResponseParseError::InvalidResults(error)),
-                },
-                None => Err(ResponseParseError::EmptyResults),
+            "results" => Ok(Response::Results(response::Results::new(parse_payload!(EmptyResults, InvalidResults)))),
+            "dbstats" => Ok(Response::DBstats(parse_payload!(EmptyDbStats, InvalidDbStats))),
+            "error" => Ok(Response::Error(parse_payload!(EmptyError, InvalidError))),
+            "session" => match payload.map(str::trim) {
+                Some(token) if !token.is_empty() => Ok(Response::Session(token.trim_matches('"').to_owned())),
+                _ => Err(ResponseParseError::new(ResponseParseErrorKind::EmptySession, command, "")),
             },
-            "dbstats" => match split_msg.next() {
-                Some(dbstats) => match serde_json::from_str(dbstats) {
-                    Ok(dbstats) => Ok(Response::DBstats(dbstats)),
-                    Err(error) => Err(ResponseParseError::InvalidDbStats(error)),
-                },
-                None => Err(ResponseParseError::EmptyDbStats),
-            },
-            "error" => match split_msg.next() {
-                Some(error) => match serde_json::from_str(error) {
-                    Ok(error) => Ok(Response::Error(error)),
-                    Err(error) => Err(ResponseParseError::InvalidError(error)),
-                },
-                None => Err(ResponseParseError::EmptyDbStats),
-            }
-            _ => Err(ResponseParseError::UnknownComamnd),
+            _ => Err(ResponseParseError::new(ResponseParseErrorKind::UnknownCommand, command, payload.unwrap_or(""))),
+        }
+    }
+}
+
+fn parse_json<T: serde::de::DeserializeOwned>(payload: &str) -> Result<T, (String, serde_json::Error)> {
+    let mut deserializer = serde_json::Deserializer::from_str(payload);
+    let result = match serde_path_to_error::deserialize(&mut deserializer) {
+        Ok(result) => result,
+        Err(error) => {
+            let path = error.path().to_string();
+            return Err((path, error.into_inner()));
         }
+    };
+
+    match deserializer.end() {
+        Ok(()) => Ok(result),
+        Err(error) => Err((".".to_owned(), error)),
     }
 }
 
 #[derive(Debug)]
-///Result of Response parser
-pub enum ResponseParseError {
+///Kind of [ResponseParseError](struct.ResponseParseError.html)
+pub enum ResponseParseErrorKind {
     ///Response is empty
     EmptyResponse,
     ///Results is without payload
@@ -126,6 +247,8 @@ pub enum ResponseParseError {
     EmptyDbStats,
     ///Error is without payload
     EmptyError,
+    ///Session is without token
+    EmptySession,
     ///Invalid Results payload.
     InvalidResults(serde_json::Error),
     ///Invalid DBstats payload.
@@ -133,23 +256,87 @@ pub enum ResponseParseError {
     ///Invalid Error payload.
     InvalidError(serde_json::Error),
     ///Unknown command is specified.
-    UnknownComamnd,
+    UnknownCommand,
+}
+
+#[derive(Debug)]
+///Result of Response parser
+pub struct ResponseParseError {
+    ///Kind of error.
+    pub kind: ResponseParseErrorKind,
+    ///Response's command.
+    ///
+    ///Empty if response is empty.
+    pub command: String,
+    ///Beginning of response's payload.
+    ///
+    ///Truncated to at most [EXCERPT_LEN](struct.ResponseParseError.html#associatedconstant.EXCERPT_LEN) bytes.
+    ///Payload of `session` and unknown commands may contain secrets, so only its length is kept, as `<N bytes>`.
+    pub excerpt: String,
+    ///JSON path to the element, that failed to parse, in format `items[0].id`.
+    ///
+    ///Available only for invalid payloads. Root is denoted as `.`
+    pub path: Option<String>,
+}
+
+impl ResponseParseError {
+    ///Maximum length of payload's excerpt.
+    pub const EXCERPT_LEN: usize = 64;
+
+    pub(crate) fn new(kind: ResponseParseErrorKind, command: &str, payload: &str) -> Self {
+        let is_sensitive = match kind {
+            ResponseParseErrorKind::UnknownCommand => true,
+            _ => command == "session",
+        };
+
+        let excerpt = match payload.len() > Self::EXCERPT_LEN {
+            _ if is_sensitive && !payload.is_empty() => format!("<{} bytes>", payload.len()),
+            true => {
+                let mut end = Self::EXCERPT_LEN;
+                while !payload.is_char_boundary(end) {
+                    end -= 1;
+                }
+                format!("{}...", &payload[..end])
+            },
+            false => payload.to_owned(),
+        };
+
+        Self {
+            kind,
+            command: command.to_owned(),
+            excerpt,
+            path: None,
+        }
+    }
 }
 
 impl fmt::Display for ResponseParseError {
     #[inline]
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
-        match self {
-            ResponseParseError::EmptyResponse => fmt.write_str("VNDB sent empty response."),
-            ResponseParseError::EmptyResults => fmt.write_str("VNDB sent Results with no payload."),
-            ResponseParseError::EmptyDbStats => fmt.write_str("VNDB sent DBstats with no payload."),
-            ResponseParseError::EmptyError => fmt.write_str("VNDB sent Error with no payload."),
-            ResponseParseError::InvalidResults(ref error) => write!(fmt, "VNDB sent invalid JSON in Results: {}", error),
-            ResponseParseError::InvalidDbStats(ref error) => write!(fmt, "VNDB sent invalid JSON in DBstats: {}", error),
-            ResponseParseError::InvalidError(ref error) => write!(fmt, "VNDB sent invalid JSON in Error: {}", error),
-            ResponseParseError::UnknownComamnd => fmt.write_str("VNDB sent unknown command"),
+        let path = self.path.as_deref().unwrap_or(".");
+        match self.kind {
+            ResponseParseErrorKind::EmptyResponse => return fmt.write_str("VNDB sent empty response."),
+            ResponseParseErrorKind::EmptyResults => return fmt.write_str("VNDB sent Results with no payload."),
+            ResponseParseErrorKind::EmptyDbStats => return fmt.write_str("VNDB sent DBstats with no payload."),
+            ResponseParseErrorKind::EmptyError => return fmt.write_str("VNDB sent Error with no payload."),
+            ResponseParseErrorKind::EmptySession => return fmt.write_str("VNDB sent Session with no token."),
+            ResponseParseErrorKind::InvalidResults(ref error) => write!(fmt, "VNDB sent invalid JSON in Results at '{}': {}", path, error)?,
+            ResponseParseErrorKind::InvalidDbStats(ref error) => write!(fmt, "VNDB sent invalid JSON in DBstats at '{}': {}", path, error)?,
+            ResponseParseErrorKind::InvalidError(ref error) => write!(fmt, "VNDB sent invalid JSON in Error at '{}': {}", path, error)?,
+            ResponseParseErrorKind::UnknownCommand => write!(fmt, "VNDB sent unknown command '{}'", self.command)?,
         }
+
+        write!(fmt, " Payload: '{}'", self.excerpt)
     }
 }
 
-impl std::error::Error for ResponseParseError {}
+impl std::error::Error for ResponseParseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self.kind {
+            ResponseParseErrorKind::InvalidResults(ref error) => Some(error),
+            ResponseParseErrorKind::InvalidDbStats(ref error) => Some(error),
+            ResponseParseErrorKind::InvalidError(ref error) => Some(error),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,401 @@
+//!Filter expressions of get command.
+//!
+//!VNDB [Reference](https://vndb.org/d11#6)
+
+use core::fmt;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+///Filter's comparison operator.
+pub enum Operator {
+    ///`=`
+    Eq,
+    ///`!=`
+    NotEq,
+    ///`>`
+    Greater,
+    ///`>=`
+    GreaterEq,
+    ///`<`
+    Less,
+    ///`<=`
+    LessEq,
+    ///`~`, used for text search.
+    Like,
+}
+
+impl Operator {
+    ///Returns textual representation of operator.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Operator::Eq => "=",
+            Operator::NotEq => "!=",
+            Operator::Greater => ">",
+            Operator::GreaterEq => ">=",
+            Operator::Less => "<",
+            Operator::LessEq => "<=",
+            Operator::Like => "~",
+        }
+    }
+}
+
+impl fmt::Display for Operator {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+///Single filter condition, i.e. `id = 1`
+pub struct Condition {
+    ///Name of field.
+    pub field: String,
+    ///Operator.
+    pub op: Operator,
+    ///Value in JSON format.
+    pub value: serde_json::Value,
+}
+
+impl fmt::Display for Condition {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {} {}", self.field, self.op, self.value)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+///Filter expression.
+///
+///Its `Display` implementation produces expression without outer parenthesis.
+pub enum Expr {
+    ///Single condition.
+    Condition(Condition),
+    ///All expressions must match.
+    And(Vec<Expr>),
+    ///Any of expressions must match.
+    Or(Vec<Expr>),
+}
+
+impl Expr {
+    ///Parses expression, with or without outer parenthesis.
+    pub fn parse(text: &str) -> Result<Self, FilterParseError> {
+        let mut parser = Parser {
+            text,
+            pos: 0,
+        };
+
+        let expr = parser.parse_or()?;
+        parser.skip_whitespace();
+        match parser.pos == text.len() {
+            true => Ok(expr),
+            false => Err(parser.error(FilterParseErrorKind::TrailingCharacters)),
+        }
+    }
+
+    ///Returns all conditions of expression.
+    pub fn conditions(&self) -> Vec<&Condition> {
+        let mut result = Vec::new();
+        let mut stack = vec![self];
+        while let Some(expr) = stack.pop() {
+            match expr {
+                Expr::Condition(condition) => result.push(condition),
+                Expr::And(exprs) | Expr::Or(exprs) => stack.extend(exprs.iter().rev()),
+            }
+        }
+        result
+    }
+
+    ///Returns canonical form of expression.
+    ///
+    ///Nested groups of the same kind are flattened, while duplicate operands are removed
+    ///and the rest are sorted by their textual representation.
+    pub fn normalized(&self) -> Self {
+        fn normalize(exprs: &[Expr], is_and: bool) -> Vec<Expr> {
+            let mut result = Vec::with_capacity(exprs.len());
+            for expr in exprs.iter().map(Expr::normalized) {
+                match expr {
+                    Expr::And(exprs) if is_and => result.extend(exprs),
+                    Expr::Or(exprs) if !is_and => result.extend(exprs),
+                    expr => result.push(expr),
+                }
+            }
+
+            let mut result = result.into_iter().map(|expr| (expr.to_string(), expr)).collect::<Vec<_>>();
+            result.sort_by(|(left, _), (right, _)| left.cmp(right));
+            result.dedup_by(|(left, _), (right, _)| left == right);
+            result.into_iter().map(|(_, expr)| expr).collect()
+        }
+
+        let (mut exprs, is_and) = match self {
+            Expr::Condition(condition) => return Expr::Condition(condition.clone()),
+            Expr::And(exprs) => (normalize(exprs, true), true),
+            Expr::Or(exprs) => (normalize(exprs, false), false),
+        };
+
+        match (exprs.len(), is_and) {
+            (1, _) => exprs.pop().unwrap(),
+            (_, true) => Expr::And(exprs),
+            (_, false) => Expr::Or(exprs),
+        }
+    }
+}
+
+impl From<Condition> for Expr {
+    #[inline]
+    fn from(condition: Condition) -> Self {
+        Expr::Condition(condition)
+    }
+}
+
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fn join(f: &mut fmt::Formatter, exprs: &[Expr], sep: &str) -> fmt::Result {
+            for (idx, expr) in exprs.iter().enumerate() {
+                if idx > 0 {
+                    write!(f, " {} ", sep)?;
+                }
+                match expr {
+                    Expr::Condition(condition) => write!(f, "{}", condition)?,
+                    expr => write!(f, "({})", expr)?,
+                }
+            }
+            Ok(())
+        }
+
+        match self {
+            Expr::Condition(condition) => write!(f, "{}", condition),
+            Expr::And(exprs) => join(f, exprs, "and"),
+            Expr::Or(exprs) => join(f, exprs, "or"),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+///Kind of [FilterParseError](struct.FilterParseError.html)
+pub enum FilterParseErrorKind {
+    ///Expression ends unexpectedly.
+    UnexpectedEnd,
+    ///Expected field name.
+    ExpectedField,
+    ///Expected operator.
+    ExpectedOperator,
+    ///Value is not valid JSON.
+    InvalidValue,
+    ///Parenthesis is not closed.
+    UnclosedParenthesis,
+    ///Unexpected characters after expression.
+    TrailingCharacters,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+///Filter expression parse error.
+pub struct FilterParseError {
+    ///Kind of error.
+    pub kind: FilterParseErrorKind,
+    ///Byte offset within expression.
+    pub pos: usize,
+}
+
+impl fmt::Display for FilterParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let desc = match self.kind {
+            FilterParseErrorKind::UnexpectedEnd => "Unexpected end of expression",
+            FilterParseErrorKind::ExpectedField => "Expected field name",
+            FilterParseErrorKind::ExpectedOperator => "Expected operator",
+            FilterParseErrorKind::InvalidValue => "Invalid value",
+            FilterParseErrorKind::UnclosedParenthesis => "Parenthesis is not closed",
+            FilterParseErrorKind::TrailingCharacters => "Unexpected characters after expression",
+        };
+
+        write!(f, "{} at position {}", desc, self.pos)
+    }
+}
+
+impl std::error::Error for FilterParseError {}
+
+struct Parser<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    #[inline]
+    fn error(&self, kind: FilterParseErrorKind) -> FilterParseError {
+        FilterParseError {
+            kind,
+            pos: self.pos,
+        }
+    }
+
+    #[inline]
+    fn rest(&self) -> &'a str {
+        &self.text[self.pos..]
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    //Consumes keyword, if it is followed by non-word character.
+    fn keyword(&mut self, keyword: &str) -> bool {
+        self.skip_whitespace();
+        let rest = self.rest();
+        let is_keyword = rest.len() >= keyword.len()
+                         && rest[..keyword.len()].eq_ignore_ascii_case(keyword)
+                         && !rest[keyword.len()..].starts_with(|ch: char| ch.is_ascii_alphanumeric() || ch == '_');
+        if is_keyword {
+            self.pos += keyword.len();
+        }
+        is_keyword
+    }
+
+    fn parse_or(&mut self) -> Result<Expr, FilterParseError> {
+        let mut exprs = vec![self.parse_and()?];
+        while self.keyword("or") {
+            exprs.push(self.parse_and()?);
+        }
+
+        match exprs.len() {
+            1 => Ok(exprs.pop().unwrap()),
+            _ => Ok(Expr::Or(exprs)),
+        }
+    }
+
+    fn parse_and(&mut self) -> Result<Expr, FilterParseError> {
+        let mut exprs = vec![self.parse_primary()?];
+        while self.keyword("and") {
+            exprs.push(self.parse_primary()?);
+        }
+
+        match exprs.len() {
+            1 => Ok(exprs.pop().unwrap()),
+            _ => Ok(Expr::And(exprs)),
+        }
+    }
+
+    fn parse_primary(&mut self) -> Result<Expr, FilterParseError> {
+        self.skip_whitespace();
+
+        if self.rest().starts_with('(') {
+            let start = self.pos;
+            self.pos += 1;
+            let expr = self.parse_or()?;
+            self.skip_whitespace();
+            return match self.rest().starts_with(')') {
+                true => {
+                    self.pos += 1;
+                    Ok(expr)
+                },
+                false => Err(FilterParseError {
+                    kind: FilterParseErrorKind::UnclosedParenthesis,
+                    pos: start,
+                }),
+            }
+        }
+
+        let rest = self.rest();
+        if rest.is_empty() {
+            return Err(self.error(FilterParseErrorKind::UnexpectedEnd));
+        }
+
+        let field_len = rest.find(|ch: char| !(ch.is_ascii_alphanumeric() || ch == '_')).unwrap_or(rest.len());
+        if field_len == 0 {
+            return Err(self.error(FilterParseErrorKind::ExpectedField));
+        }
+        let field = rest[..field_len].to_owned();
+        self.pos += field_len;
+
+        self.skip_whitespace();
+        let rest = self.rest();
+        let op = if rest.starts_with("!=") {
+            Operator::NotEq
+        } else if rest.starts_with(">=") {
+            Operator::GreaterEq
+        } else if rest.starts_with("<=") {
+            Operator::LessEq
+        } else if rest.starts_with('=') {
+            Operator::Eq
+        } else if rest.starts_with('>') {
+            Operator::Greater
+        } else if rest.starts_with('<') {
+            Operator::Less
+        } else if rest.starts_with('~') {
+            Operator::Like
+        } else if rest.is_empty() {
+            return Err(self.error(FilterParseErrorKind::UnexpectedEnd));
+        } else {
+            return Err(self.error(FilterParseErrorKind::ExpectedOperator));
+        };
+        self.pos += op.as_str().len();
+
+        self.skip_whitespace();
+        let value = self.parse_value()?;
+
+        Ok(Expr::Condition(Condition {
+            field,
+            op,
+            value
+        }))
+    }
+
+    fn parse_value(&mut self) -> Result<serde_json::Value, FilterParseError> {
+        let rest = self.rest();
+        let len = match json_value_len(rest) {
+            Some(0) | None => return Err(self.error(FilterParseErrorKind::InvalidValue)),
+            Some(len) => len,
+        };
+
+        match serde_json::from_str(&rest[..len]) {
+            Ok(value) => {
+                self.pos += len;
+                Ok(value)
+            },
+            Err(_) => Err(self.error(FilterParseErrorKind::InvalidValue)),
+        }
+    }
+}
+
+//Determines length of JSON value at the start of text.
+fn json_value_len(text: &str) -> Option<usize> {
+    let bytes = text.as_bytes();
+    match bytes.first()? {
+        b'"' => string_len(bytes),
+        b'[' | b'{' => {
+            let mut depth = 0usize;
+            let mut idx = 0;
+            while idx < bytes.len() {
+                match bytes[idx] {
+                    b'"' => {
+                        idx += string_len(&bytes[idx..])?;
+                        continue;
+                    },
+                    b'[' | b'{' => depth += 1,
+                    b']' | b'}' => {
+                        depth -= 1;
+                        if depth == 0 {
+                            return Some(idx + 1);
+                        }
+                    },
+                    _ => (),
+                }
+                idx += 1;
+            }
+            None
+        },
+        _ => Some(bytes.iter().position(|byte| !(byte.is_ascii_alphanumeric() || *byte == b'.' || *byte == b'-' || *byte == b'+')).unwrap_or(bytes.len())),
+    }
+}
+
+//Length of JSON string, including quotes.
+fn string_len(bytes: &[u8]) -> Option<usize> {
+    let mut idx = 1;
+    while idx < bytes.len() {
+        match bytes[idx] {
+            b'\\' => idx += 2,
+            b'"' => return Some(idx + 1),
+            _ => idx += 1,
+        }
+    }
+    None
+}
@@ -2,7 +2,9 @@
 
 use core::fmt;
 
-#[derive(Clone)]
+use super::filter;
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 ///Flags for get command.
 ///
 ///Determines which information to retrieve.
@@ -26,6 +28,7 @@ impl Flags {
     const TRAITS: u16 = 0b001_000_000_000_00;
     const VNS: u16 = 0b010_000_000_000_00;
     const VOICED: u16 = 0b100_000_000_000_00;
+    const LABELS: u16 = 0b1_000_000_000_000_00;
 
     ///Creates new instance with no flags;
     pub fn new() -> Self {
@@ -68,27 +71,39 @@ impl Flags {
     pub fn vns(self) -> Self { self.push(Self::VNS) }
     ///Adds voiced information.
     pub fn voiced(self) -> Self { self.push(Self::VOICED) }
+    ///Adds labels information.
+    pub fn labels(self) -> Self { self.push(Self::LABELS) }
+
+    ///Parses comma separated list of flags, such as `basic,details`.
+    ///
+    ///On error returns unknown flag.
+    pub fn from_str(flags: &str) -> Result<Self, &str> {
+        let mut result = Self::new();
+
+        for name in flags.split(',') {
+            match FLAGS.iter().find(|(_, flag_name)| *flag_name == name) {
+                Some((flag, _)) => result = result.push(*flag),
+                None => return Err(name),
+            }
+        }
+
+        Ok(result)
+    }
+
+    ///Returns whether no flags are set.
+    pub fn is_empty(&self) -> bool {
+        self.flags == 0
+    }
+
+    ///Returns names of all set flags.
+    pub fn names(&self) -> impl Iterator<Item=&'static str> + '_ {
+        FLAGS.iter().filter(move |(flag, _)| self.flags & flag > 0).map(|(_, name)| *name)
+    }
 }
 
 impl fmt::Display for Flags {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let mut has_prev = false;
-        const FLAGS: [(u16, &'static str); 14] = [
-            (Flags::BASIC, "basic"),
-            (Flags::DETAILS, "details"),
-            (Flags::ANIME, "anime"),
-            (Flags::RELATIONS, "relations"),
-            (Flags::TAGS, "tags"),
-            (Flags::STATS, "stats"),
-            (Flags::SCREENS, "screens"),
-            (Flags::STAFF, "staff"),
-            (Flags::VN, "vn"),
-            (Flags::PRODUCERS, "producers"),
-            (Flags::MEAS, "meas"),
-            (Flags::TRAITS, "traits"),
-            (Flags::VNS, "vns"),
-            (Flags::VOICED, "voiced"),
-        ];
 
         for (flag, name) in FLAGS.iter() {
             if self.flags & flag > 0 {
@@ -105,7 +120,25 @@ impl fmt::Display for Flags {
     }
 }
 
-#[derive(Clone)]
+const FLAGS: [(u16, &'static str); 15] = [
+    (Flags::BASIC, "basic"),
+    (Flags::DETAILS, "details"),
+    (Flags::ANIME, "anime"),
+    (Flags::RELATIONS, "relations"),
+    (Flags::TAGS, "tags"),
+    (Flags::STATS, "stats"),
+    (Flags::SCREENS, "screens"),
+    (Flags::STAFF, "staff"),
+    (Flags::VN, "vn"),
+    (Flags::PRODUCERS, "producers"),
+    (Flags::MEAS, "meas"),
+    (Flags::TRAITS, "traits"),
+    (Flags::VNS, "vns"),
+    (Flags::VOICED, "voiced"),
+    (Flags::LABELS, "labels"),
+];
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 ///Type of VNDB entity.
 ///
 ///On request can be issued only on one type.
@@ -135,6 +168,27 @@ impl Type {
     pub const fn wishlist() -> Self { Self { inner: "wishlist" } }
     ///Combination of `votelist`, `vnlist` and `wishlist`.
     pub const fn ulist() -> Self { Self { inner: "ulist" } }
+    ///Tag information.
+    pub const fn tag() -> Self { Self { inner: "tag" } }
+    ///Character's trait information.
+    pub const fn r#trait() -> Self { Self { inner: "trait" } }
+
+    ///Returns type by its name, if it is known.
+    pub fn from_str(name: &str) -> Option<Self> {
+        const TYPES: [Type; 12] = [
+            Type::vn(), Type::release(), Type::producer(), Type::character(), Type::staff(),
+            Type::user(), Type::votelist(), Type::vnlist(), Type::wishlist(), Type::ulist(),
+            Type::tag(), Type::r#trait(),
+        ];
+
+        TYPES.iter().find(|kind| kind.inner == name).cloned()
+    }
+
+    #[inline]
+    ///Returns name of type.
+    pub fn as_str(&self) -> &'static str {
+        self.inner
+    }
 
     ///Returns short ID alias of type.
     ///
@@ -163,7 +217,7 @@ macro_rules! filter {
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 ///Filters that controls what information to retrieve.
 ///
 ///Example of usage:
@@ -211,6 +265,22 @@ impl Filters {
         self.filter("or").filter(filter)
     }
 
+    ///Parses filters in format `(<expression>)`.
+    ///
+    ///Expression is stored in normalized form, with single space between elements.
+    pub fn parse(filters: &str) -> Result<Self, filter::FilterParseError> {
+        filter::Expr::parse(filters).map(|expr| Self::new().filter(expr))
+    }
+
+    ///Returns whether there are no filters.
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    ///Parses filters into expression.
+    pub fn expr(&self) -> Result<filter::Expr, filter::FilterParseError> {
+        filter::Expr::parse(&self.to_string())
+    }
 }
 
 impl fmt::Display for Filters {
@@ -229,7 +299,8 @@ impl fmt::Display for Filters {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
 ///Optional Options for get command
 pub struct Options<'a> {
     ///Index of pagination.
@@ -265,7 +336,7 @@ impl<'a> fmt::Display for Options<'a> {
                 write!(f, ",")?;
             }
             has_prev = true;
-            write!(f, "\"sort\":{}", sort)?;
+            write!(f, "\"sort\":\"{}\"", sort)?;
         }
 
         if let Some(ref reverse) = self.reverse {
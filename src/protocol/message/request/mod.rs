@@ -1,10 +1,12 @@
 //!Requests toward VNDB.
 
 use core::fmt;
+use std::borrow::Cow;
 
 pub mod get;
+pub mod filter;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 ///Login command arguments
 ///
 ///Defaults:
@@ -17,11 +19,17 @@ pub struct Login<'a> {
     ///Protocol. For now should be always 1.
     pub protocol: u8,
     ///Client name
-    pub client: &'static str,
+    pub client: Cow<'a, str>,
     ///Client version
     pub clientver: f32,
     ///User credentials
-    pub creds: Option<(&'a str, &'a str)>
+    pub creds: Option<(Cow<'a, str>, Cow<'a, str>)>,
+    ///User session as username and session token, used instead of credentials.
+    pub session: Option<(Cow<'a, str>, Cow<'a, str>)>,
+    ///Whether to create session token, which is returned as `Response::Session`.
+    ///
+    ///Requires credentials.
+    pub create_session: bool,
 }
 
 impl<'a> Default for Login<'a> {
@@ -36,24 +44,80 @@ impl<'a> Login<'a> {
     pub fn new(creds: Option<(&'a str, &'a str)>) -> Self {
         Login {
             protocol: 1,
-            client: "rusty",
+            client: Cow::Borrowed("rusty"),
             clientver: 0.1,
-            creds,
+            creds: creds.map(|(username, password)| (username.into(), password.into())),
+            session: None,
+            create_session: false,
+        }
+    }
+}
+
+impl<'a> Login<'a> {
+    ///Parses JSON arguments of login command.
+    ///
+    ///Strings are borrowed, unless they contain escape sequences.
+    pub fn from_str(args: &'a str) -> serde_json::Result<Self> {
+        #[derive(serde::Deserialize)]
+        #[serde(deny_unknown_fields)]
+        struct Args<'a> {
+            protocol: u8,
+            #[serde(borrow)]
+            client: Cow<'a, str>,
+            clientver: f32,
+            #[serde(borrow)]
+            username: Option<Cow<'a, str>>,
+            #[serde(borrow)]
+            password: Option<Cow<'a, str>>,
+            #[serde(borrow)]
+            sessiontoken: Option<Cow<'a, str>>,
+            #[serde(default)]
+            createsession: bool,
         }
+
+        let args: Args<'a> = serde_json::from_str(args)?;
+        let (creds, session) = match (args.username, args.password, args.sessiontoken) {
+            (Some(username), Some(password), None) => (Some((username, password)), None),
+            (Some(username), None, Some(token)) => (None, Some((username, token))),
+            (None, None, None) => (None, None),
+            (Some(_), None, None) => return Err(serde::de::Error::missing_field("password")),
+            (Some(_), Some(_), Some(_)) => return Err(serde::de::Error::custom("password and sessiontoken are mutually exclusive")),
+            (None, _, _) => return Err(serde::de::Error::missing_field("username")),
+        };
+
+        Ok(Login {
+            protocol: args.protocol,
+            client: args.client,
+            clientver: args.clientver,
+            creds,
+            session,
+            create_session: args.createsession,
+        })
     }
 }
 
+//Escapes string as JSON string literal.
+fn json_str(text: &str) -> Result<String, fmt::Error> {
+    serde_json::to_string(text).map_err(|_| fmt::Error)
+}
+
 impl<'a> fmt::Display for Login<'a> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "login {{\"protocol\":{},\"client\":\"{}\",\"clientver\":{}", self.protocol, self.client, self.clientver)?;
-        match self.creds.as_ref() {
-            Some((ref login, ref password)) => write!(f, ",\"username\":\"{}\",\"password\":\"{}\"}}", login, password),
-            _ => write!(f, "}}"),
+        write!(f, "login {{\"protocol\":{},\"client\":{},\"clientver\":{}", self.protocol, json_str(&self.client)?, self.clientver)?;
+        if let Some((ref login, ref password)) = self.creds.as_ref() {
+            write!(f, ",\"username\":{},\"password\":{}", json_str(login)?, json_str(password)?)?;
+        } else if let Some((ref login, ref token)) = self.session.as_ref() {
+            write!(f, ",\"username\":{},\"sessiontoken\":{}", json_str(login)?, json_str(token)?)?;
+        }
+
+        match self.create_session {
+            true => write!(f, ",\"createsession\":true}}"),
+            false => write!(f, "}}"),
         }
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq)]
 ///Get command.
 ///
 ///Used to retrieve information about various entities.
@@ -80,3 +144,189 @@ impl<'a> fmt::Display for Get<'a> {
         }
     }
 }
+
+impl<'a> Get<'a> {
+    ///Parses arguments of get command in format `<type> <flags> (<filters>) [{options}]`.
+    pub fn from_str(args: &'a str) -> Result<Self, GetParseError<'a>> {
+        let args = args.trim();
+        let (kind, args) = args.split_once(' ').unwrap_or((args, ""));
+        let kind = match get::Type::from_str(kind) {
+            Some(kind) => kind,
+            None => return Err(GetParseError::UnknownType(kind)),
+        };
+
+        let args = args.trim_start();
+        let (flags, args) = args.split_once(' ').unwrap_or((args, ""));
+        let flags = match flags.is_empty() {
+            true => return Err(GetParseError::MissingFlags),
+            false => get::Flags::from_str(flags).map_err(GetParseError::UnknownFlag)?,
+        };
+
+        //Options are the last JSON object, outside of filters.
+        let args = args.trim();
+        let (filters, options) = match args.ends_with('}') {
+            true => match options_start(args) {
+                Some(start) => (args[..start].trim_end(), Some(&args[start..])),
+                None => (args, None),
+            },
+            false => (args, None),
+        };
+
+        let filters = match filters.is_empty() {
+            true => get::Filters::new(),
+            false => get::Filters::parse(filters).map_err(GetParseError::InvalidFilters)?,
+        };
+
+        let options = match options {
+            Some(options) => Some(serde_json::from_str(options).map_err(GetParseError::InvalidOptions)?),
+            None => None,
+        };
+
+        Ok(Get {
+            kind,
+            flags,
+            filters,
+            options,
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+///Set command.
+///
+///Used to add, modify or remove items of user's lists, requires login with credentials or session.
+///On success returns `Response::Ok`.
+pub struct Set {
+    ///Type of list: `ulist`, `vnlist`, `votelist` or `wishlist`.
+    pub kind: get::Type,
+    ///Identifier of VN.
+    pub id: u64,
+    ///Fields to set, such as `vote` or `notes`.
+    ///
+    ///When `None`, item is removed from the list.
+    pub fields: Option<serde_json::Map<String, serde_json::Value>>,
+}
+
+impl Set {
+    ///Types of lists, that can be modified.
+    pub const TYPES: [get::Type; 4] = [get::Type::ulist(), get::Type::vnlist(), get::Type::votelist(), get::Type::wishlist()];
+
+    ///Parses arguments of set command in format `<type> <id> [{fields}]`.
+    pub fn from_str(args: &str) -> Result<Self, SetParseError<'_>> {
+        let args = args.trim();
+        let (kind, args) = args.split_once(' ').unwrap_or((args, ""));
+        let kind = match Self::TYPES.iter().find(|set_kind| set_kind.as_str() == kind) {
+            Some(kind) => kind.clone(),
+            None => return Err(SetParseError::UnknownType(kind)),
+        };
+
+        let args = args.trim_start();
+        let (id, fields) = args.split_once(' ').unwrap_or((args, ""));
+        let id = match id.parse() {
+            Ok(id) => id,
+            Err(_) => return Err(SetParseError::InvalidId(id)),
+        };
+
+        let fields = match fields.trim() {
+            "" => None,
+            fields => Some(serde_json::from_str(fields).map_err(SetParseError::InvalidFields)?),
+        };
+
+        Ok(Set {
+            kind,
+            id,
+            fields,
+        })
+    }
+}
+
+impl fmt::Display for Set {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "set {} {}", self.kind, self.id)?;
+
+        match self.fields {
+            Some(ref fields) => write!(f, " {}", serde_json::Value::from(fields.clone())),
+            None => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug)]
+///Error parsing arguments of [Set](struct.Set.html) command.
+pub enum SetParseError<'a> {
+    ///Type of list is not supported.
+    UnknownType(&'a str),
+    ///Invalid identifier of VN.
+    InvalidId(&'a str),
+    ///Invalid fields.
+    InvalidFields(serde_json::Error),
+}
+
+impl<'a> fmt::Display for SetParseError<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SetParseError::UnknownType(kind) => write!(f, "Unknown type '{}'", kind),
+            SetParseError::InvalidId(id) => write!(f, "Invalid id '{}'", id),
+            SetParseError::InvalidFields(error) => write!(f, "Invalid fields: {}", error),
+        }
+    }
+}
+
+impl<'a> std::error::Error for SetParseError<'a> {}
+
+//Finds start of trailing options object, skipping braces within strings.
+fn options_start(args: &str) -> Option<usize> {
+    let bytes = args.as_bytes();
+    let mut depth = 0usize;
+    let mut in_string = false;
+    let mut object_start = None;
+    let mut idx = 0;
+
+    while idx < bytes.len() {
+        match bytes[idx] {
+            b'\\' if in_string => idx += 1,
+            b'"' => in_string = !in_string,
+            b'{' if !in_string => {
+                if depth == 0 {
+                    object_start = Some(idx);
+                }
+                depth += 1;
+            },
+            b'}' if !in_string => depth = depth.saturating_sub(1),
+            b'(' | b'[' if !in_string && depth == 0 => object_start = None,
+            _ => (),
+        }
+        idx += 1;
+    }
+
+    object_start
+}
+
+#[derive(Debug)]
+///Error parsing arguments of [Get](struct.Get.html) command.
+pub enum GetParseError<'a> {
+    ///Unknown type of entity.
+    UnknownType(&'a str),
+    ///Flags are not specified.
+    MissingFlags,
+    ///Unknown flag.
+    UnknownFlag(&'a str),
+    ///Invalid filters.
+    InvalidFilters(filter::FilterParseError),
+    ///Invalid options.
+    InvalidOptions(serde_json::Error),
+}
+
+impl<'a> fmt::Display for GetParseError<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GetParseError::UnknownType(kind) => write!(f, "Unknown type '{}'", kind),
+            GetParseError::MissingFlags => f.write_str("Flags are not specified"),
+            GetParseError::UnknownFlag(flag) => write!(f, "Unknown flag '{}'", flag),
+            GetParseError::InvalidFilters(error) => write!(f, "Invalid filters: {}", error),
+            GetParseError::InvalidOptions(error) => write!(f, "Invalid options: {}", error),
+        }
+    }
+}
+
+impl<'a> std::error::Error for GetParseError<'a> {}
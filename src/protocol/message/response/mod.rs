@@ -4,6 +4,7 @@ use serde::{Serialize, Deserialize};
 
 use core::fmt;
 use core::ops::Deref;
+use std::collections::BTreeMap;
 
 pub mod results;
 ///Typed module for [Results](struct.Results.html)
@@ -20,9 +21,27 @@ pub struct VndbError {
     ///
     ///Note that the value of "msg" is not directly linked to the error identifier
     pub msg: String,
+    #[serde(flatten)]
+    ///Additional fields, specific to error identifier, such as `field` or `minwait`.
+    pub extra: BTreeMap<String, serde_json::Value>,
 }
 
 impl VndbError {
+    ///Creates new error without additional fields.
+    pub fn new(id: &str, msg: &str) -> Self {
+        Self {
+            id: id.to_owned(),
+            msg: msg.to_owned(),
+            extra: BTreeMap::new(),
+        }
+    }
+
+    ///Adds field to error.
+    pub fn with<V: Into<serde_json::Value>>(mut self, name: &str, value: V) -> Self {
+        self.extra.insert(name.to_owned(), value.into());
+        self
+    }
+
     ///Parses text message into VNDB Error.
     pub fn from_str(error: &str) -> serde_json::Result<Self> {
         serde_json::from_str(error)
@@ -35,6 +54,8 @@ impl fmt::Display for VndbError {
     }
 }
 
+impl std::error::Error for VndbError {}
+
 #[derive(Clone, Deserialize, Serialize, Debug)]
 ///DBstats response
 pub struct DBstats {
@@ -49,7 +70,38 @@ pub struct DBstats {
     ///Number of VNs.
     pub vn: u64,
     ///Number of traits.
-    pub traits: u64
+    pub traits: u64,
+    #[serde(default)]
+    ///Number of users.
+    pub users: u64,
+    #[serde(default)]
+    ///Number of forum threads.
+    pub threads: u64,
+    #[serde(default)]
+    ///Number of forum posts.
+    pub posts: u64,
+    #[serde(default)]
+    ///Number of staff.
+    pub staff: u64,
+    #[serde(flatten)]
+    ///Statistics, unknown to this library.
+    pub extra: BTreeMap<String, serde_json::Value>,
+}
+
+//Deserializes part of results, located at `prefix`, reporting path to invalid field relative to results.
+pub(crate) fn deserialize_at<'de, T: Deserialize<'de>>(value: &'de serde_json::Value, prefix: &str) -> crate::Result<T> {
+    use super::{ResponseParseError, ResponseParseErrorKind};
+
+    serde_path_to_error::deserialize(value).map_err(|error| {
+        let path = match (prefix, error.path().to_string()) {
+            ("", path) => path,
+            (prefix, path) if path == "." => prefix.to_owned(),
+            (prefix, path) => format!("{}.{}", prefix, path),
+        };
+        let mut error = ResponseParseError::new(ResponseParseErrorKind::InvalidResults(error.into_inner()), "results", &value.to_string());
+        error.path = Some(path);
+        error.into()
+    })
 }
 
 #[derive(Clone, Debug)]
@@ -69,6 +121,14 @@ pub struct Results {
 }
 
 impl Results {
+    #[inline]
+    ///Creates new instance from JSON value.
+    pub fn new(inner: serde_json::Value) -> Self {
+        Self {
+            inner
+        }
+    }
+
     ///Creates new instance from string with JSON.
     ///
     ///Notes that it expects string to be without special `0x04` character.
@@ -78,11 +138,24 @@ impl Results {
         })
     }
 
+    ///Creates new instance from typed results.
+    pub fn from_typed<T: Serialize>(results: &typed::Results<T>) -> serde_json::Result<Self> {
+        serde_json::to_value(results).map(Self::new)
+    }
+
     #[inline(always)]
     fn to<'de, T: Deserialize<'de>>(&'de self) -> serde_json::Result<T> {
         T::deserialize(&self.inner)
     }
 
+    #[inline]
+    ///Attempts to convert data to typed results, such as [typed::Release](typed/type.Release.html).
+    ///
+    ///Unlike methods for particular type, error contains path to invalid field.
+    pub fn to_typed<'de, T: Deserialize<'de>>(&'de self) -> crate::Result<T> {
+        deserialize_at(&self.inner, "")
+    }
+
     #[inline]
     ///Attempts to convert data to [Vn information](results/Struct.Vn.html).
     pub fn vn(&self) -> serde_json::Result<typed::VN> {
@@ -107,6 +180,18 @@ impl Results {
         self.to()
     }
 
+    #[inline]
+    ///Attempts to convert data to [Tag information](results/Struct.Tag.html).
+    pub fn tag(&self) -> serde_json::Result<typed::Tag> {
+        self.to()
+    }
+
+    #[inline]
+    ///Attempts to convert data to [Trait information](results/Struct.Trait.html).
+    pub fn r#trait(&self) -> serde_json::Result<typed::Trait> {
+        self.to()
+    }
+
     #[inline]
     ///Attempts to convert data to [User information](results/Struct.User.html).
     pub fn user(&self) -> serde_json::Result<typed::User> {
@@ -125,6 +210,12 @@ impl Results {
         self.to()
     }
 
+    #[inline]
+    ///Attempts to convert data to [WishList information](results/Struct.WishList.html).
+    pub fn wish_list(&self) -> serde_json::Result<typed::WishList> {
+        self.to()
+    }
+
     #[inline]
     ///Attempts to convert data to [UList information](results/Struct.UList.html).
     pub fn u_list(&self) -> serde_json::Result<typed::UList> {
@@ -132,6 +223,14 @@ impl Results {
     }
 }
 
+impl fmt::Display for Results {
+    #[inline]
+    ///Writes results as JSON.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.inner, f)
+    }
+}
+
 impl Deref for Results {
     type Target = serde_json::Value;
 
@@ -2,6 +2,117 @@
 use serde::{Serialize, Deserialize};
 use serde::de::Error;
 
+use core::fmt;
+
+///Generates enum with forward compatible `Unknown` variant, mapped to VNDB's string codes.
+macro_rules! code_enum {
+    ($(#[$meta:meta])* $name:ident($repr:ty, $unknown_doc:literal) { $($(#[$var_meta:meta])* $var:ident => ($code:expr, $label:literal),)+ }) => {
+        $(#[$meta])*
+        #[derive(Clone, Debug, PartialEq, Eq, Hash)]
+        pub enum $name {
+            $(
+                $(#[$var_meta])*
+                $var,
+            )+
+            #[doc = $unknown_doc]
+            Unknown($repr),
+        }
+
+        impl $name {
+            ///Creates instance from VNDB's code.
+            pub fn from_code(code: $repr) -> Self {
+                $(
+                    if code == $code {
+                        return $name::$var;
+                    }
+                )+
+                $name::Unknown(code)
+            }
+
+            ///Returns human readable label.
+            pub fn label(&self) -> &str {
+                match self {
+                    $(
+                        $name::$var => $label,
+                    )+
+                    $name::Unknown(_) => "Unknown",
+                }
+            }
+        }
+
+        impl fmt::Display for $name {
+            #[inline]
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str(self.label())
+            }
+        }
+    };
+}
+
+///Generates enum mapped to VNDB's string codes.
+macro_rules! str_code_enum {
+    ($(#[$meta:meta])* $name:ident { $($(#[$var_meta:meta])* $var:ident => ($code:literal, $label:literal),)+ }) => {
+        code_enum!($(#[$meta])* $name(String, "Code, unknown to this library.") { $($(#[$var_meta])* $var => ($code, $label),)+ });
+
+        impl $name {
+            ///Returns VNDB's code.
+            pub fn code(&self) -> &str {
+                match self {
+                    $(
+                        $name::$var => $code,
+                    )+
+                    $name::Unknown(code) => code.as_str(),
+                }
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D: serde::de::Deserializer<'de>>(code: D) -> Result<Self, D::Error> {
+                let code: String = Deserialize::deserialize(code)?;
+                Ok(Self::from_code(code))
+            }
+        }
+
+        impl Serialize for $name {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_str(self.code())
+            }
+        }
+    };
+}
+
+///Generates enum mapped to VNDB's integer codes.
+macro_rules! int_code_enum {
+    ($(#[$meta:meta])* $name:ident { $($(#[$var_meta:meta])* $var:ident => ($code:literal, $label:literal),)+ }) => {
+        code_enum!($(#[$meta])* $name(u8, "Code, unknown to this library.") { $($(#[$var_meta])* $var => ($code, $label),)+ });
+
+        impl $name {
+            ///Returns VNDB's code.
+            pub fn code(&self) -> u8 {
+                match self {
+                    $(
+                        $name::$var => $code,
+                    )+
+                    $name::Unknown(code) => *code,
+                }
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D: serde::de::Deserializer<'de>>(code: D) -> Result<Self, D::Error> {
+                let code: u8 = Deserialize::deserialize(code)?;
+                Ok(Self::from_code(code))
+            }
+        }
+
+        impl Serialize for $name {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_u8(self.code())
+            }
+        }
+    };
+}
+
 #[derive(Deserialize, Serialize, Debug)]
 ///Links for VN.
 ///
@@ -35,13 +146,41 @@ pub struct VnAnime {
     pub kind: Option<String>
 }
 
+str_code_enum!(
+    ///Kind of relation between two VNs.
+    ///
+    ///Describes what related VN is to the VN it is attached to.
+    VnRelationKind {
+        ///Sequel.
+        Sequel => ("seq", "Sequel"),
+        ///Prequel.
+        Prequel => ("preq", "Prequel"),
+        ///Same setting.
+        SameSetting => ("set", "Same setting"),
+        ///Alternative version.
+        Alternative => ("alt", "Alternative version"),
+        ///Shares characters.
+        SharedCharacters => ("char", "Shares characters"),
+        ///Side story.
+        SideStory => ("side", "Side story"),
+        ///Parent story.
+        ParentStory => ("par", "Parent story"),
+        ///Same series.
+        SameSeries => ("ser", "Same series"),
+        ///Fandisc.
+        FanDisc => ("fan", "Fandisc"),
+        ///Original game.
+        Original => ("orig", "Original game"),
+    }
+);
+
 ///Related VN.
 #[derive(Deserialize, Serialize, Debug)]
 pub struct VnRelation {
     ///VN's ID.
     pub id: u64,
-    ///Description of relation.
-    pub relation: String,
+    ///Kind of relation.
+    pub relation: VnRelationKind,
     ///Title in romaji.
     pub title: String,
     ///Title in kanji.
@@ -50,6 +189,35 @@ pub struct VnRelation {
     pub official: bool
 }
 
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+///Spoiler severity of tag, trait or character's appearance.
+pub enum SpoilerLevel {
+    ///No spoiler.
+    None = 0,
+    ///Minor spoiler.
+    Minor = 1,
+    ///Major spoiler.
+    Major = 2
+}
+
+impl<'de> Deserialize<'de> for SpoilerLevel {
+    fn deserialize<D: serde::de::Deserializer<'de>>(level: D) -> Result<Self, D::Error> {
+        let level: u8 = Deserialize::deserialize(level)?;
+        match level {
+            0 => Ok(SpoilerLevel::None),
+            1 => Ok(SpoilerLevel::Minor),
+            //Levels above major are treated as major, to stay on safe side.
+            _ => Ok(SpoilerLevel::Major)
+        }
+    }
+}
+
+impl Serialize for SpoilerLevel {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u8(*self as u8)
+    }
+}
+
 #[derive(Deserialize, Serialize, Debug)]
 ///VN's tag.
 pub struct VnTag {
@@ -61,12 +229,7 @@ pub struct VnTag {
     pub score: f32,
     #[serde(rename = "spoiler level")]
     ///Spoiler severity.
-    ///
-    ///Possible values:
-    ///* 0 - None;
-    ///* 1 - Minor;
-    ///* 2 - Major;
-    pub spoiler: u8
+    pub spoiler: SpoilerLevel
 }
 
 #[derive(Deserialize, Serialize, Debug)]
@@ -84,6 +247,34 @@ pub struct VnScreen {
     pub width: u16,
 }
 
+str_code_enum!(
+    ///Role of staff member in VN's production.
+    StaffRole {
+        ///Original creator.
+        Author => ("author", "Original work"),
+        ///Director.
+        Director => ("director", "Director"),
+        ///Scenario writer.
+        Scenario => ("scenario", "Scenario"),
+        ///Character designer.
+        CharacterDesign => ("chardesign", "Character design"),
+        ///Artist.
+        Artist => ("art", "Artist"),
+        ///Composer.
+        Composer => ("music", "Composer"),
+        ///Vocals and songs.
+        Songs => ("songs", "Vocals"),
+        ///Translator.
+        Translator => ("translator", "Translator"),
+        ///Editor.
+        Editor => ("editor", "Editor"),
+        ///Quality assurance.
+        QualityAssurance => ("qa", "Quality assurance"),
+        ///Other staff.
+        Staff => ("staff", "Staff"),
+    }
+);
+
 #[derive(Deserialize, Serialize, Debug)]
 ///VN's staff.
 pub struct VnStaff {
@@ -98,7 +289,7 @@ pub struct VnStaff {
     ///Name in native language.
     pub original: Option<String>,
     ///Role.
-    pub role: String,
+    pub role: StaffRole,
     ///Note.
     pub note: Option<String>
 }
@@ -232,6 +423,14 @@ pub struct Vn {
     pub staff: Vec<VnStaff>,
 }
 
+impl Vn {
+    #[inline]
+    ///Returns parsed date of the first release, if it is provided and valid.
+    pub fn release_date(&self) -> Option<ReleaseDate> {
+        self.released.as_deref().and_then(ReleaseDate::from_str)
+    }
+}
+
 #[derive(Deserialize, Serialize, Debug)]
 ///Type of media for the release.
 pub struct ReleaseMedia {
@@ -271,6 +470,260 @@ pub struct ReleaseProducer {
     pub kind: String,
 }
 
+int_code_enum!(
+    ///Voice type of the release.
+    ReleaseVoiced {
+        ///Not voiced.
+        NotVoiced => (1, "Not voiced"),
+        ///Only ero scenes voiced.
+        EroOnly => (2, "Only ero scenes voiced"),
+        ///Partially voiced.
+        Partial => (3, "Partially voiced"),
+        ///Fully voiced.
+        Full => (4, "Fully voiced"),
+    }
+);
+
+int_code_enum!(
+    ///Animation level of the release's scenes.
+    AnimationKind {
+        ///No animations.
+        NoAnimation => (1, "No animations"),
+        ///Simple animations.
+        Simple => (2, "Simple animations"),
+        ///Some fully animated scenes.
+        Partial => (3, "Some fully animated scenes"),
+        ///All scenes fully animated.
+        Full => (4, "All scenes fully animated"),
+    }
+);
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+///Animation status of the release.
+///
+///Each part is `None` when unknown or not applicable.
+pub struct ReleaseAnimation {
+    ///Animation of story scenes.
+    pub story: Option<AnimationKind>,
+    ///Animation of ero scenes.
+    pub ero: Option<AnimationKind>,
+}
+
+impl<'de> Deserialize<'de> for ReleaseAnimation {
+    fn deserialize<D: serde::de::Deserializer<'de>>(animation: D) -> Result<Self, D::Error> {
+        let (story, ero) = Deserialize::deserialize(animation)?;
+        Ok(ReleaseAnimation {
+            story,
+            ero,
+        })
+    }
+}
+
+impl Serialize for ReleaseAnimation {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        (&self.story, &self.ero).serialize(serializer)
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+///Date of release, which may be only partially known.
+///
+///Its textual form is the one used by VNDB: `YYYY-MM-DD`, `YYYY-MM`, `YYYY` or `tba`.
+///Dates are ordered the same way as on VNDB, i.e. partial date goes after all full dates within its period,
+///while `tba` goes last.
+pub enum ReleaseDate {
+    ///Only year is known.
+    Year(u16),
+    ///Year and month are known.
+    Month(u16, u8),
+    ///Full date.
+    Day(u16, u8, u8),
+    ///To be announced.
+    Tba,
+}
+
+impl ReleaseDate {
+    ///Parses date in VNDB's format, returning `None` if it is invalid.
+    pub fn from_str(date: &str) -> Option<Self> {
+        if date == "tba" {
+            return Some(ReleaseDate::Tba);
+        }
+
+        let mut parts = date.split('-');
+        let year = match parts.next() {
+            Some(year) if year.len() == 4 => year.parse().ok()?,
+            _ => return None,
+        };
+        let month = match parts.next() {
+            Some(month) if month.len() == 2 => month.parse().ok().filter(|month| (1..=12).contains(month))?,
+            Some(_) => return None,
+            None => return Some(ReleaseDate::Year(year)),
+        };
+        let day = match parts.next() {
+            Some(day) if day.len() == 2 => day.parse().ok().filter(|day| (1..=days_in_month(year, month)).contains(day))?,
+            Some(_) => return None,
+            None => return Some(ReleaseDate::Month(year, month)),
+        };
+
+        match parts.next() {
+            Some(_) => None,
+            None => Some(ReleaseDate::Day(year, month, day)),
+        }
+    }
+
+    ///Creates full date from unix timestamp, in UTC.
+    pub fn from_timestamp(timestamp: u64) -> Self {
+        let (year, month, day) = civil_date(timestamp);
+        ReleaseDate::Day(year as u16, month as u8, day as u8)
+    }
+
+    ///Returns current date, in UTC.
+    pub fn today() -> Self {
+        let now = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).map(|time| time.as_secs()).unwrap_or(0);
+        Self::from_timestamp(now)
+    }
+
+    ///Returns year, if known.
+    pub fn year(&self) -> Option<u16> {
+        match *self {
+            ReleaseDate::Year(year) | ReleaseDate::Month(year, _) | ReleaseDate::Day(year, _, _) => Some(year),
+            ReleaseDate::Tba => None,
+        }
+    }
+
+    ///Returns whether date is full.
+    pub fn is_exact(&self) -> bool {
+        match self {
+            ReleaseDate::Day(..) => true,
+            _ => false,
+        }
+    }
+
+    ///Returns first day of the period, that date denotes, as `(year, month, day)`.
+    pub fn first_day(&self) -> Option<(u16, u8, u8)> {
+        match *self {
+            ReleaseDate::Year(year) => Some((year, 1, 1)),
+            ReleaseDate::Month(year, month) => Some((year, month, 1)),
+            ReleaseDate::Day(year, month, day) => Some((year, month, day)),
+            ReleaseDate::Tba => None,
+        }
+    }
+
+    ///Returns day after the end of the period, that date denotes, as `(year, month, day)`.
+    pub fn end(&self) -> Option<(u16, u8, u8)> {
+        match *self {
+            ReleaseDate::Year(year) => Some((year + 1, 1, 1)),
+            ReleaseDate::Month(year, 12) => Some((year + 1, 1, 1)),
+            ReleaseDate::Month(year, month) => Some((year, month + 1, 1)),
+            ReleaseDate::Day(year, month, day) => match day < days_in_month(year, month) {
+                true => Some((year, month, day + 1)),
+                false => ReleaseDate::Month(year, month).end(),
+            },
+            ReleaseDate::Tba => None,
+        }
+    }
+
+    ///Returns unix timestamp of the start of the first day in UTC, if date is known.
+    pub fn timestamp(&self) -> Option<u64> {
+        let (year, month, day) = self.first_day()?;
+        let days = days_from_civil(year.into(), month.into(), day.into());
+        match days >= 0 {
+            true => Some(days as u64 * 86400),
+            false => None,
+        }
+    }
+
+    //Numeric form used by VNDB for sorting, where unknown parts are `99`.
+    fn code(&self) -> u32 {
+        match *self {
+            ReleaseDate::Year(year) => u32::from(year) * 10000 + 9999,
+            ReleaseDate::Month(year, month) => u32::from(year) * 10000 + u32::from(month) * 100 + 99,
+            ReleaseDate::Day(year, month, day) => u32::from(year) * 10000 + u32::from(month) * 100 + u32::from(day),
+            ReleaseDate::Tba => 99999999,
+        }
+    }
+}
+
+impl PartialOrd for ReleaseDate {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for ReleaseDate {
+    #[inline]
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        self.code().cmp(&other.code())
+    }
+}
+
+impl fmt::Display for ReleaseDate {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ReleaseDate::Year(year) => write!(fmt, "{:04}", year),
+            ReleaseDate::Month(year, month) => write!(fmt, "{:04}-{:02}", year, month),
+            ReleaseDate::Day(year, month, day) => write!(fmt, "{:04}-{:02}-{:02}", year, month, day),
+            ReleaseDate::Tba => fmt.write_str("tba"),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for ReleaseDate {
+    fn deserialize<D: serde::de::Deserializer<'de>>(date: D) -> Result<Self, D::Error> {
+        let date: std::borrow::Cow<'de, str> = Deserialize::deserialize(date)?;
+        ReleaseDate::from_str(&date).ok_or_else(|| D::Error::custom(format_args!("Invalid release date '{}'.", date)))
+    }
+}
+
+impl Serialize for ReleaseDate {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+fn days_in_month(year: u16, month: u8) -> u8 {
+    match month {
+        2 if year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400)) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+//Converts unix timestamp into UTC date.
+//
+//Algorithm is from http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+pub(crate) fn civil_date(timestamp: u64) -> (i32, u32, u32) {
+    let days = (timestamp / 86400) as i64 + 719468;
+    let era = days / 146097;
+    let day_of_era = days - era * 146097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month + 2) / 5 + 1;
+    let month = match month < 10 {
+        true => month + 3,
+        false => month - 9,
+    };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    (year as i32, month as u32, day as u32)
+}
+
+//Converts UTC date into number of days since unix epoch.
+//
+//Algorithm is from http://howardhinnant.github.io/date_algorithms.html#days_from_civil
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+    era * 146097 + day_of_era - 719468
+}
+
 #[derive(Deserialize, Serialize, Debug)]
 ///Release data representation. Returned by `get release`
 pub struct Release {
@@ -354,14 +807,12 @@ pub struct Release {
     pub resolution: Option<String>,
     ///Voice type available.
     ///
-    ///1 = Not voiced, 2 = Only ero scenes voiced, 3 = Partially voiced, 4 = Fully voiced
-    ///
     ///Optionally provided when `details` flag is specified.
-    pub voiced: Option<u8>,
+    pub voiced: Option<ReleaseVoiced>,
     ///Animation status.
     ///
     ///Optionally provided when `details` flag is specified.
-    pub animation: Option<[u8; 2]>,
+    pub animation: Option<ReleaseAnimation>,
     #[serde(skip_serializing_if = "Vec::is_empty", default)]
     ///Related VNs.
     ///
@@ -374,6 +825,14 @@ pub struct Release {
     pub producers: Vec<ReleaseProducer>,
 }
 
+impl Release {
+    #[inline]
+    ///Returns parsed date of release, if it is provided and valid.
+    pub fn release_date(&self) -> Option<ReleaseDate> {
+        self.released.as_deref().and_then(ReleaseDate::from_str)
+    }
+}
+
 #[derive(Deserialize, Serialize, Debug)]
 ///External links related for [Producer](struct.Prodcer.html)
 pub struct ProducerLinks {
@@ -383,13 +842,37 @@ pub struct ProducerLinks {
     pub wikidata: Option<String>
 }
 
+str_code_enum!(
+    ///Kind of relation between two producers.
+    ///
+    ///Describes what related producer is to the producer it is attached to.
+    ProducerRelationKind {
+        ///Formerly.
+        Formerly => ("old", "Formerly"),
+        ///Succeeded by.
+        SucceededBy => ("new", "Succeeded by"),
+        ///Subsidiary.
+        Subsidiary => ("sub", "Subsidiary"),
+        ///Parent producer.
+        Parent => ("par", "Parent producer"),
+        ///Imprint.
+        Imprint => ("imp", "Imprint"),
+        ///Parent brand.
+        ParentBrand => ("ipa", "Parent brand"),
+        ///Spawned.
+        Spawned => ("spa", "Spawned"),
+        ///Originated from.
+        OriginatedFrom => ("ori", "Originated from"),
+    }
+);
+
 #[derive(Deserialize, Serialize, Debug)]
 ///External links related for [Producer](struct.Prodcer.html)
 pub struct ProducerRelation {
     ///Unique identifier of Producer.
     pub id: u64,
     ///Relation to [Producer](struct.Prodcer.html).
-    pub relation: String,
+    pub relation: ProducerRelationKind,
     ///Name(romaji).
     pub name: String,
     ///Name in original language.
@@ -489,6 +972,82 @@ pub struct CharacterSeiyuu {
     pub note: String,
 }
 
+str_code_enum!(
+    ///Role of [Character](struct.Character.html) in VN.
+    CharacterRole {
+        ///Protagonist.
+        Main => ("main", "Protagonist"),
+        ///Main character.
+        Primary => ("primary", "Main character"),
+        ///Side character.
+        Side => ("side", "Side character"),
+        ///Makes an appearance.
+        Appears => ("appears", "Makes an appearance"),
+    }
+);
+
+#[derive(Debug)]
+///Character's trait.
+///
+///Encoded by VNDB as array `[id, spoiler level]`.
+pub struct CharacterTrait {
+    ///Trait's ID.
+    pub id: u64,
+    ///Spoiler severity.
+    pub spoiler: SpoilerLevel,
+}
+
+impl<'de> Deserialize<'de> for CharacterTrait {
+    fn deserialize<D: serde::de::Deserializer<'de>>(character_trait: D) -> Result<Self, D::Error> {
+        let (id, spoiler) = Deserialize::deserialize(character_trait)?;
+        Ok(CharacterTrait {
+            id,
+            spoiler,
+        })
+    }
+}
+
+impl Serialize for CharacterTrait {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        (self.id, self.spoiler).serialize(serializer)
+    }
+}
+
+#[derive(Debug)]
+///VN, in which character appears.
+///
+///Encoded by VNDB as array `[vn id, release id, spoiler level, role]`.
+pub struct CharacterVn {
+    ///VN's ID.
+    pub vn: u64,
+    ///Release's ID.
+    ///
+    ///Zero when character appears in all releases of the VN.
+    pub release: u64,
+    ///Spoiler severity.
+    pub spoiler: SpoilerLevel,
+    ///Character's role.
+    pub role: CharacterRole,
+}
+
+impl<'de> Deserialize<'de> for CharacterVn {
+    fn deserialize<D: serde::de::Deserializer<'de>>(character_vn: D) -> Result<Self, D::Error> {
+        let (vn, release, spoiler, role) = Deserialize::deserialize(character_vn)?;
+        Ok(CharacterVn {
+            vn,
+            release,
+            spoiler,
+            role,
+        })
+    }
+}
+
+impl Serialize for CharacterVn {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        (self.vn, self.release, self.spoiler, &self.role).serialize(serializer)
+    }
+}
+
 #[derive(Deserialize, Serialize, Debug)]
 ///Character data representation. Returned by `get character`
 pub struct Character {
@@ -556,24 +1115,155 @@ pub struct Character {
 
     #[serde(skip_serializing_if = "Vec::is_empty", default)]
     //Traits
-    ///List, possibly empty, of traits specified as tuple `(id, spoiler level)`.
+    ///List, possibly empty, of traits.
     ///
     ///Provided when `traits` flag is specified.
-    pub traits: Vec<(u64, u8)>,
+    pub traits: Vec<CharacterTrait>,
 
     #[serde(skip_serializing_if = "Vec::is_empty", default)]
-    ///List, possibly empty, of related VNs specified as tuple `(vn id, release id, spoiler level, role)`.
+    ///List, possibly empty, of related VNs.
     ///
     ///Provided when `vns` flag is specified.
-    pub vns: Vec<(u64, u64, u8, String)>,
+    pub vns: Vec<CharacterVn>,
 
     #[serde(skip_serializing_if = "Vec::is_empty", default)]
-    ///List, possibly empty, of related VNs specified as tuple `(vn id, release id, spoiler level, role)`.
+    ///List, possibly empty, of voice actresses.
     ///
     ///Provided when `voiced` flag is specified.
     pub voiced: Vec<CharacterSeiyuu>,
 }
 
+//Parses timestamp, that is `null` when it is not set.
+mod timestamp {
+    use serde::Deserialize;
+
+    pub fn deserialize<'de, D: serde::Deserializer<'de>>(timestamp: D) -> Result<u64, D::Error> {
+        Option::<u64>::deserialize(timestamp).map(Option::unwrap_or_default)
+    }
+}
+
+//Parses ID, that is either number or string with type prefix, i.e. `g123`.
+mod vndbid {
+    use serde::Deserialize;
+    use serde::de::Error;
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Id {
+        Num(u64),
+        Text(String),
+    }
+
+    fn parse<E: Error>(id: Id) -> Result<u64, E> {
+        match id {
+            Id::Num(id) => Ok(id),
+            Id::Text(text) => text.trim_start_matches(|ch: char| ch.is_ascii_alphabetic())
+                                  .parse()
+                                  .map_err(|_| E::custom(format_args!("Invalid ID '{}'", text))),
+        }
+    }
+
+    pub fn deserialize<'de, D: serde::Deserializer<'de>>(id: D) -> Result<u64, D::Error> {
+        parse(Id::deserialize(id)?)
+    }
+
+    pub fn deserialize_opt<'de, D: serde::Deserializer<'de>>(id: D) -> Result<Option<u64>, D::Error> {
+        match Option::<Id>::deserialize(id)? {
+            Some(id) => parse(id).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    pub fn deserialize_vec<'de, D: serde::Deserializer<'de>>(ids: D) -> Result<Vec<u64>, D::Error> {
+        Vec::<Id>::deserialize(ids)?.into_iter().map(parse).collect()
+    }
+}
+
+str_code_enum!(
+    ///Category of tag.
+    TagCategory {
+        ///Content.
+        Content => ("cont", "Content"),
+        ///Sexual content.
+        Sexual => ("ero", "Sexual content"),
+        ///Technical.
+        Technical => ("tech", "Technical"),
+    }
+);
+
+#[derive(Deserialize, Serialize, Debug)]
+///Tag data representation. Returned by `get tag` and in tags dump.
+pub struct Tag {
+    #[serde(deserialize_with = "vndbid::deserialize")]
+    ///Unique identifier of tag.
+    pub id: u64,
+    ///Name.
+    pub name: String,
+    #[serde(default)]
+    ///Description, possibly with formatting codes.
+    pub description: String,
+    #[serde(default)]
+    ///Whether tag is meta tag, that cannot be applied directly.
+    pub meta: bool,
+    #[serde(default)]
+    ///Whether tag can be used for search.
+    pub searchable: bool,
+    #[serde(default)]
+    ///Whether tag can be applied to VN.
+    pub applicable: bool,
+    #[serde(default)]
+    ///Number of VNs tagged, including child tags.
+    pub vns: u64,
+    ///Category.
+    pub cat: Option<TagCategory>,
+    #[serde(default)]
+    ///List, possibly empty, of alternative names.
+    pub aliases: Vec<String>,
+    #[serde(default, deserialize_with = "vndbid::deserialize_vec")]
+    ///List, possibly empty, of parent tags' IDs.
+    pub parents: Vec<u64>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+///Character's trait data representation. Returned by `get trait` and in traits dump.
+pub struct Trait {
+    #[serde(deserialize_with = "vndbid::deserialize")]
+    ///Unique identifier of trait.
+    pub id: u64,
+    ///Name.
+    pub name: String,
+    #[serde(default)]
+    ///Description, possibly with formatting codes.
+    pub description: String,
+    #[serde(default)]
+    ///Whether trait is meta trait, that cannot be applied directly.
+    pub meta: bool,
+    #[serde(default)]
+    ///Whether trait can be used for search.
+    pub searchable: bool,
+    #[serde(default)]
+    ///Whether trait can be applied to character.
+    pub applicable: bool,
+    #[serde(default)]
+    ///Whether trait describes sexual content.
+    pub sexual: bool,
+    #[serde(default)]
+    ///Number of characters with trait, including child traits.
+    pub chars: u64,
+    #[serde(default, deserialize_with = "vndbid::deserialize_opt")]
+    ///ID of top level trait, that groups this trait.
+    pub group_id: Option<u64>,
+    #[serde(default)]
+    ///Name of top level trait, that groups this trait.
+    pub group_name: Option<String>,
+    #[serde(default)]
+    ///List, possibly empty, of alternative names.
+    pub aliases: Vec<String>,
+    #[serde(default, deserialize_with = "vndbid::deserialize_vec")]
+    ///List, possibly empty, of parent traits' IDs.
+    pub parents: Vec<u64>,
+}
+
 #[derive(Deserialize, Serialize, Debug)]
 ///User data representation. Returned by `get user`
 pub struct User {
@@ -609,7 +1299,7 @@ pub struct VoteList {
     pub added: Option<u64>,
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 ///Status in [VnList](struct.VnList.html).
 pub enum VnStatus {
     ///Unknown.
@@ -668,9 +1358,41 @@ pub struct VnList {
     pub notes: Option<String>
 }
 
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+///Priority in [WishList](struct.WishList.html).
+pub enum WishPriority {
+    ///High.
+    High = 0,
+    ///Medium.
+    Medium = 1,
+    ///Low.
+    Low = 2,
+    ///Blacklisted.
+    Blacklist = 3
+}
+
+impl<'de> Deserialize<'de> for WishPriority {
+    fn deserialize<D: serde::de::Deserializer<'de>>(priority: D) -> Result<Self, D::Error> {
+        let priority: u8 = Deserialize::deserialize(priority)?;
+        match priority {
+            0 => Ok(WishPriority::High),
+            1 => Ok(WishPriority::Medium),
+            2 => Ok(WishPriority::Low),
+            3 => Ok(WishPriority::Blacklist),
+            _ => Err(D::Error::custom(format_args!("Unknown wish priority '{}'.", priority)))
+        }
+    }
+}
+
+impl Serialize for WishPriority {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u8(*self as u8)
+    }
+}
+
 #[derive(Deserialize, Serialize, Debug)]
-///Vote list data representation. Returned by `get user`
-pub struct UList {
+///Wish list data representation. Returned by `get wishlist`
+pub struct WishList {
     ///Unique identifier of User.
     ///
     ///Provided when `basic` flag is specified.
@@ -679,18 +1401,49 @@ pub struct UList {
     ///
     ///Provided when `basic` flag is specified.
     pub vn: Option<u64>,
+    ///Priority of wish.
+    pub priority: Option<WishPriority>,
     #[serde(default)]
+    ///Unix timestamp of when this item is added.
+    ///
+    ///Provided when `basic` flag is specified.
+    pub added: u64,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+///Label of [UList](struct.UList.html) item.
+///
+///Built-in labels are: 1 - Playing, 2 - Finished, 3 - Stalled, 4 - Dropped, 5 - Wishlist, 6 - Blacklist, 7 - Voted.
+pub struct UListLabel {
+    ///Label's identifier.
+    pub id: u32,
+    ///Label's name.
+    pub label: String,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+///User list data representation. Returned by `get ulist`
+pub struct UList {
+    ///Unique identifier of User.
+    ///
+    ///Provided when `basic` flag is specified.
+    pub uid: Option<u64>,
+    ///Unique identifier of VN.
+    ///
+    ///Provided when `basic` flag is specified.
+    pub vn: Option<u64>,
+    #[serde(default, deserialize_with = "timestamp::deserialize")]
     ///Unix timestamp of when this item has been added.
     ///
     ///Provided when `basic` flag is specified.
     pub added: u64,
-    #[serde(rename = "lastmod", default)]
+    #[serde(rename = "lastmod", default, deserialize_with = "timestamp::deserialize")]
     ///Unix timestamp of when this item has been last modified.
     ///
     ///Provided when `basic` flag is specified.
     pub last_mod: u64,
-    #[serde(default)]
-    ///Unix timestamp when the vote has been cast.
+    #[serde(default, deserialize_with = "timestamp::deserialize")]
+    ///Unix timestamp when the vote has been cast, or 0 if there is no vote.
     ///
     ///Provided when `basic` flag is specified.
     pub voted: u64,
@@ -701,5 +1454,20 @@ pub struct UList {
     ///User's notes.
     ///
     ///Optionally provided when `basic` flag is specified.
-    pub notes: Option<String>
+    pub notes: Option<String>,
+    #[serde(default)]
+    ///Date when user started playing VN, in format `YYYY-MM-DD`.
+    ///
+    ///Optionally provided when `basic` flag is specified.
+    pub started: Option<String>,
+    #[serde(default)]
+    ///Date when user finished playing VN, in format `YYYY-MM-DD`.
+    ///
+    ///Optionally provided when `basic` flag is specified.
+    pub finished: Option<String>,
+    #[serde(default)]
+    ///Labels assigned to item.
+    ///
+    ///Provided when `labels` flag is specified.
+    pub labels: Vec<UListLabel>,
 }
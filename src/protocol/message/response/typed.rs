@@ -22,6 +22,14 @@ pub struct Results<T> {
         }
     }
 
+impl<T: Serialize> Results<T> {
+    #[inline]
+    ///Converts into `Response::Results`, that can be sent over wire.
+    pub fn to_response(&self) -> serde_json::Result<super::super::Response> {
+        super::Results::from_typed(self).map(super::super::Response::Results)
+    }
+}
+
 ///Result of `get vn` command.
 pub type VN = Results<results::Vn>;
 ///Result of `get release` command.
@@ -30,11 +38,17 @@ pub type Release = Results<results::Release>;
 pub type Producer = Results<results::Producer>;
 ///Result of `get character` command.
 pub type Character = Results<results::Character>;
+///Result of `get tag` command.
+pub type Tag = Results<results::Tag>;
+///Result of `get trait` command.
+pub type Trait = Results<results::Trait>;
 ///Result of `get user` command.
 pub type User = Results<results::User>;
 ///Result of `get votelist` command.
 pub type VoteList = Results<results::VoteList>;
 ///Result of `get vnlist` command.
 pub type VnList = Results<results::VnList>;
+///Result of `get wishlist` command.
+pub type WishList = Results<results::WishList>;
 ///Result of `get ulist` command.
-pub type UList = Results<results::VnList>;
+pub type UList = Results<results::UList>;
@@ -0,0 +1,268 @@
+//!Recommendation of VNs by similarity of tags.
+//!
+//!Recommender works only on typed results, therefore VNs and tags can come from VNDB,
+//!from [dumps](../dump/index.html) or any other local source.
+//!
+//!Each VN is represented by vector of its tags, weighted by their scores,
+//!while tags above chosen spoiler severity are ignored.
+//!When tags' metadata is provided, vector also includes ancestors of each tag with decaying weight,
+//!so that VNs sharing only sibling tags are still considered similar.
+//!
+//!User's profile is weighted sum of vectors of VNs, rated highly by user,
+//!and candidates are ranked by cosine similarity to the profile.
+//!
+//!Example of usage:
+//!
+//!```no_run
+//!use vndb::protocol::message::response::results::{Tag, Vn};
+//!use vndb::recommend::{Rating, Recommender};
+//!
+//!let vns: Vec<Vn> = serde_json::from_reader(std::fs::File::open("vns.json").expect("To open VNs")).expect("To read VNs");
+//!let tags: Vec<Tag> = serde_json::from_reader(std::fs::File::open("tags.json").expect("To open tags")).expect("To read tags");
+//!let ratings = [Rating { vn: 17, vote: 100 }, Rating { vn: 2002, vote: 40 }];
+//!
+//!let recommender = Recommender::new().with_tags(&tags);
+//!let profile = recommender.profile(&ratings, &vns);
+//!for recommendation in recommender.recommend(&profile, &vns, 10) {
+//!    println!("v{}: {:.3}", recommendation.vn.id, recommendation.similarity);
+//!}
+//!```
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+use crate::protocol::message::response::results;
+use crate::spoiler::SpoilerPolicy;
+
+///Default minimum vote, for VN to be included into profile.
+pub const DEFAULT_MIN_VOTE: u8 = 70;
+///Default weight of parent tag, relative to its child.
+pub const DEFAULT_PARENT_WEIGHT: f32 = 0.5;
+///Number of tags, reported as reasons for recommendation.
+pub const REASON_TAGS: usize = 5;
+
+///Vector of tag weights.
+pub type TagVector = BTreeMap<u64, f32>;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+///User's vote for VN.
+pub struct Rating {
+    ///VN's ID.
+    pub vn: u64,
+    ///Vote value in range from 10 to 100.
+    pub vote: u8,
+}
+
+impl Rating {
+    ///Creates rating from vote list's item, if it has vote.
+    pub fn from_vote(item: &results::VoteList) -> Option<Self> {
+        Some(Self {
+            vn: item.vn?,
+            vote: item.vote?,
+        })
+    }
+
+    ///Creates rating from user list's item, if it has vote.
+    pub fn from_ulist(item: &results::UList) -> Option<Self> {
+        Some(Self {
+            vn: item.vn?,
+            vote: item.vote?,
+        })
+    }
+}
+
+#[cfg(feature = "dump")]
+impl From<&crate::dump::Vote> for Rating {
+    #[inline]
+    fn from(vote: &crate::dump::Vote) -> Self {
+        Self {
+            vn: vote.vn,
+            vote: vote.vote,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+///User's profile of tags.
+pub struct Profile {
+    ///Weights of tags.
+    pub weights: TagVector,
+    ///IDs of all rated VNs, which are never recommended.
+    pub rated: BTreeSet<u64>,
+}
+
+impl Profile {
+    ///Returns tags with the highest weights, in descending order.
+    pub fn top(&self, limit: usize) -> Vec<(u64, f32)> {
+        let mut tags = self.weights.iter().map(|(tag, weight)| (*tag, *weight)).collect::<Vec<_>>();
+        tags.sort_by(|left, right| right.1.total_cmp(&left.1).then(left.0.cmp(&right.0)));
+        tags.truncate(limit);
+        tags
+    }
+}
+
+#[derive(Debug)]
+///Recommended VN.
+pub struct Recommendation<'a> {
+    ///VN.
+    pub vn: &'a results::Vn,
+    ///Cosine similarity to profile, from 0 to 1.
+    pub similarity: f32,
+    ///Tags, contributing the most to similarity, in descending order of contribution.
+    pub tags: Vec<(u64, f32)>,
+}
+
+#[derive(Clone, Debug)]
+///Recommender of VNs.
+pub struct Recommender {
+    policy: SpoilerPolicy,
+    min_vote: u8,
+    parent_weight: f32,
+    parents: HashMap<u64, Vec<u64>>,
+}
+
+impl Default for Recommender {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Recommender {
+    ///Creates new instance, that ignores major spoilers and has no tags' metadata.
+    pub fn new() -> Self {
+        Self {
+            policy: SpoilerPolicy::Minor,
+            min_vote: DEFAULT_MIN_VOTE,
+            parent_weight: DEFAULT_PARENT_WEIGHT,
+            parents: HashMap::new(),
+        }
+    }
+
+    #[inline]
+    ///Sets maximum spoiler severity of tags to consider.
+    pub fn spoiler_policy(mut self, policy: SpoilerPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    #[inline]
+    ///Sets minimum vote, in range from 10 to 100, for VN to be included into profile.
+    pub fn min_vote(mut self, min_vote: u8) -> Self {
+        self.min_vote = min_vote;
+        self
+    }
+
+    #[inline]
+    ///Sets weight of parent tag relative to its child, with `0` disabling use of hierarchy.
+    pub fn parent_weight(mut self, parent_weight: f32) -> Self {
+        self.parent_weight = parent_weight;
+        self
+    }
+
+    ///Adds tags' metadata, enabling use of tags' hierarchy.
+    pub fn with_tags<'a, I: IntoIterator<Item = &'a results::Tag>>(mut self, tags: I) -> Self {
+        for tag in tags {
+            self.parents.insert(tag.id, tag.parents.clone());
+        }
+        self
+    }
+
+    ///Returns vector of VN's tags.
+    ///
+    ///Tags with non-positive score or above spoiler policy are ignored,
+    ///while ancestors of tags get weight of the closest descendant, multiplied by parent weight for each level.
+    pub fn vector(&self, vn: &results::Vn) -> TagVector {
+        let mut vector = TagVector::new();
+
+        for tag in vn.tags.iter().filter(|tag| tag.score > 0.0 && self.policy.allows(tag.spoiler)) {
+            let mut seen = HashSet::new();
+            let mut level = vec![tag.id];
+            let mut weight = tag.score;
+
+            while !level.is_empty() && weight > 0.0 {
+                let mut next = Vec::new();
+                for id in level {
+                    if !seen.insert(id) {
+                        continue;
+                    }
+
+                    let entry = vector.entry(id).or_insert(0.0);
+                    *entry = entry.max(weight);
+                    if let Some(parents) = self.parents.get(&id) {
+                        next.extend(parents.iter().copied());
+                    }
+                }
+
+                level = next;
+                weight *= self.parent_weight;
+            }
+        }
+
+        vector
+    }
+
+    ///Builds profile from user's ratings and VNs.
+    ///
+    ///VNs rated at least minimum vote contribute their vectors, weighted by how much vote exceeds minimum:
+    ///vote equal to minimum has weight `1`, and each further point out of 10 adds `1`.
+    ///VNs, that are not rated, are ignored.
+    pub fn profile<'a, I: IntoIterator<Item = &'a results::Vn>>(&self, ratings: &[Rating], vns: I) -> Profile {
+        let votes = ratings.iter().map(|rating| (rating.vn, rating.vote)).collect::<HashMap<_, _>>();
+        let mut profile = Profile {
+            weights: TagVector::new(),
+            rated: votes.keys().copied().collect(),
+        };
+
+        for vn in vns {
+            let vote = match votes.get(&vn.id) {
+                Some(vote) if *vote >= self.min_vote => *vote,
+                _ => continue,
+            };
+
+            let weight = f32::from(vote - self.min_vote) / 10.0 + 1.0;
+            for (tag, score) in self.vector(vn) {
+                *profile.weights.entry(tag).or_insert(0.0) += score * weight;
+            }
+        }
+
+        profile
+    }
+
+    ///Ranks candidates by similarity to profile, returning at most `limit` of the most similar ones.
+    ///
+    ///Rated VNs and VNs without common tags are skipped.
+    pub fn recommend<'a, I: IntoIterator<Item = &'a results::Vn>>(&self, profile: &Profile, candidates: I, limit: usize) -> Vec<Recommendation<'a>> {
+        let profile_norm = norm(&profile.weights);
+        if profile_norm == 0.0 {
+            return Vec::new();
+        }
+
+        let mut recommendations = Vec::new();
+        for vn in candidates.into_iter().filter(|vn| !profile.rated.contains(&vn.id)) {
+            let vector = self.vector(vn);
+            let mut tags = vector.iter()
+                                 .filter_map(|(tag, score)| profile.weights.get(tag).map(|weight| (*tag, score * weight)))
+                                 .collect::<Vec<_>>();
+            let dot = tags.iter().map(|(_, contribution)| contribution).sum::<f32>();
+            if dot <= 0.0 {
+                continue;
+            }
+
+            tags.sort_by(|left, right| right.1.total_cmp(&left.1).then(left.0.cmp(&right.0)));
+            tags.truncate(REASON_TAGS);
+            recommendations.push(Recommendation {
+                vn,
+                similarity: dot / (profile_norm * norm(&vector)),
+                tags,
+            });
+        }
+
+        recommendations.sort_by(|left, right| right.similarity.total_cmp(&left.similarity).then(left.vn.id.cmp(&right.vn.id)));
+        recommendations.truncate(limit);
+        recommendations
+    }
+}
+
+fn norm(vector: &TagVector) -> f32 {
+    vector.values().map(|weight| weight * weight).sum::<f32>().sqrt()
+}
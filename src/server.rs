@@ -0,0 +1,222 @@
+//!Local VNDB-compatible server.
+//!
+//!Server speaks VNDB TCP protocol and answers `login`, `dbstats` and `get` commands
+//!for `vn`, `release`, `producer`, `character`, `staff`, `tag` and `trait` types, using local [Dataset](dataset/trait.Dataset.html),
+//!such as JSON file or SQLite mirror (`mirror` feature).
+//!
+//!Filters, flags, sorting and pagination are evaluated as described in [API](https://vndb.org/d11),
+//!and invalid requests are answered with the same errors as VNDB would.
+//!Note that credentials are not verified and any user is allowed to log in.
+//!User's lists are not stored, hence `set` commands are rejected.
+//!
+//!Example of usage:
+//!
+//!```
+//!use vndb::server::{Server, dataset::JsonDataset};
+//!
+//!let dataset = JsonDataset::from_reader(r#"{"vn": [{"id": 17, "title": "Ever17"}]}"#.as_bytes()).expect("To parse dataset");
+//!let server = Server::builder(dataset).start("127.0.0.1:0").expect("To start server");
+//!
+//!let mut client = vndb::client::simple::Client::connect_to(server.addr()).expect("To connect");
+//!client.send(&vndb::protocol::message::request::Login::new(None).into()).expect("To send login");
+//!client.flush().expect("To flush");
+//!client.receive().expect("To receive");
+//!assert_eq!(client.dbstats().expect("To get dbstats").vn, 1);
+//!```
+
+use std::{io, net};
+use std::sync::Arc;
+
+use crate::listener::Listener;
+use crate::protocol::message::{Request, RequestParseError, Response};
+use crate::protocol::message::request::{GetParseError, Login, SetParseError};
+use crate::protocol::message::response::{DBstats, Results, VndbError};
+
+pub mod dataset;
+mod query;
+
+pub use dataset::Dataset;
+
+///Answers request using dataset.
+///
+///Login is not tracked, use [Session](struct.Session.html) to handle connection's requests.
+pub fn reply<D: Dataset + ?Sized>(dataset: &D, request: &Request<'_>) -> Response {
+    match request {
+        Request::Login(login) => match check_login(login) {
+            Ok(()) if login.create_session => Response::Session(session_token()),
+            Ok(()) => Response::Ok,
+            Err(error) => Response::Error(error),
+        },
+        Request::DBstats => Response::DBstats(DBstats {
+            tags: dataset.count("tag"),
+            releases: dataset.count("release"),
+            producers: dataset.count("producer"),
+            chars: dataset.count("character"),
+            vn: dataset.count("vn"),
+            traits: dataset.count("trait"),
+            users: 0,
+            threads: 0,
+            posts: 0,
+            staff: dataset.count("staff"),
+            extra: Default::default(),
+        }),
+        Request::Get(get) => match query::check(get) {
+            Ok(expr) => Response::Results(Results::new(query::execute(get, &expr, &dataset.entities(get.kind.as_str())))),
+            Err(error) => Response::Error(error),
+        },
+        Request::Set(_) => Response::Error(VndbError::new("auth", "User's lists are not available on local server")),
+    }
+}
+
+fn check_login(login: &Login<'_>) -> Result<(), VndbError> {
+    if login.protocol != 1 {
+        return Err(VndbError::new("badarg", "Unknown protocol version").with("field", "protocol"));
+    }
+
+    let is_valid_client = login.client.len() >= 3 && login.client.len() <= 50
+                          && login.client.chars().all(|ch| ch.is_ascii_alphanumeric() || ch == ' ' || ch == '_' || ch == '-');
+    if !is_valid_client {
+        return Err(VndbError::new("badarg", "Invalid client name").with("field", "client"));
+    }
+
+    match login.create_session && login.creds.is_none() {
+        true => Err(VndbError::new("badarg", "Session requires credentials").with("field", "createsession")),
+        false => Ok(()),
+    }
+}
+
+//Local server doesn't verify credentials, hence token is only required to be unique.
+fn session_token() -> String {
+    use core::hash::{BuildHasher, Hasher};
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+    let mut token = String::with_capacity(40);
+    let state = std::collections::hash_map::RandomState::new();
+    let counter = COUNTER.fetch_add(1, Ordering::Relaxed);
+    for part in 0..3u64 {
+        let mut hasher = state.build_hasher();
+        hasher.write_u64(counter);
+        hasher.write_u64(part);
+        token.push_str(&format!("{:016x}", hasher.finish()));
+    }
+    token.truncate(40);
+    token
+}
+
+fn parse_error(error: RequestParseError<'_>) -> VndbError {
+    match error {
+        RequestParseError::InvalidGet(GetParseError::UnknownType(kind)) => VndbError::new("gettype", "Unknown get type").with("type", kind),
+        RequestParseError::InvalidGet(GetParseError::UnknownFlag(flag)) => VndbError::new("getinfo", "Unknown info flag").with("flag", flag),
+        RequestParseError::InvalidGet(GetParseError::InvalidOptions(error)) => VndbError::new("badarg", &format!("Invalid options: {}", error)).with("field", "options"),
+        RequestParseError::InvalidSet(SetParseError::UnknownType(kind)) => VndbError::new("settype", "Unknown set type").with("type", kind),
+        RequestParseError::InvalidLogin(error) => VndbError::new("badarg", &format!("Invalid login arguments: {}", error)),
+        RequestParseError::MissingArguments(_) => VndbError::new("missing", "Missing arguments"),
+        error => VndbError::new("parse", &format!("Invalid command or argument: {}", error)),
+    }
+}
+
+#[derive(Debug, Default)]
+///State of client's connection.
+pub struct Session {
+    is_logged_in: bool,
+}
+
+impl Session {
+    #[inline]
+    ///Creates new session, that is not logged in.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[inline]
+    ///Returns whether client logged in.
+    pub fn is_logged_in(&self) -> bool {
+        self.is_logged_in
+    }
+
+    ///Answers request's text without `0x04` byte.
+    pub fn reply<D: Dataset + ?Sized>(&mut self, dataset: &D, request: &str) -> Response {
+        let request = match Request::from_str(request) {
+            Ok(request) => request,
+            Err(error) => return Response::Error(parse_error(error)),
+        };
+
+        match (&request, self.is_logged_in) {
+            (Request::Login(_), true) => return Response::Error(VndbError::new("loggedin", "Already logged in")),
+            (Request::Login(_), false) => (),
+            (_, false) => return Response::Error(VndbError::new("needlogin", "Not logged in")),
+            (_, true) => (),
+        }
+
+        let response = reply(dataset, &request);
+        if let (Request::Login(_), Response::Ok | Response::Session(_)) = (&request, &response) {
+            self.is_logged_in = true;
+        }
+        response
+    }
+}
+
+///Builder of [Server](struct.Server.html)
+pub struct Builder<D> {
+    dataset: Arc<D>,
+    #[cfg(feature = "rustls-on")]
+    tls: Option<Arc<rustls::ServerConfig>>,
+}
+
+impl<D: Dataset> Builder<D> {
+    #[cfg(feature = "rustls-on")]
+    ///Enables TLS, using provided configuration.
+    pub fn tls(mut self, config: Arc<rustls::ServerConfig>) -> Self {
+        self.tls = Some(config);
+        self
+    }
+
+    ///Starts server on specified address.
+    pub fn start<A: net::ToSocketAddrs>(self, addr: A) -> io::Result<Server> {
+        #[cfg(feature = "rustls-on")]
+        let tls = self.tls;
+        #[cfg(not(feature = "rustls-on"))]
+        let tls = None;
+
+        let dataset = self.dataset;
+        let listener = Listener::start(addr, tls, move || {
+            let dataset = dataset.clone();
+            let mut session = Session::new();
+            Box::new(move |request: &str, io: &mut dyn io::Write| {
+                let response = session.reply(&*dataset, request);
+                io.write_all(response.to_string().as_bytes())?;
+                io.flush()
+            })
+        })?;
+
+        Ok(Server {
+            listener,
+        })
+    }
+}
+
+///Local VNDB server.
+///
+///Server is stopped on drop.
+pub struct Server {
+    listener: Listener,
+}
+
+impl Server {
+    ///Creates builder of server with dataset.
+    pub fn builder<D: Dataset>(dataset: D) -> Builder<D> {
+        Builder {
+            dataset: Arc::new(dataset),
+            #[cfg(feature = "rustls-on")]
+            tls: None,
+        }
+    }
+
+    #[inline]
+    ///Returns address on which server listens.
+    pub fn addr(&self) -> net::SocketAddr {
+        self.listener.addr()
+    }
+}
@@ -0,0 +1,121 @@
+//!Datasets of local server.
+
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::{fs, io};
+#[cfg(feature = "mirror")]
+use std::sync::Mutex;
+
+use serde_json::Value;
+
+///Source of entities for local server.
+///
+///Entities are JSON objects in format of `get` results, with fields of all flags present.
+///Fields, that are missing in entity, are omitted from results.
+pub trait Dataset: Send + Sync + 'static {
+    ///Returns all entities of type, such as `vn` or `trait`.
+    ///
+    ///Unsupported type should be treated as empty.
+    fn entities(&self, kind: &str) -> Cow<'_, [Value]>;
+
+    ///Returns number of entities of type.
+    fn count(&self, kind: &str) -> u64 {
+        self.entities(kind).len() as u64
+    }
+}
+
+#[derive(Clone, Debug, Default, serde::Deserialize)]
+#[serde(transparent)]
+///In-memory dataset, loaded from JSON.
+///
+///JSON must be an object, mapping type to array of entities:
+///
+///```json
+///{
+///    "vn": [{"id": 17, "title": "Ever17 -the out of infinity-"}],
+///    "producer": [{"id": 24, "name": "KID"}]
+///}
+///```
+pub struct JsonDataset {
+    entities: BTreeMap<String, Vec<Value>>,
+}
+
+impl JsonDataset {
+    #[inline]
+    ///Creates empty dataset.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    ///Reads dataset from JSON.
+    pub fn from_reader<R: io::Read>(reader: R) -> serde_json::Result<Self> {
+        serde_json::from_reader(reader)
+    }
+
+    ///Reads dataset from JSON file.
+    pub fn open<P: AsRef<std::path::Path>>(path: P) -> io::Result<Self> {
+        let file = fs::File::open(path)?;
+        Self::from_reader(io::BufReader::new(file)).map_err(io::Error::from)
+    }
+
+    ///Adds entity of type.
+    pub fn insert(&mut self, kind: &str, entity: Value) {
+        self.entities.entry(kind.to_owned()).or_default().push(entity);
+    }
+}
+
+impl Dataset for JsonDataset {
+    fn entities(&self, kind: &str) -> Cow<'_, [Value]> {
+        match self.entities.get(kind) {
+            Some(entities) => Cow::Borrowed(entities.as_slice()),
+            None => Cow::Borrowed(&[]),
+        }
+    }
+}
+
+#[cfg(feature = "mirror")]
+///Dataset, backed by SQLite [mirror](../../mirror/index.html).
+///
+///Provides `vn` and `release` entities, which are read from database on each request.
+///As dataset cannot report errors, entities are treated as missing when database cannot be read.
+pub struct MirrorDataset {
+    mirror: Mutex<crate::mirror::Mirror>,
+}
+
+#[cfg(feature = "mirror")]
+impl MirrorDataset {
+    #[inline]
+    ///Creates dataset from mirror.
+    pub fn new(mirror: crate::mirror::Mirror) -> Self {
+        Self {
+            mirror: Mutex::new(mirror),
+        }
+    }
+
+    #[inline]
+    ///Opens mirror from database file.
+    pub fn open<P: AsRef<std::path::Path>>(path: P) -> crate::mirror::Result<Self> {
+        crate::mirror::Mirror::open(path).map(Self::new)
+    }
+}
+
+#[cfg(feature = "mirror")]
+impl Dataset for MirrorDataset {
+    fn entities(&self, kind: &str) -> Cow<'_, [Value]> {
+        let mirror = match self.mirror.lock() {
+            Ok(mirror) => mirror,
+            Err(error) => error.into_inner(),
+        };
+
+        Cow::Owned(mirror.entities(kind).unwrap_or_default())
+    }
+
+    fn count(&self, kind: &str) -> u64 {
+        let mirror = match self.mirror.lock() {
+            Ok(mirror) => mirror,
+            Err(error) => error.into_inner(),
+        };
+
+        mirror.count(kind).unwrap_or(0)
+    }
+}
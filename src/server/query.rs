@@ -0,0 +1,459 @@
+//!Evaluation of `get` command over dataset's entities.
+
+use core::cmp;
+
+use serde_json::{Value, Map};
+
+use crate::protocol::message::request::Get;
+use crate::protocol::message::request::filter::{Condition, Expr, Operator};
+use crate::protocol::message::response::VndbError;
+
+const MAX_RESULTS: u32 = 25;
+const DEFAULT_RESULTS: u32 = 10;
+
+#[derive(Copy, Clone)]
+enum FilterKind {
+    ///Numeric ID, accepts array for `=` and `!=`.
+    Id,
+    ///Text field, that can be `null`.
+    Text(&'static str),
+    ///Case insensitive search through text of several fields, only `~`.
+    Search(&'static [&'static str]),
+    ///First character of `title` or `name`. `null` for non-letters.
+    Firstchar(&'static str),
+    ///Date in `YYYY-MM-DD` format, possibly partial.
+    Date(&'static str),
+    ///Boolean, only `=`.
+    Bool(&'static str),
+    ///List of strings, checked for any of values.
+    Strings(&'static str),
+    ///List of references to other entities, checked for any of IDs.
+    Refs(&'static str),
+}
+
+struct Schema {
+    flags: &'static [(&'static str, &'static [&'static str])],
+    filters: &'static [(&'static str, FilterKind)],
+    sort: &'static [&'static str],
+}
+
+const VN: Schema = Schema {
+    flags: &[
+        ("basic", &["title", "original", "released", "languages", "orig_lang", "platforms"]),
+        ("details", &["aliases", "length", "description", "links", "image", "image_flagging"]),
+        ("anime", &["anime"]),
+        ("relations", &["relations"]),
+        ("tags", &["tags"]),
+        ("stats", &["popularity", "rating", "votecount"]),
+        ("screens", &["screens"]),
+        ("staff", &["staff"]),
+    ],
+    filters: &[
+        ("id", FilterKind::Id),
+        ("title", FilterKind::Text("title")),
+        ("original", FilterKind::Text("original")),
+        ("firstchar", FilterKind::Firstchar("title")),
+        ("released", FilterKind::Date("released")),
+        ("platforms", FilterKind::Strings("platforms")),
+        ("languages", FilterKind::Strings("languages")),
+        ("orig_lang", FilterKind::Strings("orig_lang")),
+        ("search", FilterKind::Search(&["title", "original", "aliases"])),
+        ("tags", FilterKind::Refs("tags")),
+    ],
+    sort: &["id", "title", "released", "popularity", "rating", "votecount"],
+};
+
+const RELEASE: Schema = Schema {
+    flags: &[
+        ("basic", &["title", "original", "released", "type", "patch", "freeware", "doujin", "languages"]),
+        ("details", &["website", "notes", "minage", "gtin", "catalog", "platforms", "media", "resolution", "voiced", "animation"]),
+        ("vn", &["vn"]),
+        ("producers", &["producers"]),
+    ],
+    filters: &[
+        ("id", FilterKind::Id),
+        ("vn", FilterKind::Refs("vn")),
+        ("producer", FilterKind::Refs("producers")),
+        ("title", FilterKind::Text("title")),
+        ("original", FilterKind::Text("original")),
+        ("released", FilterKind::Date("released")),
+        ("patch", FilterKind::Bool("patch")),
+        ("freeware", FilterKind::Bool("freeware")),
+        ("doujin", FilterKind::Bool("doujin")),
+        ("type", FilterKind::Text("type")),
+        ("gtin", FilterKind::Text("gtin")),
+        ("catalog", FilterKind::Text("catalog")),
+        ("languages", FilterKind::Strings("languages")),
+        ("platforms", FilterKind::Strings("platforms")),
+    ],
+    sort: &["id", "title", "released"],
+};
+
+const PRODUCER: Schema = Schema {
+    flags: &[
+        ("basic", &["name", "original", "type", "language"]),
+        ("details", &["links", "aliases", "description"]),
+        ("relations", &["relations"]),
+    ],
+    filters: &[
+        ("id", FilterKind::Id),
+        ("name", FilterKind::Text("name")),
+        ("original", FilterKind::Text("original")),
+        ("type", FilterKind::Text("type")),
+        ("language", FilterKind::Strings("language")),
+        ("search", FilterKind::Search(&["name", "original", "aliases"])),
+    ],
+    sort: &["id", "name"],
+};
+
+const CHARACTER: Schema = Schema {
+    flags: &[
+        ("basic", &["name", "original", "gender", "spoil_gender", "bloodt", "birthday"]),
+        ("details", &["aliases", "description", "age", "image", "image_flagging"]),
+        ("meas", &["bust", "waist", "hip", "height", "weight", "cup_size"]),
+        ("traits", &["traits"]),
+        ("vns", &["vns"]),
+        ("voiced", &["voiced"]),
+    ],
+    filters: &[
+        ("id", FilterKind::Id),
+        ("name", FilterKind::Text("name")),
+        ("original", FilterKind::Text("original")),
+        ("search", FilterKind::Search(&["name", "original", "aliases"])),
+        ("vn", FilterKind::Refs("vns")),
+        ("traits", FilterKind::Refs("traits")),
+    ],
+    sort: &["id", "name"],
+};
+
+const STAFF: Schema = Schema {
+    flags: &[
+        ("basic", &["name", "original", "gender", "language"]),
+        ("details", &["links", "description", "aliases", "main_alias"]),
+        ("vns", &["vns"]),
+        ("voiced", &["voiced"]),
+    ],
+    filters: &[
+        ("id", FilterKind::Id),
+        ("aid", FilterKind::Refs("aliases")),
+        ("search", FilterKind::Search(&["name", "original", "aliases"])),
+    ],
+    sort: &["id", "name"],
+};
+
+const TAG: Schema = Schema {
+    flags: &[
+        ("basic", &["name", "description", "meta", "searchable", "applicable", "vns", "cat", "aliases", "parents"]),
+    ],
+    filters: &[
+        ("id", FilterKind::Id),
+        ("name", FilterKind::Text("name")),
+        ("search", FilterKind::Search(&["name", "aliases"])),
+    ],
+    sort: &["id", "name", "vns"],
+};
+
+const TRAIT: Schema = Schema {
+    flags: &[
+        ("basic", &["name", "description", "meta", "searchable", "applicable", "chars", "aliases", "parents"]),
+    ],
+    filters: &[
+        ("id", FilterKind::Id),
+        ("name", FilterKind::Text("name")),
+        ("search", FilterKind::Search(&["name", "aliases"])),
+    ],
+    sort: &["id", "name", "chars"],
+};
+
+fn schema(kind: &str) -> Option<&'static Schema> {
+    match kind {
+        "vn" => Some(&VN),
+        "release" => Some(&RELEASE),
+        "producer" => Some(&PRODUCER),
+        "character" => Some(&CHARACTER),
+        "staff" => Some(&STAFF),
+        "tag" => Some(&TAG),
+        "trait" => Some(&TRAIT),
+        _ => None,
+    }
+}
+
+fn filter_error(msg: &str, condition: &Condition) -> VndbError {
+    VndbError::new("filter", msg).with("field", condition.field.as_str())
+                                 .with("op", condition.op.as_str())
+                                 .with("value", condition.value.clone())
+}
+
+//Checks that condition is applicable to filter.
+fn validate(kind: FilterKind, condition: &Condition) -> Result<(), VndbError> {
+    let op = condition.op;
+    let value = &condition.value;
+
+    let is_valid_op = match kind {
+        FilterKind::Id => op != Operator::Like,
+        FilterKind::Text(_) => op == Operator::Eq || op == Operator::NotEq || op == Operator::Like,
+        FilterKind::Search(_) => op == Operator::Like,
+        FilterKind::Firstchar(_) | FilterKind::Strings(_) | FilterKind::Refs(_) => op == Operator::Eq || op == Operator::NotEq,
+        FilterKind::Date(_) => op != Operator::Like,
+        FilterKind::Bool(_) => op == Operator::Eq,
+    };
+    if !is_valid_op {
+        return Err(filter_error("Invalid operator", condition));
+    }
+
+    let is_id = |value: &Value| value.as_u64().map(|id| id > 0).unwrap_or(false);
+    let is_valid_value = match kind {
+        FilterKind::Id | FilterKind::Refs(_) => match value {
+            Value::Array(ids) => (op == Operator::Eq || op == Operator::NotEq) && !ids.is_empty() && ids.iter().all(is_id),
+            value => is_id(value),
+        },
+        FilterKind::Text(_) => value.is_string() || (value.is_null() && op != Operator::Like),
+        FilterKind::Search(_) => value.is_string(),
+        FilterKind::Firstchar(_) => match value {
+            Value::Null => true,
+            Value::String(text) => text.len() == 1 && text.chars().all(|ch| ch.is_ascii_lowercase()),
+            _ => false,
+        },
+        FilterKind::Date(_) => value.is_string() || (value.is_null() && (op == Operator::Eq || op == Operator::NotEq)),
+        FilterKind::Bool(_) => value.is_boolean(),
+        FilterKind::Strings(_) => match value {
+            Value::Array(values) => !values.is_empty() && values.iter().all(Value::is_string),
+            value => value.is_string(),
+        },
+    };
+    match is_valid_value {
+        true => Ok(()),
+        false => Err(filter_error("Invalid value", condition)),
+    }
+}
+
+fn find_filter(schema: &Schema, condition: &Condition) -> Result<FilterKind, VndbError> {
+    let kind = match schema.filters.iter().find(|(name, _)| *name == condition.field) {
+        Some((_, kind)) => *kind,
+        None => return Err(filter_error("Unknown field", condition)),
+    };
+
+    validate(kind, condition).map(|_| kind)
+}
+
+fn check_filters(schema: &Schema, expr: &Expr) -> Result<(), VndbError> {
+    for condition in expr.conditions() {
+        find_filter(schema, condition)?;
+    }
+    Ok(())
+}
+
+//Collects all strings within value.
+fn collect_text<'a>(value: &'a Value, result: &mut Vec<&'a str>) {
+    match value {
+        Value::String(text) => result.push(text),
+        Value::Array(values) => values.iter().for_each(|value| collect_text(value, result)),
+        Value::Object(values) => values.values().for_each(|value| collect_text(value, result)),
+        _ => (),
+    }
+}
+
+//Extracts ID of reference, that is either number, array with ID first or object with `id` field.
+fn ref_id(value: &Value) -> Option<u64> {
+    match value {
+        Value::Array(values) => values.first().and_then(Value::as_u64),
+        Value::Object(values) => values.get("id").and_then(Value::as_u64),
+        value => value.as_u64(),
+    }
+}
+
+fn any_of<F: Fn(&Value) -> bool>(value: &Value, op: Operator, matches: F) -> bool {
+    let is_match = match value {
+        Value::Array(values) => values.iter().any(matches),
+        value => matches(value),
+    };
+
+    match op {
+        Operator::NotEq => !is_match,
+        _ => is_match,
+    }
+}
+
+fn compare<T: PartialOrd>(left: Option<T>, op: Operator, right: Option<T>) -> bool {
+    match (left, right) {
+        (Some(left), Some(right)) => match op {
+            Operator::Eq => left == right,
+            Operator::NotEq => left != right,
+            Operator::Greater => left > right,
+            Operator::GreaterEq => left >= right,
+            Operator::Less => left < right,
+            Operator::LessEq => left <= right,
+            Operator::Like => false,
+        },
+        (None, None) => op == Operator::Eq,
+        _ => op == Operator::NotEq,
+    }
+}
+
+fn matches_condition(schema: &Schema, condition: &Condition, item: &Value) -> bool {
+    let kind = match find_filter(schema, condition) {
+        Ok(kind) => kind,
+        Err(_) => return false,
+    };
+    let value = &condition.value;
+    let op = condition.op;
+
+    match kind {
+        FilterKind::Id => {
+            let id = item["id"].as_u64();
+            match value {
+                Value::Array(ids) => {
+                    let is_match = ids.iter().any(|expected| expected.as_u64() == id);
+                    is_match == (op == Operator::Eq)
+                },
+                value => compare(id, op, value.as_u64()),
+            }
+        },
+        FilterKind::Text(field) => match op {
+            Operator::Like => match (item[field].as_str(), value.as_str()) {
+                (Some(text), Some(pattern)) => text.to_lowercase().contains(&pattern.to_lowercase()),
+                _ => false,
+            },
+            op => compare(item[field].as_str(), op, value.as_str()),
+        },
+        FilterKind::Search(fields) => {
+            let pattern = value.as_str().unwrap_or_default().to_lowercase();
+            let mut text = Vec::new();
+            for field in fields {
+                collect_text(&item[*field], &mut text);
+            }
+            text.iter().any(|text| text.to_lowercase().contains(&pattern))
+        },
+        FilterKind::Firstchar(field) => {
+            let first = item[field].as_str().and_then(|text| text.chars().next()).map(|ch| ch.to_ascii_lowercase());
+            let first = first.filter(char::is_ascii_lowercase);
+            compare(first, op, value.as_str().and_then(|text| text.chars().next()))
+        },
+        FilterKind::Date(field) => compare(item[field].as_str(), op, value.as_str()),
+        FilterKind::Bool(field) => item[field].as_bool() == value.as_bool(),
+        FilterKind::Strings(field) => {
+            let mut text = Vec::new();
+            collect_text(&item[field], &mut text);
+            any_of(value, op, |expected| text.iter().any(|text| Some(*text) == expected.as_str()))
+        },
+        FilterKind::Refs(field) => {
+            let ids = match item[field].as_array() {
+                Some(refs) => refs.iter().filter_map(ref_id).collect::<Vec<_>>(),
+                None => Vec::new(),
+            };
+            any_of(value, op, |expected| expected.as_u64().map(|expected| ids.contains(&expected)).unwrap_or(false))
+        },
+    }
+}
+
+fn matches(schema: &Schema, expr: &Expr, item: &Value) -> bool {
+    match expr {
+        Expr::Condition(condition) => matches_condition(schema, condition, item),
+        Expr::And(exprs) => exprs.iter().all(|expr| matches(schema, expr, item)),
+        Expr::Or(exprs) => exprs.iter().any(|expr| matches(schema, expr, item)),
+    }
+}
+
+fn cmp_values(left: &Value, right: &Value) -> cmp::Ordering {
+    match (left, right) {
+        (Value::Null, Value::Null) => cmp::Ordering::Equal,
+        (Value::Null, _) => cmp::Ordering::Less,
+        (_, Value::Null) => cmp::Ordering::Greater,
+        (Value::Number(left), Value::Number(right)) => left.as_f64().partial_cmp(&right.as_f64()).unwrap_or(cmp::Ordering::Equal),
+        (Value::String(left), Value::String(right)) => left.cmp(right),
+        (Value::Bool(left), Value::Bool(right)) => left.cmp(right),
+        _ => cmp::Ordering::Equal,
+    }
+}
+
+//Selects fields according to flags.
+fn select(schema: &Schema, flags: &[&str], item: &Value) -> Value {
+    let mut result = Map::new();
+    result.insert("id".to_owned(), item["id"].clone());
+
+    for (flag, fields) in schema.flags {
+        if flags.contains(flag) {
+            for field in fields.iter() {
+                if let Some(value) = item.get(*field) {
+                    result.insert((*field).to_owned(), value.clone());
+                }
+            }
+        }
+    }
+
+    Value::Object(result)
+}
+
+///Validates `get` command against type's schema.
+///
+///Must be called before [execute](fn.execute.html).
+pub fn check(get: &Get<'_>) -> Result<Expr, VndbError> {
+    let kind = get.kind.as_str();
+    let schema = match schema(kind) {
+        Some(schema) => schema,
+        None => return Err(VndbError::new("gettype", "Unknown get type").with("type", kind)),
+    };
+
+    if let Some(flag) = get.flags.names().find(|flag| !schema.flags.iter().any(|(name, _)| name == flag)) {
+        return Err(VndbError::new("getinfo", "Unknown info flag").with("flag", flag));
+    }
+
+    if get.filters.is_empty() {
+        return Err(VndbError::new("missing", "No filters specified").with("field", "filters"));
+    }
+
+    let expr = match get.filters.expr() {
+        Ok(expr) => expr,
+        Err(error) => return Err(VndbError::new("parse", &format!("Invalid filter expression: {}", error))),
+    };
+    check_filters(schema, &expr)?;
+
+    if let Some(ref options) = get.options {
+        if options.page == Some(0) {
+            return Err(VndbError::new("badarg", "Invalid page number").with("field", "page"));
+        }
+        match options.results {
+            Some(results) if results == 0 || results > MAX_RESULTS => return Err(VndbError::new("badarg", "Invalid number of results").with("field", "results")),
+            _ => (),
+        }
+        match options.sort {
+            Some(sort) if !schema.sort.contains(&sort) => return Err(VndbError::new("badarg", "Invalid sort field").with("field", "sort")),
+            _ => (),
+        }
+    }
+
+    Ok(expr)
+}
+
+///Executes `get` command, checked by [check](fn.check.html), producing `results` response's payload.
+pub fn execute(get: &Get<'_>, expr: &Expr, entities: &[Value]) -> Value {
+    let schema = match schema(get.kind.as_str()) {
+        Some(schema) => schema,
+        None => unreachable!(),
+    };
+
+    let (page, per_page, sort, reverse) = match get.options {
+        Some(ref options) => (options.page.unwrap_or(1), options.results.unwrap_or(DEFAULT_RESULTS), options.sort.unwrap_or("id"), options.reverse.unwrap_or(false)),
+        None => (1, DEFAULT_RESULTS, "id", false),
+    };
+    let (page, per_page) = (page as usize, per_page as usize);
+
+    let mut items = entities.iter().filter(|item| matches(schema, expr, item)).collect::<Vec<_>>();
+    items.sort_by(|left, right| cmp_values(&left[sort], &right[sort]).then_with(|| cmp_values(&left["id"], &right["id"])));
+    if reverse {
+        items.reverse();
+    }
+
+    let more = items.len() > page.saturating_mul(per_page);
+    let flags = get.flags.names().collect::<Vec<_>>();
+    let items = items.iter().skip((page - 1).saturating_mul(per_page))
+                            .take(per_page)
+                            .map(|item| select(schema, &flags, item))
+                            .collect::<Vec<_>>();
+
+    serde_json::json!({
+        "num": items.len(),
+        "more": more,
+        "items": items,
+    })
+}
@@ -0,0 +1,119 @@
+//!Spoiler filtering of typed results.
+//!
+//!Example of usage:
+//!
+//!```
+//!use vndb::protocol::message::response::Results;
+//!use vndb::spoiler::{SpoilerPolicy, ApplySpoilerPolicy};
+//!
+//!let results = Results::from_str(r#"{"num":1,"more":false,"items":[{
+//!    "id":17,
+//!    "description":"Time loop. [spoiler]Twist[/spoiler]",
+//!    "tags":[{"id":1,"score":2.5,"spoiler level":0},{"id":2,"score":1.0,"spoiler level":2}]
+//!}]}"#).unwrap();
+//!let mut vn = results.vn().unwrap();
+//!
+//!vn.apply_spoiler_policy(SpoilerPolicy::Minor);
+//!assert_eq!(vn[0].tags.len(), 1);
+//!assert_eq!(vn[0].description.as_deref(), Some("Time loop. "));
+//!```
+
+use crate::markup::{Markup, SpoilerMode};
+use crate::protocol::message::response::results::{SpoilerLevel, Vn, Character, Release, Producer};
+use crate::protocol::message::response::typed::Results;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+///Maximum spoiler severity, user agreed to see.
+pub enum SpoilerPolicy {
+    ///No spoilers are shown.
+    None,
+    ///Only minor spoilers are shown.
+    Minor,
+    ///All spoilers are shown.
+    Major,
+}
+
+impl SpoilerPolicy {
+    #[inline]
+    ///Returns whether data with specified spoiler severity can be shown.
+    pub fn allows(self, level: SpoilerLevel) -> bool {
+        let max = match self {
+            SpoilerPolicy::None => SpoilerLevel::None,
+            SpoilerPolicy::Minor => SpoilerLevel::Minor,
+            SpoilerPolicy::Major => SpoilerLevel::Major,
+        };
+
+        level <= max
+    }
+
+    #[inline]
+    ///Returns how `[spoiler]` blocks of descriptions are to be rendered.
+    ///
+    ///Description's spoilers have no severity, so they are treated as major ones.
+    pub fn markup_mode(self) -> SpoilerMode {
+        match self {
+            SpoilerPolicy::Major => SpoilerMode::Show,
+            _ => SpoilerMode::Hide,
+        }
+    }
+
+    ///Removes `[spoiler]` blocks from description, unless policy allows major spoilers.
+    ///
+    ///Rest of description is kept as VNDB formatting codes.
+    pub fn filter_description(self, description: &mut Option<String>) {
+        if self.markup_mode() == SpoilerMode::Show {
+            return;
+        }
+
+        if let Some(text) = description.as_mut() {
+            let mut markup = Markup::parse(text);
+            if markup.has_spoilers() {
+                markup.strip_spoilers();
+                *text = markup.to_string();
+            }
+        }
+    }
+}
+
+///Describes data that can be filtered according to [SpoilerPolicy](enum.SpoilerPolicy.html)
+pub trait ApplySpoilerPolicy {
+    ///Removes all data above policy's threshold.
+    fn apply_spoiler_policy(&mut self, policy: SpoilerPolicy);
+}
+
+impl ApplySpoilerPolicy for Vn {
+    fn apply_spoiler_policy(&mut self, policy: SpoilerPolicy) {
+        self.tags.retain(|tag| policy.allows(tag.spoiler));
+        policy.filter_description(&mut self.description);
+    }
+}
+
+impl ApplySpoilerPolicy for Character {
+    fn apply_spoiler_policy(&mut self, policy: SpoilerPolicy) {
+        self.traits.retain(|character_trait| policy.allows(character_trait.spoiler));
+        self.vns.retain(|vn| policy.allows(vn.spoiler));
+        policy.filter_description(&mut self.description);
+    }
+}
+
+impl ApplySpoilerPolicy for Release {
+    #[inline]
+    fn apply_spoiler_policy(&mut self, policy: SpoilerPolicy) {
+        policy.filter_description(&mut self.notes);
+    }
+}
+
+impl ApplySpoilerPolicy for Producer {
+    #[inline]
+    fn apply_spoiler_policy(&mut self, policy: SpoilerPolicy) {
+        policy.filter_description(&mut self.description);
+    }
+}
+
+impl<T: ApplySpoilerPolicy> ApplySpoilerPolicy for Results<T> {
+    fn apply_spoiler_policy(&mut self, policy: SpoilerPolicy) {
+        for item in self.items.iter_mut() {
+            item.apply_spoiler_policy(policy);
+        }
+    }
+}
@@ -0,0 +1,305 @@
+//!Statistics of user's lists.
+//!
+//!Statistics are computed from typed items of `ulist`, `votelist` or `vnlist`,
+//!and are serializable for use in dashboards.
+//!Votes are reported on scale from 1 to 10, same as global rating of VN,
+//!while dates are in UTC.
+//!
+//!Example of usage:
+//!
+//!```no_run
+//!use vndb::protocol::message::request::{get, Get};
+//!use vndb::stats::ListStats;
+//!
+//!let mut client = vndb::client::simple::Client::connect().expect("To connect");
+//!let get = Get {
+//!    kind: get::Type::ulist(),
+//!    flags: get::Flags::new().basic().labels(),
+//!    filters: get::Filters::new().filter(vndb::filter!(uid = 2)),
+//!    options: None,
+//!};
+//!let ulist = client.get(&get).expect("To get list").u_list().expect("To parse list");
+//!
+//!let stats = ListStats::from_ulist(&ulist.items);
+//!println!("{}", serde_json::to_string_pretty(&stats).expect("To serialize"));
+//!```
+
+use std::collections::{BTreeMap, HashMap};
+
+use serde::Serialize;
+
+use crate::export::{score, Status};
+use crate::protocol::message::response::results::{self, civil_date};
+use crate::recommend::Rating;
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+///Number of votes and their mean.
+pub struct Votes {
+    ///Number of votes.
+    pub count: usize,
+    ///Mean vote.
+    pub mean: Option<f32>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+///Statistics of votes.
+pub struct VoteStats {
+    ///Number of votes.
+    pub count: usize,
+    ///Number of votes, rounded down to whole points, where first element is for votes from 1 to 1.9 and last one is for 10.
+    pub distribution: [usize; 10],
+    ///Mean vote.
+    pub mean: Option<f32>,
+    ///Median vote.
+    pub median: Option<f32>,
+    ///Votes by year, in which they are cast.
+    ///
+    ///Votes without time are not included.
+    pub per_year: BTreeMap<i32, Votes>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+///Number of items by status.
+pub struct Statuses {
+    ///Items without status.
+    pub unknown: usize,
+    ///Currently playing.
+    pub playing: usize,
+    ///Finished.
+    pub finished: usize,
+    ///Stalled.
+    pub stalled: usize,
+    ///Dropped.
+    pub dropped: usize,
+    ///In wishlist.
+    pub wishlist: usize,
+    ///Blacklisted.
+    pub blacklist: usize,
+}
+
+impl Statuses {
+    fn add(&mut self, status: Status) {
+        let count = match status {
+            Status::Unknown => &mut self.unknown,
+            Status::Playing => &mut self.playing,
+            Status::Finished => &mut self.finished,
+            Status::Stalled => &mut self.stalled,
+            Status::Dropped => &mut self.dropped,
+            Status::Wishlist => &mut self.wishlist,
+            Status::Blacklist => &mut self.blacklist,
+        };
+        *count += 1;
+    }
+
+    ///Returns share of finished VNs among started ones, i.e. playing, finished, stalled or dropped.
+    pub fn completion_rate(&self) -> Option<f32> {
+        let started = self.playing + self.finished + self.stalled + self.dropped;
+        match started {
+            0 => None,
+            started => Some(self.finished as f32 / started as f32),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+///Point of monthly time series.
+pub struct Point {
+    ///Month in format `YYYY-MM`.
+    pub month: String,
+    ///Number of events within month.
+    pub count: usize,
+    ///Number of events up to the end of month.
+    pub total: usize,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+///Statistics of user's list.
+pub struct ListStats {
+    ///Number of items.
+    pub items: usize,
+    ///Statistics of votes.
+    pub votes: VoteStats,
+    ///Number of items by status.
+    ///
+    ///Not available for vote list.
+    pub statuses: Option<Statuses>,
+    ///Share of finished VNs among started ones.
+    pub completion_rate: Option<f32>,
+    ///Monthly series of added items, without gaps between first and last month.
+    pub added: Vec<Point>,
+    ///Monthly series of cast votes, without gaps between first and last month.
+    pub voted: Vec<Point>,
+}
+
+//Common representation of list's item.
+struct Item {
+    vote: Option<u8>,
+    status: Option<Status>,
+    added: u64,
+    voted: u64,
+}
+
+impl ListStats {
+    ///Computes statistics of `ulist` items, taking status from built-in labels.
+    pub fn from_ulist(items: &[results::UList]) -> Self {
+        Self::compute(items.iter().map(|item| Item {
+            vote: item.vote,
+            status: Some(Status::from_labels(&item.labels)),
+            added: item.added,
+            voted: item.voted,
+        }))
+    }
+
+    ///Computes statistics of `votelist` items.
+    pub fn from_votes(items: &[results::VoteList]) -> Self {
+        Self::compute(items.iter().map(|item| Item {
+            vote: item.vote,
+            status: None,
+            added: item.added.unwrap_or(0),
+            voted: item.added.unwrap_or(0),
+        }))
+    }
+
+    ///Computes statistics of `vnlist` items, which have no votes.
+    pub fn from_vnlist(items: &[results::VnList]) -> Self {
+        Self::compute(items.iter().map(|item| Item {
+            vote: None,
+            status: Some(item.status.map(Status::from).unwrap_or(Status::Unknown)),
+            added: item.added,
+            voted: 0,
+        }))
+    }
+
+    fn compute<I: Iterator<Item = Item>>(items: I) -> Self {
+        let mut stats = Self::default();
+        let mut votes = Vec::new();
+        let mut added = Vec::new();
+        let mut voted = Vec::new();
+        let mut years = BTreeMap::<i32, Vec<f32>>::new();
+
+        for item in items {
+            stats.items += 1;
+
+            if let Some(status) = item.status {
+                stats.statuses.get_or_insert_with(Statuses::default).add(status);
+            }
+            if item.added > 0 {
+                added.push(item.added);
+            }
+            if let Some(vote) = item.vote {
+                let vote = score(vote);
+                votes.push(vote);
+                stats.votes.distribution[(vote as usize).clamp(1, 10) - 1] += 1;
+
+                if item.voted > 0 {
+                    voted.push(item.voted);
+                    years.entry(civil_date(item.voted).0).or_default().push(vote);
+                }
+            }
+        }
+
+        stats.votes.per_year = years.into_iter().map(|(year, votes)| (year, Votes {
+            count: votes.len(),
+            mean: mean(votes.into_iter()),
+        })).collect();
+
+        votes.sort_by(f32::total_cmp);
+        stats.votes.count = votes.len();
+        stats.votes.mean = mean(votes.iter().copied());
+        stats.votes.median = match votes.len() {
+            0 => None,
+            len if len % 2 == 0 => Some((votes[len / 2 - 1] + votes[len / 2]) / 2.0),
+            len => Some(votes[len / 2]),
+        };
+        stats.completion_rate = stats.statuses.as_ref().and_then(Statuses::completion_rate);
+        stats.added = series(added);
+        stats.voted = series(voted);
+
+        stats
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+///User's votes compared to global rating of VNs.
+pub struct RatingComparison {
+    ///Number of voted VNs with global rating.
+    pub count: usize,
+    ///User's mean vote.
+    pub user_mean: f32,
+    ///Mean global rating.
+    pub global_mean: f32,
+    ///Difference of user's mean vote from mean global rating.
+    ///
+    ///Positive when user rates higher than others.
+    pub difference: f32,
+}
+
+impl RatingComparison {
+    ///Compares user's ratings with global rating of the same VNs, requested with `stats` flag.
+    ///
+    ///Returns `None`, if none of rated VNs has global rating.
+    pub fn compute<'a, I: IntoIterator<Item = &'a results::Vn>>(ratings: &[Rating], vns: I) -> Option<Self> {
+        let votes = ratings.iter().map(|rating| (rating.vn, score(rating.vote))).collect::<HashMap<_, _>>();
+
+        let mut user = Vec::new();
+        let mut global = Vec::new();
+        for vn in vns {
+            if let (Some(vote), Some(rating)) = (votes.get(&vn.id), vn.rating) {
+                user.push(*vote);
+                global.push(rating);
+            }
+        }
+
+        let user_mean = mean(user.iter().copied())?;
+        let global_mean = mean(global.iter().copied())?;
+        Some(Self {
+            count: user.len(),
+            user_mean,
+            global_mean,
+            difference: user_mean - global_mean,
+        })
+    }
+}
+
+fn mean<I: ExactSizeIterator<Item = f32>>(values: I) -> Option<f32> {
+    match values.len() {
+        0 => None,
+        len => Some(values.sum::<f32>() / len as f32),
+    }
+}
+
+//Builds monthly series from timestamps.
+fn series(mut timestamps: Vec<u64>) -> Vec<Point> {
+    timestamps.sort_unstable();
+
+    let mut counts = BTreeMap::<(i32, u32), usize>::new();
+    for timestamp in timestamps {
+        let (year, month, _) = civil_date(timestamp);
+        *counts.entry((year, month)).or_default() += 1;
+    }
+
+    let (mut current, last) = match (counts.keys().next(), counts.keys().next_back()) {
+        (Some(first), Some(last)) => (*first, *last),
+        _ => return Vec::new(),
+    };
+
+    let mut points = Vec::new();
+    let mut total = 0;
+    loop {
+        let count = counts.get(&current).copied().unwrap_or(0);
+        total += count;
+        points.push(Point {
+            month: format!("{:04}-{:02}", current.0, current.1),
+            count,
+            total,
+        });
+
+        if current == last {
+            break points;
+        }
+        current = match current.1 {
+            12 => (current.0 + 1, 1),
+            month => (current.0, month + 1),
+        };
+    }
+}
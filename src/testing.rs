@@ -0,0 +1,347 @@
+//!In-process mock VNDB server for testing.
+//!
+//!Server listens on local TCP port and responds to requests according to scripted expectations.
+//!Expectations are checked in order of their declaration and each of them is used only once.
+//!When no expectation matches, server falls back to canned fixture data, if enabled,
+//!or responds with `parse` error otherwise.
+//!
+//!Example of usage:
+//!
+//!```
+//!use vndb::testing::{MockServer, Reply};
+//!use vndb::protocol::message::{Request, Response};
+//!
+//!let server = MockServer::builder().expect("dbstats", Reply::Throttled)
+//!                                  .fixtures()
+//!                                  .start()
+//!                                  .expect("To start server");
+//!
+//!let mut client = vndb::client::simple::Client::connect_to(server.addr()).expect("To connect");
+//!client.send(&Request::DBstats).expect("To send");
+//!client.send(&Request::DBstats).expect("To send");
+//!client.flush().expect("To flush");
+//!
+//!match client.receive().expect("To receive").expect("To get response") {
+//!    Response::Error(error) => assert_eq!(error.id, "throttled"),
+//!    response => panic!("Unexpected response={:?}", response),
+//!}
+//!match client.receive().expect("To receive").expect("To get response") {
+//!    Response::DBstats(stats) => assert_eq!(stats.vn, fixtures::dbstats().vn),
+//!    response => panic!("Unexpected response={:?}", response),
+//!}
+//!# use vndb::testing::fixtures;
+//!assert_eq!(server.requests(), ["dbstats", "dbstats"]);
+//!```
+
+use std::{io, net, thread};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::listener::Listener;
+
+use crate::protocol::message::{Request, Response};
+use crate::protocol::message::response::{Results, VndbError};
+
+pub mod fixtures;
+
+///Request matcher of expectation.
+pub enum Matcher {
+    ///Matches any request.
+    Any,
+    ///Matches request's text exactly, without `0x04` byte.
+    Exact(String),
+    ///Matches request's command, such as `get`.
+    Command(String),
+    ///Matches parsed request using predicate.
+    Predicate(Box<dyn Fn(&Request) -> bool + Send>),
+}
+
+impl Matcher {
+    ///Creates matcher from predicate.
+    pub fn predicate<F: Fn(&Request) -> bool + Send + 'static>(predicate: F) -> Self {
+        Matcher::Predicate(Box::new(predicate))
+    }
+
+    ///Creates matcher of `get` request with specified type, such as `vn`.
+    pub fn get(kind: &str) -> Self {
+        let kind = kind.to_owned();
+        Self::predicate(move |request| match request {
+            Request::Get(get) => get.kind.as_str() == kind,
+            _ => false,
+        })
+    }
+
+    ///Creates matcher of `get` request with specified type and filters, compared by their text.
+    pub fn get_filtered(kind: &str, filters: &str) -> Self {
+        let (kind, filters) = (kind.to_owned(), filters.to_owned());
+        Self::predicate(move |request| match request {
+            Request::Get(get) => get.kind.as_str() == kind && get.filters.to_string() == filters,
+            _ => false,
+        })
+    }
+
+    fn matches(&self, request: &str) -> bool {
+        match self {
+            Matcher::Any => true,
+            Matcher::Exact(expected) => expected == request,
+            Matcher::Command(command) => request.split(' ').next() == Some(command.as_str()),
+            Matcher::Predicate(predicate) => match Request::from_str(request) {
+                Ok(request) => predicate(&request),
+                Err(_) => false,
+            },
+        }
+    }
+}
+
+impl From<&str> for Matcher {
+    #[inline]
+    fn from(request: &str) -> Self {
+        Matcher::Exact(request.to_owned())
+    }
+}
+
+#[derive(Clone, Debug)]
+///Server's reply on request.
+pub enum Reply {
+    ///Sends response.
+    Response(Response),
+    ///Sends raw text, as it is.
+    ///
+    ///Caller is responsible for adding `0x04` byte.
+    Raw(String),
+    ///Sends `throttled` error.
+    Throttled,
+    ///Closes connection without response.
+    Disconnect,
+    ///Sends first half of response and closes connection.
+    Partial(Response),
+    ///Waits specified duration, before sending reply.
+    Delay(Duration, Box<Reply>),
+}
+
+impl Reply {
+    #[inline]
+    ///Creates reply with VNDB error.
+    pub fn error(id: &str, msg: &str) -> Self {
+        Reply::Response(Response::Error(VndbError::new(id, msg)))
+    }
+
+    ///Creates reply with results, consisting of specified array of items.
+    pub fn results<I: Into<serde_json::Value>>(items: I, more: bool) -> Self {
+        let items = items.into();
+        let num = items.as_array().map(|items| items.len()).unwrap_or(0);
+        Reply::Response(Response::Results(Results::new(serde_json::json!({"num": num, "more": more, "items": items}))))
+    }
+}
+
+impl From<Response> for Reply {
+    #[inline]
+    fn from(response: Response) -> Self {
+        Reply::Response(response)
+    }
+}
+
+struct Expectation {
+    matcher: Matcher,
+    reply: Reply,
+}
+
+struct State {
+    expectations: Vec<Expectation>,
+    fixtures: bool,
+    requests: Vec<String>,
+}
+
+impl State {
+    fn reply(&mut self, request: &str) -> Reply {
+        self.requests.push(request.to_owned());
+
+        if let Some(idx) = self.expectations.iter().position(|expectation| expectation.matcher.matches(request)) {
+            return self.expectations.remove(idx).reply;
+        }
+
+        match self.fixtures {
+            true => fixtures::reply(request),
+            false => Reply::error("parse", "Unexpected request"),
+        }
+    }
+}
+
+///Builder of [MockServer](struct.MockServer.html)
+pub struct Builder {
+    state: State,
+    tls: bool,
+}
+
+impl Builder {
+    ///Adds expectation of request, that is to be answered with reply.
+    pub fn expect<M: Into<Matcher>, R: Into<Reply>>(mut self, matcher: M, reply: R) -> Self {
+        self.state.expectations.push(Expectation {
+            matcher: matcher.into(),
+            reply: reply.into(),
+        });
+        self
+    }
+
+    ///Enables canned [fixtures](fixtures/index.html) for `login`, `dbstats` and `get` requests.
+    pub fn fixtures(mut self) -> Self {
+        self.state.fixtures = true;
+        self
+    }
+
+    ///Enables TLS, using self-signed certificate for `localhost`.
+    ///
+    ///Use [MockServer::tls_config](struct.MockServer.html#method.tls_config) to connect.
+    pub fn tls(mut self) -> Self {
+        self.tls = true;
+        self
+    }
+
+    ///Starts server on random local port.
+    pub fn start(self) -> io::Result<MockServer> {
+        let state = Arc::new(Mutex::new(self.state));
+
+        let tls = match self.tls {
+            true => Some(tls::Tls::generate()?),
+            false => None,
+        };
+        let tls_config = tls.as_ref().map(|tls| tls.server.clone());
+
+        let listener = {
+            let state = state.clone();
+            Listener::start((net::Ipv4Addr::LOCALHOST, 0), tls_config, move || {
+                let state = state.clone();
+                Box::new(move |request: &str, io: &mut dyn io::Write| {
+                    let reply = match state.lock() {
+                        Ok(mut state) => state.reply(request),
+                        Err(_) => return Err(io::ErrorKind::Other.into()),
+                    };
+                    send_reply(io, reply)
+                })
+            })?
+        };
+
+        Ok(MockServer {
+            listener,
+            state,
+            tls,
+        })
+    }
+}
+
+//Returns error, when connection must be closed.
+fn send_reply(io: &mut dyn io::Write, reply: Reply) -> io::Result<()> {
+    match reply {
+        Reply::Response(response) => io.write_all(response.to_string().as_bytes())?,
+        Reply::Raw(text) => io.write_all(text.as_bytes())?,
+        Reply::Throttled => io.write_all(b"error {\"id\":\"throttled\",\"msg\":\"Throttle limit reached.\",\"type\":\"cmd\",\"minwait\":1.0,\"fullwait\":5.0}\x04")?,
+        Reply::Disconnect => return Err(io::ErrorKind::ConnectionAborted.into()),
+        Reply::Partial(response) => {
+            let response = response.to_string().into_bytes();
+            io.write_all(&response[..response.len() / 2])?;
+            io.flush()?;
+            return Err(io::ErrorKind::ConnectionAborted.into());
+        },
+        Reply::Delay(duration, reply) => {
+            thread::sleep(duration);
+            return send_reply(io, *reply);
+        },
+    }
+
+    io.flush()
+}
+
+mod tls {
+    use std::io;
+    use std::sync::Arc;
+
+    #[derive(Clone)]
+    pub struct Tls {
+        pub server: Arc<rustls::ServerConfig>,
+        pub client: Arc<rustls::ClientConfig>,
+    }
+
+    impl Tls {
+        pub fn generate() -> io::Result<Self> {
+            let cert = match rcgen::generate_simple_self_signed(vec!["localhost".to_owned()]) {
+                Ok(cert) => cert,
+                Err(error) => return Err(io::Error::new(io::ErrorKind::Other, error)),
+            };
+            let cert_der = cert.cert.der().clone();
+            let key_der = rustls::pki_types::PrivatePkcs8KeyDer::from(cert.key_pair.serialize_der());
+
+            let server = rustls::ServerConfig::builder().with_no_client_auth()
+                                                        .with_single_cert(vec![cert_der.clone()], key_der.into())
+                                                        .map_err(|error| io::Error::new(io::ErrorKind::Other, error))?;
+
+            let mut roots = rustls::RootCertStore::empty();
+            roots.add(cert_der).map_err(|error| io::Error::new(io::ErrorKind::Other, error))?;
+            let client = rustls::ClientConfig::builder().with_root_certificates(roots)
+                                                        .with_no_client_auth();
+
+            Ok(Self {
+                server: Arc::new(server),
+                client: Arc::new(client),
+            })
+        }
+    }
+}
+
+///Mock VNDB server.
+///
+///Server is stopped on drop.
+pub struct MockServer {
+    listener: Listener,
+    state: Arc<Mutex<State>>,
+    tls: Option<tls::Tls>,
+}
+
+impl MockServer {
+    ///Creates builder of server.
+    pub fn builder() -> Builder {
+        Builder {
+            state: State {
+                expectations: Vec::new(),
+                fixtures: false,
+                requests: Vec::new(),
+            },
+            tls: false,
+        }
+    }
+
+    #[inline]
+    ///Returns address on which server listens.
+    pub fn addr(&self) -> net::SocketAddr {
+        self.listener.addr()
+    }
+
+    ///Returns all requests received so far, without `0x04` byte.
+    pub fn requests(&self) -> Vec<String> {
+        match self.state.lock() {
+            Ok(state) => state.requests.clone(),
+            Err(error) => error.into_inner().requests.clone(),
+        }
+    }
+
+    ///Returns number of expectations that are not met yet.
+    pub fn pending_expectations(&self) -> usize {
+        match self.state.lock() {
+            Ok(state) => state.expectations.len(),
+            Err(error) => error.into_inner().expectations.len(),
+        }
+    }
+
+    ///Returns server name and client's configuration that trusts server's certificate.
+    ///
+    ///Returns `None` if TLS is not enabled.
+    pub fn tls_config(&self) -> Option<(rustls::pki_types::ServerName<'static>, Arc<rustls::ClientConfig>)> {
+        use core::convert::TryFrom;
+
+        let server_name = match rustls::pki_types::ServerName::try_from("localhost") {
+            Ok(server_name) => server_name,
+            Err(_) => unreachable!(),
+        };
+
+        self.tls.as_ref().map(|tls| (server_name, tls.client.clone()))
+    }
+}
@@ -0,0 +1,211 @@
+//!Canned data for mock server.
+//!
+//!Fixtures are returned regardless of requested flags, but `get` requests respect `id` filters and pagination options.
+
+use serde_json::{json, Value};
+
+use super::Reply;
+use crate::protocol::message::{Request, Response};
+use crate::protocol::message::request::filter::{Expr, Operator};
+use crate::protocol::message::response::{DBstats, Results};
+
+///Session token, returned on login with `createsession`.
+pub const SESSION_TOKEN: &str = "0c3f3b0b8b3e4f6a9d2e7c1a5b4d8e9f0a1b2c3d";
+
+///Returns database statistics.
+pub fn dbstats() -> DBstats {
+    DBstats {
+        tags: 2647,
+        releases: 82183,
+        producers: 12187,
+        chars: 97812,
+        vn: 31207,
+        traits: 2979,
+        users: 232512,
+        threads: 17813,
+        posts: 152317,
+        staff: 27171,
+        extra: Default::default(),
+    }
+}
+
+///Returns VN entities.
+pub fn vn() -> Vec<Value> {
+    vec![
+        json!({
+            "id": 17,
+            "title": "Ever17 -the out of infinity-",
+            "original": null,
+            "released": "2002-08-29",
+            "languages": ["en", "ja", "ru", "zh"],
+            "orig_lang": ["ja"],
+            "platforms": ["drc", "ps2", "psp", "win"],
+            "aliases": "E17",
+            "length": 4,
+            "description": "Ever17 is a [url=/g3]time loop[/url] story.[spoiler] Twist.[/spoiler]",
+            "links": { "wikidata": "Q1325216", "renai": "ever17" },
+            "image": "https://s2.vndb.org/cv/89/35689.jpg",
+            "anime": [],
+            "relations": [
+                { "id": 18, "relation": "fan", "title": "Ever17 Premium Edition", "original": null, "official": true },
+            ],
+            "tags": [
+                { "id": 19, "score": 2.8, "spoiler level": 0 },
+                { "id": 3, "score": 2.2, "spoiler level": 2 },
+            ],
+            "popularity": 38.5,
+            "rating": 8.66,
+            "votecount": 11215,
+            "staff": [
+                { "sid": 226, "aid": 226, "name": "Uchikoshi Kotaro", "original": "打越鋼太郎", "role": "scenario", "note": null },
+                { "sid": 1011, "aid": 1011, "name": "Abe Takeshi", "original": "阿保剛", "role": "music", "note": null },
+            ],
+        }),
+        json!({
+            "id": 18,
+            "title": "Ever17 Premium Edition",
+            "original": null,
+            "released": "2003-03-28",
+            "languages": ["ja"],
+            "orig_lang": ["ja"],
+            "platforms": ["win"],
+            "relations": [
+                { "id": 17, "relation": "orig", "title": "Ever17 -the out of infinity-", "original": null, "official": true },
+            ],
+            "tags": [
+                { "id": 19, "score": 2.0, "spoiler level": 0 },
+            ],
+            "popularity": 2.1,
+            "rating": 7.5,
+            "votecount": 120,
+        }),
+    ]
+}
+
+///Returns release entities.
+pub fn release() -> Vec<Value> {
+    vec![
+        json!({
+            "id": 29,
+            "title": "Ever17 -the out of infinity- Premium Edition",
+            "original": null,
+            "released": "2003-03-28",
+            "type": "complete",
+            "patch": false,
+            "freeware": false,
+            "doujin": false,
+            "languages": ["ja"],
+            "platforms": ["win"],
+            "voiced": 4,
+            "animation": [1, null],
+            "vn": [{ "id": 17, "title": "Ever17 -the out of infinity-", "original": null }],
+            "producers": [
+                { "id": 24, "developer": true, "publisher": true, "name": "KID", "original": "キッド", "type": "co" },
+            ],
+        }),
+    ]
+}
+
+///Returns producer entities.
+pub fn producer() -> Vec<Value> {
+    vec![
+        json!({
+            "id": 24,
+            "name": "KID",
+            "original": "キッド",
+            "type": "co",
+            "language": "ja",
+            "relations": [
+                { "id": 1164, "relation": "new", "name": "5pb.", "original": null },
+            ],
+        }),
+    ]
+}
+
+///Returns character entities.
+pub fn character() -> Vec<Value> {
+    vec![
+        json!({
+            "id": 9,
+            "name": "Tsugumi Komachi",
+            "original": "小町 つぐみ",
+            "gender": "f",
+            "description": "Mysterious girl.[spoiler] Infected by Cure.[/spoiler]",
+            "traits": [[35, 0], [1046, 2]],
+            "vns": [[17, 0, 0, "primary"]],
+        }),
+    ]
+}
+
+fn entities(kind: &str) -> Vec<Value> {
+    match kind {
+        "vn" => vn(),
+        "release" => release(),
+        "producer" => producer(),
+        "character" => character(),
+        _ => Vec::new(),
+    }
+}
+
+//Checks `id` conditions, ignoring any other field.
+fn matches_id(expr: &Expr, id: u64) -> bool {
+    match expr {
+        Expr::And(exprs) => exprs.iter().all(|expr| matches_id(expr, id)),
+        Expr::Or(exprs) => exprs.iter().any(|expr| matches_id(expr, id)),
+        Expr::Condition(condition) if condition.field == "id" => match (condition.op, &condition.value) {
+            (Operator::Eq, Value::Array(ids)) => ids.iter().any(|value| value.as_u64() == Some(id)),
+            (Operator::NotEq, Value::Array(ids)) => ids.iter().all(|value| value.as_u64() != Some(id)),
+            (op, value) => match value.as_u64() {
+                Some(value) => match op {
+                    Operator::Eq => id == value,
+                    Operator::NotEq => id != value,
+                    Operator::Greater => id > value,
+                    Operator::GreaterEq => id >= value,
+                    Operator::Less => id < value,
+                    Operator::LessEq => id <= value,
+                    Operator::Like => false,
+                },
+                None => false,
+            },
+        },
+        Expr::Condition(_) => true,
+    }
+}
+
+///Creates reply to request using fixtures.
+pub fn reply(request: &str) -> Reply {
+    let request = match Request::from_str(request) {
+        Ok(request) => request,
+        Err(error) => return Reply::error("parse", &error.to_string()),
+    };
+
+    let get = match request {
+        Request::Login(login) if login.create_session => return Reply::Response(Response::Session(SESSION_TOKEN.to_owned())),
+        Request::Login(_) => return Reply::Response(Response::Ok),
+        Request::DBstats => return Reply::Response(Response::DBstats(dbstats())),
+        Request::Set(_) => return Reply::Response(Response::Ok),
+        Request::Get(get) => get,
+    };
+
+    let expr = match get.filters.expr() {
+        Ok(expr) => expr,
+        Err(error) => return Reply::error("filter", &error.to_string()),
+    };
+
+    let (page, per_page) = match get.options {
+        Some(ref options) => (options.page.unwrap_or(1).max(1) as usize, options.results.unwrap_or(10).clamp(1, 25) as usize),
+        None => (1, 10),
+    };
+
+    let items = entities(get.kind.as_str()).into_iter()
+                                           .filter(|item| item["id"].as_u64().map(|id| matches_id(&expr, id)).unwrap_or(false))
+                                           .collect::<Vec<_>>();
+    let more = items.len() > page * per_page;
+    let items = items.into_iter().skip((page - 1) * per_page).take(per_page).collect::<Vec<_>>();
+
+    Reply::Response(Response::Results(Results::new(json!({
+        "num": items.len(),
+        "more": more,
+        "items": items,
+    }))))
+}
@@ -1,8 +1,10 @@
+#[cfg(feature = "tokio-on")]
 pub(crate) trait AsPin {
     ///Gets `Pin` out of self.
     fn as_pin(&mut self) -> core::pin::Pin<&'_ mut Self>;
 }
 
+#[cfg(feature = "tokio-on")]
 impl<T> AsPin for T {
     #[inline(always)]
     fn as_pin(&mut self) -> core::pin::Pin<&'_ mut Self> {
@@ -12,6 +14,11 @@ impl<T> AsPin for T {
     }
 }
 
+///Escapes text for use in XML element or attribute.
+pub(crate) fn xml_escape(value: &str) -> String {
+    value.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
 //pub fn serde_from_str<'de, T: core::str::FromStr, D: serde::de::Deserializer<'de>>(deserializer: D) -> Result<T, D::Error> where T::Err: core::fmt::Display {
 //    use serde::Deserialize;
 //
@@ -0,0 +1,428 @@
+//!Watcher of changes in user's list or VNs.
+//!
+//!Watcher periodically fetches snapshot of its target and compares it with previous one,
+//!delivering typed [events](enum.Event.html) to a [sink](trait.Sink.html), such as callback or channel.
+//!Snapshot is stored on disk after each poll, so that changes, made while watcher is not running,
+//!are reported after restart.
+//!As snapshot is stored only once changes are delivered, each change is delivered at least once.
+//!First poll without stored snapshot only records it, without reporting any events.
+//!
+//!Example of usage:
+//!
+//!```no_run
+//!use std::time::Duration;
+//!
+//!use vndb::protocol::message::request::Login;
+//!use vndb::watch::{Event, Target, Watcher};
+//!
+//!let connect = || {
+//!    let mut client = vndb::client::simple::Client::connect()?;
+//!    client.login(&Login::default())?;
+//!    Ok(client)
+//!};
+//!let mut watcher = Watcher::new(Target::UserList(2), "ulist-2.json").expect("To load snapshot");
+//!
+//!let (mut sender, receiver) = std::sync::mpsc::channel::<Event>();
+//!std::thread::spawn(move || for event in receiver {
+//!    println!("{:?}", event);
+//!});
+//!
+//!let error = watcher.run(connect, Duration::from_secs(3600), &mut sender).unwrap_err();
+//!eprintln!("Watcher stopped: {}", error);
+//!```
+
+use core::fmt;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::client::simple::Client;
+use crate::export::{self, Status};
+use crate::protocol::message::request::get;
+use crate::protocol::message::response::results::{self, VnRelationKind};
+
+#[derive(Debug)]
+///Watcher error.
+pub enum Error {
+    ///Client error.
+    Client(crate::Error),
+    ///Unable to read or write snapshot.
+    Io(io::Error),
+    ///Invalid results or snapshot.
+    Json(serde_json::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Client(error) => write!(fmt, "Client error: {}", error),
+            Error::Io(error) => write!(fmt, "Snapshot I/O error: {}", error),
+            Error::Json(error) => write!(fmt, "Invalid data: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Client(error) => Some(error),
+            Error::Io(error) => Some(error),
+            Error::Json(error) => Some(error),
+        }
+    }
+}
+
+impl From<crate::Error> for Error {
+    #[inline]
+    fn from(error: crate::Error) -> Self {
+        Error::Client(error)
+    }
+}
+
+impl From<io::Error> for Error {
+    #[inline]
+    fn from(error: io::Error) -> Self {
+        Error::Io(error)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    #[inline]
+    fn from(error: serde_json::Error) -> Self {
+        Error::Json(error)
+    }
+}
+
+impl From<export::Error> for Error {
+    #[inline]
+    fn from(error: export::Error) -> Self {
+        match error {
+            export::Error::Client(error) => Error::Client(error),
+            export::Error::Json(error) => Error::Json(error),
+        }
+    }
+}
+
+///Watcher result.
+pub type Result<T> = core::result::Result<T, Error>;
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+///What is watched.
+pub enum Target {
+    ///User's list, by user's ID.
+    UserList(u64),
+    ///VNs, by their IDs.
+    Vns(Vec<u64>),
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+///State of watched VN.
+pub struct Item {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    ///User's vote in range from 10 to 100.
+    pub vote: Option<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    ///Status in user's list.
+    pub status: Option<Status>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    ///Date of the first release.
+    pub released: Option<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    ///Related VNs with kind of relation.
+    pub relations: BTreeMap<u64, VnRelationKind>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+///Snapshot of watched target.
+pub struct Snapshot {
+    ///Watched target.
+    pub target: Target,
+    ///Unix timestamp of when snapshot is taken.
+    pub taken: u64,
+    ///Watched VNs by their IDs.
+    pub items: BTreeMap<u64, Item>,
+}
+
+impl Snapshot {
+    ///Reads snapshot from file, returning `None` if there is no file.
+    pub fn load(path: &Path) -> Result<Option<Self>> {
+        match fs::read(path) {
+            Ok(data) => Ok(Some(serde_json::from_slice(&data)?)),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    ///Writes snapshot to file, replacing previous one atomically.
+    pub fn store(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            fs::create_dir_all(dir)?;
+        }
+
+        let tmp = path.with_extension("tmp");
+        fs::write(&tmp, serde_json::to_vec_pretty(self)?)?;
+        fs::rename(tmp, path)?;
+        Ok(())
+    }
+
+    ///Returns changes from previous snapshot to this one, ordered by VN's ID.
+    pub fn diff(&self, previous: &Snapshot) -> Vec<Event> {
+        let mut events = Vec::new();
+
+        for vn in previous.items.keys() {
+            if !self.items.contains_key(vn) {
+                events.push(Event::Removed { vn: *vn });
+            }
+        }
+
+        for (vn, after) in self.items.iter() {
+            let vn = *vn;
+            let before = match previous.items.get(&vn) {
+                Some(before) => before,
+                None => {
+                    events.push(Event::Added { vn });
+                    continue;
+                },
+            };
+
+            if before.status != after.status {
+                events.push(Event::StatusChanged { vn, before: before.status, after: after.status });
+            }
+            if before.vote != after.vote {
+                events.push(Event::VoteChanged { vn, before: before.vote, after: after.vote });
+            }
+            if before.released != after.released {
+                events.push(Event::ReleaseDateChanged { vn, before: before.released.clone(), after: after.released.clone() });
+            }
+            for (related, relation) in after.relations.iter() {
+                if before.relations.get(related) != Some(relation) {
+                    events.push(Event::RelationAdded { vn, related: *related, relation: relation.clone() });
+                }
+            }
+        }
+
+        events.sort_by_key(|event| event.vn());
+        events
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+///Change between snapshots.
+pub enum Event {
+    ///VN is added to the list or became available.
+    Added {
+        ///VN's ID.
+        vn: u64,
+    },
+    ///VN is removed from the list or is no longer available.
+    Removed {
+        ///VN's ID.
+        vn: u64,
+    },
+    ///User's vote is changed.
+    VoteChanged {
+        ///VN's ID.
+        vn: u64,
+        ///Previous vote.
+        before: Option<u8>,
+        ///New vote.
+        after: Option<u8>,
+    },
+    ///Status in user's list is changed.
+    StatusChanged {
+        ///VN's ID.
+        vn: u64,
+        ///Previous status.
+        before: Option<Status>,
+        ///New status.
+        after: Option<Status>,
+    },
+    ///Date of VN's first release is changed.
+    ReleaseDateChanged {
+        ///VN's ID.
+        vn: u64,
+        ///Previous date.
+        before: Option<String>,
+        ///New date.
+        after: Option<String>,
+    },
+    ///VN has new relation.
+    RelationAdded {
+        ///VN's ID.
+        vn: u64,
+        ///Related VN's ID.
+        related: u64,
+        ///What related VN is to the VN.
+        relation: VnRelationKind,
+    },
+}
+
+impl Event {
+    ///Returns ID of changed VN.
+    pub fn vn(&self) -> u64 {
+        match self {
+            Event::Added { vn } | Event::Removed { vn } => *vn,
+            Event::VoteChanged { vn, .. } | Event::StatusChanged { vn, .. } => *vn,
+            Event::ReleaseDateChanged { vn, .. } | Event::RelationAdded { vn, .. } => *vn,
+        }
+    }
+}
+
+///Receiver of events.
+pub trait Sink {
+    ///Delivers event.
+    fn send(&mut self, event: Event);
+}
+
+impl<F: FnMut(Event)> Sink for F {
+    #[inline]
+    fn send(&mut self, event: Event) {
+        self(event)
+    }
+}
+
+impl Sink for mpsc::Sender<Event> {
+    #[inline]
+    fn send(&mut self, event: Event) {
+        //Receiver is gone, so there is nobody to deliver to.
+        let _ = mpsc::Sender::send(self, event);
+    }
+}
+
+///Watcher of target's changes.
+pub struct Watcher {
+    target: Target,
+    path: PathBuf,
+    snapshot: Option<Snapshot>,
+}
+
+impl Watcher {
+    ///Creates new instance, loading previous snapshot from file.
+    ///
+    ///IDs of watched VNs are sorted and deduplicated.
+    ///Snapshot of another target is ignored and is overwritten on the next poll.
+    pub fn new<P: Into<PathBuf>>(target: Target, path: P) -> Result<Self> {
+        let target = match target {
+            Target::Vns(mut ids) => {
+                ids.sort_unstable();
+                ids.dedup();
+                Target::Vns(ids)
+            },
+            target => target,
+        };
+        let path = path.into();
+        let snapshot = Snapshot::load(&path)?.filter(|snapshot| snapshot.target == target);
+
+        Ok(Self {
+            target,
+            path,
+            snapshot,
+        })
+    }
+
+    #[inline]
+    ///Returns last snapshot, if any.
+    pub fn snapshot(&self) -> Option<&Snapshot> {
+        self.snapshot.as_ref()
+    }
+
+    ///Fetches new snapshot, delivering changes to sink and storing snapshot.
+    ///
+    ///Snapshot is stored after changes are delivered, so if storing fails, they are delivered again on the next poll.
+    ///
+    ///Returns number of delivered events.
+    pub fn poll<IO: Read + Write, S: Sink + ?Sized>(&mut self, client: &mut Client<IO>, sink: &mut S) -> Result<usize> {
+        let snapshot = self.fetch(client)?;
+
+        let events = match self.snapshot.as_ref() {
+            Some(previous) => snapshot.diff(previous),
+            None => Vec::new(),
+        };
+
+        let len = events.len();
+        for event in events {
+            sink.send(event);
+        }
+
+        snapshot.store(&self.path)?;
+        self.snapshot = Some(snapshot);
+
+        Ok(len)
+    }
+
+    ///Polls with specified interval, until snapshot cannot be stored or results are invalid.
+    ///
+    ///Client is created by `connect`, which should also log in, and is created again after connection error.
+    ///Other client errors are retried on the next poll, while throttled poll is retried after time suggested by VNDB.
+    pub fn run<IO, C, S>(&mut self, mut connect: C, interval: Duration, sink: &mut S) -> Result<()> where IO: Read + Write, C: FnMut() -> crate::Result<Client<IO>>, S: Sink + ?Sized {
+        let mut client = None;
+
+        loop {
+            let result = match client.as_mut() {
+                Some(client) => self.poll(client, sink),
+                None => match connect() {
+                    Ok(connected) => self.poll(client.get_or_insert(connected), sink),
+                    Err(error) => Err(error.into()),
+                },
+            };
+
+            let wait = match result {
+                Ok(_) => interval,
+                Err(Error::Client(error)) => {
+                    //Connection is left in unknown state, unless VNDB responded with error.
+                    if !matches!(error, crate::Error::Server(_)) {
+                        client = None;
+                    }
+                    crate::client::throttle_wait(&error).unwrap_or(interval)
+                },
+                Err(error) => return Err(error),
+            };
+            std::thread::sleep(wait);
+        }
+    }
+
+    fn fetch<IO: Read + Write>(&self, client: &mut Client<IO>) -> Result<Snapshot> {
+        let mut items = BTreeMap::new();
+
+        match self.target {
+            Target::UserList(uid) => {
+                let list: Vec<results::UList> = export::fetch_all(|get| client.get(get), get::Type::ulist(), get::Flags::new().basic().labels(), uid)?;
+                for item in list {
+                    let vn = match item.vn {
+                        Some(vn) => vn,
+                        None => continue,
+                    };
+
+                    items.insert(vn, Item {
+                        vote: item.vote,
+                        status: Some(Status::from_labels(&item.labels)),
+                        ..Default::default()
+                    });
+                }
+            },
+            Target::Vns(ref ids) => {
+                let vns = client.get_many_vn(ids, get::Flags::new().basic().relations())?;
+                for vn in vns.items {
+                    items.insert(vn.id, Item {
+                        released: vn.released,
+                        relations: vn.relations.into_iter().map(|relation| (relation.id, relation.relation)).collect(),
+                        ..Default::default()
+                    });
+                }
+            },
+        }
+
+        Ok(Snapshot {
+            target: self.target.clone(),
+            taken: SystemTime::now().duration_since(UNIX_EPOCH).map(|time| time.as_secs()).unwrap_or(0),
+            items,
+        })
+    }
+}
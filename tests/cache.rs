@@ -0,0 +1,188 @@
+#![cfg(feature = "testing")]
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde_json::json;
+
+use vndb::client::cache::{Backend, Cache, Cached, Directory, Entry, Key, Memory};
+use vndb::protocol::message::Response;
+use vndb::protocol::message::request::{get, Get, Login};
+use vndb::protocol::message::response::Results;
+use vndb::testing::{Matcher, MockServer, Reply};
+
+type Client = vndb::client::simple::Client<std::net::TcpStream>;
+
+fn get_vn(filters: &str) -> Get<'static> {
+    Get {
+        kind: get::Type::vn(),
+        flags: get::Flags::new().basic().details(),
+        filters: get::Filters::parse(filters).expect("To parse filters"),
+        options: None,
+    }
+}
+
+fn results(id: u64) -> Results {
+    Results::new(json!({"num": 1, "more": false, "items": [{"id": id}]}))
+}
+
+fn login(server: &MockServer, username: &str) -> Client {
+    let mut client = Client::connect_to(server.addr()).expect("To connect");
+    client.send(&Login::new(Some((username, "password"))).into()).expect("To send login");
+    client.flush().expect("To flush");
+    match client.receive().expect("To receive").expect("To get response") {
+        Response::Ok => client,
+        response => panic!("Unexpected response={:?}", response),
+    }
+}
+
+fn get_requests(server: &MockServer) -> usize {
+    server.requests().iter().filter(|request| request.starts_with("get ")).count()
+}
+
+#[test]
+fn cache_key_should_be_normalized() {
+    let key = Key::new(&get_vn("(id = 17 and (title ~ \"ever\" and id = 17))"));
+    assert_eq!(key.as_str(), "get vn basic,details (id = 17 and title ~ \"ever\") {\"page\":1,\"results\":10,\"reverse\":false}");
+
+    let mut get = get_vn("(title ~ \"ever\" and id = 17)");
+    get.flags = get::Flags::new().details().basic();
+    get.options = Some(get::Options {
+        page: Some(1),
+        results: Some(10),
+        sort: None,
+        reverse: Some(false),
+    });
+    assert_eq!(Key::new(&get), key);
+
+    get.options.as_mut().unwrap().page = Some(2);
+    assert_ne!(Key::new(&get), key);
+    assert_ne!(Key::new(&get_vn("(id = 17 or title ~ \"ever\")")), key);
+}
+
+#[test]
+fn memory_backend_should_evict_least_recently_used() {
+    let memory = Memory::new(2);
+    let entry = |id| Entry {
+        results: results(id),
+        expires_at: std::time::SystemTime::now() + Duration::from_secs(60),
+    };
+    let (first, second, third) = (Key::new(&get_vn("(id = 1)")), Key::new(&get_vn("(id = 2)")), Key::new(&get_vn("(id = 3)")));
+
+    memory.store(&first, &entry(1)).expect("To store");
+    memory.store(&second, &entry(2)).expect("To store");
+    assert!(memory.load(&first).expect("To load").is_some());
+    memory.store(&third, &entry(3)).expect("To store");
+
+    assert_eq!(memory.len(), 2);
+    assert!(memory.load(&second).expect("To load").is_none());
+    assert_eq!(memory.load(&first).expect("To load").expect("To find entry").results["items"][0]["id"], 1);
+    assert_eq!(memory.load(&third).expect("To load").expect("To find entry").results["items"][0]["id"], 3);
+}
+
+#[test]
+fn directory_backend_should_persist_entries() {
+    let dir = std::env::temp_dir().join(format!("vndb-cache-{}", std::process::id()));
+    let key = Key::new(&get_vn("(id = 17)"));
+
+    {
+        let cache = Cache::with_backend(Directory::new(&dir).expect("To create directory"), Duration::from_secs(60));
+        cache.insert(&key, results(17));
+    }
+
+    let cache = Cache::with_backend(Directory::new(&dir).expect("To open directory"), Duration::from_secs(60));
+    assert_eq!(cache.get(&key).expect("To find results")["items"][0]["id"], 17);
+    assert!(cache.get(&Key::new(&get_vn("(id = 18)"))).is_none());
+
+    cache.invalidate(&key).expect("To invalidate");
+    assert!(cache.get(&key).is_none());
+
+    let _ = std::fs::remove_dir_all(dir);
+}
+
+#[test]
+fn cached_client_should_reuse_results_until_expired() {
+    let server = MockServer::builder().fixtures().start().expect("To start server");
+    let mut client = Cached::new(Client::connect_to(server.addr()).expect("To connect"), Arc::new(Cache::new(16, Duration::from_secs(60))));
+
+    let results = client.get(&get_vn("(id = 17)")).expect("To get results");
+    assert_eq!(results.vn().expect("To parse").items[0].id, 17);
+    let results = client.get(&get_vn("( id = 17 )")).expect("To get results");
+    assert_eq!(results.vn().expect("To parse").items[0].id, 17);
+    assert_eq!(get_requests(&server), 1);
+
+    client.get(&get_vn("(id = 18)")).expect("To get results");
+    assert_eq!(get_requests(&server), 2);
+
+    let mut client = Cached::new(client.into_inner(), Arc::new(Cache::new(16, Duration::ZERO)));
+    client.get(&get_vn("(id = 17)")).expect("To get results");
+    client.get(&get_vn("(id = 17)")).expect("To get results");
+    assert_eq!(get_requests(&server), 4);
+}
+
+#[test]
+fn cached_client_should_not_cache_errors() {
+    let server = MockServer::builder().fixtures()
+                                      .expect(Matcher::Command("get".to_owned()), Reply::Throttled)
+                                      .start()
+                                      .expect("To start server");
+    let mut client = Cached::new(Client::connect_to(server.addr()).expect("To connect"), Arc::new(Cache::new(16, Duration::from_secs(60))));
+
+    match client.get(&get_vn("(id = 17)")) {
+        Err(vndb::Error::Server(error)) => assert_eq!(error.id, "throttled"),
+        result => panic!("Unexpected result={:?}", result),
+    }
+    client.get(&get_vn("(id = 17)")).expect("To get results");
+    client.get(&get_vn("(id = 17)")).expect("To get results");
+    assert_eq!(get_requests(&server), 2);
+}
+
+#[test]
+fn cached_clients_should_not_share_user_lists() {
+    let ulist = |vn: u64| Reply::Response(Response::Results(Results::new(json!({"num": 1, "more": false, "items": [{"uid": 0, "vn": vn, "labels": []}]}))));
+    let server = MockServer::builder().fixtures()
+                                      .expect(Matcher::Command("get".to_owned()), ulist(17))
+                                      .expect(Matcher::Command("get".to_owned()), ulist(18))
+                                      .start()
+                                      .expect("To start server");
+    let cache = Arc::new(Cache::new(16, Duration::from_secs(60)));
+    let get = Get {
+        kind: get::Type::ulist(),
+        flags: get::Flags::new().basic(),
+        filters: get::Filters::parse("(uid = 0)").expect("To parse filters"),
+        options: None,
+    };
+
+    let mut first = Cached::new(login(&server, "first"), cache.clone());
+    let mut second = Cached::new(login(&server, "second"), cache.clone());
+
+    assert_eq!(first.get(&get).expect("To get results")["items"][0]["vn"], 17);
+    assert_eq!(second.get(&get).expect("To get results")["items"][0]["vn"], 18);
+    assert_eq!(get_requests(&server), 2);
+    assert!(cache.get(&Key::new(&get)).is_none());
+}
+
+#[test]
+fn cached_clients_should_coalesce_concurrent_requests() {
+    let delayed = Reply::Delay(Duration::from_millis(300), Box::new(Reply::Response(Response::Results(results(17)))));
+    let server = MockServer::builder().fixtures()
+                                      .expect(Matcher::Command("get".to_owned()), delayed)
+                                      .start()
+                                      .expect("To start server");
+    let cache = Arc::new(Cache::new(16, Duration::from_secs(60)));
+
+    let threads = (0..3).map(|idx| {
+        let mut client = Cached::new(Client::connect_to(server.addr()).expect("To connect"), cache.clone());
+        std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(idx * 50));
+            client.get(&get_vn("(id = 17)")).expect("To get results")
+        })
+    }).collect::<Vec<_>>();
+
+    for thread in threads {
+        let results = thread.join().expect("To join thread");
+        assert_eq!(results["items"][0]["id"], 17);
+    }
+    assert_eq!(get_requests(&server), 1);
+    assert_eq!(server.pending_expectations(), 0);
+}
@@ -0,0 +1,80 @@
+#![cfg(feature = "testing")]
+
+use serde_json::{json, Value};
+
+use vndb::catalogue::Catalogue;
+use vndb::protocol::message::response::results::ProducerRelationKind;
+use vndb::testing::{Matcher, MockServer, Reply};
+
+fn producer(id: u64, relations: &[(u64, &str)]) -> Value {
+    let relations = relations.iter().map(|(id, relation)| json!({"id": id, "relation": relation, "name": format!("P{}", id), "original": null})).collect::<Vec<_>>();
+    json!({"id": id, "name": format!("P{}", id), "original": null, "relations": relations})
+}
+
+fn release(id: u64, released: Option<&str>, vn: u64, producers: &[(u64, bool, bool)]) -> Value {
+    let producers = producers.iter().map(|(id, developer, publisher)| json!({"id": id, "developer": developer, "publisher": publisher, "name": format!("P{}", id), "original": null, "type": "co"})).collect::<Vec<_>>();
+    json!({"id": id, "title": format!("R{}", id), "original": null, "released": released, "type": "complete", "vn": [{"id": vn, "title": format!("V{}", vn), "original": null}], "producers": producers})
+}
+
+#[test]
+fn catalogue_should_group_releases_by_vn() {
+    let server = MockServer::builder().fixtures().start().expect("To start server");
+    let mut client = vndb::client::simple::Client::connect_to(server.addr()).expect("To connect");
+
+    let catalogue = Catalogue::fetch(&mut client, 24).expect("To fetch catalogue");
+    assert_eq!(catalogue.producers.len(), 1);
+    assert_eq!(catalogue.producers[0].name, "KID");
+    assert_eq!(catalogue.producers[0].relation, None);
+    assert_eq!(catalogue.titles.len(), 1);
+
+    let title = &catalogue.titles[0];
+    assert_eq!((title.id, title.title.as_str()), (17, "Ever17 -the out of infinity-"));
+    assert_eq!(title.developed.len(), 1);
+    assert_eq!(title.developed[0].released.as_deref(), Some("2003-03-28"));
+    assert_eq!(title.developed[0].producers, [24]);
+    assert_eq!(title.published, title.developed);
+
+    assert_eq!(server.requests(), [
+        "get producer basic (id = [24]) {\"page\":1,\"results\":25}",
+        "get release basic,vn,producers (producer = 24) {\"page\":1,\"results\":25}",
+    ]);
+}
+
+#[test]
+fn catalogue_should_follow_producer_relations() {
+    let server = MockServer::builder().fixtures()
+                                      .expect(Matcher::get_filtered("producer", "(id = [1])"), Reply::results(vec![producer(1, &[(2, "sub"), (3, "par"), (9, "imp")])], false))
+                                      .expect(Matcher::get_filtered("producer", "(id = [2,3])"), Reply::results(vec![producer(2, &[(1, "par"), (4, "sub")]), producer(3, &[(1, "sub"), (5, "sub"), (6, "par")])], false))
+                                      .expect(Matcher::get_filtered("producer", "(id = [4,6])"), Reply::results(vec![producer(4, &[(2, "par")])], false))
+                                      .expect(Matcher::get_filtered("release", "(producer = 1)"), Reply::results(vec![release(10, Some("2002-08-29"), 100, &[(1, true, false), (77, false, true)])], true))
+                                      .expect(Matcher::get_filtered("release", "(producer = 1)"), Reply::results(vec![release(11, Some("2001"), 100, &[(1, false, true), (2, true, false)])], false))
+                                      .expect(Matcher::get_filtered("release", "(producer = 2)"), Reply::results(vec![release(11, Some("2001"), 100, &[(1, false, true), (2, true, false)])], false))
+                                      .expect(Matcher::get_filtered("release", "(producer = 3)"), Reply::results(json!([]), false))
+                                      .expect(Matcher::get_filtered("release", "(producer = 4)"), Reply::results(vec![release(12, None, 101, &[(4, true, true)]), release(13, Some("2010-01-01"), 101, &[(4, true, false)])], false))
+                                      .start()
+                                      .expect("To start server");
+    let mut client = vndb::client::simple::Client::connect_to(server.addr()).expect("To connect");
+
+    let catalogue = Catalogue::fetch_related(&mut client, 1, &[ProducerRelationKind::Subsidiary, ProducerRelationKind::Parent]).expect("To fetch catalogue");
+    assert_eq!(server.pending_expectations(), 0);
+
+    let producers = catalogue.producers.iter().map(|producer| (producer.id, producer.relation.clone())).collect::<Vec<_>>();
+    assert_eq!(producers, [
+        (1, None),
+        (2, Some(ProducerRelationKind::Subsidiary)),
+        (3, Some(ProducerRelationKind::Parent)),
+        (4, Some(ProducerRelationKind::Subsidiary)),
+    ]);
+
+    let roles = |releases: &[vndb::catalogue::Release]| releases.iter().map(|release| (release.id, release.producers.clone())).collect::<Vec<_>>();
+    assert_eq!(catalogue.titles.iter().map(|title| title.id).collect::<Vec<_>>(), [100, 101]);
+    assert_eq!(roles(&catalogue.titles[0].developed), [(11, vec![2]), (10, vec![1])]);
+    assert_eq!(roles(&catalogue.titles[0].published), [(11, vec![1])]);
+    assert_eq!(roles(&catalogue.titles[1].developed), [(13, vec![4]), (12, vec![4])]);
+    assert_eq!(catalogue.developed().count(), 2);
+    assert_eq!(catalogue.published().map(|title| title.id).collect::<Vec<_>>(), [100, 101]);
+
+    let json = serde_json::to_value(&catalogue).expect("To serialize");
+    assert_eq!(json["producers"][1]["relation"], "sub");
+    assert_eq!(json["titles"][1]["published"][0]["released"], Value::Null);
+}
@@ -0,0 +1,291 @@
+#![cfg(all(feature = "cli", feature = "testing"))]
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use serde_json::{json, Value};
+
+use vndb::protocol::message::Response;
+use vndb::protocol::message::response::Results;
+use vndb::testing::{fixtures, Matcher, MockServer};
+
+fn config_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("vndb-cli-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+fn vndb(server: &MockServer, config_dir: &Path, args: &[&str]) -> (bool, String, String) {
+    let output = Command::new(env!("CARGO_BIN_EXE_vndb")).arg("--insecure")
+                                                         .arg("--addr")
+                                                         .arg(server.addr().to_string())
+                                                         .arg("--config-dir")
+                                                         .arg(config_dir)
+                                                         .args(args)
+                                                         .env_remove("VNDB_PASSWORD")
+                                                         .output()
+                                                         .expect("To run vndb");
+
+    (output.status.success(), String::from_utf8(output.stdout).expect("UTF-8 stdout"), String::from_utf8(output.stderr).expect("UTF-8 stderr"))
+}
+
+fn logins(server: &MockServer) -> Vec<String> {
+    server.requests().into_iter().filter(|request| request.starts_with("login ")).collect()
+}
+
+#[test]
+fn cli_should_print_dbstats() {
+    let server = MockServer::builder().fixtures().start().expect("To start server");
+    let (success, stdout, stderr) = vndb(&server, &config_dir("dbstats"), &["dbstats"]);
+    assert!(success, "stderr={}", stderr);
+
+    let stats: Value = serde_json::from_str(&stdout).expect("To parse JSON");
+    assert_eq!(stats["vn"], fixtures::dbstats().vn);
+    assert_eq!(logins(&server).len(), 1);
+    assert!(!logins(&server)[0].contains("sessiontoken"));
+}
+
+#[test]
+fn cli_should_get_entities_as_table() {
+    let server = MockServer::builder().fixtures().start().expect("To start server");
+    let (success, stdout, stderr) = vndb(&server, &config_dir("table"), &["get", "vn", "--flags", "basic,details", "--filter", "id = 17", "-o", "table"]);
+    assert!(success, "stderr={}", stderr);
+
+    let mut lines = stdout.lines();
+    let header = lines.next().expect("To have header");
+    assert!(header.starts_with("id  "), "header={}", header);
+    assert!(header.contains("title"));
+    assert!(lines.next().expect("To have separator").starts_with("--  "));
+    let row = lines.next().expect("To have row");
+    assert!(row.starts_with("17  E17  "), "row={}", row);
+    assert!(row.contains("  Ever17 -the out of infinity-  "), "row={}", row);
+    assert!(lines.next().is_none());
+
+    let requests = server.requests();
+    assert!(requests.iter().any(|request| request.starts_with("get vn basic,details (id = 17) {\"page\":1,\"results\":10")), "requests={:?}", requests);
+}
+
+#[test]
+fn cli_should_get_all_pages_as_yaml() {
+    let server = MockServer::builder().fixtures().start().expect("To start server");
+    let (success, stdout, stderr) = vndb(&server, &config_dir("yaml"), &["get", "vn", "--results", "1", "--all", "-o", "yaml"]);
+    assert!(success, "stderr={}", stderr);
+
+    assert!(stdout.starts_with("items:\n  - aliases: \"E17\"\n"), "stdout={}", stdout);
+    assert!(stdout.contains("\n    id: 18\n"), "stdout={}", stdout);
+    assert!(stdout.contains("\n    title: \"Ever17 Premium Edition\"\n"), "stdout={}", stdout);
+    assert!(stdout.ends_with("\nmore: false\nnum: 2\n"), "stdout={}", stdout);
+    assert_eq!(server.requests().iter().filter(|request| request.starts_with("get vn basic (id >= 1)")).count(), 2);
+}
+
+#[test]
+fn cli_should_store_session_on_login() {
+    let server = MockServer::builder().fixtures().start().expect("To start server");
+    let dir = config_dir("login");
+    std::fs::create_dir_all(&dir).expect("To create config dir");
+    std::fs::write(dir.join("session.json"), "{}").expect("To write stale session");
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(dir.join("session.json"), std::fs::Permissions::from_mode(0o644)).expect("To set permissions");
+    }
+
+    let (success, _, stderr) = vndb(&server, &dir, &["login", "--username", "ayo", "--password", "secret"]);
+    assert!(success, "stderr={}", stderr);
+    let session: Value = serde_json::from_slice(&std::fs::read(dir.join("session.json")).expect("To store session")).expect("To parse session");
+    assert_eq!(session, json!({"username": "ayo", "token": fixtures::SESSION_TOKEN}));
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(dir.join("session.json")).expect("To read metadata").permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+    assert!(!dir.join("session.tmp").exists());
+    assert!(logins(&server)[0].contains("\"createsession\":true"));
+
+    let (success, _, stderr) = vndb(&server, &dir, &["dbstats"]);
+    assert!(success, "stderr={}", stderr);
+    let login = logins(&server).pop().expect("To login");
+    assert!(login.contains(&format!("\"username\":\"ayo\",\"sessiontoken\":\"{}\"", fixtures::SESSION_TOKEN)), "login={}", login);
+    assert!(!login.contains("password"));
+
+    let (success, stdout, stderr) = vndb(&server, &dir, &["logout"]);
+    assert!(success, "stderr={}", stderr);
+    assert_eq!(serde_json::from_str::<Value>(&stdout).expect("To parse JSON"), json!({"logged_out": true}));
+    assert!(!dir.join("session.json").exists());
+
+    let _ = std::fs::remove_dir_all(dir);
+}
+
+#[test]
+fn cli_should_export_user_list() {
+    let page = |page: u64, more: bool| Response::Results(Results::new(json!({
+        "num": 1,
+        "more": more,
+        "items": [{"uid": 2, "vn": page * 10, "added": 1, "lastmod": 2, "voted": 3, "vote": 80, "notes": null, "labels": []}],
+    })));
+    let server = MockServer::builder().fixtures()
+                                      .expect(Matcher::Command("get".to_owned()), page(1, true))
+                                      .expect(Matcher::Command("get".to_owned()), page(2, false))
+                                      .start()
+                                      .expect("To start server");
+
+    let (success, stdout, stderr) = vndb(&server, &config_dir("export"), &["list", "export", "--uid", "2"]);
+    assert!(success, "stderr={}", stderr);
+
+    let list: Value = serde_json::from_str(&stdout).expect("To parse JSON");
+    assert_eq!(list["num"], 2);
+    assert_eq!(list["items"][0]["vn"], 10);
+    assert_eq!(list["items"][1]["vn"], 20);
+    assert_eq!(list["items"][1]["lastmod"], 2);
+
+    let requests = server.requests();
+    assert!(requests.iter().any(|request| request.starts_with("get ulist basic (uid = 2) {\"page\":2,\"results\":25")), "requests={:?}", requests);
+}
+
+#[test]
+fn cli_should_not_send_credentials_without_tls() {
+    let server = MockServer::builder().fixtures().start().expect("To start server");
+    let dir = config_dir("tls");
+    let output = Command::new(env!("CARGO_BIN_EXE_vndb")).arg("--addr")
+                                                         .arg(format!("localhost:{}", server.addr().port()))
+                                                         .arg("--config-dir")
+                                                         .arg(&dir)
+                                                         .args(["login", "--username", "ayo", "--password", "secret"])
+                                                         .output()
+                                                         .expect("To run vndb");
+
+    assert!(!output.status.success());
+    assert!(!dir.join("session.json").exists());
+    assert!(logins(&server).is_empty());
+    assert!(server.requests().iter().all(|request| !request.contains("secret")));
+}
+
+#[test]
+fn cli_should_report_errors() {
+    let server = MockServer::builder().fixtures().start().expect("To start server");
+    let (success, stdout, stderr) = vndb(&server, &config_dir("error"), &["get", "novel"]);
+
+    assert!(!success);
+    assert!(stdout.is_empty());
+    assert_eq!(stderr, "error: Unknown type 'novel'\n");
+}
+
+#[test]
+fn cli_repl_should_send_raw_and_short_requests() {
+    let server = MockServer::builder().fixtures().start().expect("To start server");
+    let dir = config_dir("repl");
+    let mut child = Command::new(env!("CARGO_BIN_EXE_vndb")).arg("--insecure")
+                                                            .arg("--addr")
+                                                            .arg(server.addr().to_string())
+                                                            .arg("--config-dir")
+                                                            .arg(&dir)
+                                                            .arg("repl")
+                                                            .stdin(std::process::Stdio::piped())
+                                                            .stdout(std::process::Stdio::piped())
+                                                            .stderr(std::process::Stdio::piped())
+                                                            .spawn()
+                                                            .expect("To run vndb");
+
+    {
+        use std::io::Write;
+        let mut stdin = child.stdin.take().expect("To have stdin");
+        stdin.write_all(b":bytes\ndbstats\nrelease vn = 17\n:bytes\nget vn basic (id = 18)\nvn 404\nnovel 1\nget vn nothing (id = 1)\n:quit\n").expect("To write input");
+    }
+    let output = child.wait_with_output().expect("To wait vndb");
+    let stdout = String::from_utf8(output.stdout).expect("UTF-8 stdout");
+    assert!(output.status.success(), "stderr={}", String::from_utf8_lossy(&output.stderr));
+
+    assert!(stdout.contains("bytes: on\n"), "stdout={}", stdout);
+    assert!(stdout.contains(">> 8 bytes: dbstats\\x04\n"), "stdout={}", stdout);
+    assert!(stdout.contains(&format!("\"vn\": {}", fixtures::dbstats().vn)), "stdout={}", stdout);
+    assert!(stdout.contains(">> 28 bytes: get release basic (vn = 17)\\x04\n"), "stdout={}", stdout);
+    assert!(stdout.contains("bytes: off\n"), "stdout={}", stdout);
+    assert!(!stdout.contains("bytes: get vn basic (id = 18)"), "stdout={}", stdout);
+    assert!(stdout.contains("\"title\": \"Ever17 Premium Edition\""), "stdout={}", stdout);
+    assert!(stdout.contains("\"num\": 0"), "stdout={}", stdout);
+    assert!(stdout.contains("error: Unknown type 'novel'\n"), "stdout={}", stdout);
+    assert!(stdout.contains("error: Invalid get arguments: Unknown flag 'nothing'\n"), "stdout={}", stdout);
+
+    let requests = server.requests();
+    assert_eq!(&requests[1..], ["dbstats", "get release basic (vn = 17)", "get vn basic (id = 18)", "get vn basic (id = 404)"]);
+
+    let history = std::fs::read_to_string(dir.join("history")).expect("To save history");
+    assert!(history.contains("release vn = 17\n"), "history={}", history);
+    let _ = std::fs::remove_dir_all(dir);
+}
+
+#[test]
+fn cli_repl_should_not_save_login_in_history() {
+    let server = MockServer::builder().fixtures().start().expect("To start server");
+    let dir = config_dir("repl-login");
+    let mut child = Command::new(env!("CARGO_BIN_EXE_vndb")).arg("--insecure")
+                                                            .arg("--addr")
+                                                            .arg(server.addr().to_string())
+                                                            .arg("--config-dir")
+                                                            .arg(&dir)
+                                                            .arg("repl")
+                                                            .stdin(std::process::Stdio::piped())
+                                                            .stdout(std::process::Stdio::piped())
+                                                            .stderr(std::process::Stdio::piped())
+                                                            .spawn()
+                                                            .expect("To run vndb");
+
+    {
+        use std::io::Write;
+        let mut stdin = child.stdin.take().expect("To have stdin");
+        stdin.write_all(b"login {\"protocol\":1,\"client\":\"test\",\"clientver\":0.1,\"username\":\"ayo\",\"password\":\"secret\"}\n  login {\"sessiontoken\":\"token\"}\nvn 17\n:quit\n").expect("To write input");
+    }
+    let output = child.wait_with_output().expect("To wait vndb");
+    assert!(output.status.success(), "stderr={}", String::from_utf8_lossy(&output.stderr));
+
+    let history = std::fs::read_to_string(dir.join("history")).expect("To save history");
+    assert!(history.contains("vn 17\n"), "history={}", history);
+    assert!(!history.contains("login"), "history={}", history);
+    assert!(!history.contains("secret"), "history={}", history);
+    assert!(!history.contains("token"), "history={}", history);
+    let _ = std::fs::remove_dir_all(dir);
+}
+
+#[test]
+fn cli_should_export_user_list_as_csv() {
+    let ulist = Response::Results(Results::new(json!({
+        "num": 1,
+        "more": false,
+        "items": [{"uid": 2, "vn": 17, "added": 1, "lastmod": 2, "voted": 3, "vote": 80, "notes": null, "labels": [{"id": 2, "label": "Finished"}]}],
+    })));
+    let server = MockServer::builder().fixtures()
+                                      .expect(Matcher::Command("get".to_owned()), ulist)
+                                      .start()
+                                      .expect("To start server");
+
+    let (success, stdout, stderr) = vndb(&server, &config_dir("export-csv"), &["list", "export", "--uid", "2", "--to", "csv"]);
+    assert!(success, "stderr={}", stderr);
+    assert_eq!(stdout, "vn,title,status,vote,notes,labels,added,modified,voted,started,finished\n17,Ever17 -the out of infinity-,finished,8,,,1,2,3,,\n");
+}
+
+#[test]
+fn cli_should_import_user_list() {
+    let ulist = || Response::Results(Results::new(json!({"num": 0, "more": false, "items": []})));
+    let server = MockServer::builder().fixtures()
+                                      .expect(Matcher::Command("get".to_owned()), ulist())
+                                      .expect(Matcher::Command("get".to_owned()), ulist())
+                                      .start()
+                                      .expect("To start server");
+    let dir = config_dir("import");
+    std::fs::create_dir_all(&dir).expect("To create config dir");
+    let file = dir.join("list.csv");
+    std::fs::write(&file, "vn,status,vote\n17,finished,8\n").expect("To write list");
+    let file = file.to_str().expect("UTF-8 path");
+
+    let (success, stdout, stderr) = vndb(&server, &dir, &["list", "import", "--uid", "2", file]);
+    assert!(success, "stderr={}", stderr);
+    assert_eq!(stdout, "+ v17: status=\"finished\", vote=80\n1 to add, 0 to update, 0 unchanged, 0 unmatched\n");
+    assert!(server.requests().iter().all(|request| !request.starts_with("set ")));
+
+    let (success, stdout, stderr) = vndb(&server, &dir, &["list", "import", "--uid", "2", "--apply", file]);
+    assert!(success, "stderr={}", stderr);
+    assert!(stdout.ends_with("applied 1 changes\n"), "stdout={}", stdout);
+    let sets = server.requests().into_iter().filter(|request| request.starts_with("set ")).collect::<Vec<_>>();
+    assert_eq!(sets, ["set ulist 17 {\"labels\":[2],\"vote\":80}"]);
+}
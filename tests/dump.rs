@@ -0,0 +1,254 @@
+#![cfg(feature = "dump")]
+
+use std::fs;
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+
+use vndb::dump;
+use vndb::protocol::message::response::results;
+
+enum Compression {
+    Plain,
+    Gzip,
+    Zstd,
+}
+
+fn write_file(path: &Path, content: &str, compression: Compression) {
+    let content = match compression {
+        Compression::Plain => content.as_bytes().to_vec(),
+        Compression::Gzip => {
+            let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+            encoder.write_all(content.as_bytes()).expect("To compress");
+            encoder.finish().expect("To finish compression")
+        },
+        Compression::Zstd => zstd::stream::encode_all(content.as_bytes(), 0).expect("To compress"),
+    };
+
+    fs::write(path, content).expect("To write file");
+}
+
+fn write_table(dir: &Path, name: &str, columns: &[&str], rows: &[&[&str]], compression: Compression) {
+    write_file(&dir.join(format!("{}.header", name)), &format!("{}\n", columns.join("\t")), Compression::Plain);
+
+    let mut content = String::new();
+    for row in rows {
+        content.push_str(&row.join("\t"));
+        content.push('\n');
+    }
+    write_file(&dir.join(name), &content, compression);
+}
+
+struct TempDir(PathBuf);
+
+impl TempDir {
+    fn new(name: &str) -> Self {
+        let dir = std::env::temp_dir().join(format!("vndb-dump-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).expect("To create directory");
+        TempDir(dir)
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+fn database(dir: &Path) {
+    write_table(dir, "vn", &["id", "olang", "image", "l_wikidata", "c_votecount", "c_rating", "length", "alias", "l_renai", "description", "c_released", "c_languages", "c_platforms"], &[
+        &["v17", "ja", "cv35689", "1325216", "11215", "866", "4", "E17", "ever17", "Ever17 is a\\ntime loop story.", "20020829", "{en,ja}", "{ps2,win}"],
+        &["v18", "ja", "\\N", "\\N", "120", "750", "0", "", "", "", "20039999", "{ja}", "{win}"],
+    ], Compression::Plain);
+    write_table(dir, "vn_titles", &["id", "lang", "official", "title", "latin"], &[
+        &["v17", "ja", "t", "Ever17 -the out of infinity-", "\\N"],
+        &["v18", "ja", "t", "エバー17 PE", "Ever17 Premium Edition"],
+    ], Compression::Plain);
+    write_table(dir, "vn_relations", &["id", "vid", "relation", "official"], &[
+        &["v17", "v18", "fan", "t"],
+        &["v18", "v17", "orig", "t"],
+    ], Compression::Plain);
+    write_table(dir, "tags_vn", &["date", "tag", "vid", "uid", "vote", "spoiler", "ignore"], &[
+        &["2020-01-01", "g19", "v17", "u1", "3", "0", "f"],
+        &["2020-01-01", "g19", "v17", "u2", "2", "1", "f"],
+        &["2020-01-01", "g3", "v17", "u3", "2", "\\N", "t"],
+    ], Compression::Plain);
+    write_table(dir, "vn_staff", &["id", "aid", "role", "note"], &[
+        &["v17", "226", "scenario", "\\N"],
+    ], Compression::Plain);
+    write_table(dir, "staff_alias", &["id", "aid", "name", "latin"], &[
+        &["s226", "226", "打越鋼太郎", "Uchikoshi Kotaro"],
+    ], Compression::Plain);
+
+    write_table(dir, "releases", &["id", "olang", "released", "patch", "freeware", "doujin", "minage", "gtin", "catalog", "reso_x", "reso_y", "voiced", "ani_story", "ani_ero", "website", "notes"], &[
+        &["r29", "ja", "20030328", "f", "f", "f", "15", "0", "KIDCD-0005", "800", "600", "4", "1", "0", "", "\\N"],
+    ], Compression::Gzip);
+    write_table(dir, "releases_titles", &["id", "lang", "mtl", "title", "latin"], &[
+        &["r29", "ja", "f", "Ever17 -the out of infinity- Premium Edition", "\\N"],
+    ], Compression::Gzip);
+    write_table(dir, "releases_vn", &["id", "vid", "rtype"], &[
+        &["r29", "v17", "complete"],
+    ], Compression::Plain);
+    write_table(dir, "releases_producers", &["id", "pid", "developer", "publisher"], &[
+        &["r29", "p24", "t", "t"],
+    ], Compression::Plain);
+    write_table(dir, "releases_platforms", &["id", "platform"], &[
+        &["r29", "win"],
+    ], Compression::Plain);
+
+    write_table(dir, "producers", &["id", "type", "lang", "name", "latin", "alias", "website", "l_wikidata", "description"], &[
+        &["p24", "co", "ja", "キッド", "KID", "", "http://www.kid-game.co.jp/", "\\N", "Defunct."],
+        &["p1164", "co", "ja", "5pb.", "\\N", "", "", "\\N", ""],
+    ], Compression::Zstd);
+    write_table(dir, "producers_relations", &["id", "pid", "relation"], &[
+        &["p24", "p1164", "new"],
+    ], Compression::Plain);
+
+    write_table(dir, "chars", &["id", "image", "gender", "bloodt", "s_bust", "s_waist", "s_hip", "b_month", "b_day", "height", "weight", "age", "name", "latin", "alias", "description"], &[
+        &["c9", "ch1234", "f", "a", "0", "0", "0", "9", "2", "158", "0", "\\N", "小町 つぐみ", "Komachi Tsugumi", "", "Mysterious girl."],
+    ], Compression::Plain);
+    write_table(dir, "chars_traits", &["id", "tid", "spoil", "lie"], &[
+        &["c9", "i35", "0", "f"],
+        &["c9", "i1046", "2", "f"],
+    ], Compression::Plain);
+    write_table(dir, "chars_vns", &["id", "vid", "rid", "spoil", "role"], &[
+        &["c9", "v17", "\\N", "0", "primary"],
+    ], Compression::Plain);
+}
+
+#[test]
+fn should_read_database_dump() {
+    let dir = TempDir::new("db");
+    database(&dir.0);
+    let database = dump::Database::open(&dir.0).expect("To open database");
+
+    let vn = database.vn().expect("To read VN").collect::<Result<Vec<results::Vn>, _>>().expect("To parse VN");
+    assert_eq!(vn.len(), 2);
+    assert_eq!(vn[0].id, 17);
+    assert_eq!(vn[0].title.as_deref(), Some("Ever17 -the out of infinity-"));
+    assert_eq!(vn[0].original, None);
+    assert_eq!(vn[0].released.as_deref(), Some("2002-08-29"));
+    assert_eq!(vn[0].languages, ["en", "ja"]);
+    assert_eq!(vn[0].description.as_deref(), Some("Ever17 is a\ntime loop story."));
+    assert_eq!(vn[0].image.as_deref(), Some("https://s2.vndb.org/cv/89/35689.jpg"));
+    assert_eq!(vn[0].rating, Some(8.66));
+    assert_eq!(vn[0].relations[0].id, 18);
+    assert_eq!(vn[0].relations[0].title, "Ever17 Premium Edition");
+    assert_eq!(vn[0].tags.len(), 1);
+    assert_eq!(vn[0].tags[0].id, 19);
+    assert_eq!(vn[0].tags[0].score, 2.5);
+    assert_eq!(vn[0].tags[0].spoiler, results::SpoilerLevel::Minor);
+    assert_eq!(vn[0].staff[0].name, "Uchikoshi Kotaro");
+    assert_eq!(vn[0].staff[0].role, results::StaffRole::Scenario);
+    assert_eq!(vn[1].title.as_deref(), Some("Ever17 Premium Edition"));
+    assert_eq!(vn[1].original.as_deref(), Some("エバー17 PE"));
+    assert_eq!(vn[1].released.as_deref(), Some("2003"));
+    assert_eq!(vn[1].length, None);
+    assert_eq!(vn[1].aliases, None);
+
+    let releases = database.releases().expect("To read releases").collect::<Result<Vec<results::Release>, _>>().expect("To parse releases");
+    assert_eq!(releases.len(), 1);
+    assert_eq!(releases[0].released.as_deref(), Some("2003-03-28"));
+    assert_eq!(releases[0].kind.as_deref(), Some("complete"));
+    assert_eq!(releases[0].code, None);
+    assert_eq!(releases[0].resolution.as_deref(), Some("800x600"));
+    assert_eq!(releases[0].voiced, Some(results::ReleaseVoiced::Full));
+    assert_eq!(releases[0].vn[0].title, "Ever17 -the out of infinity-");
+    assert_eq!(releases[0].producers[0].name, "KID");
+    assert_eq!(releases[0].producers[0].original.as_deref(), Some("キッド"));
+    assert_eq!(releases[0].platforms, ["win"]);
+
+    let producers = database.producers().expect("To read producers").collect::<Result<Vec<results::Producer>, _>>().expect("To parse producers");
+    assert_eq!(producers.len(), 2);
+    assert_eq!(producers[0].name.as_deref(), Some("KID"));
+    assert_eq!(producers[0].relations[0].name, "5pb.");
+    assert_eq!(producers[0].relations[0].relation, results::ProducerRelationKind::SucceededBy);
+
+    let characters = database.characters().expect("To read characters").collect::<Result<Vec<results::Character>, _>>().expect("To parse characters");
+    assert_eq!(characters.len(), 1);
+    assert_eq!(characters[0].name.as_deref(), Some("Komachi Tsugumi"));
+    assert_eq!(characters[0].birthday, Some((2, 9)));
+    assert_eq!(characters[0].bust, None);
+    assert_eq!(characters[0].height, Some(158));
+    assert_eq!(characters[0].traits[1].id, 1046);
+    assert_eq!(characters[0].traits[1].spoiler, results::SpoilerLevel::Major);
+    assert_eq!(characters[0].vns[0].role, results::CharacterRole::Primary);
+}
+
+#[test]
+fn should_read_table_escapes() {
+    let table = dump::table::Table::new(vec!["id".to_owned(), "text".to_owned(), "list".to_owned()], "1\ta\\tb\\\\c\\101\t{a,\"b c\",\"d\\\\\"e\"}\n2\t\\N\t{}\n\\.\n3\tignored\t{}\n".as_bytes());
+    let rows = table.collect::<Result<Vec<_>, _>>().expect("To read rows");
+    assert_eq!(rows.len(), 2);
+    assert_eq!(rows[0].get("text"), Some("a\tb\\cA"));
+    assert_eq!(rows[0].get_array("list"), ["a", "b c", "d\"e"]);
+    assert_eq!(rows[1].get("text"), None);
+    assert!(rows[1].get_array("list").is_empty());
+    assert_eq!(rows[1].get("missing"), None);
+
+    let mut table = dump::table::Table::new(vec!["id".to_owned()], "1\t2\n".as_bytes());
+    assert!(table.next().expect("To have row").is_err());
+}
+
+#[test]
+fn should_decode_table_octal_escapes_as_utf8() {
+    //Octal escapes of UTF-8 encoded `é` and `時`
+    let table = dump::table::Table::new(vec!["id".to_owned(), "text".to_owned()], "1\tCaf\\303\\251 \\346\\231\\202\n2\t\\377\n".as_bytes());
+    let rows = table.collect::<Result<Vec<_>, _>>().expect("To read rows");
+    assert_eq!(rows[0].get("text"), Some("Café 時"));
+    assert_eq!(rows[1].get("text"), Some("\u{fffd}"));
+}
+
+#[test]
+fn should_reject_malformed_json_dump() {
+    for (malformed, kind) in [("[{\"id\":1}}]", ErrorKind::InvalidData), ("[1, }]", ErrorKind::InvalidData), ("[{\"id\":1}", ErrorKind::UnexpectedEof)].iter() {
+        let mut array = dump::JsonArray::<_, serde_json::Value>::new(malformed.as_bytes());
+        let error = loop {
+            match array.next() {
+                Some(Ok(_)) => continue,
+                Some(Err(error)) => break error,
+                None => panic!("{}: expected error", malformed),
+            }
+        };
+        assert_eq!(error.kind(), *kind, "{}", malformed);
+        assert!(array.next().is_none(), "{}", malformed);
+    }
+}
+
+#[test]
+fn should_read_tags_traits_and_votes_dumps() {
+    let dir = TempDir::new("json");
+
+    let tags = r#"[
+        {"id":19,"name":"Time Loop","description":"[url=/g3]Loop[/url]","meta":false,"searchable":true,"applicable":true,"vns":412,"cat":"cont","aliases":["Time Travel Loop"],"parents":[3]},
+        {"id":"g3","name":"Plot, \"Time\"","meta":true,"cat":"tech","aliases":[],"parents":["g1"]}
+    ]"#;
+    write_file(&dir.0.join("tags.json.gz"), tags, Compression::Gzip);
+    let tags = dump::tags(dir.0.join("tags.json.gz")).expect("To open tags").collect::<Result<Vec<_>, _>>().expect("To parse tags");
+    assert_eq!(tags.len(), 2);
+    assert_eq!(tags[0].id, 19);
+    assert_eq!(tags[0].cat, Some(results::TagCategory::Content));
+    assert_eq!(tags[0].parents, [3]);
+    assert_eq!(tags[1].id, 3);
+    assert_eq!(tags[1].name, "Plot, \"Time\"");
+    assert_eq!(tags[1].parents, [1]);
+
+    write_file(&dir.0.join("traits.json"), "[{\"id\":35,\"name\":\"Blue\",\"group_id\":1,\"group_name\":\"Hair\",\"chars\":1200,\"sexual\":false}]", Compression::Plain);
+    let traits = dump::traits(dir.0.join("traits.json")).expect("To open traits").collect::<Result<Vec<_>, _>>().expect("To parse traits");
+    assert_eq!(traits.len(), 1);
+    assert_eq!(traits[0].group_id, Some(1));
+    assert_eq!(traits[0].group_name.as_deref(), Some("Hair"));
+
+    write_file(&dir.0.join("empty.json"), " [ ] ", Compression::Plain);
+    assert_eq!(dump::traits(dir.0.join("empty.json")).expect("To open traits").count(), 0);
+
+    write_file(&dir.0.join("votes.zst"), "17 2 80 2008-06-12\nv18 u3 100 2010-01-01\n\n", Compression::Zstd);
+    let votes = dump::votes(dir.0.join("votes.zst")).expect("To open votes").collect::<Result<Vec<_>, _>>().expect("To parse votes");
+    assert_eq!(votes, [
+        dump::Vote { vn: 17, uid: 2, vote: 80, date: "2008-06-12".to_owned() },
+        dump::Vote { vn: 18, uid: 3, vote: 100, date: "2010-01-01".to_owned() },
+    ]);
+
+    write_file(&dir.0.join("invalid.txt"), "17 2 high 2008-06-12\n", Compression::Plain);
+    assert!(dump::votes(dir.0.join("invalid.txt")).expect("To open votes").next().expect("To have vote").is_err());
+}
@@ -0,0 +1,143 @@
+#![cfg(feature = "testing")]
+
+use serde_json::{json, Value};
+
+use vndb::export::{Entry, Format, Status, UserList};
+use vndb::testing::{Matcher, MockServer, Reply};
+
+fn user() -> Reply {
+    Reply::results(json!([{"id": 2, "username": "ayo"}]), false)
+}
+
+fn ulist_server() -> MockServer {
+    let first = json!([{
+        "uid": 2, "vn": 17, "added": 100, "lastmod": 200, "voted": 150, "vote": 85,
+        "notes": "Best, \"ever\"", "started": "2019-01-02", "finished": "2019-02-03",
+        "labels": [{"id": 2, "label": "Finished"}, {"id": 7, "label": "Voted"}, {"id": 10, "label": "Favourite"}],
+    }]);
+    let second = json!([{
+        "uid": 2, "vn": 404, "added": 300, "lastmod": 300, "voted": null, "vote": null, "notes": "",
+        "started": null, "finished": null, "labels": [{"id": 5, "label": "Wishlist"}],
+    }]);
+
+    MockServer::builder().fixtures()
+                         .expect(Matcher::get("ulist"), Reply::results(first, true))
+                         .expect(Matcher::get("ulist"), Reply::results(second, false))
+                         .expect(Matcher::get("user"), user())
+                         .start()
+                         .expect("To start server")
+}
+
+fn expected_ulist() -> UserList {
+    UserList {
+        uid: 2,
+        username: Some("ayo".to_owned()),
+        entries: vec![
+            Entry {
+                vn: 17,
+                title: Some("Ever17 -the out of infinity-".to_owned()),
+                status: Status::Finished,
+                vote: Some(85),
+                notes: Some("Best, \"ever\"".to_owned()),
+                labels: vec!["Favourite".to_owned()],
+                added: Some(100),
+                modified: Some(200),
+                voted: Some(150),
+                started: Some("2019-01-02".to_owned()),
+                finished: Some("2019-02-03".to_owned()),
+            },
+            Entry {
+                vn: 404,
+                title: None,
+                status: Status::Wishlist,
+                vote: None,
+                notes: None,
+                labels: vec![],
+                added: Some(300),
+                modified: Some(300),
+                voted: None,
+                started: None,
+                finished: None,
+            },
+        ],
+    }
+}
+
+#[test]
+fn export_should_join_ulist_with_titles() {
+    let server = ulist_server();
+    let mut client = vndb::client::simple::Client::connect_to(server.addr()).expect("To connect");
+
+    let list = UserList::fetch(&mut client, 2).expect("To fetch list");
+    assert_eq!(list, expected_ulist());
+    assert_eq!(server.pending_expectations(), 0);
+
+    let requests = server.requests();
+    assert_eq!(requests[0], "get ulist basic,labels (uid = 2) {\"page\":1,\"results\":25}");
+    assert_eq!(requests[1], "get ulist basic,labels (uid = 2) {\"page\":2,\"results\":25}");
+    assert!(requests[2].starts_with("get vn basic (id = [17,404])"), "request={}", requests[2]);
+}
+
+#[test]
+fn export_should_merge_legacy_lists() {
+    let server = MockServer::builder().fixtures()
+                                      .expect(Matcher::get("vnlist"), Reply::results(json!([{"uid": 2, "vn": 17, "status": 2, "added": 100, "notes": "Good"}]), false))
+                                      .expect(Matcher::get("votelist"), Reply::results(json!([{"uid": 2, "vn": 17, "vote": 70, "added": 110}, {"uid": 2, "vn": 18, "vote": 92, "added": 120}]), false))
+                                      .expect(Matcher::get("wishlist"), Reply::results(json!([{"uid": 2, "vn": 18, "priority": 0, "added": 90}, {"uid": 2, "vn": 404, "priority": 3, "added": 80}]), false))
+                                      .expect(Matcher::get("user"), user())
+                                      .start()
+                                      .expect("To start server");
+    let mut client = vndb::client::simple::Client::connect_to(server.addr()).expect("To connect");
+
+    let list = UserList::fetch_legacy(&mut client, 2).expect("To fetch list");
+    let summary = list.entries.iter().map(|entry| (entry.vn, entry.status, entry.vote, entry.added, entry.voted)).collect::<Vec<_>>();
+    assert_eq!(summary, [
+        (17, Status::Finished, Some(70), Some(100), Some(110)),
+        (18, Status::Wishlist, Some(92), Some(120), Some(120)),
+        (404, Status::Blacklist, None, Some(80), None),
+    ]);
+    assert_eq!(list.entries[0].notes.as_deref(), Some("Good"));
+    assert_eq!(list.entries[1].title.as_deref(), Some("Ever17 Premium Edition"));
+    assert_eq!(server.pending_expectations(), 0);
+}
+
+#[test]
+fn export_should_write_formats() {
+    let list = expected_ulist();
+
+    let mut json = Vec::new();
+    list.write(Format::Json, &mut json).expect("To write JSON");
+    let value: Value = serde_json::from_slice(&json).expect("To parse JSON");
+    assert_eq!(value["entries"][0]["vote"], 8.5);
+    assert_eq!(value["entries"][0]["status"], "finished");
+    assert_eq!(UserList::from_json(json.as_slice()).expect("To read JSON"), list);
+
+    let partial = UserList::from_json(r#"{"uid":2,"entries":[{"vn":17,"status":"playing"}]}"#.as_bytes()).expect("To read JSON without optional fields");
+    assert_eq!(partial.entries.len(), 1);
+    assert_eq!(partial.entries[0].status, Status::Playing);
+    assert_eq!(partial.entries[0].vote, None);
+
+    let mut csv = Vec::new();
+    list.write(Format::Csv, &mut csv).expect("To write CSV");
+    assert_eq!(String::from_utf8(csv).expect("UTF-8"), "\
+vn,title,status,vote,notes,labels,added,modified,voted,started,finished
+17,Ever17 -the out of infinity-,finished,8.5,\"Best, \"\"ever\"\"\",Favourite,100,200,150,2019-01-02,2019-02-03
+404,,wishlist,,,,300,300,,,
+");
+
+    let mut xml = Vec::new();
+    list.write(Format::Xml, &mut xml).expect("To write XML");
+    let xml = String::from_utf8(xml).expect("UTF-8");
+    assert!(xml.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\" ?>\n<myanimelist>\n\t<myinfo>\n\t\t<user_id>2</user_id>\n\t\t<user_name>ayo</user_name>\n"), "xml={}", xml);
+    assert!(xml.contains("\t\t<user_total_completed>1</user_total_completed>\n\t\t<user_total_onhold>0</user_total_onhold>\n"), "xml={}", xml);
+    assert!(xml.contains("\t\t<series_animedb_id>17</series_animedb_id>\n\t\t<series_title><![CDATA[Ever17 -the out of infinity-]]></series_title>\n"), "xml={}", xml);
+    assert!(xml.contains("\t\t<my_start_date>2019-01-02</my_start_date>\n\t\t<my_finish_date>2019-02-03</my_finish_date>\n\t\t<my_score>9</my_score>\n\t\t<my_status>Completed</my_status>\n"), "xml={}", xml);
+    assert!(xml.contains("\t\t<my_start_date>0000-00-00</my_start_date>\n\t\t<my_finish_date>0000-00-00</my_finish_date>\n\t\t<my_score>0</my_score>\n\t\t<my_status>Plan to Watch</my_status>\n"), "xml={}", xml);
+    assert!(xml.ends_with("\t</anime>\n</myanimelist>\n"));
+
+    let mut list = list;
+    list.username = Some("<\"a&b\">".to_owned());
+    let mut xml = Vec::new();
+    list.write(Format::Xml, &mut xml).expect("To write XML");
+    assert!(String::from_utf8(xml).expect("UTF-8").contains("\t\t<user_name>&lt;&quot;a&amp;b&quot;&gt;</user_name>\n"));
+}
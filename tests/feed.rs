@@ -0,0 +1,151 @@
+use serde_json::json;
+
+use vndb::feed::{Entry, Error, Feed, Format, Query};
+use vndb::protocol::message::response::results::{Release, ReleaseDate};
+
+fn entry(id: u64, title: &str, released: ReleaseDate) -> Entry {
+    Entry {
+        id,
+        title: title.to_owned(),
+        original: None,
+        released,
+        kind: None,
+        languages: vec!["en".to_owned()],
+        platforms: Vec::new(),
+        vns: Vec::new(),
+        producers: Vec::new(),
+    }
+}
+
+#[test]
+fn feed_should_parse_release_dates() {
+    assert_eq!(ReleaseDate::from_str("2003-03-28"), Some(ReleaseDate::Day(2003, 3, 28)));
+    assert_eq!(ReleaseDate::from_str("2003-03"), Some(ReleaseDate::Month(2003, 3)));
+    assert_eq!(ReleaseDate::from_str("2003"), Some(ReleaseDate::Year(2003)));
+    assert_eq!(ReleaseDate::from_str("tba"), Some(ReleaseDate::Tba));
+    for invalid in ["", "03", "2003-13", "2003-02-29", "2003-3-28", "2003-03-28-01", "today"].iter() {
+        assert_eq!(ReleaseDate::from_str(invalid), None, "{}", invalid);
+    }
+    assert_eq!(ReleaseDate::from_str("2004-02-29").map(|date| date.to_string()), Some("2004-02-29".to_owned()));
+
+    let mut dates = vec![ReleaseDate::Tba, ReleaseDate::Year(2003), ReleaseDate::Day(2003, 12, 31), ReleaseDate::Month(2003, 3), ReleaseDate::Day(2003, 3, 28)];
+    dates.sort();
+    assert_eq!(dates, [ReleaseDate::Day(2003, 3, 28), ReleaseDate::Month(2003, 3), ReleaseDate::Day(2003, 12, 31), ReleaseDate::Year(2003), ReleaseDate::Tba]);
+
+    assert_eq!(ReleaseDate::Day(2004, 2, 28).end(), Some((2004, 2, 29)));
+    assert_eq!(ReleaseDate::Day(2003, 12, 31).end(), Some((2004, 1, 1)));
+    assert_eq!(ReleaseDate::Month(2003, 3).end(), Some((2003, 4, 1)));
+    assert_eq!(ReleaseDate::Year(2003).first_day(), Some((2003, 1, 1)));
+    assert_eq!(ReleaseDate::Tba.end(), None);
+    assert_eq!(ReleaseDate::Day(2003, 3, 28).timestamp(), Some(1048809600));
+    assert_eq!(ReleaseDate::from_timestamp(1048809600 + 86399), ReleaseDate::Day(2003, 3, 28));
+
+    let release: Release = serde_json::from_value(json!({"id": 1, "released": "2019-03"})).expect("To parse release");
+    assert_eq!(release.release_date(), Some(ReleaseDate::Month(2019, 3)));
+    assert_eq!(serde_json::to_value(ReleaseDate::Tba).expect("To serialize"), json!("tba"));
+    assert!(serde_json::from_value::<ReleaseDate>(json!("2019-00")).is_err());
+}
+
+#[test]
+fn feed_should_resolve_saved_query() {
+    let today = ReleaseDate::Day(2019, 3, 28);
+
+    let query = Query::upcoming("Upcoming").language("en").language("ja").platform("win");
+    assert_eq!(query.resolve(today).expect("To resolve").to_string(), "(released > \"2019-03-28\" and languages = [\"en\",\"ja\"] and platforms = \"win\")");
+
+    let saved = serde_json::to_string(&Query::recent("Recent").limit(10)).expect("To serialize");
+    let query: Query = serde_json::from_str(&saved).expect("To deserialize");
+    assert_eq!(query, Query::recent("Recent").limit(10));
+    assert!(query.reverse);
+    assert_eq!(query.resolve(today).expect("To resolve").to_string(), "(released <= \"2019-03-28\")");
+
+    let query: Query = serde_json::from_value(json!({"title": "All", "filters": "(released = \"today\" or title = \"today\")"})).expect("To deserialize");
+    assert_eq!(query.limit, vndb::feed::DEFAULT_LIMIT);
+    assert_eq!(query.resolve(today).expect("To resolve").to_string(), "(released = \"2019-03-28\" or title = \"today\")");
+    assert_eq!(Query::new("All", "").resolve(today).expect("To resolve").to_string(), "(id >= 1)");
+
+    match Query::new("Invalid", "released >").resolve(today) {
+        Err(Error::Filter(_)) => (),
+        result => panic!("Unexpected result: {:?}", result),
+    }
+}
+
+#[test]
+fn feed_should_render_partial_dates() {
+    let feed = Feed {
+        title: "Releases & more".to_owned(),
+        link: "https://example.com/feed".to_owned(),
+        updated: 1553731200,
+        entries: vec![
+            entry(1, "Exact, <day>", ReleaseDate::Day(2019, 3, 28)),
+            entry(2, "Month; only", ReleaseDate::Month(2019, 12)),
+            entry(3, "Year", ReleaseDate::Year(2020)),
+            entry(4, "Unknown", ReleaseDate::Tba),
+            entry(5, &"Long".repeat(30), ReleaseDate::Day(2019, 4, 1)),
+        ],
+    };
+
+    let mut ical = Vec::new();
+    feed.write(Format::Ical, &mut ical).expect("To write calendar");
+    let ical = String::from_utf8(ical).expect("To be UTF-8");
+    assert!(ical.ends_with("END:VCALENDAR\r\n"));
+    assert!(ical.lines().all(|line| line.len() <= 76), "{}", ical);
+    assert_eq!(ical.matches("BEGIN:VEVENT").count(), 4);
+    assert!(!ical.contains("r4@vndb.org"));
+    assert!(ical.contains("X-WR-CALNAME:Releases & more\r\n"));
+    assert!(ical.contains("UID:r1@vndb.org\r\nDTSTAMP:20190328T000000Z\r\nDTSTART;VALUE=DATE:20190328\r\nDTEND;VALUE=DATE:20190329\r\nSUMMARY:Exact\\, <day> (2019-03-28)\r\nDESCRIPTION:Languages: en\r\nURL:https://vndb.org/r1\r\nSTATUS:CONFIRMED\r\n"));
+    assert!(ical.contains("DTSTART;VALUE=DATE:20191201\r\nDTEND;VALUE=DATE:20200101\r\nSUMMARY:Month\\; only (2019-12)\r\n"));
+    assert!(ical.contains("DTSTART;VALUE=DATE:20200101\r\nDTEND;VALUE=DATE:20210101\r\nSUMMARY:Year (2020)\r\nDESCRIPTION:Languages: en\r\nURL:https://vndb.org/r3\r\nSTATUS:TENTATIVE\r\n"));
+    assert!(ical.replace("\r\n ", "").contains(&format!("SUMMARY:{} (2019-04-01)\r\n", "Long".repeat(30))));
+
+    let mut atom = Vec::new();
+    feed.write(Format::Atom, &mut atom).expect("To write Atom");
+    let atom = String::from_utf8(atom).expect("To be UTF-8");
+    assert!(atom.contains("\t<title>Releases &amp; more</title>\n\t<id>https://example.com/feed</id>\n"));
+    assert!(atom.contains("\t<updated>2019-03-28T00:00:00Z</updated>\n"));
+    assert!(atom.contains("\t\t<title>Unknown (TBA)</title>\n\t\t<id>https://vndb.org/r4</id>\n\t\t<link href=\"https://vndb.org/r4\"/>\n\t\t<updated>2019-03-28T00:00:00Z</updated>\n"));
+    assert!(atom.contains("\t\t<title>Year (2020)</title>\n\t\t<id>https://vndb.org/r3</id>\n\t\t<link href=\"https://vndb.org/r3\"/>\n\t\t<updated>2020-01-01T00:00:00Z</updated>\n"));
+
+    let mut rss = Vec::new();
+    feed.write(Format::Rss, &mut rss).expect("To write RSS");
+    let rss = String::from_utf8(rss).expect("To be UTF-8");
+    assert!(rss.contains("\t\t<lastBuildDate>Thu, 28 Mar 2019 00:00:00 GMT</lastBuildDate>\n"));
+    assert!(rss.contains("\t\t\t<title>Exact, &lt;day&gt; (2019-03-28)</title>\n"));
+    assert!(rss.contains("\t\t\t<pubDate>Sun, 01 Dec 2019 00:00:00 GMT</pubDate>\n"));
+    assert_eq!(rss.matches("<item>").count(), 5);
+    assert_eq!(rss.matches("<pubDate>").count(), 4);
+}
+
+#[cfg(feature = "testing")]
+#[test]
+fn feed_should_fetch_releases_matching_query() {
+    use vndb::testing::MockServer;
+
+    let server = MockServer::builder().fixtures().start().expect("To start server");
+    let mut client = vndb::client::simple::Client::<std::net::TcpStream>::connect_to(server.addr()).expect("To connect");
+
+    let query = Query::recent("Recent Japanese releases").language("ja");
+    let feed = Feed::fetch_at(&mut client, &query, 1553731200).expect("To fetch feed");
+    assert_eq!(server.requests(), ["get release basic,details,vn,producers (released <= \"2019-03-28\" and languages = \"ja\") {\"page\":1,\"results\":25,\"sort\":\"released\",\"reverse\":true}"]);
+
+    assert_eq!(feed.title, "Recent Japanese releases");
+    assert_eq!(feed.link, "https://vndb.org/r");
+    assert_eq!(feed.updated, 1553731200);
+    assert_eq!(feed.entries.len(), 1);
+    let entry = &feed.entries[0];
+    assert_eq!(entry.id, 29);
+    assert_eq!(entry.released, ReleaseDate::Day(2003, 3, 28));
+    assert_eq!(entry.headline(), "Ever17 -the out of infinity- Premium Edition (2003-03-28)");
+    assert_eq!(entry.summary(), "VN: Ever17 -the out of infinity-\nProducers: KID\nType: complete\nLanguages: ja\nPlatforms: win");
+
+    let mut rss = Vec::new();
+    feed.write_rss(&mut rss).expect("To write RSS");
+    let rss = String::from_utf8(rss).expect("To be UTF-8");
+    assert!(rss.contains("\t\t\t<link>https://vndb.org/r29</link>\n\t\t\t<guid isPermaLink=\"true\">https://vndb.org/r29</guid>\n"));
+    assert!(rss.contains("\t\t\t<pubDate>Fri, 28 Mar 2003 00:00:00 GMT</pubDate>\n"));
+
+    let query = Query::upcoming("Nothing").limit(0);
+    let feed = Feed::fetch_at(&mut client, &query, 1553731200).expect("To fetch feed");
+    assert!(feed.entries.is_empty());
+    assert_eq!(server.requests().len(), 1);
+}
@@ -0,0 +1,104 @@
+#![cfg(feature = "testing")]
+
+use serde_json::{json, Value};
+
+use vndb::graph::{Crawler, Edge, Format, Node};
+use vndb::protocol::message::response::results::VnRelationKind;
+use vndb::testing::{Matcher, MockServer, Reply};
+
+fn node(id: u64, title: &str, depth: u32) -> Node {
+    Node {
+        id,
+        title: title.to_owned(),
+        original: None,
+        depth,
+    }
+}
+
+fn vn(id: u64, relations: &[(u64, &str, bool)]) -> Value {
+    let relations = relations.iter().map(|(id, relation, official)| json!({"id": id, "relation": relation, "title": format!("VN {}", id), "original": null, "official": official})).collect::<Vec<_>>();
+    json!({"id": id, "title": format!("VN {}", id), "original": null, "relations": relations})
+}
+
+#[test]
+fn graph_should_crawl_vn_relations() {
+    let server = MockServer::builder().fixtures().start().expect("To start server");
+    let mut client = vndb::client::simple::Client::connect_to(server.addr()).expect("To connect");
+
+    let graph = Crawler::new().crawl_vn(&mut client, 17).expect("To crawl graph");
+    assert_eq!(graph.nodes, [node(17, "Ever17 -the out of infinity-", 0), node(18, "Ever17 Premium Edition", 1)]);
+    assert_eq!(graph.edges, [
+        Edge { from: 17, to: 18, relation: VnRelationKind::FanDisc, official: true },
+        Edge { from: 18, to: 17, relation: VnRelationKind::Original, official: true },
+    ]);
+    assert!(!graph.truncated);
+    assert!(graph.missing.is_empty());
+    assert_eq!(server.requests(), [
+        "get vn basic,relations (id = [17]) {\"page\":1,\"results\":25}",
+        "get vn basic,relations (id = [18]) {\"page\":1,\"results\":25}",
+    ]);
+
+    let graph = Crawler::new().max_depth(0).crawl_vn(&mut client, 17).expect("To crawl graph");
+    assert_eq!(graph.nodes, [node(17, "Ever17 -the out of infinity-", 0)]);
+    assert!(graph.edges.is_empty());
+    assert!(graph.truncated);
+
+    let graph = Crawler::new().crawl_producer(&mut client, 24).expect("To crawl graph");
+    assert_eq!(graph.nodes.len(), 1);
+    assert_eq!(graph.nodes[0].original.as_deref(), Some("キッド"));
+    assert_eq!(graph.missing, [1164]);
+    assert!(graph.edges.is_empty());
+}
+
+#[test]
+fn graph_should_batch_levels_within_limits() {
+    let server = MockServer::builder().fixtures()
+                                      .expect(Matcher::get("vn"), Reply::results(vec![vn(1, &[(2, "seq", true), (3, "side", false), (4, "char", true)])], false))
+                                      .expect(Matcher::get("vn"), Reply::results(vec![vn(2, &[(1, "preq", true), (3, "set", true), (5, "seq", true)]), vn(3, &[(1, "par", false)])], false))
+                                      .start()
+                                      .expect("To start server");
+    let mut client = vndb::client::simple::Client::connect_to(server.addr()).expect("To connect");
+
+    let graph = Crawler::new().max_nodes(3).crawl_vn(&mut client, 1).expect("To crawl graph");
+    assert_eq!(server.pending_expectations(), 0);
+    assert_eq!(graph.nodes, [node(1, "VN 1", 0), node(2, "VN 2", 1), node(3, "VN 3", 1)]);
+    assert_eq!(graph.edges.iter().map(|edge| (edge.from, edge.to, edge.relation.code())).collect::<Vec<_>>(), [
+        (1, 2, "seq"),
+        (1, 3, "side"),
+        (2, 1, "preq"),
+        (2, 3, "set"),
+        (3, 1, "par"),
+    ]);
+    assert_eq!(graph.edges_from(3).count(), 1);
+    assert!(graph.truncated);
+    assert!(server.requests()[1].starts_with("get vn basic,relations (id = [2,3]) "), "request={}", server.requests()[1]);
+
+    let mut dot = Vec::new();
+    graph.write(Format::Dot, &mut dot).expect("To write DOT");
+    assert_eq!(String::from_utf8(dot).expect("UTF-8"), "\
+digraph v1 {
+\tv1 [label=\"VN 1\", shape=box];
+\tv2 [label=\"VN 2\"];
+\tv3 [label=\"VN 3\"];
+\tv1 -> v2 [label=\"Sequel\"];
+\tv1 -> v3 [label=\"Side story\", style=dashed];
+\tv2 -> v1 [label=\"Prequel\"];
+\tv2 -> v3 [label=\"Same setting\"];
+\tv3 -> v1 [label=\"Parent story\", style=dashed];
+}
+");
+
+    let mut graphml = Vec::new();
+    graph.write(Format::GraphMl, &mut graphml).expect("To write GraphML");
+    let graphml = String::from_utf8(graphml).expect("UTF-8");
+    assert!(graphml.contains("\t<graph id=\"v1\" edgedefault=\"directed\">\n\t\t<node id=\"v1\">\n\t\t\t<data key=\"title\">VN 1</data>\n\t\t\t<data key=\"depth\">0</data>\n\t\t</node>\n"), "graphml={}", graphml);
+    assert!(graphml.contains("\t\t<edge source=\"v1\" target=\"v3\">\n\t\t\t<data key=\"relation\">side</data>\n\t\t\t<data key=\"official\">false</data>\n\t\t</edge>\n"), "graphml={}", graphml);
+    assert!(graphml.ends_with("\t</graph>\n</graphml>\n"));
+
+    let mut json = Vec::new();
+    graph.write(Format::Json, &mut json).expect("To write JSON");
+    let json: Value = serde_json::from_slice(&json).expect("To parse JSON");
+    assert_eq!(json["root"], 1);
+    assert_eq!(json["truncated"], true);
+    assert_eq!(json["edges"][1], json!({"from": 1, "to": 3, "relation": "side", "official": false}));
+}
@@ -0,0 +1,174 @@
+#![cfg(feature = "testing")]
+
+use std::time::Duration;
+
+use serde_json::json;
+
+use vndb::export::{Entry, Format, Status, UserList};
+use vndb::import::{self, Importer, Item};
+use vndb::protocol::message::Request;
+use vndb::testing::{Matcher, MockServer, Reply};
+
+fn item(vn: Option<u64>, title: Option<&str>, status: Status, vote: Option<u8>) -> Item {
+    Item {
+        vn,
+        title: title.map(str::to_owned),
+        status,
+        vote,
+        notes: None,
+        started: None,
+        finished: None,
+    }
+}
+
+#[test]
+fn import_should_read_exported_formats() {
+    let list = UserList {
+        uid: 2,
+        username: Some("ayo".to_owned()),
+        entries: vec![Entry {
+            vn: 17,
+            title: Some("Ever17, \"the\" <out> of infinity".to_owned()),
+            status: Status::Finished,
+            vote: Some(80),
+            notes: Some("Line\nbreak & more".to_owned()),
+            labels: vec!["Favourite".to_owned()],
+            added: Some(100),
+            modified: Some(200),
+            voted: Some(150),
+            started: Some("2019-01-02".to_owned()),
+            finished: None,
+        }],
+    };
+    let expected = Item {
+        vn: Some(17),
+        title: list.entries[0].title.clone(),
+        status: Status::Finished,
+        vote: Some(80),
+        notes: list.entries[0].notes.clone(),
+        started: Some("2019-01-02".to_owned()),
+        finished: None,
+    };
+
+    for format in [Format::Json, Format::Csv, Format::Xml] {
+        let mut out = Vec::new();
+        list.write(format, &mut out).expect("To write list");
+        let items = import::read(format, out.as_slice()).expect("To read list");
+        assert_eq!(items, std::slice::from_ref(&expected), "format={:?}", format);
+    }
+}
+
+#[test]
+fn import_should_read_mal_xml() {
+    let xml = "<?xml version=\"1.0\" encoding=\"UTF-8\" ?>
+<myanimelist>
+    <anime>
+        <series_animedb_id>9253</series_animedb_id>
+        <series_title><![CDATA[Steins;Gate]]></series_title>
+        <series_type>TV</series_type>
+        <my_start_date>2011-04-06</my_start_date>
+        <my_finish_date>0000-00-00</my_finish_date>
+        <my_score>9</my_score>
+        <my_status>Completed</my_status>
+        <my_comments>El &amp; Psy</my_comments>
+    </anime>
+    <manga>
+        <manga_title><![CDATA[Ever17]]></manga_title>
+        <my_score>0</my_score>
+        <my_status>Plan to Read</my_status>
+    </manga>
+</myanimelist>";
+
+    let items = import::read_mal_xml(xml.as_bytes()).expect("To read XML");
+    assert_eq!(items, [
+        Item {
+            vn: None,
+            title: Some("Steins;Gate".to_owned()),
+            status: Status::Finished,
+            vote: Some(90),
+            notes: Some("El & Psy".to_owned()),
+            started: Some("2011-04-06".to_owned()),
+            finished: None,
+        },
+        item(None, Some("Ever17"), Status::Wishlist, None),
+    ]);
+
+    match import::read_csv("title,status\nEver17,abandoned\n".as_bytes()) {
+        Err(error) => assert_eq!(error.to_string(), "Record 1: unknown status 'abandoned'"),
+        result => panic!("Unexpected result={:?}", result),
+    }
+}
+
+#[test]
+fn import_should_plan_changes_against_current_list() {
+    let current = json!([{
+        "uid": 2, "vn": 17, "added": 1, "lastmod": 1, "voted": null, "vote": null, "notes": "Keep",
+        "started": null, "finished": null, "labels": [{"id": 1, "label": "Playing"}, {"id": 10, "label": "Favourite"}],
+    }, {
+        "uid": 2, "vn": 19, "added": 1, "lastmod": 1, "voted": 1, "vote": 70, "notes": "",
+        "started": null, "finished": null, "labels": [{"id": 2, "label": "Finished"}, {"id": 7, "label": "Voted"}],
+    }]);
+    let server = MockServer::builder().fixtures()
+                                      .expect(Matcher::get_filtered("vn", "(search ~ \"ever17 premium edition\")"), Reply::results(json!([{"id": 18, "title": "Ever17 Premium Edition", "original": null}, {"id": 17, "title": "Ever17", "original": null}]), false))
+                                      .expect(Matcher::get_filtered("vn", "(search ~ \"Unknown\")"), Reply::results(json!([]), false))
+                                      .expect(Matcher::get("ulist"), Reply::results(current, false))
+                                      .start()
+                                      .expect("To start server");
+    let mut client = vndb::client::simple::Client::connect_to(server.addr()).expect("To connect");
+
+    let items = vec![
+        item(Some(17), None, Status::Finished, Some(85)),
+        item(None, Some("ever17 premium edition"), Status::Wishlist, None),
+        item(None, Some("Unknown"), Status::Unknown, Some(50)),
+        item(Some(19), Some("Remember11"), Status::Finished, Some(70)),
+    ];
+    let plan = Importer::new().plan(&mut client, 2, items).expect("To plan import");
+    assert_eq!(server.pending_expectations(), 0);
+
+    assert_eq!(plan.to_string(), "\
+~ v17: status: \"playing\" -> \"finished\", vote: null -> 85
++ v18 Ever17 Premium Edition: status=\"wishlist\"
+? \"Unknown\": no match
+1 to add, 1 to update, 1 unchanged, 1 unmatched
+");
+    assert_eq!(plan.unchanged, [19]);
+    assert_eq!(plan.changes[0].request().to_string(), "set ulist 17 {\"labels\":[2,10],\"vote\":85}");
+    assert_eq!(plan.changes[1].request().to_string(), "set ulist 18 {\"labels\":[5]}");
+}
+
+#[test]
+fn import_should_retry_throttled_set() {
+    let set = || Matcher::predicate(|request| matches!(request, Request::Set(_)));
+    let server = MockServer::builder().fixtures()
+                                      .expect(Matcher::get("ulist"), Reply::results(json!([]), false))
+                                      .expect(set(), Reply::Throttled)
+                                      .expect(set(), Reply::Throttled)
+                                      .start()
+                                      .expect("To start server");
+    let mut client = vndb::client::simple::Client::connect_to(server.addr()).expect("To connect");
+
+    let importer = Importer::new().max_wait(Duration::from_millis(10));
+    let plan = importer.plan(&mut client, 2, vec![item(Some(17), None, Status::Playing, None), item(Some(18), None, Status::Unknown, Some(60))]).expect("To plan import");
+    let stats = importer.apply(&mut client, &plan).expect("To apply import");
+
+    assert_eq!(stats.applied, 2);
+    assert_eq!(stats.retries, 2);
+    let sets = server.requests().into_iter().filter(|request| request.starts_with("set ")).collect::<Vec<_>>();
+    assert_eq!(sets, [
+        "set ulist 17 {\"labels\":[1]}",
+        "set ulist 17 {\"labels\":[1]}",
+        "set ulist 17 {\"labels\":[1]}",
+        "set ulist 18 {\"labels\":[],\"vote\":60}",
+    ]);
+
+    let server = MockServer::builder().fixtures()
+                                      .expect(set(), Reply::Throttled)
+                                      .expect(set(), Reply::Throttled)
+                                      .start()
+                                      .expect("To start server");
+    let mut client = vndb::client::simple::Client::connect_to(server.addr()).expect("To connect");
+    match importer.max_retries(1).apply(&mut client, &plan) {
+        Err(vndb::export::Error::Client(vndb::Error::Server(error))) => assert_eq!(error.id, "throttled"),
+        result => panic!("Unexpected result={:?}", result),
+    }
+}
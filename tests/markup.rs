@@ -0,0 +1,93 @@
+use vndb::markup::{Markup, Node, SpoilerMode};
+
+#[test]
+fn parse_markup_into_nodes() {
+    let markup = Markup::parse("See [url=https://example.com/]site[/url] and v17.\n[b]Bold [i]italic[/i][/b]");
+
+    assert_eq!(markup.nodes, vec![
+        Node::Text("See ".to_owned()),
+        Node::Url { url: "https://example.com/".to_owned(), children: vec![Node::Text("site".to_owned())] },
+        Node::Text(" and ".to_owned()),
+        Node::Ref { kind: 'v', id: 17 },
+        Node::Text(".\n".to_owned()),
+        Node::Bold(vec![Node::Text("Bold ".to_owned()), Node::Italic(vec![Node::Text("italic".to_owned())])]),
+    ]);
+}
+
+#[test]
+fn parse_markup_literal_and_invalid_tags() {
+    let markup = Markup::parse("[raw][b]v17[/b][/raw] [code]x < y[/code] [b]open [unknown] [/i]");
+
+    assert_eq!(markup.nodes, vec![
+        Node::Raw("[b]v17[/b]".to_owned()),
+        Node::Text(" ".to_owned()),
+        Node::Code("x < y".to_owned()),
+        Node::Text(" [b]open [unknown] [/i]".to_owned()),
+    ]);
+    assert_eq!(markup.to_string(), "[raw][b]v17[/b][/raw] [code]x < y[/code] [b]open [unknown] [/i]");
+}
+
+#[test]
+fn parse_markup_stray_brackets_do_not_swallow_tags() {
+    let markup = Markup::parse("a [ note [b]bold[/b]");
+    assert_eq!(markup.nodes, vec![
+        Node::Text("a [ note ".to_owned()),
+        Node::Bold(vec![Node::Text("bold".to_owned())]),
+    ]);
+
+    let markup = Markup::parse("[From [url=/v1]x[/url]]");
+    assert_eq!(markup.nodes, vec![
+        Node::Text("[From ".to_owned()),
+        Node::Url { url: "/v1".to_owned(), children: vec![Node::Text("x".to_owned())] },
+        Node::Text("]".to_owned()),
+    ]);
+    assert_eq!(markup.to_string(), "[From [url=/v1]x[/url]]");
+}
+
+#[test]
+fn parse_markup_unclosed_spoiler_is_hidden() {
+    let markup = Markup::parse("[spoiler]x");
+    assert_eq!(markup.nodes, vec![Node::Spoiler(vec![Node::Text("x".to_owned())])]);
+    assert_eq!(markup.to_text(SpoilerMode::Hide), "");
+
+    let markup = Markup::parse("[b]a [spoiler]x[/b] y");
+    assert_eq!(markup.nodes, vec![
+        Node::Bold(vec![Node::Text("a ".to_owned()), Node::Spoiler(vec![Node::Text("x".to_owned())])]),
+        Node::Text(" y".to_owned()),
+    ]);
+    assert_eq!(markup.to_text(SpoilerMode::Hide), "a  y");
+}
+
+#[test]
+fn parse_markup_does_not_link_within_words() {
+    let markup = Markup::parse("mp3 v2x c45");
+
+    assert_eq!(markup.nodes, vec![
+        Node::Text("mp3 v2x ".to_owned()),
+        Node::Ref { kind: 'c', id: 45 },
+    ]);
+}
+
+#[test]
+fn render_markup_html() {
+    let markup = Markup::parse("<script> [url=javascript:alert(1)]click[/url] [url=/c45]char[/url]\n[spoiler]dies[/spoiler]");
+
+    assert_eq!(markup.to_html(SpoilerMode::Hide), "&lt;script&gt; click <a href=\"https://vndb.org/c45\" rel=\"nofollow\">char</a><br>");
+    assert_eq!(markup.to_html(SpoilerMode::Show), "&lt;script&gt; click <a href=\"https://vndb.org/c45\" rel=\"nofollow\">char</a><br>dies");
+    assert_eq!(markup.to_html(SpoilerMode::Mark), "&lt;script&gt; click <a href=\"https://vndb.org/c45\" rel=\"nofollow\">char</a><br><span class=\"spoiler\">dies</span>");
+}
+
+#[test]
+fn render_markup_text() {
+    let markup = Markup::parse("[quote]line 1\nline 2[/quote]\nHe [spoiler]dies[/spoiler].");
+
+    assert!(markup.has_spoilers());
+    assert_eq!(markup.to_text(SpoilerMode::Hide), "> line 1\n> line 2\nHe .");
+    assert_eq!(markup.to_text(SpoilerMode::Show), "> line 1\n> line 2\nHe dies.");
+    assert_eq!(markup.to_text(SpoilerMode::Mark), "> line 1\n> line 2\nHe [Spoiler: dies].");
+
+    let mut markup = markup;
+    markup.strip_spoilers();
+    assert!(!markup.has_spoilers());
+    assert_eq!(markup.to_string(), "[quote]line 1\nline 2[/quote]\nHe .");
+}
@@ -0,0 +1,203 @@
+#![cfg(all(feature = "mirror", feature = "testing"))]
+
+use std::time::Duration;
+
+use serde_json::json;
+
+use vndb::mirror::{Mirror, SyncStats};
+use vndb::protocol::message::{Request, Response};
+use vndb::protocol::message::request::Login;
+use vndb::protocol::message::response::Results;
+use vndb::testing::MockServer;
+
+type Client = vndb::client::simple::Client<std::net::TcpStream>;
+
+fn connect(server: &MockServer) -> Client {
+    login(server.addr())
+}
+
+fn login(addr: std::net::SocketAddr) -> Client {
+    let mut client = Client::connect_to(addr).expect("To connect");
+    client.send(&Request::Login(Login::default())).expect("To send login");
+    client.flush().expect("To flush");
+    match client.receive().expect("To receive").expect("To get response") {
+        Response::Ok => client,
+        response => panic!("Unexpected response={:?}", response),
+    }
+}
+
+#[test]
+fn mirror_should_sync_and_query_entities() {
+    let server = MockServer::builder().fixtures().start().expect("To start server");
+    let mut client = connect(&server);
+    let mut mirror = Mirror::open_in_memory().expect("To open mirror").page_size(1);
+
+    let stats = mirror.sync_vn(&mut client, 1..=50).expect("To sync vn");
+    assert_eq!(stats, SyncStats { fetched: 1, skipped: 0, requests: 2, updated: 2, removed: 0, retries: 0 });
+    let stats = mirror.sync_releases(&mut client, 29..=29).expect("To sync releases");
+    assert_eq!(stats, SyncStats { fetched: 1, skipped: 0, requests: 1, updated: 1, removed: 0, retries: 0 });
+
+    let requests = server.requests();
+    assert_eq!(requests[1], "get vn basic,details,anime,relations,tags,stats,screens,staff (id >= 0 and id <= 99) {\"page\":1,\"results\":1}");
+    assert_eq!(requests[2], "get vn basic,details,anime,relations,tags,stats,screens,staff (id >= 0 and id <= 99) {\"page\":2,\"results\":1}");
+
+    let vn = mirror.vn(17).expect("To query").expect("To find vn");
+    assert_eq!(vn.title.as_deref(), Some("Ever17 -the out of infinity-"));
+    assert_eq!(vn.tags.len(), 2);
+    assert_eq!(vn.staff.len(), 2);
+    assert!(mirror.vn(1).expect("To query").is_none());
+    assert!(mirror.vn_fetched_at(17).expect("To query").is_some());
+    assert!(mirror.vn_fetched_at(1).expect("To query").is_none());
+
+    let ids = |vns: Vec<vndb::protocol::message::response::results::Vn>| vns.into_iter().map(|vn| vn.id).collect::<Vec<_>>();
+    assert_eq!(ids(mirror.vn_by_title("EVER17").expect("To query")), [17, 18]);
+    assert_eq!(ids(mirror.vn_by_title("premium").expect("To query")), [18]);
+    assert_eq!(ids(mirror.vn_by_title("E17").expect("To query")), [17]);
+    assert_eq!(ids(mirror.vn_by_tag(19).expect("To query")), [17, 18]);
+    assert_eq!(ids(mirror.vn_by_tag(3).expect("To query")), [17]);
+    assert!(mirror.vn_by_tag(1).expect("To query").is_empty());
+
+    let release = mirror.release(29).expect("To query").expect("To find release");
+    assert_eq!(release.producers.len(), 1);
+    assert_eq!(mirror.release_by_vn(17).expect("To query").len(), 1);
+    assert_eq!(mirror.release_by_title("premium edition").expect("To query").len(), 1);
+    assert!(mirror.release_by_vn(18).expect("To query").is_empty());
+}
+
+#[test]
+fn mirror_should_refresh_only_stale_batches() {
+    let server = MockServer::builder().fixtures().start().expect("To start server");
+    let mut client = connect(&server);
+    let mut mirror = Mirror::open_in_memory().expect("To open mirror").batch_size(10);
+
+    let stats = mirror.sync_vn(&mut client, 5..=25).expect("To sync vn");
+    assert_eq!(stats, SyncStats { fetched: 3, skipped: 0, requests: 3, updated: 2, removed: 0, retries: 0 });
+
+    let stats = mirror.sync_vn(&mut client, 17..=18).expect("To sync vn");
+    assert_eq!(stats, SyncStats { fetched: 0, skipped: 1, requests: 0, updated: 0, removed: 0, retries: 0 });
+
+    let mut mirror = mirror.max_age(Duration::ZERO);
+    let stats = mirror.sync_vn(&mut client, 17..=18).expect("To sync vn");
+    assert_eq!(stats, SyncStats { fetched: 1, skipped: 0, requests: 1, updated: 2, removed: 0, retries: 0 });
+    assert_eq!(server.requests().len(), 5);
+}
+
+#[test]
+fn mirror_should_remove_deleted_entities() {
+    let server = MockServer::builder().fixtures().start().expect("To start server");
+    let mut client = connect(&server);
+    let mut mirror = Mirror::open_in_memory().expect("To open mirror").max_age(Duration::ZERO);
+    mirror.sync_vn(&mut client, 17..=18).expect("To sync vn");
+
+    let results = Results::new(json!({"num": 1, "more": false, "items": [{"id": 18, "title": "Ever17 Premium Edition"}]}));
+    let server = MockServer::builder().expect("login {\"protocol\":1,\"client\":\"rusty\",\"clientver\":0.1}", Response::Ok)
+                                      .expect(vndb::testing::Matcher::Command("get".to_owned()), Response::Results(results))
+                                      .start()
+                                      .expect("To start server");
+    let mut client = connect(&server);
+
+    let stats = mirror.sync_vn(&mut client, 17..=18).expect("To sync vn");
+    assert_eq!(stats, SyncStats { fetched: 1, skipped: 0, requests: 1, updated: 1, removed: 1, retries: 0 });
+    assert!(mirror.vn(17).expect("To query").is_none());
+    assert!(mirror.vn_by_tag(3).expect("To query").is_empty());
+    assert_eq!(mirror.vn(18).expect("To query").expect("To find vn").title.as_deref(), Some("Ever17 Premium Edition"));
+    assert_eq!(server.pending_expectations(), 0);
+}
+
+#[test]
+fn mirror_should_report_vndb_errors() {
+    let server = MockServer::builder().fixtures()
+                                      .expect(vndb::testing::Matcher::Command("get".to_owned()), vndb::testing::Reply::Throttled)
+                                      .start()
+                                      .expect("To start server");
+    let mut client = connect(&server);
+    let mut mirror = Mirror::open_in_memory().expect("To open mirror").max_retries(0);
+
+    match mirror.sync_vn(&mut client, 17..=18) {
+        Err(vndb::mirror::Error::Client(vndb::Error::Server(error))) => assert_eq!(error.id, "throttled"),
+        result => panic!("Unexpected result={:?}", result),
+    }
+    assert!(mirror.vn(17).expect("To query").is_none());
+
+    let stats = mirror.sync_vn(&mut client, 17..=18).expect("To sync vn");
+    assert_eq!(stats.updated, 2);
+}
+
+#[test]
+fn mirror_should_retry_throttled_batch() {
+    let server = MockServer::builder().fixtures()
+                                      .expect(vndb::testing::Matcher::Command("get".to_owned()), Response::Results(Results::new(json!({"num": 1, "more": false, "items": [{"id": 1, "title": "First"}]}))))
+                                      .expect(vndb::testing::Matcher::Command("get".to_owned()), vndb::testing::Reply::Throttled)
+                                      .expect(vndb::testing::Matcher::Command("get".to_owned()), vndb::testing::Reply::Throttled)
+                                      .start()
+                                      .expect("To start server");
+    let mut client = connect(&server);
+    let mut mirror = Mirror::open_in_memory().expect("To open mirror").batch_size(10).max_wait(Duration::from_millis(10));
+
+    let stats = mirror.sync_vn(&mut client, 1..=18).expect("To sync vn");
+    assert_eq!(stats.fetched, 2);
+    assert_eq!(stats.requests, 4);
+    assert_eq!(stats.retries, 2);
+    assert_eq!(stats.updated, 3);
+    assert_eq!(mirror.vn(1).expect("To query").map(|vn| vn.title), Some(Some("First".to_owned())));
+    assert!(mirror.vn(17).expect("To query").is_some());
+    let requests = server.requests();
+    assert_eq!(requests.len(), 5);
+    assert_eq!(requests[2], requests[4]);
+}
+
+#[cfg(feature = "server")]
+#[test]
+fn mirror_should_serve_as_dataset() {
+    use vndb::protocol::message::request::{get, Get};
+    use vndb::server::{Server, dataset::MirrorDataset};
+
+    let server = MockServer::builder().fixtures().start().expect("To start server");
+    let mut client = connect(&server);
+    let mut mirror = Mirror::open_in_memory().expect("To open mirror");
+    mirror.sync_vn(&mut client, 17..=18).expect("To sync vn");
+    mirror.sync_releases(&mut client, 29..=29).expect("To sync releases");
+
+    fn results(client: &mut Client, get: Get) -> Results {
+        client.send(&Request::Get(get)).expect("To send get");
+        client.flush().expect("To flush");
+        match client.receive().expect("To receive").expect("To get response") {
+            Response::Results(results) => results,
+            response => panic!("Unexpected response={:?}", response),
+        }
+    }
+
+    let local = Server::builder(MirrorDataset::new(mirror)).start("127.0.0.1:0").expect("To start local server");
+    let mut client = login(local.addr());
+
+    let dbstats = client.dbstats().expect("To get dbstats");
+    assert_eq!((dbstats.vn, dbstats.releases, dbstats.producers), (2, 1, 0));
+
+    let get = Get {
+        kind: get::Type::vn(),
+        flags: get::Flags::new().basic().relations(),
+        filters: get::Filters::new().filter(vndb::filter!(title ~ "premium")),
+        options: None,
+    };
+    let vns = results(&mut client, get).vn().expect("To parse vn");
+    assert_eq!(vns.items.iter().map(|vn| vn.id).collect::<Vec<_>>(), [18]);
+    assert_eq!(vns.items[0].relations[0].id, 17);
+
+    let get = Get {
+        kind: get::Type::release(),
+        flags: get::Flags::new().basic().producers(),
+        filters: get::Filters::new().filter(vndb::filter!(vn = 17)),
+        options: None,
+    };
+    let releases = results(&mut client, get).release().expect("To parse release");
+    assert_eq!(releases.items.iter().map(|release| release.id).collect::<Vec<_>>(), [29]);
+    assert_eq!(releases.items[0].producers[0].name, "KID");
+
+    let get = Get {
+        kind: get::Type::producer(),
+        flags: get::Flags::new().basic(),
+        filters: get::Filters::new().filter(vndb::filter!(id = 24)),
+        options: None,
+    };
+    assert_eq!(results(&mut client, get).producer().expect("To parse producer").num, 0);
+}
@@ -14,9 +14,11 @@ fn get_type_short() {
 fn format_request_login_without_auth() {
     let login = message::request::Login {
         protocol: 2,
-        client: "test",
+        client: "test".into(),
         clientver: 0.666,
         creds: None,
+        session: None,
+        create_session: false,
     };
     let login = message::Request::Login(login);
 
@@ -28,9 +30,11 @@ fn format_request_login_without_auth() {
 fn format_request_login_with_auth() {
     let login = message::request::Login {
         protocol: 2,
-        client: "test",
+        client: "test".into(),
         clientver: 0.666,
-        creds: Some(("username", "pass")),
+        creds: Some(("username".into(), "pass".into())),
+        session: None,
+        create_session: false,
     };
     let login = message::Request::Login(login);
 
@@ -70,6 +74,60 @@ fn format_request_get_wit_options() {
 
 }
 
+#[test]
+fn format_request_login_with_session() {
+    let mut login = message::request::Login::new(Some(("username", "pass")));
+    login.create_session = true;
+    let result = message::Request::Login(login).to_string();
+    assert_eq!(result, "login {\"protocol\":1,\"client\":\"rusty\",\"clientver\":0.1,\"username\":\"username\",\"password\":\"pass\",\"createsession\":true}\x04");
+    assert_eq!(message::Request::from_str(&result).expect("To parse").to_string(), result);
+
+    let mut login = message::request::Login::new(None);
+    login.session = Some(("username".into(), "0123abcd".into()));
+    let result = message::Request::Login(login).to_string();
+    assert_eq!(result, "login {\"protocol\":1,\"client\":\"rusty\",\"clientver\":0.1,\"username\":\"username\",\"sessiontoken\":\"0123abcd\"}\x04");
+    assert_eq!(message::Request::from_str(&result).expect("To parse").to_string(), result);
+
+    match message::Response::from_str("session 0123abcd").expect("To parse") {
+        message::Response::Session(token) => assert_eq!(token, "0123abcd"),
+        response => panic!("Unexpected response={:?}", response),
+    }
+    assert!(message::Response::from_str("session").is_err());
+}
+
+#[test]
+fn parse_request_login_with_escapes() {
+    let request = message::Request::from_str(r#"login {"protocol":1,"client":"test","clientver":0.1,"username":"us\u0065r","password":"pa\"ss\\word"}"#);
+    match request.expect("To parse") {
+        message::Request::Login(login) => {
+            assert_eq!(login.client, "test");
+            assert_eq!(login.creds, Some(("user".into(), "pa\"ss\\word".into())));
+        },
+        request => panic!("Unexpected request={:?}", request),
+    }
+
+    let request = message::Request::from_str(r#"login {"protocol":1,"client":"test","clientver":0.1,"username":"user","sessiontoken":"0123\/abcd"}"#);
+    match request.expect("To parse") {
+        message::Request::Login(login) => assert_eq!(login.session, Some(("user".into(), "0123/abcd".into()))),
+        request => panic!("Unexpected request={:?}", request),
+    }
+}
+
+#[test]
+fn format_request_login_with_escapes() {
+    let login = message::request::Login::new(Some(("user", "pa\"ss\\word\",\"createsession\":true")));
+    let text = login.to_string();
+    assert_eq!(text, r#"login {"protocol":1,"client":"rusty","clientver":0.1,"username":"user","password":"pa\"ss\\word\",\"createsession\":true"}"#);
+
+    match message::Request::from_str(&text).expect("To parse") {
+        message::Request::Login(parsed) => {
+            assert_eq!(parsed, login);
+            assert!(!parsed.create_session);
+        },
+        request => panic!("Unexpected request={:?}", request),
+    }
+}
+
 #[test]
 fn format_request_dbstats() {
     let dbstats = message::Request::DBstats;
@@ -83,8 +141,8 @@ fn parse_response_ok() {
     let message = "ok";
     let result = message::Response::from_str(message).expect("To parse");
     match result {
-        message::Response::Ok => assert!(true),
-        _ => assert!(false, "Unexpected type of result")
+        message::Response::Ok => (),
+        _ => panic!("Unexpected type of result")
     }
 }
 
@@ -98,7 +156,7 @@ fn parse_response_error() {
             assert_eq!(error.id, "parse");
             assert_eq!(error.msg, "Invalid command or argument");
         },
-        _ => assert!(false, "Unexpected type of result")
+        _ => panic!("Unexpected type of result")
     }
 }
 
@@ -121,8 +179,30 @@ fn parse_response_dbstats() {
             assert_eq!(stats.chars, 14046);
             assert_eq!(stats.vn, 13051);
             assert_eq!(stats.traits, 1272);
+            assert_eq!(stats.users, 0);
+            assert!(stats.extra.is_empty());
+        },
+        _ => panic!("Unexpected type of result")
+    }
+}
+
+#[test]
+fn parse_response_full_dbstats() {
+    let message = "dbstats {\"tags\":1627,\"releases\":28071,\"producers\":3456,\"chars\":14046,\"vn\":13051,\"traits\":1272,\"users\":129436,\"threads\":11420,\"posts\":97125,\"staff\":17895,\"quotes\":42}";
+
+    match message::Response::from_str(message).expect("To parse") {
+        message::Response::DBstats(stats) => {
+            assert_eq!(stats.users, 129436);
+            assert_eq!(stats.threads, 11420);
+            assert_eq!(stats.posts, 97125);
+            assert_eq!(stats.staff, 17895);
+            assert_eq!(stats.extra.get("quotes"), Some(&json!(42)));
+
+            let encoded = serde_json::to_value(&stats).expect("To serialize");
+            assert_eq!(encoded["quotes"], json!(42));
+            assert_eq!(encoded["staff"], json!(17895));
         },
-        _ => assert!(false, "Unexpected type of result")
+        _ => panic!("Unexpected type of result")
     }
 }
 
@@ -150,7 +230,7 @@ fn parse_response_results() {
             let results = results.vn().unwrap();
 
             assert_eq!(results.num, 1);
-            assert_eq!(results.more, false);
+            assert!(!results.more);
             assert_eq!(results.len(), 1);
             assert_eq!(results.items.len(), 1);
             let item = &results.items[0];
@@ -159,6 +239,313 @@ fn parse_response_results() {
             assert_eq!(item.title, Some("Ever17 -the out of infinity-".to_owned()));
             assert_eq!(item.original, None);
         },
-        _ => assert!(false, "Unexpected type of result")
+        _ => panic!("Unexpected type of result")
     }
 }
+
+#[test]
+fn parse_typed_relation_and_role_codes() {
+    use message::response::results::{VnRelation, VnRelationKind, StaffRole, CharacterRole, ReleaseVoiced, ReleaseAnimation, AnimationKind};
+
+    let relation: VnRelation = serde_json::from_value(json!({
+        "id": 18,
+        "relation": "fan",
+        "title": "Ever17 Fandisc",
+        "original": null,
+        "official": true
+    })).expect("To parse relation");
+    assert_eq!(relation.relation, VnRelationKind::FanDisc);
+    assert_eq!(relation.relation.label(), "Fandisc");
+
+    let unknown: VnRelationKind = serde_json::from_value(json!("new-kind")).expect("To parse unknown relation");
+    assert_eq!(unknown, VnRelationKind::Unknown("new-kind".to_owned()));
+    assert_eq!(serde_json::to_value(&unknown).unwrap(), json!("new-kind"));
+
+    let role: StaffRole = serde_json::from_value(json!("music")).expect("To parse staff role");
+    assert_eq!(role, StaffRole::Composer);
+    assert_eq!(serde_json::to_value(&role).unwrap(), json!("music"));
+
+    let role: CharacterRole = serde_json::from_value(json!("main")).expect("To parse character role");
+    assert_eq!(role, CharacterRole::Main);
+
+    let voiced: ReleaseVoiced = serde_json::from_value(json!(4)).expect("To parse voiced");
+    assert_eq!(voiced, ReleaseVoiced::Full);
+    let voiced: ReleaseVoiced = serde_json::from_value(json!(9)).expect("To parse unknown voiced");
+    assert_eq!(voiced, ReleaseVoiced::Unknown(9));
+
+    let animation: ReleaseAnimation = serde_json::from_value(json!([2, null])).expect("To parse animation");
+    assert_eq!(animation.story, Some(AnimationKind::Simple));
+    assert_eq!(animation.ero, None);
+    assert_eq!(serde_json::to_value(&animation).unwrap(), json!([2, null]));
+}
+
+#[test]
+fn parse_character_traits_and_vns() {
+    use message::response::results::{SpoilerLevel, CharacterRole};
+
+    let message = "results {
+        \"num\":1,
+        \"more\":false,
+        \"items\":[{
+            \"id\": 9,
+            \"traits\": [[35, 0], [1046, 2]],
+            \"vns\": [[17, 0, 1, \"main\"], [18, 54, 0, \"appears\"]]
+        }]
+    }";
+
+    let results = match message::Response::from_str(message).expect("To parse") {
+        message::Response::Results(results) => results.character().expect("To parse characters"),
+        _ => panic!("Unexpected type of result")
+    };
+    let character = &results[0];
+
+    assert_eq!(character.traits.len(), 2);
+    assert_eq!(character.traits[1].id, 1046);
+    assert_eq!(character.traits[1].spoiler, SpoilerLevel::Major);
+
+    assert_eq!(character.vns.len(), 2);
+    assert_eq!(character.vns[0].vn, 17);
+    assert_eq!(character.vns[0].spoiler, SpoilerLevel::Minor);
+    assert_eq!(character.vns[0].role, CharacterRole::Main);
+    assert_eq!(character.vns[1].release, 54);
+    assert_eq!(character.vns[1].role, CharacterRole::Appears);
+
+    let level: SpoilerLevel = serde_json::from_value(json!(3)).expect("To parse unknown spoiler level");
+    assert_eq!(level, SpoilerLevel::Major);
+
+    let encoded = serde_json::to_value(character).expect("To serialize");
+    assert_eq!(encoded["traits"], json!([[35, 0], [1046, 2]]));
+    assert_eq!(encoded["vns"], json!([[17, 0, 1, "main"], [18, 54, 0, "appears"]]));
+}
+
+#[test]
+fn parse_user_lists() {
+    use message::response::results::WishPriority;
+
+    let message = "results {
+        \"num\":1,
+        \"more\":false,
+        \"items\":[{
+            \"uid\": 2, \"vn\": 17, \"added\": 100, \"lastmod\": 200, \"voted\": null, \"vote\": null,
+            \"notes\": \"\", \"started\": \"2019-01-02\", \"finished\": null,
+            \"labels\": [{\"id\": 1, \"label\": \"Playing\"}]
+        }]
+    }";
+
+    let results = match message::Response::from_str(message).expect("To parse") {
+        message::Response::Results(results) => results,
+        _ => panic!("Unexpected type of result")
+    };
+    let ulist = results.u_list().expect("To parse ulist");
+
+    assert_eq!(ulist[0].last_mod, 200);
+    assert_eq!(ulist[0].voted, 0);
+    assert_eq!(ulist[0].started.as_deref(), Some("2019-01-02"));
+    assert_eq!(ulist[0].labels[0].id, 1);
+    assert_eq!(ulist[0].labels[0].label, "Playing");
+
+    let results = message::response::Results::new(json!({"num": 1, "more": false, "items": [{"uid": 2, "vn": 18, "priority": 3, "added": 5}]}));
+    let wishlist = results.wish_list().expect("To parse wishlist");
+    assert_eq!(wishlist[0].priority, Some(WishPriority::Blacklist));
+    assert_eq!(serde_json::to_value(&wishlist[0]).expect("To serialize")["priority"], 3);
+}
+
+#[test]
+fn apply_spoiler_policy_to_characters() {
+    use vndb::spoiler::{SpoilerPolicy, ApplySpoilerPolicy};
+
+    let message = "results {
+        \"num\":1,
+        \"more\":false,
+        \"items\":[{
+            \"id\": 9,
+            \"description\": \"Cheerful girl.[spoiler] Not human.[/spoiler]\",
+            \"traits\": [[35, 0], [36, 1], [1046, 2]],
+            \"vns\": [[17, 0, 0, \"main\"], [18, 0, 2, \"side\"]]
+        }]
+    }";
+
+    let results = match message::Response::from_str(message).expect("To parse") {
+        message::Response::Results(results) => results,
+        _ => panic!("Unexpected type of result")
+    };
+
+    let mut characters = results.character().expect("To parse characters");
+    characters.apply_spoiler_policy(SpoilerPolicy::Major);
+    assert_eq!(characters[0].traits.len(), 3);
+    assert_eq!(characters[0].vns.len(), 2);
+    assert_eq!(characters[0].description.as_deref(), Some("Cheerful girl.[spoiler] Not human.[/spoiler]"));
+
+    let mut characters = results.character().expect("To parse characters");
+    characters.apply_spoiler_policy(SpoilerPolicy::Minor);
+    assert_eq!(characters[0].traits.iter().map(|character_trait| character_trait.id).collect::<Vec<_>>(), [35, 36]);
+    assert_eq!(characters[0].vns.len(), 1);
+    assert_eq!(characters[0].description.as_deref(), Some("Cheerful girl."));
+
+    let mut characters = results.character().expect("To parse characters");
+    characters.apply_spoiler_policy(SpoilerPolicy::None);
+    assert_eq!(characters[0].traits.iter().map(|character_trait| character_trait.id).collect::<Vec<_>>(), [35]);
+    assert_eq!(characters[0].vns[0].vn, 17);
+}
+
+#[test]
+fn parse_response_errors_with_context() {
+    use message::ResponseParseErrorKind;
+
+    let error = message::Response::from_str("error").expect_err("To fail");
+    assert!(matches!(error.kind, ResponseParseErrorKind::EmptyError));
+    assert_eq!(error.command, "error");
+
+    let error = message::Response::from_str("token {\"token\":\"secret\"}").expect_err("To fail");
+    assert!(matches!(error.kind, ResponseParseErrorKind::UnknownCommand));
+    assert_eq!(error.command, "token");
+    assert!(!error.excerpt.contains("secret"));
+    assert!(!error.to_string().contains("secret"));
+    assert_eq!(error.excerpt, "<18 bytes>");
+    assert_eq!(error.path, None);
+
+    let error = message::Response::from_str("dbstats {\"tags\":1,\"releases\":\"many\"}").expect_err("To fail");
+    assert!(matches!(error.kind, ResponseParseErrorKind::InvalidDbStats(_)));
+    assert_eq!(error.path.as_deref(), Some("releases"));
+
+    let error = message::Response::from_str("results {\"num\":1,\"more\":false,\"items\":[{\"id\":1,\"title\":tru}]}").expect_err("To fail");
+    assert!(matches!(error.kind, ResponseParseErrorKind::InvalidResults(_)));
+    assert_eq!(error.path.as_deref(), Some("items[0].title"));
+
+    let payload = format!("{{\"items\":[{}]", "1,".repeat(100));
+    let error = message::Response::from_str(&format!("results {}", payload)).expect_err("To fail");
+    assert_eq!(error.excerpt, format!("{}...", &payload[..message::ResponseParseError::EXCERPT_LEN]));
+}
+
+#[test]
+fn convert_results_to_typed_with_path() {
+    let results = message::response::Results::new(serde_json::json!({"num": 2, "more": false, "items": [{"id": 1}, {"id": 2, "title": 2}]}));
+    match results.to_typed::<message::response::typed::VN>() {
+        Err(vndb::Error::Parse(error)) => {
+            assert!(matches!(error.kind, message::ResponseParseErrorKind::InvalidResults(_)));
+            assert_eq!(error.command, "results");
+            assert_eq!(error.path.as_deref(), Some("items[1].title"));
+        },
+        result => panic!("Unexpected result={:?}", result),
+    }
+
+    let results = message::response::Results::new(serde_json::json!({"num": 1, "more": false, "items": [{"id": 1}]}));
+    let typed = results.to_typed::<message::response::typed::VN>().expect("To convert");
+    assert_eq!(typed.items[0].id, 1);
+}
+
+#[test]
+fn parse_request_round_trip() {
+    let requests = [
+        "login {\"protocol\":1,\"client\":\"test\",\"clientver\":0.666}\x04",
+        "login {\"protocol\":1,\"client\":\"test\",\"clientver\":0.666,\"username\":\"username\",\"password\":\"pass\"}\x04",
+        "dbstats\x04",
+        "get vn basic,anime (title = \"Lolka\" or title = \"lolka\")\x04",
+        "get release basic,details (id = [1,2,3] and (released > \"2010\" or title ~ \"(x) {y}\")) {\"page\":2,\"results\":25,\"sort\":\"title\",\"reverse\":true}\x04",
+        "set ulist 17 {\"labels\":[2,10],\"notes\":\"{x}\",\"vote\":85}\x04",
+        "set wishlist 18\x04",
+    ];
+
+    for request in requests.iter() {
+        let parsed = message::Request::from_str(request).expect("To parse request");
+        assert_eq!(parsed.to_string(), *request);
+    }
+}
+
+#[test]
+fn parse_request_get() {
+    use message::request::filter::{Expr, Condition, Operator};
+
+    let request = message::Request::from_str("get vn basic,details (id>=1 and id <= 10 or search ~ \"ever\") {\"page\":3}").expect("To parse");
+    let get = match request {
+        message::Request::Get(get) => get,
+        request => panic!("Unexpected request={:?}", request),
+    };
+
+    assert_eq!(get.kind, message::request::get::Type::vn());
+    assert_eq!(get.flags, message::request::get::Flags::new().details().basic());
+    assert_eq!(get.flags.names().collect::<Vec<_>>(), ["basic", "details"]);
+    assert_eq!(get.options.as_ref().and_then(|options| options.page), Some(3));
+    assert_eq!(get.filters.to_string(), "((id >= 1 and id <= 10) or search ~ \"ever\")");
+
+    let condition = |field: &str, op, value| Expr::Condition(Condition {
+        field: field.to_owned(),
+        op,
+        value,
+    });
+    assert_eq!(get.filters.expr().expect("To parse filters"), Expr::Or(vec![
+        Expr::And(vec![
+            condition("id", Operator::GreaterEq, json!(1)),
+            condition("id", Operator::LessEq, json!(10)),
+        ]),
+        condition("search", Operator::Like, json!("ever")),
+    ]));
+}
+
+#[test]
+fn normalize_filter_expression() {
+    use message::request::filter::Expr;
+
+    let expr = Expr::parse("(title ~ \"ever\" and (id = 17 and title ~ \"ever\")) or id = 18").expect("To parse");
+    assert_eq!(expr.normalized().to_string(), "(id = 17 and title ~ \"ever\") or id = 18");
+
+    let expr = Expr::parse("(id = 17 or (id = 18 or id = 17))").expect("To parse");
+    assert_eq!(expr.normalized().to_string(), "id = 17 or id = 18");
+    assert_eq!(Expr::parse("id = 17 and id = 17").expect("To parse").normalized().to_string(), "id = 17");
+}
+
+#[test]
+fn parse_invalid_request() {
+    use message::RequestParseError;
+    use message::request::{GetParseError, SetParseError};
+
+    assert!(matches!(message::Request::from_str(""), Err(RequestParseError::EmptyRequest)));
+    assert!(matches!(message::Request::from_str("vote vn 17 {}"), Err(RequestParseError::UnknownCommand("vote"))));
+    assert!(matches!(message::Request::from_str("set vn 17 {}"), Err(RequestParseError::InvalidSet(SetParseError::UnknownType("vn")))));
+    assert!(matches!(message::Request::from_str("set ulist v17"), Err(RequestParseError::InvalidSet(SetParseError::InvalidId("v17")))));
+    assert!(matches!(message::Request::from_str("set ulist 17 {\"vote\":}"), Err(RequestParseError::InvalidSet(SetParseError::InvalidFields(_)))));
+    assert!(matches!(message::Request::from_str("get"), Err(RequestParseError::MissingArguments("get"))));
+    assert!(matches!(message::Request::from_str("get novel basic (id = 1)"), Err(RequestParseError::InvalidGet(GetParseError::UnknownType("novel")))));
+    assert!(matches!(message::Request::from_str("get vn basic,all (id = 1)"), Err(RequestParseError::InvalidGet(GetParseError::UnknownFlag("all")))));
+    assert!(matches!(message::Request::from_str("get vn basic (id = 1"), Err(RequestParseError::InvalidGet(GetParseError::InvalidFilters(_)))));
+    assert!(matches!(message::Request::from_str("get vn basic (id = 1) {\"pages\":1}"), Err(RequestParseError::InvalidGet(GetParseError::InvalidOptions(_)))));
+    assert!(matches!(message::Request::from_str("login {\"protocol\":1}"), Err(RequestParseError::InvalidLogin(_))));
+}
+
+#[test]
+fn format_response_round_trip() {
+    let responses = [
+        "ok\x04",
+        "error {\"id\":\"parse\",\"msg\":\"Invalid command or argument\"}\x04",
+        "dbstats {\"tags\":1627,\"releases\":28071,\"producers\":3456,\"chars\":14046,\"vn\":13051,\"traits\":1272,\"users\":7,\"threads\":8,\"posts\":9,\"staff\":10,\"quotes\":42}\x04",
+        "results {\"items\":[{\"id\":17,\"title\":\"Ever17\"}],\"more\":false,\"num\":1}\x04",
+    ];
+
+    for response in responses.iter() {
+        let parsed = message::Response::from_str(response.trim_end_matches('\x04')).expect("To parse response");
+        assert_eq!(parsed.to_string(), *response);
+    }
+}
+
+#[test]
+fn format_typed_results() {
+    let message = "results {\"num\":1,\"more\":true,\"items\":[{\"id\":9,\"name\":\"Yuki\",\"gender\":\"f\",\"traits\":[[35,1]],\"vns\":[[17,0,0,\"main\"]]}]}";
+    let results = match message::Response::from_str(message).expect("To parse") {
+        message::Response::Results(results) => results.character().expect("To parse characters"),
+        _ => panic!("Unexpected type of result")
+    };
+
+    let response = results.to_response().expect("To encode").to_string();
+    assert!(response.starts_with("results {"));
+    assert!(response.ends_with("}\x04"));
+
+    let decoded = match message::Response::from_str(response.trim_end_matches('\x04')).expect("To parse encoded") {
+        message::Response::Results(results) => results.character().expect("To parse characters"),
+        _ => panic!("Unexpected type of result")
+    };
+    assert_eq!(decoded.num, 1);
+    assert!(decoded.more);
+    assert_eq!(decoded[0].name.as_deref(), Some("Yuki"));
+    assert_eq!(decoded[0].traits[0].id, 35);
+    assert_eq!(decoded[0].vns[0].vn, 17);
+}
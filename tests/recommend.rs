@@ -0,0 +1,100 @@
+use serde_json::json;
+
+use vndb::protocol::message::response::results::{Tag, UList, Vn, VoteList};
+use vndb::recommend::{Rating, Recommender};
+use vndb::spoiler::SpoilerPolicy;
+
+fn vn(id: u64, tags: &[(u64, f32, u8)]) -> Vn {
+    let tags = tags.iter().map(|(id, score, spoiler)| json!({"id": id, "score": score, "spoiler level": spoiler})).collect::<Vec<_>>();
+    serde_json::from_value(json!({"id": id, "title": format!("VN {}", id), "tags": tags})).expect("To parse VN")
+}
+
+fn tags() -> Vec<Tag> {
+    serde_json::from_value(json!([
+        {"id": "g1", "name": "Romance"},
+        {"id": "g2", "name": "Childhood Friend Heroine", "parents": ["g1"]},
+        {"id": "g3", "name": "Kouhai Heroine", "parents": ["g1"]},
+        {"id": "g10", "name": "Time Loop"},
+        {"id": "g11", "name": "Science Fiction"},
+        {"id": "g20", "name": "Protagonist's Death"},
+    ])).expect("To parse tags")
+}
+
+fn vns() -> Vec<Vn> {
+    vec![
+        vn(1, &[(2, 3.0, 0), (10, 3.0, 0), (20, 3.0, 2)]),
+        vn(2, &[(11, 3.0, 0)]),
+        vn(3, &[(10, 2.0, 0), (11, 1.0, 0), (1, -1.0, 0)]),
+        vn(4, &[(3, 3.0, 0)]),
+        vn(5, &[(20, 3.0, 2)]),
+        vn(6, &[(11, 2.5, 0)]),
+    ]
+}
+
+fn assert_close(actual: f32, expected: f32) {
+    assert!((actual - expected).abs() < 1e-4, "actual={} expected={}", actual, expected);
+}
+
+const RATINGS: [Rating; 2] = [Rating { vn: 1, vote: 90 }, Rating { vn: 2, vote: 40 }];
+
+#[test]
+fn recommend_should_rank_by_cosine_similarity() {
+    let vns = vns();
+    let recommender = Recommender::new();
+
+    let profile = recommender.profile(&RATINGS, &vns);
+    assert_eq!(profile.top(10), [(2, 9.0), (10, 9.0)]);
+    assert_eq!(profile.rated.iter().copied().collect::<Vec<_>>(), [1, 2]);
+
+    let recommendations = recommender.recommend(&profile, &vns, 10);
+    assert_eq!(recommendations.len(), 1);
+    assert_eq!(recommendations[0].vn.id, 3);
+    assert_close(recommendations[0].similarity, 18.0 / (162f32.sqrt() * 5f32.sqrt()));
+    assert_eq!(recommendations[0].tags, [(10, 18.0)]);
+
+    let recommender = Recommender::new().spoiler_policy(SpoilerPolicy::Major);
+    let profile = recommender.profile(&RATINGS, &vns);
+    let recommendations = recommender.recommend(&profile, &vns, 1);
+    assert_eq!(recommendations.iter().map(|recommendation| recommendation.vn.id).collect::<Vec<_>>(), [5]);
+
+    let profile = Recommender::new().min_vote(95).profile(&RATINGS, &vns);
+    assert!(profile.weights.is_empty());
+    assert!(recommender.recommend(&profile, &vns, 10).is_empty());
+}
+
+#[test]
+fn recommend_should_use_tag_hierarchy() {
+    let vns = vns();
+    let tags = tags();
+    let recommender = Recommender::new().with_tags(&tags);
+
+    assert_eq!(recommender.vector(&vns[0]).into_iter().collect::<Vec<_>>(), [(1, 1.5), (2, 3.0), (10, 3.0)]);
+    let profile = recommender.profile(&RATINGS, &vns);
+    assert_eq!(profile.top(1), [(2, 9.0)]);
+
+    let recommendations = recommender.recommend(&profile, &vns, 10);
+    let ranked = recommendations.iter().map(|recommendation| recommendation.vn.id).collect::<Vec<_>>();
+    assert_eq!(ranked, [3, 4]);
+    assert_close(recommendations[0].similarity, 18.0 / (13.5 * 5f32.sqrt()));
+    assert_close(recommendations[1].similarity, 6.75 / (13.5 * 11.25f32.sqrt()));
+    assert_eq!(recommendations[1].tags, [(1, 6.75)]);
+
+    let recommender = recommender.parent_weight(0.0);
+    let profile = recommender.profile(&RATINGS, &vns);
+    assert_eq!(recommender.recommend(&profile, &vns, 10).len(), 1);
+}
+
+#[test]
+fn recommend_should_read_ratings_from_lists() {
+    let votes: Vec<VoteList> = serde_json::from_value(json!([
+        {"uid": 2, "vn": 17, "vote": 85, "added": 1},
+        {"uid": 2, "vn": 18, "vote": null, "added": 1},
+    ])).expect("To parse votes");
+    let ulist: Vec<UList> = serde_json::from_value(json!([
+        {"uid": 2, "vn": 19, "added": 1, "lastmod": 1, "voted": 1, "vote": 60, "notes": null, "labels": []},
+        {"uid": 2, "vn": 20, "added": 1, "lastmod": 1, "voted": null, "vote": null, "notes": null, "labels": []},
+    ])).expect("To parse list");
+
+    assert_eq!(votes.iter().filter_map(Rating::from_vote).collect::<Vec<_>>(), [Rating { vn: 17, vote: 85 }]);
+    assert_eq!(ulist.iter().filter_map(Rating::from_ulist).collect::<Vec<_>>(), [Rating { vn: 19, vote: 60 }]);
+}
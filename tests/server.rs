@@ -0,0 +1,212 @@
+#![cfg(feature = "server")]
+
+use serde_json::json;
+
+use vndb::protocol::message::{self, Response};
+use vndb::server::{Server, Session};
+use vndb::server::dataset::JsonDataset;
+
+fn dataset() -> JsonDataset {
+    let dataset = json!({
+        "vn": [
+            { "id": 17, "title": "Ever17 -the out of infinity-", "original": null, "released": "2002-08-29", "languages": ["en", "ja"], "aliases": "E17", "tags": [{ "id": 19, "score": 2.8, "spoiler level": 0 }], "rating": 8.66 },
+            { "id": 18, "title": "Ever17 Premium Edition", "original": null, "released": "2003-03-28", "languages": ["ja"], "tags": [], "rating": 7.5 },
+            { "id": 3, "title": "Remember11", "original": null, "released": "2004-03-18", "languages": ["ja"], "tags": [{ "id": 19, "score": 2.0, "spoiler level": 0 }], "rating": null },
+        ],
+        "release": [
+            { "id": 29, "title": "Ever17 Premium Edition", "released": "2003-03-28", "patch": false, "vn": [{ "id": 17, "title": "Ever17", "original": null }], "producers": [{ "id": 24 }] },
+        ],
+        "character": [
+            { "id": 9, "name": "Tsugumi Komachi", "traits": [[35, 0]], "vns": [[17, 0, 0, "primary"]] },
+        ],
+        "staff": [
+            { "id": 226, "name": "Uchikoshi Kotaro", "aliases": [[226, "Uchikoshi Kotaro", "打越鋼太郎"], [3001, "Uchikoshi", null]] },
+        ],
+        "tag": [
+            { "id": 19, "name": "Time Loop", "aliases": ["Time Travel Loop"], "vns": 412 },
+        ],
+        "trait": [
+            { "id": 35, "name": "Blue Hair", "aliases": [], "chars": 1200 },
+        ],
+    });
+
+    JsonDataset::from_reader(dataset.to_string().as_bytes()).expect("To parse dataset")
+}
+
+fn logged_in() -> Session {
+    let mut session = Session::new();
+    match session.reply(&dataset(), "login {\"protocol\":1,\"client\":\"test\",\"clientver\":1}") {
+        Response::Ok => (),
+        response => panic!("Unexpected response={:?}", response),
+    }
+    session
+}
+
+fn expect_error(response: Response, id: &str) -> message::response::VndbError {
+    match response {
+        Response::Error(error) => {
+            assert_eq!(error.id, id, "Unexpected error={:?}", error);
+            error
+        },
+        response => panic!("Unexpected response={:?}", response),
+    }
+}
+
+fn expect_ids(response: Response) -> Vec<u64> {
+    match response {
+        Response::Results(results) => results["items"].as_array().expect("To have items").iter().map(|item| item["id"].as_u64().expect("To have id")).collect(),
+        response => panic!("Unexpected response={:?}", response),
+    }
+}
+
+#[test]
+fn server_should_require_login() {
+    let dataset = dataset();
+    let mut session = Session::new();
+
+    expect_error(session.reply(&dataset, "dbstats"), "needlogin");
+    expect_error(session.reply(&dataset, "login {\"protocol\":2,\"client\":\"test\",\"clientver\":1}"), "badarg");
+    assert!(!session.is_logged_in());
+
+    let mut session = logged_in();
+    expect_error(session.reply(&dataset, "login {\"protocol\":1,\"client\":\"test\",\"clientver\":1}"), "loggedin");
+
+    match session.reply(&dataset, "dbstats") {
+        Response::DBstats(stats) => {
+            assert_eq!(stats.vn, 3);
+            assert_eq!(stats.tags, 1);
+            assert_eq!(stats.traits, 1);
+            assert_eq!(stats.staff, 1);
+        },
+        response => panic!("Unexpected response={:?}", response),
+    }
+}
+
+#[test]
+fn server_should_reject_invalid_get() {
+    let dataset = dataset();
+    let mut session = logged_in();
+
+    let error = expect_error(session.reply(&dataset, "get vote basic (id = 1)"), "gettype");
+    assert_eq!(error.extra["type"], "vote");
+    expect_error(session.reply(&dataset, "get ulist basic (uid = 1)"), "gettype");
+    let error = expect_error(session.reply(&dataset, "get release basic,tags (id = 1)"), "getinfo");
+    assert_eq!(error.extra["flag"], "tags");
+    expect_error(session.reply(&dataset, "get vn basic"), "missing");
+    let error = expect_error(session.reply(&dataset, "get vn basic (rating = 1)"), "filter");
+    assert_eq!(error.msg, "Unknown field");
+    assert_eq!(error.extra["field"], "rating");
+    expect_error(session.reply(&dataset, "get vn basic (search = \"ever\")"), "filter");
+    expect_error(session.reply(&dataset, "get vn basic (id = \"17\")"), "filter");
+    expect_error(session.reply(&dataset, "get vn basic (id = 17 and)"), "parse");
+    expect_error(session.reply(&dataset, "get vn basic (id >= 1) {\"results\":26}"), "badarg");
+    expect_error(session.reply(&dataset, "get vn basic (id >= 1) {\"sort\":\"name\"}"), "badarg");
+    expect_error(session.reply(&dataset, "set ulist 17 {}"), "auth");
+    expect_error(session.reply(&dataset, "set vn 17 {}"), "settype");
+}
+
+#[test]
+fn server_should_evaluate_filters() {
+    let dataset = dataset();
+    let mut session = logged_in();
+
+    assert_eq!(expect_ids(session.reply(&dataset, "get vn basic (id = [18, 3])")), [3, 18]);
+    assert_eq!(expect_ids(session.reply(&dataset, "get vn basic (title ~ \"ever17\" and id != 18)")), [17]);
+    assert_eq!(expect_ids(session.reply(&dataset, "get vn basic (search ~ \"e17\")")), [17]);
+    assert_eq!(expect_ids(session.reply(&dataset, "get vn basic (tags = 19)")), [3, 17]);
+    assert_eq!(expect_ids(session.reply(&dataset, "get vn basic (released > \"2003\" or languages = \"en\")")), [3, 17, 18]);
+    assert_eq!(expect_ids(session.reply(&dataset, "get vn basic (firstchar = \"r\")")), [3]);
+    assert_eq!(expect_ids(session.reply(&dataset, "get release basic (vn = 17 and producer = [24])")), [29]);
+    assert_eq!(expect_ids(session.reply(&dataset, "get release basic (patch = false)")), [29]);
+    assert_eq!(expect_ids(session.reply(&dataset, "get character basic (vn = 17 and traits = 35)")), [9]);
+    assert_eq!(expect_ids(session.reply(&dataset, "get staff basic (aid = 3001)")), [226]);
+    assert_eq!(expect_ids(session.reply(&dataset, "get tag basic (search ~ \"travel\")")), [19]);
+    assert_eq!(expect_ids(session.reply(&dataset, "get trait basic (name = \"Blue Hair\")")), [35]);
+}
+
+#[test]
+fn server_should_apply_flags_sort_and_pagination() {
+    let dataset = dataset();
+    let mut session = logged_in();
+
+    match session.reply(&dataset, "get vn basic,stats (id >= 1) {\"sort\":\"rating\",\"reverse\":true,\"results\":2}") {
+        Response::Results(results) => {
+            assert_eq!(results["more"], true);
+            assert_eq!(results["num"], 2);
+            assert_eq!(results["items"][0]["id"], 17);
+            assert_eq!(results["items"][0]["rating"], 8.66);
+            assert_eq!(results["items"][0]["released"], "2002-08-29");
+            assert!(results["items"][0].get("tags").is_none());
+            assert_eq!(results["items"][1]["id"], 18);
+        },
+        response => panic!("Unexpected response={:?}", response),
+    }
+
+    match session.reply(&dataset, "get vn basic,stats (id >= 1) {\"sort\":\"rating\",\"reverse\":true,\"results\":2,\"page\":2}") {
+        Response::Results(results) => {
+            assert_eq!(results["more"], false);
+            assert_eq!(results["num"], 1);
+            assert_eq!(results["items"][0]["id"], 3);
+        },
+        response => panic!("Unexpected response={:?}", response),
+    }
+
+    match session.reply(&dataset, "get vn basic,tags (id = 17)") {
+        Response::Results(results) => {
+            let vn = results.vn().expect("To parse VN");
+            assert_eq!(vn[0].tags[0].id, 19);
+        },
+        response => panic!("Unexpected response={:?}", response),
+    }
+}
+
+#[test]
+fn server_should_answer_client_over_tcp() {
+    let server = Server::builder(dataset()).start("127.0.0.1:0").expect("To start server");
+
+    let mut client = vndb::client::simple::Client::connect_to(server.addr()).expect("To connect");
+    client.send(&message::request::Login::new(None).into()).expect("To send login");
+    client.send(&message::request::Get {
+        kind: message::request::get::Type::vn(),
+        flags: message::request::get::Flags::new().basic(),
+        filters: message::request::get::Filters::new().filter(vndb::filter!(title ~ "Ever17")),
+        options: None,
+    }.into()).expect("To send Get");
+    client.flush().expect("To flush");
+
+    match client.receive().expect("To receive").expect("To get response") {
+        Response::Ok => (),
+        response => panic!("Unexpected response={:?}", response),
+    }
+    match client.receive().expect("To receive").expect("To get response") {
+        Response::Results(results) => {
+            let vn = results.vn().expect("To parse VN");
+            assert_eq!(vn.len(), 2);
+            assert_eq!(vn[0].title.as_deref(), Some("Ever17 -the out of infinity-"));
+        },
+        response => panic!("Unexpected response={:?}", response),
+    }
+}
+
+#[test]
+fn server_should_answer_get_many_in_requested_order() {
+    let mut dataset = JsonDataset::new();
+    for id in 1..=130u64 {
+        dataset.insert("vn", json!({ "id": id, "title": format!("VN {}", id) }));
+    }
+    let server = Server::builder(dataset).start("127.0.0.1:0").expect("To start server");
+
+    let mut client = vndb::client::simple::Client::connect_to(server.addr()).expect("To connect");
+    client.send(&message::request::Login::new(None).into()).expect("To send login");
+    client.flush().expect("To flush");
+    client.receive().expect("To receive").expect("To get response");
+
+    let mut ids = (1..=130u64).rev().collect::<Vec<_>>();
+    ids.insert(50, 500);
+    ids.extend([130, 17, 501]);
+
+    let vns = client.get_many_vn(&ids, message::request::get::Flags::new().basic()).expect("To get VNs");
+    assert_eq!(vns.iter().map(|vn| vn.id).collect::<Vec<_>>(), (1..=130u64).rev().collect::<Vec<_>>());
+    assert_eq!(vns[0].title.as_deref(), Some("VN 130"));
+    assert_eq!(vns.missing, [500, 501]);
+}
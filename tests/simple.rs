@@ -1,6 +1,7 @@
 use vndb::protocol::message;
 
 #[test]
+#[ignore = "requires access to api.vndb.org"]
 fn simple_client_should_send_message_over_tcp() {
     let get = message::request::Get {
         kind: message::request::get::Type::vn(),
@@ -46,6 +47,7 @@ fn simple_client_should_send_message_over_tcp() {
 
 #[cfg(feature = "rustls-on")]
 #[test]
+#[ignore = "requires access to api.vndb.org"]
 fn simple_tls_client_should_send_message_over_tcp() {
     let get = message::request::Get {
         kind: message::request::get::Type::vn(),
@@ -88,3 +90,272 @@ fn simple_tls_client_should_send_message_over_tcp() {
         response => panic!("Unexpected response={:?}", response),
     }
 }
+
+struct CannedIo {
+    input: std::io::Cursor<Vec<u8>>,
+    output: Vec<u8>,
+}
+
+impl CannedIo {
+    fn new(input: &str) -> Self {
+        Self {
+            input: std::io::Cursor::new(input.as_bytes().to_vec()),
+            output: Vec::new(),
+        }
+    }
+}
+
+impl std::io::Read for CannedIo {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.input.read(buf)
+    }
+}
+
+impl std::io::Write for CannedIo {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.output.write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn simple_client_should_request_dbstats() {
+    let io = CannedIo::new("dbstats {\"tags\":1,\"releases\":2,\"producers\":3,\"chars\":4,\"vn\":5,\"traits\":6,\"users\":7,\"threads\":8,\"posts\":9,\"staff\":10}\x04error {\"id\":\"throttled\",\"msg\":\"Throttled\"}\x04ok");
+    let mut client = vndb::client::simple::Client::new(io);
+
+    let stats = client.dbstats().expect("To get dbstats");
+    assert_eq!(stats.vn, 5);
+    assert_eq!(stats.staff, 10);
+
+    match client.dbstats().expect_err("To fail on error response") {
+        vndb::Error::Server(error) => assert_eq!(error.id, "throttled"),
+        error => panic!("Unexpected error={:?}", error),
+    }
+
+    match client.receive().expect_err("To fail on closed connection") {
+        vndb::Error::Framing(vndb::FramingError::Incomplete) => (),
+        error => panic!("Unexpected error={:?}", error),
+    }
+}
+
+#[cfg(feature = "testing")]
+fn mock_get() -> message::request::Get<'static> {
+    message::request::Get {
+        kind: message::request::get::Type::vn(),
+        flags: message::request::get::Flags::new().basic().details(),
+        filters: message::request::get::Filters::new().filter(vndb::filter!(id = 17)),
+        options: None,
+    }
+}
+
+#[cfg(feature = "testing")]
+fn run_mock_session<IO: std::io::Read + std::io::Write>(client: &mut vndb::client::simple::Client<IO>) {
+    client.send(&message::request::Login::new(None).into()).expect("To send login");
+    client.send(&message::Request::DBstats).expect("To send DbStats");
+    client.send(&mock_get().into()).expect("To send Get");
+    client.flush().expect("To flush");
+
+    match client.receive().expect("To receive message").expect("To not fail receiving") {
+        message::Response::Ok => (),
+        response => panic!("Unexpected response={:?}", response),
+    }
+
+    match client.receive().expect("To receive message").expect("To not fail receiving") {
+        message::Response::DBstats(response) => assert_eq!(response.vn, vndb::testing::fixtures::dbstats().vn),
+        response => panic!("Unexpected response={:?}", response),
+    }
+
+    match client.receive().expect("To receive message").expect("To not fail receiving") {
+        message::Response::Results(response) => {
+            let results = response.vn().expect("Parse into VN Results");
+            assert_eq!(results.len(), 1);
+            assert_eq!(results[0].id, 17);
+        }
+        response => panic!("Unexpected response={:?}", response),
+    }
+}
+
+#[cfg(feature = "testing")]
+#[test]
+fn simple_client_should_send_message_to_mock_server() {
+    let server = vndb::testing::MockServer::builder().fixtures().start().expect("To start server");
+
+    let mut client = vndb::client::simple::Client::connect_to(server.addr()).expect("To connect");
+    run_mock_session(&mut client);
+
+    assert_eq!(server.requests(), [
+        "login {\"protocol\":1,\"client\":\"rusty\",\"clientver\":0.1}",
+        "dbstats",
+        "get vn basic,details (id = 17)",
+    ]);
+}
+
+#[cfg(all(feature = "testing", feature = "rustls-on"))]
+#[test]
+fn simple_tls_client_should_send_message_to_mock_server() {
+    let server = vndb::testing::MockServer::builder().fixtures().tls().start().expect("To start server");
+    let (server_name, config) = server.tls_config().expect("To have TLS config");
+
+    let mut client = vndb::client::simple::Client::connect_tls_to(server.addr(), server_name, config).expect("To connect");
+    run_mock_session(&mut client);
+}
+
+#[cfg(feature = "testing")]
+#[test]
+fn simple_client_should_handle_injected_errors() {
+    use vndb::testing::{MockServer, Reply, Matcher};
+
+    let server = MockServer::builder().expect(Matcher::Command("get".to_owned()), Reply::Throttled)
+                                      .expect("dbstats", Reply::Partial(message::Response::DBstats(vndb::testing::fixtures::dbstats())))
+                                      .expect(Matcher::Any, Reply::Disconnect)
+                                      .fixtures()
+                                      .start()
+                                      .expect("To start server");
+
+    let mut client = vndb::client::simple::Client::connect_to(server.addr()).expect("To connect");
+    client.send(&mock_get().into()).expect("To send Get");
+    client.flush().expect("To flush");
+    match client.receive().expect("To receive message").expect("To not fail receiving") {
+        message::Response::Error(error) => assert_eq!(error.id, "throttled"),
+        response => panic!("Unexpected response={:?}", response),
+    }
+
+    match client.dbstats().expect_err("To fail on partial frame") {
+        vndb::Error::Framing(vndb::FramingError::Incomplete) => (),
+        error => panic!("Unexpected error={:?}", error),
+    }
+
+    let mut client = vndb::client::simple::Client::connect_to(server.addr()).expect("To connect");
+    client.send(&message::Request::DBstats).expect("To send DbStats");
+    client.flush().expect("To flush");
+    assert!(client.receive().expect("To receive").is_none());
+    assert_eq!(server.pending_expectations(), 0);
+}
+
+#[test]
+fn simple_client_should_record_and_replay_transcript() {
+    use vndb::client::transcript::{Direction, Recorder, Replayer, ReplayOptions};
+
+    let io = CannedIo::new("ok\x04dbstats {\"tags\":1,\"releases\":2,\"producers\":3,\"chars\":4,\"vn\":5,\"traits\":6}\x04");
+    let mut transcript = Vec::new();
+    let recorder = Recorder::new(io, &mut transcript).redact_credentials(true);
+    let mut client = vndb::client::simple::Client::new(recorder);
+    client.send(&message::request::Login::new(Some(("user", "secret"))).into()).expect("To send login");
+    client.flush().expect("To flush");
+    match client.receive().expect("To receive").expect("To get response") {
+        message::Response::Ok => (),
+        response => panic!("Unexpected response={:?}", response),
+    }
+    assert_eq!(client.dbstats().expect("To get dbstats").vn, 5);
+
+    drop(client);
+    let transcript = String::from_utf8(transcript).expect("Valid UTF-8");
+    assert!(!transcript.contains("secret"));
+
+    let replayer = Replayer::from_reader(transcript.as_bytes(), ReplayOptions::default()).expect("To read transcript");
+    assert_eq!(replayer.remaining(), 4);
+    let mut client = vndb::client::simple::Client::new(replayer);
+    match client.send(&message::request::Login::new(Some(("other", "password"))).into()).expect_err("To fail on mismatched credentials") {
+        vndb::Error::Io(error) => assert_eq!(error.kind(), std::io::ErrorKind::InvalidData),
+        error => panic!("Unexpected error={:?}", error),
+    }
+
+    let options = ReplayOptions {
+        ignore_credentials: true,
+        ..ReplayOptions::default()
+    };
+    let replayer = Replayer::from_reader(transcript.as_bytes(), options).expect("To read transcript");
+    let mut client = vndb::client::simple::Client::new(replayer);
+    client.send(&message::request::Login::new(Some(("other", "password"))).into()).expect("To send login");
+    client.flush().expect("To flush");
+    match client.receive().expect("To receive").expect("To get response") {
+        message::Response::Ok => (),
+        response => panic!("Unexpected response={:?}", response),
+    }
+    assert_eq!(client.dbstats().expect("To get dbstats").vn, 5);
+    assert!(client.receive().expect("To receive").is_none());
+
+    match client.send(&message::Request::DBstats).expect_err("To fail on unexpected request") {
+        vndb::Error::Io(error) => assert_eq!(error.kind(), std::io::ErrorKind::InvalidData),
+        error => panic!("Unexpected error={:?}", error),
+    }
+
+    let entries = transcript.lines().map(|line| serde_json::from_str::<vndb::client::transcript::Entry>(line).expect("Valid entry")).collect::<Vec<_>>();
+    let sent = entries.iter().filter(|entry| entry.direction == Direction::Send).map(|entry| entry.frame.as_str()).collect::<Vec<_>>();
+    assert_eq!(sent, ["login {\"protocol\":1,\"client\":\"rusty\",\"clientver\":0.1,\"username\":\"<redacted>\",\"password\":\"<redacted>\"}", "dbstats"]);
+    assert_eq!(entries.iter().filter(|entry| entry.direction == Direction::Receive).count(), 2);
+}
+
+#[test]
+fn simple_client_should_redact_received_session_token() {
+    use vndb::client::transcript::{Direction, Recorder};
+
+    let io = CannedIo::new("session 0123456789abcdef\x04");
+    let mut transcript = Vec::new();
+    let recorder = Recorder::new(io, &mut transcript).redact_credentials(true);
+    let mut client = vndb::client::simple::Client::new(recorder);
+    let mut login = message::request::Login::new(Some(("user", "secret")));
+    login.create_session = true;
+    client.send(&login.into()).expect("To send login");
+    client.flush().expect("To flush");
+    match client.receive().expect("To receive").expect("To get response") {
+        message::Response::Session(token) => assert_eq!(token, "0123456789abcdef"),
+        response => panic!("Unexpected response={:?}", response),
+    }
+
+    drop(client);
+    let transcript = String::from_utf8(transcript).expect("Valid UTF-8");
+    assert!(!transcript.contains("secret"));
+    assert!(!transcript.contains("0123456789abcdef"));
+
+    let entries = transcript.lines().map(|line| serde_json::from_str::<vndb::client::transcript::Entry>(line).expect("Valid entry")).collect::<Vec<_>>();
+    let received = entries.iter().filter(|entry| entry.direction == Direction::Receive).map(|entry| entry.frame.as_str()).collect::<Vec<_>>();
+    assert_eq!(received, ["session <redacted>"]);
+}
+
+#[cfg(feature = "testing")]
+#[test]
+fn simple_client_should_get_many_by_id_in_chunks() {
+    let server = vndb::testing::MockServer::builder().fixtures().start().expect("To start server");
+    let mut client = vndb::client::simple::Client::connect_to(server.addr()).expect("To connect");
+
+    let mut ids = vec![18, 17, 18];
+    ids.extend(1000..1100);
+    let vns = client.get_many_vn(&ids, message::request::get::Flags::new().basic()).expect("To get VNs");
+    assert_eq!(vns.iter().map(|vn| vn.id).collect::<Vec<_>>(), [18, 17]);
+    assert_eq!(vns.missing, (1000..1100).collect::<Vec<_>>());
+
+    let requests = server.requests();
+    assert_eq!(requests.len(), 2);
+    assert!(requests[0].starts_with("get vn basic (id = [18,17,1000,1001,"));
+    assert!(requests[0].ends_with(",1096,1097]) {\"page\":1,\"results\":25}"));
+    assert_eq!(requests[1], "get vn basic (id = [1098,1099]) {\"page\":1,\"results\":25}");
+
+    let releases = client.get_many_release(&[29], message::request::get::Flags::new().basic()).expect("To get releases");
+    assert_eq!(releases.len(), 1);
+    assert!(releases.missing.is_empty());
+    assert!(client.get_many_producer(&[], message::request::get::Flags::new().basic()).expect("To get nothing").is_empty());
+    assert_eq!(server.requests().len(), 3);
+}
+
+#[cfg(feature = "testing")]
+#[test]
+fn simple_client_should_report_path_of_invalid_item() {
+    use vndb::testing::{Matcher, MockServer, Reply};
+
+    let results = message::response::Results::new(serde_json::json!({"num": 2, "more": false, "items": [{"id": 17}, {"id": 18, "title": 18}]}));
+    let server = MockServer::builder().expect(Matcher::Command("get".to_owned()), Reply::Response(message::Response::Results(results))).start().expect("To start server");
+    let mut client = vndb::client::simple::Client::connect_to(server.addr()).expect("To connect");
+
+    let kind = message::request::get::Type::vn();
+    match client.get_many::<message::response::results::Vn>(kind, &[17, 18], message::request::get::Flags::new().basic()) {
+        Err(vndb::Error::Parse(error)) => {
+            assert_eq!(error.path.as_deref(), Some("items[1].title"));
+            assert!(error.excerpt.starts_with("{\"id\":18"), "{}", error.excerpt);
+        },
+        result => panic!("Unexpected result={:?}", result.map(|many| many.len())),
+    }
+}
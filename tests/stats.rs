@@ -0,0 +1,109 @@
+use serde_json::json;
+
+use vndb::protocol::message::response::results::{UList, Vn, VnList, VoteList};
+use vndb::recommend::Rating;
+use vndb::stats::{ListStats, Point, RatingComparison, Statuses, Votes};
+
+fn assert_close(actual: Option<f32>, expected: f32) {
+    let actual = actual.expect("To have value");
+    assert!((actual - expected).abs() < 1e-4, "actual={} expected={}", actual, expected);
+}
+
+fn point(month: &str, count: usize, total: usize) -> Point {
+    Point {
+        month: month.to_owned(),
+        count,
+        total,
+    }
+}
+
+#[test]
+fn stats_should_summarize_ulist() {
+    let ulist: Vec<UList> = serde_json::from_value(json!([
+        {"uid": 2, "vn": 17, "added": 1546300799, "lastmod": 1, "voted": 1547510400, "vote": 85, "notes": null, "labels": [{"id": 2, "label": "Finished"}, {"id": 7, "label": "Voted"}]},
+        {"uid": 2, "vn": 18, "added": 1547510400, "lastmod": 1, "voted": 1582934400, "vote": 100, "notes": null, "labels": [{"id": 1, "label": "Playing"}]},
+        {"uid": 2, "vn": 19, "added": 1551528000, "lastmod": 1, "voted": null, "vote": null, "notes": null, "labels": [{"id": 4, "label": "Dropped"}]},
+        {"uid": 2, "vn": 20, "added": 1551528000, "lastmod": 1, "voted": null, "vote": 10, "notes": null, "labels": [{"id": 5, "label": "Wishlist"}]},
+        {"uid": 2, "vn": 21, "added": null, "lastmod": 1, "voted": null, "vote": null, "notes": null, "labels": [{"id": 10, "label": "Favourite"}]},
+    ])).expect("To parse list");
+
+    let stats = ListStats::from_ulist(&ulist);
+    assert_eq!(stats.items, 5);
+    assert_eq!(stats.votes.count, 3);
+    assert_eq!(stats.votes.distribution, [1, 0, 0, 0, 0, 0, 0, 1, 0, 1]);
+    assert_close(stats.votes.mean, 6.5);
+    assert_close(stats.votes.median, 8.5);
+    assert_eq!(stats.votes.per_year.clone().into_iter().collect::<Vec<_>>(), [
+        (2019, Votes { count: 1, mean: Some(8.5) }),
+        (2020, Votes { count: 1, mean: Some(10.0) }),
+    ]);
+    assert_eq!(stats.statuses, Some(Statuses {
+        unknown: 1,
+        playing: 1,
+        finished: 1,
+        stalled: 0,
+        dropped: 1,
+        wishlist: 1,
+        blacklist: 0,
+    }));
+    assert_close(stats.completion_rate, 1.0 / 3.0);
+    assert_eq!(stats.added, [point("2018-12", 1, 1), point("2019-01", 1, 2), point("2019-02", 0, 2), point("2019-03", 2, 4)]);
+    assert_eq!(stats.voted.len(), 14);
+    assert_eq!(stats.voted[0], point("2019-01", 1, 1));
+    assert_eq!(stats.voted[12], point("2020-01", 0, 1));
+    assert_eq!(stats.voted[13], point("2020-02", 1, 2));
+
+    let json = serde_json::to_value(&stats).expect("To serialize");
+    assert_eq!(json["statuses"]["finished"], 1);
+    assert_eq!(json["votes"]["per_year"]["2020"]["count"], 1);
+}
+
+#[test]
+fn stats_should_summarize_legacy_lists() {
+    let votes: Vec<VoteList> = serde_json::from_value(json!([
+        {"uid": 2, "vn": 17, "vote": 70, "added": 1547510400},
+        {"uid": 2, "vn": 18, "vote": 75, "added": 4107542400u64},
+        {"uid": 2, "vn": 19, "vote": 95, "added": null},
+    ])).expect("To parse votes");
+
+    let stats = ListStats::from_votes(&votes);
+    assert_eq!(stats.statuses, None);
+    assert_eq!(stats.completion_rate, None);
+    assert_close(stats.votes.median, 7.5);
+    assert_eq!(stats.votes.per_year.keys().copied().collect::<Vec<_>>(), [2019, 2100]);
+    assert_eq!(stats.added.first(), Some(&point("2019-01", 1, 1)));
+    assert_eq!(stats.added.last(), Some(&point("2100-03", 1, 2)));
+    assert_eq!(stats.added, stats.voted);
+
+    let vnlist: Vec<VnList> = serde_json::from_value(json!([
+        {"uid": 2, "vn": 17, "status": 2, "added": 0},
+        {"uid": 2, "vn": 18, "status": 3, "added": 0},
+        {"uid": 2, "vn": 19, "status": null, "added": 0},
+    ])).expect("To parse list");
+
+    let stats = ListStats::from_vnlist(&vnlist);
+    assert_eq!(stats.votes.count, 0);
+    assert_eq!(stats.votes.mean, None);
+    assert_eq!(stats.votes.median, None);
+    assert!(stats.added.is_empty());
+    assert_eq!(stats.statuses.as_ref().map(|statuses| (statuses.finished, statuses.stalled, statuses.unknown)), Some((1, 1, 1)));
+    assert_close(stats.completion_rate, 0.5);
+}
+
+#[test]
+fn stats_should_compare_with_global_rating() {
+    let vns: Vec<Vn> = serde_json::from_value(json!([
+        {"id": 17, "popularity": 38.5, "rating": 8.66, "votecount": 11215},
+        {"id": 18, "popularity": 2.1, "rating": 7.5, "votecount": 120},
+        {"id": 19, "popularity": 0.0, "rating": null, "votecount": null},
+    ])).expect("To parse VNs");
+    let ratings = [Rating { vn: 17, vote: 85 }, Rating { vn: 18, vote: 100 }, Rating { vn: 19, vote: 60 }];
+
+    let comparison = RatingComparison::compute(&ratings, &vns).expect("To compare");
+    assert_eq!(comparison.count, 2);
+    assert_close(Some(comparison.user_mean), 9.25);
+    assert_close(Some(comparison.global_mean), 8.08);
+    assert_close(Some(comparison.difference), 1.17);
+
+    assert_eq!(RatingComparison::compute(&ratings[2..], &vns), None);
+}
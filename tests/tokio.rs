@@ -1,7 +1,10 @@
+#![cfg(feature = "tokio-on")]
+
 use vndb::protocol::message;
 
 #[cfg(feature = "tokio-on")]
 #[tokio::test]
+#[ignore = "requires access to api.vndb.org"]
 async fn tokio_client_should_send_message_over_tcp() {
     let get = message::request::Get {
         kind: message::request::get::Type::vn(),
@@ -47,6 +50,7 @@ async fn tokio_client_should_send_message_over_tcp() {
 
 #[cfg(all(feature = "tokio-on", feature = "rustls-on"))]
 #[tokio::test]
+#[ignore = "requires access to api.vndb.org"]
 async fn tokio_client_should_send_message_over_tls() {
     let get = message::request::Get {
         kind: message::request::get::Type::vn(),
@@ -89,3 +93,105 @@ async fn tokio_client_should_send_message_over_tls() {
         response => panic!("Unexpected response={:?}", response),
     }
 }
+
+#[cfg(feature = "testing")]
+async fn run_mock_session<IO: tokio::io::AsyncRead + tokio::io::AsyncWrite>(client: &mut vndb::client::tokio::Client<IO>) {
+    let get = message::request::Get {
+        kind: message::request::get::Type::release(),
+        flags: message::request::get::Flags::new().basic().details(),
+        filters: message::request::get::Filters::new().filter(vndb::filter!(id = 29)),
+        options: None,
+    };
+
+    client.send(&message::request::Login::new(None).into()).await.expect("To send login");
+    client.send(&get.into()).await.expect("To send Get");
+    client.flush().await.expect("To flush");
+
+    match client.receive().await.expect("To receive message").expect("To not fail receiving") {
+        message::Response::Ok => (),
+        response => panic!("Unexpected response={:?}", response),
+    }
+
+    match client.receive().await.expect("To receive message").expect("To not fail receiving") {
+        message::Response::Results(response) => {
+            let results = response.release().expect("Parse into Release Results");
+            assert_eq!(results.len(), 1);
+            assert_eq!(results[0].id, 29);
+        }
+        response => panic!("Unexpected response={:?}", response),
+    }
+
+    let stats = client.dbstats().await.expect("To get dbstats");
+    assert_eq!(stats.vn, vndb::testing::fixtures::dbstats().vn);
+}
+
+#[cfg(feature = "testing")]
+#[tokio::test]
+async fn tokio_client_should_send_message_to_mock_server() {
+    let server = vndb::testing::MockServer::builder().fixtures().start().expect("To start server");
+
+    let mut client = vndb::client::tokio::Client::connect_to(server.addr()).await.expect("To connect");
+    run_mock_session(&mut client).await;
+    assert_eq!(server.requests().len(), 3);
+}
+
+#[cfg(all(feature = "testing", feature = "rustls-on"))]
+#[tokio::test]
+async fn tokio_client_should_send_message_to_mock_server_over_tls() {
+    let server = vndb::testing::MockServer::builder().fixtures().tls().start().expect("To start server");
+    let (server_name, config) = server.tls_config().expect("To have TLS config");
+
+    let mut client = vndb::client::tokio::Client::connect_tls_to(server.addr(), server_name, config).await.expect("To connect");
+    run_mock_session(&mut client).await;
+}
+
+#[cfg(feature = "testing")]
+#[tokio::test]
+async fn tokio_client_should_record_and_replay_transcript() {
+    use vndb::client::transcript::{Recorder, Replayer, ReplayOptions};
+
+    let server = vndb::testing::MockServer::builder().fixtures().start().expect("To start server");
+    let mut transcript = Vec::new();
+
+    let io = tokio::net::TcpStream::connect(server.addr()).await.expect("To connect");
+    let mut client = vndb::client::tokio::Client::new(Recorder::new(io, &mut transcript));
+    run_mock_session(&mut client).await;
+    drop(client);
+    drop(server);
+
+    let replayer = Replayer::from_reader(transcript.as_slice(), ReplayOptions::default()).expect("To read transcript");
+    let mut client = vndb::client::tokio::Client::new(replayer);
+    run_mock_session(&mut client).await;
+    assert!(client.receive().await.expect("To receive").is_none());
+}
+
+#[cfg(feature = "testing")]
+#[tokio::test]
+async fn tokio_cached_clients_should_coalesce_concurrent_requests() {
+    use std::sync::Arc;
+    use std::time::Duration;
+    use vndb::client::cache::{Cache, Cached};
+    use vndb::testing::{Matcher, Reply};
+
+    let results = message::response::Results::new(serde_json::json!({"num": 1, "more": false, "items": [{"id": 17}]}));
+    let delayed = Reply::Delay(Duration::from_millis(200), Box::new(Reply::Response(message::Response::Results(results))));
+    let server = vndb::testing::MockServer::builder().fixtures()
+                                                     .expect(Matcher::Command("get".to_owned()), delayed)
+                                                     .start()
+                                                     .expect("To start server");
+    let cache = Arc::new(Cache::new(16, Duration::from_secs(60)));
+
+    let get = message::request::Get {
+        kind: message::request::get::Type::vn(),
+        flags: message::request::get::Flags::new().basic(),
+        filters: message::request::get::Filters::new().filter(vndb::filter!(id = 17)),
+        options: None,
+    };
+    let mut first = Cached::new(vndb::client::tokio::Client::connect_to(server.addr()).await.expect("To connect"), cache.clone());
+    let mut second = Cached::new(vndb::client::tokio::Client::connect_to(server.addr()).await.expect("To connect"), cache.clone());
+
+    let (first, second) = tokio::join!(first.get(&get), second.get(&get));
+    assert_eq!(first.expect("To get results")["items"][0]["id"], 17);
+    assert_eq!(second.expect("To get results")["items"][0]["id"], 17);
+    assert_eq!(server.requests().len(), 1);
+}
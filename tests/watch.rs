@@ -0,0 +1,149 @@
+#![cfg(feature = "testing")]
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use serde_json::{json, Value};
+
+use vndb::export::Status;
+use vndb::protocol::message::response::results::VnRelationKind;
+use vndb::testing::{Matcher, MockServer, Reply};
+use vndb::watch::{Event, Item, Snapshot, Target, Watcher};
+
+fn snapshot_path(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("vndb-watch-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir.join("snapshot.json")
+}
+
+fn ulist_server(items: Value) -> MockServer {
+    MockServer::builder().fixtures()
+                         .expect(Matcher::get("ulist"), Reply::results(items, false))
+                         .start()
+                         .expect("To start server")
+}
+
+#[test]
+fn watch_should_report_ulist_changes_after_restart() {
+    let path = snapshot_path("ulist");
+
+    let server = ulist_server(json!([
+        {"uid": 2, "vn": 17, "added": 1, "lastmod": 1, "voted": null, "vote": null, "notes": null, "labels": [{"id": 1, "label": "Playing"}]},
+        {"uid": 2, "vn": 18, "added": 1, "lastmod": 1, "voted": null, "vote": null, "notes": null, "labels": [{"id": 5, "label": "Wishlist"}]},
+    ]));
+    let mut client = vndb::client::simple::Client::connect_to(server.addr()).expect("To connect");
+    let mut watcher = Watcher::new(Target::UserList(2), &path).expect("To create watcher");
+    assert!(watcher.snapshot().is_none());
+
+    let mut events = Vec::new();
+    assert_eq!(watcher.poll(&mut client, &mut |event| events.push(event)).expect("To poll"), 0);
+    assert!(events.is_empty());
+    assert!(path.exists());
+    assert_eq!(server.requests(), ["get ulist basic,labels (uid = 2) {\"page\":1,\"results\":25}"]);
+
+    let server = ulist_server(json!([
+        {"uid": 2, "vn": 17, "added": 1, "lastmod": 2, "voted": 2, "vote": 85, "notes": null, "labels": [{"id": 2, "label": "Finished"}, {"id": 7, "label": "Voted"}]},
+        {"uid": 2, "vn": 19, "added": 2, "lastmod": 2, "voted": null, "vote": null, "notes": null, "labels": []},
+    ]));
+    let mut client = vndb::client::simple::Client::connect_to(server.addr()).expect("To connect");
+    let mut watcher = Watcher::new(Target::UserList(2), &path).expect("To create watcher");
+    assert_eq!(watcher.snapshot().map(|snapshot| snapshot.items.len()), Some(2));
+
+    let mut events = Vec::new();
+    assert_eq!(watcher.poll(&mut client, &mut |event| events.push(event)).expect("To poll"), 4);
+    assert_eq!(events, [
+        Event::StatusChanged { vn: 17, before: Some(Status::Playing), after: Some(Status::Finished) },
+        Event::VoteChanged { vn: 17, before: None, after: Some(85) },
+        Event::Removed { vn: 18 },
+        Event::Added { vn: 19 },
+    ]);
+    assert_eq!(serde_json::to_value(&events[1]).expect("To serialize"), json!({"event": "vote_changed", "vn": 17, "before": null, "after": 85}));
+
+    let watcher = Watcher::new(Target::UserList(3), &path).expect("To create watcher");
+    assert!(watcher.snapshot().is_none());
+}
+
+#[test]
+fn watch_should_deliver_events_before_storing_snapshot() {
+    let path = snapshot_path("run");
+    let mut items = BTreeMap::new();
+    items.insert(17, Item {
+        status: Some(Status::Playing),
+        ..Default::default()
+    });
+    Snapshot { target: Target::UserList(2), taken: 1, items }.store(&path).expect("To store snapshot");
+    let mut watcher = Watcher::new(Target::UserList(2), &path).expect("To create watcher");
+
+    //Snapshot cannot replace directory, so watcher stops once it is fetched.
+    std::fs::remove_file(&path).expect("To remove snapshot");
+    std::fs::create_dir_all(path.join("blocker")).expect("To create directory");
+
+    let throttled = Reply::Raw("error {\"id\":\"throttled\",\"msg\":\"Throttle limit reached.\",\"type\":\"cmd\",\"minwait\":0.01,\"fullwait\":0.05}\x04".to_owned());
+    let server = MockServer::builder().fixtures()
+                                      .expect(Matcher::get("ulist"), throttled)
+                                      .expect(Matcher::get("ulist"), Reply::Disconnect)
+                                      .expect(Matcher::get("ulist"), Reply::results(json!([
+                                          {"uid": 2, "vn": 17, "added": 1, "lastmod": 2, "voted": null, "vote": null, "notes": null, "labels": [{"id": 2, "label": "Finished"}]},
+                                      ]), false))
+                                      .start()
+                                      .expect("To start server");
+
+    let mut connections = 0;
+    let mut events = Vec::new();
+    let connect = || {
+        connections += 1;
+        vndb::client::simple::Client::connect_to(server.addr())
+    };
+    match watcher.run(connect, std::time::Duration::from_millis(10), &mut |event| events.push(event)) {
+        Err(vndb::watch::Error::Io(_)) => (),
+        result => panic!("Unexpected result={:?}", result),
+    }
+
+    assert_eq!(connections, 2);
+    assert_eq!(server.requests().len(), 3);
+    assert_eq!(events, [Event::StatusChanged { vn: 17, before: Some(Status::Playing), after: Some(Status::Finished) }]);
+    assert_eq!(watcher.snapshot().and_then(|snapshot| snapshot.items[&17].status), Some(Status::Playing));
+    let _ = std::fs::remove_dir_all(path);
+}
+
+#[test]
+fn watch_should_report_vn_changes_to_channel() {
+    let path = snapshot_path("vns");
+    let target = Target::Vns(vec![17, 18, 404]);
+
+    let mut items = BTreeMap::new();
+    items.insert(17, Item::default());
+    items.insert(18, Item {
+        released: Some("2003-03-28".to_owned()),
+        relations: vec![(17, VnRelationKind::Original)].into_iter().collect(),
+        ..Default::default()
+    });
+    items.insert(404, Item::default());
+    let previous = Snapshot {
+        target: target.clone(),
+        taken: 1,
+        items,
+    };
+    previous.store(&path).expect("To store snapshot");
+
+    let server = MockServer::builder().fixtures().start().expect("To start server");
+    let mut client = vndb::client::simple::Client::connect_to(server.addr()).expect("To connect");
+    let mut watcher = Watcher::new(target, &path).expect("To create watcher");
+
+    let (mut sender, receiver) = std::sync::mpsc::channel();
+    assert_eq!(watcher.poll(&mut client, &mut sender).expect("To poll"), 3);
+    assert_eq!(receiver.try_iter().collect::<Vec<_>>(), [
+        Event::ReleaseDateChanged { vn: 17, before: None, after: Some("2002-08-29".to_owned()) },
+        Event::RelationAdded { vn: 17, related: 18, relation: VnRelationKind::FanDisc },
+        Event::Removed { vn: 404 },
+    ]);
+    assert_eq!(server.requests(), ["get vn basic,relations (id = [17,18,404]) {\"page\":1,\"results\":25}"]);
+
+    let stored = Snapshot::load(&path).expect("To load snapshot").expect("To have snapshot");
+    assert_eq!(Some(&stored), watcher.snapshot());
+    assert!(stored.taken > 1);
+    assert_eq!(stored.diff(&stored), []);
+
+    let watcher = Watcher::new(Target::Vns(vec![404, 18, 17, 18]), &path).expect("To create watcher");
+    assert_eq!(watcher.snapshot(), Some(&stored));
+}